@@ -1,6 +1,6 @@
 use baseview::{Size, WindowOpenOptions, WindowScalePolicy};
 use imgui::{Context, Ui};
-use imgui_baseview::{HiDpiMode, ImguiWindow, RenderSettings, Settings};
+use imgui_baseview::{HiDpiMode, ImguiWindow, RenderSettings, Settings, WindowContext};
 
 fn main() {
     let settings = Settings {
@@ -12,6 +12,7 @@ fn main() {
         clear_color: (0.0, 0.0, 0.0),
         hidpi_mode: HiDpiMode::Default,
         render_settings: RenderSettings::default(),
+        ..Settings::default()
     };
 
     let state = ();
@@ -20,8 +21,9 @@ fn main() {
         settings,
         state,
         |_context: &mut Context, _state: &mut ()| {},
-        |run: &mut bool, ui: &Ui, _state: &mut ()| {
+        |run: &mut bool, ui: &Ui, _ctx: &mut WindowContext, _state: &mut ()| {
             ui.show_demo_window(run);
         },
-    );
+    )
+    .unwrap();
 }
@@ -1,6 +1,6 @@
 use baseview::{Size, WindowOpenOptions, WindowScalePolicy};
 use imgui::{Condition, Context, Ui, Window};
-use imgui_baseview::{HiDpiMode, ImguiWindow, RenderSettings, Settings};
+use imgui_baseview::{HiDpiMode, ImguiWindow, RenderSettings, Settings, WindowContext};
 
 fn main() {
     let settings = Settings {
@@ -12,6 +12,7 @@ fn main() {
         clear_color: (0.0, 0.0, 0.0),
         hidpi_mode: HiDpiMode::Default,
         render_settings: RenderSettings::default(),
+        ..Settings::default()
     };
 
     let state = ();
@@ -24,7 +25,7 @@ fn main() {
         |_context: &mut Context, _state: &mut ()| {},
         // Called before each frame. Here you should update the state of your
         // application and build the UI.
-        |run: &mut bool, ui: &Ui, _state: &mut ()| {
+        |run: &mut bool, ui: &Ui, _ctx: &mut WindowContext, _state: &mut ()| {
             Window::new("Hello world")
                 .opened(run)
                 .size([300.0, 110.0], Condition::FirstUseEver)
@@ -41,5 +42,6 @@ fn main() {
                     ));
                 });
         },
-    );
+    )
+    .unwrap();
 }
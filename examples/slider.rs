@@ -1,6 +1,6 @@
 use baseview::{Size, WindowOpenOptions, WindowScalePolicy};
 use imgui::*;
-use imgui_baseview::{HiDpiMode, ImguiWindow, RenderSettings, Settings};
+use imgui_baseview::{HiDpiMode, ImguiWindow, RenderSettings, Settings, WindowContext};
 
 fn main() {
     let settings = Settings {
@@ -12,13 +12,14 @@ fn main() {
         clear_color: (0.0, 0.0, 0.0),
         hidpi_mode: HiDpiMode::Default,
         render_settings: RenderSettings::default(),
+        ..Settings::default()
     };
 
     ImguiWindow::open_blocking(
         settings,
         State::default(),
         |_context: &mut Context, _state: &mut State| {},
-        |run: &mut bool, ui: &Ui, state: &mut State| {
+        |run: &mut bool, ui: &Ui, _ctx: &mut WindowContext, state: &mut State| {
             example_selector(run, ui, state);
             match state.example {
                 1 => example_1(ui, state),
@@ -26,7 +27,8 @@ fn main() {
                 _ => (),
             }
         },
-    );
+    )
+    .unwrap();
 }
 
 fn example_selector(run: &mut bool, ui: &Ui, state: &mut State) {
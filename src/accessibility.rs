@@ -0,0 +1,208 @@
+//! Expose the UI to assistive technology through AccessKit.
+//!
+//! imgui does not keep a retained tree of its items, so the items that should be visible to
+//! screen readers are annotated with `WindowContext::accessible` right after they are built.
+//! The crate turns those annotations into an AccessKit tree every frame and hands it to a
+//! platform adapter bound to the window, and keyboard focus requests coming from assistive
+//! technology are applied to the matching imgui item.
+//!
+//! The platform adapter is created by the application through
+//! `Settings::accessibility_adapter`, since it depends on the platform and on the version of
+//! the AccessKit adapter crate in use.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::num::NonZeroU128;
+use std::sync::{Arc, Mutex};
+
+use accesskit::{Action, ActionRequest, Node, NodeId, Role, Tree, TreeUpdate};
+use raw_window_handle::RawWindowHandle;
+
+/// Receives the accessibility tree of a window, usually an AccessKit platform adapter.
+pub trait AccessibilityAdapter {
+    fn update(&mut self, update: TreeUpdate);
+}
+
+/// Creates the adapter for a window.
+///
+/// * `RawWindowHandle` - The handle of the window the adapter should be bound to.
+/// * `ActionQueue` - Where the adapter should push the action requests it receives.
+pub type AdapterFactory = fn(RawWindowHandle, ActionQueue) -> Box<dyn AccessibilityAdapter>;
+
+/// Action requests from assistive technology, applied at the start of the next frame.
+#[derive(Clone, Default)]
+pub struct ActionQueue {
+    requests: Arc<Mutex<Vec<ActionRequest>>>,
+}
+
+impl ActionQueue {
+    pub fn push(&self, request: ActionRequest) {
+        self.requests.lock().unwrap().push(request);
+    }
+
+    fn take(&self) -> Vec<ActionRequest> {
+        std::mem::take(&mut *self.requests.lock().unwrap())
+    }
+}
+
+/// What kind of item an annotated item is.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AccessibleRole {
+    Button,
+    CheckBox,
+    Slider,
+    ComboBox,
+    TextInput,
+    Label,
+    Group,
+}
+
+impl AccessibleRole {
+    fn to_accesskit(self) -> Role {
+        match self {
+            AccessibleRole::Button => Role::Button,
+            AccessibleRole::CheckBox => Role::CheckBox,
+            AccessibleRole::Slider => Role::Slider,
+            AccessibleRole::ComboBox => Role::PopupButton,
+            AccessibleRole::TextInput => Role::TextField,
+            AccessibleRole::Label => Role::StaticText,
+            AccessibleRole::Group => Role::Group,
+        }
+    }
+
+    fn is_focusable(self) -> bool {
+        !matches!(self, AccessibleRole::Label | AccessibleRole::Group)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Item {
+    id: NodeId,
+    role: AccessibleRole,
+    label: String,
+    value: Option<String>,
+    /// The bounds in physical pixels.
+    rect: [f64; 4],
+    focused: bool,
+}
+
+const ROOT_ID: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(1) });
+
+pub(crate) struct Accessibility {
+    adapter: Box<dyn AccessibilityAdapter>,
+    actions: ActionQueue,
+    items: Vec<Item>,
+    last_items: Option<Vec<Item>>,
+    pending_focus: Option<NodeId>,
+    hidpi_factor: f64,
+}
+
+impl Accessibility {
+    pub fn new(window: RawWindowHandle, factory: AdapterFactory) -> Self {
+        let actions = ActionQueue::default();
+
+        Self {
+            adapter: factory(window, actions.clone()),
+            actions,
+            items: Vec::new(),
+            last_items: None,
+            pending_focus: None,
+            hidpi_factor: 1.0,
+        }
+    }
+
+    pub fn begin_frame(&mut self, hidpi_factor: f64) {
+        self.items.clear();
+        self.hidpi_factor = hidpi_factor;
+
+        for request in self.actions.take() {
+            if request.action == Action::Focus {
+                self.pending_focus = Some(request.target);
+            }
+        }
+    }
+
+    /// Annotate the item that was just built.
+    pub fn item(&mut self, ui: &imgui::Ui, role: AccessibleRole, label: &str, value: Option<&str>) {
+        // The id has to stay the same across frames for focus tracking to work, so derive it
+        // from the label and the number of earlier items with the same label.
+        let occurrence = self.items.iter().filter(|item| item.label == label).count();
+        let mut hasher = DefaultHasher::new();
+        label.hash(&mut hasher);
+        occurrence.hash(&mut hasher);
+        let hash = hasher.finish() as u128;
+        let id = NodeId(NonZeroU128::new(hash.max(2)).unwrap());
+
+        if self.pending_focus == Some(id) {
+            ui.set_keyboard_focus_here_with_offset(imgui::FocusedWidget::Previous);
+            self.pending_focus = None;
+        }
+
+        let scale = self.hidpi_factor;
+        let min = ui.item_rect_min();
+        let max = ui.item_rect_max();
+
+        self.items.push(Item {
+            id,
+            role,
+            label: label.to_string(),
+            value: value.map(str::to_string),
+            rect: [
+                min[0] as f64 * scale,
+                min[1] as f64 * scale,
+                max[0] as f64 * scale,
+                max[1] as f64 * scale,
+            ],
+            focused: ui.is_item_focused(),
+        });
+    }
+
+    /// Publish the tree if it changed since the last frame.
+    pub fn end_frame(&mut self) {
+        if self.last_items.as_ref() == Some(&self.items) {
+            return;
+        }
+
+        let mut nodes = Vec::with_capacity(self.items.len() + 1);
+        nodes.push((
+            ROOT_ID,
+            Arc::new(Node {
+                role: Role::Window,
+                children: self.items.iter().map(|item| item.id).collect(),
+                ..Node::default()
+            }),
+        ));
+
+        let mut focus = None;
+        for item in &self.items {
+            if item.focused {
+                focus = Some(item.id);
+            }
+
+            nodes.push((
+                item.id,
+                Arc::new(Node {
+                    role: item.role.to_accesskit(),
+                    bounds: Some(accesskit::kurbo::Rect::new(
+                        item.rect[0],
+                        item.rect[1],
+                        item.rect[2],
+                        item.rect[3],
+                    )),
+                    name: Some(item.label.clone().into_boxed_str()),
+                    value: item.value.clone().map(String::into_boxed_str),
+                    focusable: item.role.is_focusable(),
+                    ..Node::default()
+                }),
+            ));
+        }
+
+        self.adapter.update(TreeUpdate {
+            nodes,
+            tree: Some(Tree::new(ROOT_ID)),
+            focus: focus.or(Some(ROOT_ID)),
+        });
+
+        self.last_items = Some(self.items.clone());
+    }
+}
@@ -0,0 +1,296 @@
+//! Waiting for the imgui context.
+//!
+//! imgui has one current context per process, so a window can only handle an event or build
+//! a frame while no other window of the process is doing the same. Frames of different
+//! windows can't run in parallel, even on different threads. With `ContextPolicy::Queue`
+//! the windows of the process take turns in the order they asked for the context, sleeping
+//! while they wait, so many instances don't starve each other.
+//!
+//! When another window holds the context for too long, for example because it deadlocked in
+//! its update closure, the waiting window gives up after `Settings::context_timeout` and
+//! skips the event or frame, instead of freezing the GUI thread of the host.
+//! `Settings::on_context_timeout` is called with a report of who held the context.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Called on the thread of a window that gave up waiting for the imgui context.
+pub type TimeoutCallback = Arc<dyn Fn(&TimeoutReport) + Send + Sync>;
+
+const TRY_UNLOCK_WAIT_DURATION: Duration = Duration::from_micros(10);
+
+/// How the windows of the process share the imgui context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ContextPolicy {
+    /// Try to take the context until it is free. A window that tries at the right moment can
+    /// get it before one that has waited longer.
+    Poll,
+    /// Wait in line for the context, which windows get in the order they asked for it.
+    Queue,
+}
+
+impl Default for ContextPolicy {
+    fn default() -> Self {
+        ContextPolicy::Queue
+    }
+}
+
+/// The window that has the imgui context.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextHolder {
+    /// The title of the window.
+    pub window: String,
+    /// How long it has had the context.
+    pub held_for: Duration,
+}
+
+/// What a window that gave up waiting for the imgui context was waiting on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeoutReport {
+    /// The title of the waiting window.
+    pub window: String,
+    pub waited: Duration,
+    /// The window that has the context, or `None` if it was made current outside of this
+    /// crate.
+    pub holder: Option<ContextHolder>,
+}
+
+struct Holder {
+    id: u64,
+    window: String,
+    since: Instant,
+}
+
+static HOLDER: Mutex<Option<Holder>> = Mutex::new(None);
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Taken by the tests that use an imgui context, so they don't run at the same time.
+#[cfg(test)]
+pub(crate) static TEST_CONTEXT: Mutex<()> = Mutex::new(());
+
+/// The tickets of the windows waiting with `ContextPolicy::Queue`.
+struct Queue {
+    next_ticket: u64,
+    serving: u64,
+    /// Tickets of windows that gave up before their turn.
+    abandoned: Vec<u64>,
+}
+
+impl Queue {
+    /// Give up a ticket or end its turn.
+    fn finish(&mut self, ticket: u64) {
+        if ticket != self.serving {
+            self.abandoned.push(ticket);
+            return;
+        }
+
+        self.serving += 1;
+        while let Some(i) = self.abandoned.iter().position(|t| *t == self.serving) {
+            self.abandoned.swap_remove(i);
+            self.serving += 1;
+        }
+    }
+}
+
+static QUEUE: Mutex<Queue> = Mutex::new(Queue {
+    next_ticket: 0,
+    serving: 0,
+    abandoned: Vec::new(),
+});
+static QUEUE_TURN: Condvar = Condvar::new();
+
+/// The turn of a window in the queue, which ends when it is dropped.
+struct Turn(Option<u64>);
+
+impl Drop for Turn {
+    fn drop(&mut self) {
+        if let Some(ticket) = self.0 {
+            QUEUE.lock().unwrap().finish(ticket);
+            QUEUE_TURN.notify_all();
+        }
+    }
+}
+
+/// The window that has the imgui context right now.
+pub fn context_holder() -> Option<ContextHolder> {
+    HOLDER.lock().unwrap().as_ref().map(|holder| ContextHolder {
+        window: holder.window.clone(),
+        held_for: holder.since.elapsed(),
+    })
+}
+
+/// How long a window has been waiting.
+struct Waiting {
+    start: Instant,
+    reported: Instant,
+    timeout: Option<Duration>,
+}
+
+impl Waiting {
+    fn new(timeout: Option<Duration>) -> Self {
+        let start = Instant::now();
+        Self {
+            start,
+            reported: start,
+            timeout,
+        }
+    }
+
+    /// The time until the timeout passes again.
+    fn remaining(&self) -> Option<Duration> {
+        self.timeout
+            .map(|timeout| timeout.saturating_sub(self.reported.elapsed()))
+    }
+
+    /// Whether the timeout passed since the last time it did.
+    fn timed_out(&mut self) -> bool {
+        if self.remaining() == Some(Duration::from_secs(0)) {
+            self.reported = Instant::now();
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// How a window waits for the context.
+pub(crate) struct Activation {
+    window: String,
+    policy: ContextPolicy,
+    timeout: Option<Duration>,
+    on_timeout: Option<TimeoutCallback>,
+}
+
+impl Activation {
+    pub fn new(
+        window: String,
+        policy: ContextPolicy,
+        timeout: Option<Duration>,
+        on_timeout: Option<TimeoutCallback>,
+    ) -> Self {
+        Self {
+            window,
+            policy,
+            timeout,
+            on_timeout,
+        }
+    }
+
+    /// Run `f` with the context once it is free. Gives up when the timeout passes, and
+    /// returns the context without calling `f`.
+    pub fn run<F>(
+        &self,
+        sus_context: imgui::SuspendedContext,
+        f: F,
+    ) -> Result<imgui::SuspendedContext, imgui::SuspendedContext>
+    where
+        F: FnOnce(imgui::Context) -> imgui::SuspendedContext,
+    {
+        let (context, turn) = self.activate(sus_context, true)?;
+        Ok(self.hold(context, turn, f))
+    }
+
+    /// Run `f` with the context, waiting for as long as it takes. Every time the timeout
+    /// passes it is reported.
+    pub fn wait<F>(&self, sus_context: imgui::SuspendedContext, f: F) -> imgui::SuspendedContext
+    where
+        F: FnOnce(imgui::Context) -> imgui::SuspendedContext,
+    {
+        match self.activate(sus_context, false) {
+            Ok((context, turn)) => self.hold(context, turn, f),
+            Err(_) => unreachable!("waiting for the context without giving up"),
+        }
+    }
+
+    /// Wait for the turn of the window and the context, reporting every time the timeout
+    /// passes. Gives up the first time with `give_up`.
+    fn activate(
+        &self,
+        mut sus_context: imgui::SuspendedContext,
+        give_up: bool,
+    ) -> Result<(imgui::Context, Turn), imgui::SuspendedContext> {
+        let mut waiting = Waiting::new(self.timeout);
+
+        let turn = match self.policy {
+            ContextPolicy::Poll => Turn(None),
+            ContextPolicy::Queue => {
+                let mut queue = QUEUE.lock().unwrap();
+                let ticket = queue.next_ticket;
+                queue.next_ticket += 1;
+                while queue.serving != ticket {
+                    queue = match waiting.remaining() {
+                        Some(remaining) => QUEUE_TURN.wait_timeout(queue, remaining).unwrap().0,
+                        None => QUEUE_TURN.wait(queue).unwrap(),
+                    };
+                    if queue.serving != ticket && waiting.timed_out() {
+                        if give_up {
+                            queue.finish(ticket);
+                        }
+                        // The callback runs without blocking the queue.
+                        drop(queue);
+                        self.report(waiting.start.elapsed());
+                        if give_up {
+                            return Err(sus_context);
+                        }
+                        queue = QUEUE.lock().unwrap();
+                    }
+                }
+                Turn(Some(ticket))
+            }
+        };
+
+        // In the queue this only waits for contexts made current outside of this crate.
+        loop {
+            match sus_context.activate() {
+                Ok(context) => return Ok((context, turn)),
+                Err(new_sus_context) => sus_context = new_sus_context,
+            }
+
+            if waiting.timed_out() {
+                self.report(waiting.start.elapsed());
+                if give_up {
+                    return Err(sus_context);
+                }
+            }
+            thread::sleep(TRY_UNLOCK_WAIT_DURATION);
+        }
+    }
+
+    fn hold<F>(&self, context: imgui::Context, turn: Turn, f: F) -> imgui::SuspendedContext
+    where
+        F: FnOnce(imgui::Context) -> imgui::SuspendedContext,
+    {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        *HOLDER.lock().unwrap() = Some(Holder {
+            id,
+            window: self.window.clone(),
+            since: Instant::now(),
+        });
+
+        let sus_context = f(context);
+
+        // Another window may have taken the context as soon as it was suspended.
+        {
+            let mut holder = HOLDER.lock().unwrap();
+            if holder.as_ref().map(|holder| holder.id) == Some(id) {
+                *holder = None;
+            }
+        }
+        drop(turn);
+
+        sus_context
+    }
+
+    fn report(&self, waited: Duration) {
+        if let Some(on_timeout) = &self.on_timeout {
+            on_timeout(&TimeoutReport {
+                window: self.window.clone(),
+                waited,
+                holder: context_holder(),
+            });
+        }
+    }
+}
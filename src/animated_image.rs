@@ -0,0 +1,244 @@
+//! Playback of animated GIFs and frame sequences, for tutorial animations and the like.
+//!
+//! The frames are decoded up front and uploaded into one texture each. The animation then
+//! advances with the delta time of the frames of the window, so it follows
+//! `Settings::clock`.
+
+use std::time::Duration;
+
+use crate::WindowContext;
+
+/// The delay used for frames without one, like browsers do.
+const DEFAULT_DELAY: Duration = Duration::from_millis(100);
+
+/// A decoded frame with the RGBA8 pixels of the whole image.
+#[derive(Debug, Clone)]
+pub struct ImageFrame {
+    pub rgba: Vec<u8>,
+    /// How long the frame is shown.
+    pub delay: Duration,
+}
+
+/// The decoded frames of an animation, all of the same size.
+#[derive(Debug, Clone)]
+pub struct ImageSequence {
+    pub width: u32,
+    pub height: u32,
+    pub frames: Vec<ImageFrame>,
+}
+
+impl ImageSequence {
+    /// Decode an animated GIF, compositing every frame onto the previous ones.
+    pub fn from_gif(bytes: &[u8]) -> Result<Self, gif::DecodingError> {
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = options.read_info(bytes)?;
+
+        let width = decoder.width() as usize;
+        let height = decoder.height() as usize;
+        let mut canvas = vec![0u8; width * height * 4];
+        let mut frames = Vec::new();
+
+        while let Some(frame) = decoder.read_next_frame()? {
+            let previous = if frame.dispose == gif::DisposalMethod::Previous {
+                Some(canvas.clone())
+            } else {
+                None
+            };
+
+            let rows = (frame.top as usize..height).take(frame.height as usize);
+            for (y, row) in rows.enumerate() {
+                let columns = (frame.left as usize..width).take(frame.width as usize);
+                for (x, column) in columns.enumerate() {
+                    let source = (y * frame.width as usize + x) * 4;
+                    // Transparent pixels show what is below.
+                    if frame.buffer[source + 3] != 0 {
+                        let target = (row * width + column) * 4;
+                        canvas[target..target + 4]
+                            .copy_from_slice(&frame.buffer[source..source + 4]);
+                    }
+                }
+            }
+
+            // The delay is in hundredths of a second, and tiny delays are not honored.
+            let delay = if frame.delay < 2 {
+                DEFAULT_DELAY
+            } else {
+                Duration::from_millis(frame.delay as u64 * 10)
+            };
+            frames.push(ImageFrame {
+                rgba: canvas.clone(),
+                delay,
+            });
+
+            match frame.dispose {
+                gif::DisposalMethod::Background => {
+                    let rows = (frame.top as usize..height).take(frame.height as usize);
+                    for row in rows {
+                        let start = (row * width + frame.left as usize).min(row * width + width);
+                        let end = (start + frame.width as usize).min(row * width + width);
+                        for byte in &mut canvas[start * 4..end * 4] {
+                            *byte = 0;
+                        }
+                    }
+                }
+                gif::DisposalMethod::Previous => canvas = previous.unwrap(),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            width: width as u32,
+            height: height as u32,
+            frames,
+        })
+    }
+}
+
+/// An animation with a texture per frame.
+///
+/// Call `image` every frame to show it and advance it, or `update` and `texture_id` to draw
+/// it some other way. Delete the textures with `delete` when the animation is no longer
+/// needed.
+#[derive(Debug)]
+pub struct AnimatedImage {
+    size: [u32; 2],
+    frames: Vec<(imgui::TextureId, Duration)>,
+    duration: Duration,
+    position: Duration,
+    playing: bool,
+    /// Start over at the end instead of stopping.
+    pub looping: bool,
+}
+
+impl AnimatedImage {
+    /// Upload the frames of a sequence. The animation starts playing right away, unless
+    /// `WindowContext::prefers_reduced_motion`, when it shows the first frame until `play`
+    /// is called.
+    pub fn new(ctx: &mut WindowContext, sequence: &ImageSequence) -> Self {
+        let frames: Vec<_> = sequence
+            .frames
+            .iter()
+            .map(|frame| {
+                let texture =
+                    ctx.renderer
+                        .create_texture(sequence.width, sequence.height, &frame.rgba);
+                (texture, frame.delay)
+            })
+            .collect();
+
+        Self {
+            size: [sequence.width, sequence.height],
+            duration: frames.iter().map(|(_, delay)| *delay).sum(),
+            frames,
+            position: Duration::default(),
+            playing: !ctx.prefers_reduced_motion(),
+            looping: true,
+        }
+    }
+
+    /// Decode and upload an animated GIF.
+    pub fn from_gif(ctx: &mut WindowContext, bytes: &[u8]) -> Result<Self, gif::DecodingError> {
+        Ok(Self::new(ctx, &ImageSequence::from_gif(bytes)?))
+    }
+
+    /// The size of the frames in pixels.
+    pub fn size(&self) -> [u32; 2] {
+        self.size
+    }
+
+    pub fn play(&mut self) {
+        self.playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// The total length of the animation.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    pub fn position(&self) -> Duration {
+        self.position
+    }
+
+    /// Jump to a time in the animation.
+    pub fn seek(&mut self, position: Duration) {
+        self.position = position.min(self.duration);
+    }
+
+    /// The number of frames.
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// The texture of a frame.
+    pub fn frame_texture(&self, frame: usize) -> Option<imgui::TextureId> {
+        self.frames.get(frame).map(|(texture, _)| *texture)
+    }
+
+    /// The index of the frame at the current position.
+    pub fn current_frame(&self) -> usize {
+        let mut start = Duration::default();
+        for (i, (_, delay)) in self.frames.iter().enumerate() {
+            start += *delay;
+            if self.position < start {
+                return i;
+            }
+        }
+        self.frames.len().saturating_sub(1)
+    }
+
+    /// The texture of the frame at the current position.
+    pub fn texture_id(&self) -> Option<imgui::TextureId> {
+        self.frame_texture(self.current_frame())
+    }
+
+    /// Advance the animation by the delta time of the frame.
+    pub fn update(&mut self, ui: &imgui::Ui) {
+        if !self.playing || self.duration == Duration::default() {
+            return;
+        }
+
+        self.position += Duration::from_secs_f32(ui.io().delta_time);
+        if self.position >= self.duration {
+            if self.looping {
+                let nanos = self.position.as_nanos() % self.duration.as_nanos();
+                self.position = Duration::from_nanos(nanos as u64);
+            } else {
+                self.position = self.duration;
+                self.playing = false;
+            }
+        }
+    }
+
+    /// Advance the animation and show the current frame at `size`.
+    pub fn image(&mut self, ui: &imgui::Ui, size: [f32; 2]) {
+        self.update(ui);
+        match self.texture_id() {
+            Some(texture) => imgui::Image::new(texture, size).build(ui),
+            None => ui.dummy(size),
+        }
+    }
+
+    /// Use the new texture ids after the context of the window was recreated. Call this from
+    /// `WindowContext::on_context_restored`.
+    pub fn restore(&mut self, ctx: &WindowContext) {
+        for (texture, _) in &mut self.frames {
+            *texture = ctx.restored_texture(*texture);
+        }
+    }
+
+    /// Delete the textures of the frames.
+    pub fn delete(self, ctx: &mut WindowContext) {
+        for (texture, _) in self.frames {
+            ctx.renderer.delete_texture(texture);
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Resizing the window to fit its content.
+//!
+//! Plugins that show or hide whole sections, like an FX rack that expands, would otherwise
+//! leave the user to resize the editor. With `Settings::auto_resize` the update closure
+//! marks the end of the content of its main window with `WindowContext::fit_to_item`, and
+//! after the frame the window resizes itself to fit it, or asks an embedded host to with
+//! `UiEvent::RequestResize`. The size has to stay the same for the debounce time first, so
+//! a section that animates open resizes the window once.
+
+use std::time::Duration;
+
+use crate::settings::AutoResize;
+
+pub(crate) struct AutoResizer {
+    settings: AutoResize,
+    /// The size the content wants, and since when it wants it.
+    pending: Option<([f32; 2], Duration)>,
+    /// The last size that was asked for, so it isn't asked for again while the host resizes.
+    requested: Option<[f32; 2]>,
+}
+
+impl AutoResizer {
+    pub fn new(settings: AutoResize) -> Self {
+        Self {
+            settings,
+            pending: None,
+            requested: None,
+        }
+    }
+
+    /// The size to resize the window to after a frame, in imgui's logical pixels, once the
+    /// size of the content settled.
+    pub fn update(
+        &mut self,
+        content: Option<[f32; 2]>,
+        display_size: [f32; 2],
+        now: Duration,
+    ) -> Option<[f32; 2]> {
+        let content = content?;
+        let mut size = content;
+        for i in 0..2 {
+            if let Some(max) = self.settings.max {
+                size[i] = size[i].min(max[i]);
+            }
+            if let Some(min) = self.settings.min {
+                size[i] = size[i].max(min[i]);
+            }
+            size[i] = size[i].round();
+        }
+
+        let fits = (0..2).all(|i| (size[i] - display_size[i]).abs() < 1.0);
+        if fits || self.requested == Some(size) {
+            self.pending = None;
+            return None;
+        }
+
+        let since = match self.pending {
+            Some((pending, since)) if pending == size => since,
+            _ => {
+                self.pending = Some((size, now));
+                now
+            }
+        };
+        if now.saturating_sub(since) < self.settings.debounce {
+            return None;
+        }
+
+        self.pending = None;
+        self.requested = Some(size);
+        Some(size)
+    }
+
+    /// Whether the content has a new size that is waiting for the debounce time, so another
+    /// frame is needed.
+    pub fn is_settling(&self) -> bool {
+        self.pending.is_some()
+    }
+}
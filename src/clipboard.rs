@@ -0,0 +1,253 @@
+//! The primary selection of X11, for pasting with the middle mouse button.
+//!
+//! On Linux, text that is selected can be pasted into another text field by clicking it with
+//! the middle mouse button. imgui only knows one clipboard and doesn't tell what is selected
+//! in a text input, so with `Settings::x11_primary_selection` the window gives imgui a
+//! clipboard that can be pointed at the selection, and sends imgui its copy and paste
+//! shortcuts for a frame: a copy after text is selected, and a paste after a text input is
+//! clicked with the middle button, which imgui sees as a left click that places the cursor.
+//!
+//! Without a selection imgui copies all of the text, so only what is selected by dragging,
+//! double-clicking or with Shift is copied. The clipboard itself stays in the window.
+//!
+//! The owner of the selection can take a while to send it, so a paste asks for it when the
+//! middle button is released and is sent to imgui in the first frame after it arrived.
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+use keyboard_types::{Code, KeyState, Modifiers};
+
+use crate::platform::PrimarySelection;
+
+/// How many frames a copy or paste waits for the text input to become active.
+const ACTIVATION_FRAMES: u32 = 3;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Target {
+    Clipboard,
+    Primary,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Action {
+    Copy,
+    Paste,
+}
+
+struct Backend {
+    target: Rc<Cell<Target>>,
+    primary: Rc<PrimarySelection>,
+    /// The text of the selection that arrived for the paste.
+    pasted: Rc<RefCell<Option<String>>>,
+    clipboard: Option<String>,
+}
+
+impl imgui::ClipboardBackend for Backend {
+    fn get(&mut self) -> Option<String> {
+        match self.target.get() {
+            Target::Clipboard => self.clipboard.clone(),
+            Target::Primary => self.pasted.borrow_mut().take(),
+        }
+    }
+
+    fn set(&mut self, value: &str) {
+        match self.target.get() {
+            Target::Clipboard => self.clipboard = Some(value.to_owned()),
+            Target::Primary => self.primary.set(value.to_owned()),
+        }
+    }
+}
+
+/// The shortcut that was sent to imgui, to release in the next frame.
+struct Sent {
+    key: usize,
+    modifiers: [bool; 4],
+}
+
+pub(crate) struct PrimaryClipboard {
+    target: Rc<Cell<Target>>,
+    primary: Rc<PrimarySelection>,
+    pasted: Rc<RefCell<Option<String>>>,
+    /// The selection that was asked for, for a paste once it arrives.
+    requested: Option<Receiver<Option<String>>>,
+    pending: Option<(Action, u32)>,
+    sent: Option<Sent>,
+    /// The modifiers of the last key event, which win over the ones saved in `sent`.
+    modifiers: Option<Modifiers>,
+    /// Whether imgui showed the text cursor in the last frame, so the mouse is over a text input.
+    over_text: bool,
+    /// Whether the middle button is held as a left click.
+    pasting: bool,
+    mouse: baseview::Point,
+    press: Option<(baseview::Point, Instant)>,
+    last_click: Option<Instant>,
+    /// The double-click time and drag threshold of imgui, so text is selected the same way.
+    double_click_time: Duration,
+    drag_distance: f64,
+}
+
+impl PrimaryClipboard {
+    /// Give `context` the clipboard, on the platforms with a primary selection.
+    pub fn install(context: &mut imgui::Context) -> Option<Self> {
+        let primary = Rc::new(PrimarySelection::new()?);
+        let pasted = Rc::new(RefCell::new(None));
+        let io = context.io();
+        let double_click_time = Duration::from_secs_f32(io.mouse_double_click_time.max(0.0));
+        let drag_distance = io.mouse_drag_threshold as f64;
+        let target = Rc::new(Cell::new(Target::Clipboard));
+        context.set_clipboard_backend(Box::new(Backend {
+            target: target.clone(),
+            primary: primary.clone(),
+            pasted: pasted.clone(),
+            clipboard: None,
+        }));
+
+        Some(Self {
+            target,
+            primary,
+            pasted,
+            requested: None,
+            pending: None,
+            sent: None,
+            modifiers: None,
+            over_text: false,
+            pasting: false,
+            mouse: baseview::Point::new(0.0, 0.0),
+            press: None,
+            last_click: None,
+            double_click_time,
+            drag_distance,
+        })
+    }
+
+    /// Turn a middle click on a text input into a left click, and note what selects text.
+    pub fn map_event(&mut self, event: baseview::Event) -> baseview::Event {
+        use baseview::{Event, MouseButton, MouseEvent};
+
+        match &event {
+            Event::Mouse(MouseEvent::CursorMoved { position }) => self.mouse = *position,
+            Event::Mouse(MouseEvent::ButtonPressed(MouseButton::Middle)) if self.over_text => {
+                self.pasting = true;
+                return Event::Mouse(MouseEvent::ButtonPressed(MouseButton::Left));
+            }
+            Event::Mouse(MouseEvent::ButtonReleased(MouseButton::Middle)) if self.pasting => {
+                self.pasting = false;
+                self.requested = self.primary.request();
+                return Event::Mouse(MouseEvent::ButtonReleased(MouseButton::Left));
+            }
+            Event::Mouse(MouseEvent::ButtonPressed(MouseButton::Left)) if self.over_text => {
+                let now = Instant::now();
+                let double_click = self.last_click.map_or(false, |last| {
+                    now.duration_since(last) < self.double_click_time
+                });
+                if double_click {
+                    self.pending = Some((Action::Copy, ACTIVATION_FRAMES));
+                }
+                self.press = Some((self.mouse, now));
+                self.last_click = Some(now);
+            }
+            Event::Mouse(MouseEvent::ButtonReleased(MouseButton::Left)) => {
+                if let Some((start, _)) = self.press.take() {
+                    let moved = (self.mouse.x - start.x).hypot(self.mouse.y - start.y);
+                    if moved >= self.drag_distance {
+                        self.pending = Some((Action::Copy, ACTIVATION_FRAMES));
+                    }
+                }
+            }
+            Event::Keyboard(event) => {
+                self.modifiers = Some(event.modifiers);
+                let selects = match event.code {
+                    Code::ArrowLeft
+                    | Code::ArrowRight
+                    | Code::ArrowUp
+                    | Code::ArrowDown
+                    | Code::Home
+                    | Code::End
+                    | Code::PageUp
+                    | Code::PageDown => event.modifiers.contains(Modifiers::SHIFT),
+                    Code::KeyA => event.modifiers.contains(Modifiers::CONTROL),
+                    _ => false,
+                };
+                if selects && event.state == KeyState::Down {
+                    self.pending = Some((Action::Copy, ACTIVATION_FRAMES));
+                }
+            }
+            _ => {}
+        }
+        event
+    }
+
+    /// Whether a paste waits for the selection, so frames keep coming until it arrives.
+    pub fn is_waiting(&self) -> bool {
+        self.requested.is_some()
+    }
+
+    /// Release the shortcut of the last frame and send the next one, once a text input is
+    /// active.
+    pub fn begin_frame(&mut self, io: &mut imgui::Io) {
+        if let Some(requested) = &self.requested {
+            match requested.try_recv() {
+                Ok(text) => {
+                    self.requested = None;
+                    if text.is_some() {
+                        *self.pasted.borrow_mut() = text;
+                        self.pending = Some((Action::Paste, ACTIVATION_FRAMES));
+                    }
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => self.requested = None,
+            }
+        }
+
+        self.target.set(Target::Clipboard);
+        if let Some(sent) = self.sent.take() {
+            io.keys_down[sent.key] = false;
+            let modifiers = match self.modifiers {
+                Some(modifiers) => [
+                    modifiers.contains(Modifiers::CONTROL),
+                    modifiers.contains(Modifiers::SHIFT),
+                    modifiers.contains(Modifiers::ALT),
+                    modifiers.contains(Modifiers::META),
+                ],
+                None => sent.modifiers,
+            };
+            io.key_ctrl = modifiers[0];
+            io.key_shift = modifiers[1];
+            io.key_alt = modifiers[2];
+            io.key_super = modifiers[3];
+        }
+
+        let action = match self.pending.take() {
+            Some((action, _)) if io.want_text_input => action,
+            Some((action, frames)) => {
+                self.pending = (frames > 1).then(|| (action, frames - 1));
+                return;
+            }
+            None => return,
+        };
+
+        let key = match action {
+            Action::Copy => io[imgui::Key::C],
+            Action::Paste => io[imgui::Key::V],
+        } as usize;
+        self.sent = Some(Sent {
+            key,
+            modifiers: [io.key_ctrl, io.key_shift, io.key_alt, io.key_super],
+        });
+        self.modifiers = None;
+        self.target.set(Target::Primary);
+
+        io.keys_down[key] = true;
+        io.key_ctrl = !io.config_mac_os_behaviors;
+        io.key_super = io.config_mac_os_behaviors;
+        io.key_shift = false;
+        io.key_alt = false;
+    }
+
+    pub fn end_frame(&mut self, ui: &imgui::Ui) {
+        self.over_text = ui.mouse_cursor() == Some(imgui::MouseCursor::TextInput);
+    }
+}
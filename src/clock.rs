@@ -0,0 +1,165 @@
+//! Time sources for the frame clock.
+//!
+//! The delta time given to imgui comes from a `Clock`. The default follows real time, while
+//! a `ManualClock` makes animations and double-click timing deterministic in tests.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A source of time for the frame clock.
+pub trait Clock: Send + Sync {
+    /// The time elapsed since an arbitrary, fixed point in the past.
+    fn now(&self) -> Duration;
+}
+
+/// A clock following real time.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// A clock that only moves when told to.
+///
+/// This is a cheap handle, so keep a clone to advance the clock while passing another one to
+/// `Settings::clock`.
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    time: Arc<Mutex<Duration>>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Move the clock forward.
+    pub fn advance(&self, duration: Duration) {
+        *self.time.lock().unwrap() += duration;
+    }
+
+    /// Set the current time of the clock.
+    pub fn set(&self, time: Duration) {
+        *self.time.lock().unwrap() = time;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        *self.time.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activation::TEST_CONTEXT;
+
+    /// Frames of an imgui context with the delta times of a `ManualClock`, like a window.
+    struct Frames {
+        context: imgui::Context,
+        clock: ManualClock,
+        last_frame: Duration,
+    }
+
+    impl Frames {
+        fn new(clock: ManualClock) -> Self {
+            let mut context = imgui::Context::create();
+            context.set_ini_filename(None);
+            context.fonts().build_rgba32_texture();
+            context.io_mut().display_size = [200.0, 200.0];
+            context.io_mut().mouse_pos = [10.0, 10.0];
+            let last_frame = clock.now();
+            Self {
+                context,
+                clock,
+                last_frame,
+            }
+        }
+
+        /// Run a frame with the left button held or not, and return its delta time and
+        /// whether it was a double-click.
+        fn frame(&mut self, down: bool) -> (Duration, bool) {
+            let now = self.clock.now();
+            let delta_time = now.saturating_sub(self.last_frame);
+            self.last_frame = now;
+
+            let io = self.context.io_mut();
+            io.update_delta_time(delta_time);
+            io.mouse_down[0] = down;
+            let ui = self.context.frame();
+            (
+                delta_time,
+                ui.is_mouse_double_clicked(imgui::MouseButton::Left),
+            )
+        }
+    }
+
+    #[test]
+    fn manual_clock_only_moves_when_told() {
+        let clock = ManualClock::new();
+        let shared = clock.clone();
+        assert_eq!(clock.now(), Duration::from_secs(0));
+
+        shared.advance(Duration::from_millis(16));
+        assert_eq!(clock.now(), Duration::from_millis(16));
+        assert_eq!(clock.now(), Duration::from_millis(16));
+
+        shared.set(Duration::from_secs(5));
+        assert_eq!(clock.now(), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn delta_time_follows_the_clock() {
+        let _imgui = TEST_CONTEXT.lock().unwrap_or_else(|e| e.into_inner());
+        let clock = ManualClock::new();
+        let mut frames = Frames::new(clock.clone());
+
+        clock.advance(Duration::from_millis(16));
+        assert_eq!(frames.frame(false).0, Duration::from_millis(16));
+        clock.advance(Duration::from_millis(40));
+        assert_eq!(frames.frame(false).0, Duration::from_millis(40));
+        assert!((frames.context.io().delta_time - 0.04).abs() < 1e-6);
+    }
+
+    #[test]
+    fn double_click_by_the_clock() {
+        let _imgui = TEST_CONTEXT.lock().unwrap_or_else(|e| e.into_inner());
+        let clock = ManualClock::new();
+        let mut frames = Frames::new(clock.clone());
+        let double_click_time =
+            Duration::from_secs_f32(frames.context.io().mouse_double_click_time);
+        let mut click = |after: Duration| {
+            clock.advance(after);
+            let (_, double_clicked) = frames.frame(true);
+            clock.advance(Duration::from_millis(16));
+            frames.frame(false);
+            double_clicked
+        };
+
+        click(Duration::from_millis(16));
+        assert!(click(double_click_time / 3));
+        // Too slow for a double-click.
+        click(double_click_time * 2);
+        assert!(!click(double_click_time * 2));
+    }
+}
@@ -0,0 +1,98 @@
+//! Color space conversions.
+//!
+//! imgui colors are sRGB with straight alpha. Blending them directly makes transitions go
+//! through muddy, dark intermediate colors, so interpolate in OKLab instead, which is
+//! designed so that equal steps look equally different.
+
+/// Convert an sRGB channel from 0 to 1 to linear light.
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Convert a linear channel from 0 to 1 to sRGB.
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// A color in the OKLab color space.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Oklab {
+    /// The perceived lightness, from 0 to 1.
+    pub l: f32,
+    /// Green to red.
+    pub a: f32,
+    /// Blue to yellow.
+    pub b: f32,
+}
+
+impl Oklab {
+    pub fn from_linear_rgb(rgb: [f32; 3]) -> Self {
+        let [r, g, b] = rgb;
+        let l = 0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b;
+        let m = 0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b;
+        let s = 0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b;
+
+        let (l, m, s) = (l.cbrt(), m.cbrt(), s.cbrt());
+
+        Self {
+            l: 0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+            a: 1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+            b: 0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+        }
+    }
+
+    pub fn to_linear_rgb(self) -> [f32; 3] {
+        let l = self.l + 0.396_337_78 * self.a + 0.215_803_76 * self.b;
+        let m = self.l - 0.105_561_346 * self.a - 0.063_854_17 * self.b;
+        let s = self.l - 0.089_484_18 * self.a - 1.291_485_5 * self.b;
+
+        let (l, m, s) = (l * l * l, m * m * m, s * s * s);
+
+        [
+            4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s,
+            -1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s,
+            -0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s,
+        ]
+    }
+
+    pub fn from_srgb(rgb: [f32; 3]) -> Self {
+        Self::from_linear_rgb([
+            srgb_to_linear(rgb[0]),
+            srgb_to_linear(rgb[1]),
+            srgb_to_linear(rgb[2]),
+        ])
+    }
+
+    /// Convert to sRGB, clamping colors that are out of gamut.
+    pub fn to_srgb(self) -> [f32; 3] {
+        let [r, g, b] = self.to_linear_rgb();
+        let channel = |value: f32| linear_to_srgb(value.max(0.0).min(1.0));
+        [channel(r), channel(g), channel(b)]
+    }
+
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        Self {
+            l: self.l + (other.l - self.l) * t,
+            a: self.a + (other.a - self.a) * t,
+            b: self.b + (other.b - self.b) * t,
+        }
+    }
+}
+
+/// Interpolate between two imgui colors in OKLab. The alpha is interpolated linearly.
+pub fn lerp(from: [f32; 4], to: [f32; 4], t: f32) -> [f32; 4] {
+    let t = t.max(0.0).min(1.0);
+    let start = Oklab::from_srgb([from[0], from[1], from[2]]);
+    let end = Oklab::from_srgb([to[0], to[1], to[2]]);
+    let [r, g, b] = start.lerp(end, t).to_srgb();
+
+    [r, g, b, from[3] + (to[3] - from[3]) * t]
+}
@@ -0,0 +1,745 @@
+//! Access to the window from the update closure.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use raw_window_handle::RawWindowHandle;
+
+use crate::focus_ring::FocusRing;
+use crate::fonts::FontAtlas;
+use crate::frame::{DrawStats, FrameHooks, FrameWatchdog};
+use crate::glass::{Backdrop, GlassStyle};
+use crate::handle::Handle;
+use crate::help::Help;
+use crate::instances::Instance;
+use crate::interaction::{Drag, Interaction};
+use crate::layout::{Breakpoints, SizeClass};
+use crate::locale::{Locale, TextDirection};
+use crate::midi_learn::MidiLearn;
+use crate::mouse::InputState;
+use crate::native_child::{NativeChildId, NativeChildren};
+use crate::notifications::{NotificationLevel, Notifications};
+use crate::open_url::{OpenUrlError, UrlOpener};
+use crate::platform::NativeWindow;
+use crate::renderer::{AdapterInfo, Renderer, TargetFrame, TargetHandle};
+use crate::scaling::PlatformScaling;
+use crate::settings::WindowStyle;
+use crate::shortcuts::Shortcuts;
+use crate::state_diff::{StateDiff, StateTracker};
+use crate::storage::{self, UiStorage};
+use crate::tasks::TaskRegistry;
+use crate::textures::{TextureKey, TextureMemory, TexturePixels, TextureRegistry};
+use crate::undo::UndoStack;
+use crate::widgets::{ParamMenu, XyDrag};
+
+/// An edge or corner of the window to resize it from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResizeEdge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+type RestoreCallback = Box<dyn FnMut(&mut dyn Any, &mut WindowContext)>;
+type DestroyCallback = Box<dyn FnOnce(&mut dyn Any, &mut WindowContext)>;
+
+/// Gives the update closure access to the window it is running in.
+#[allow(missing_debug_implementations)]
+pub struct WindowContext {
+    pub(crate) renderer: Renderer,
+    pub(crate) native_window: NativeWindow,
+    pub(crate) fonts: FontAtlas,
+    pub(crate) instance: Option<Arc<Instance>>,
+    pub(crate) handle: Handle,
+    pub(crate) midi_learn: Option<MidiLearn>,
+    pub(crate) param_menu: ParamMenu,
+    pub(crate) interaction: Interaction,
+    pub(crate) drag: Option<Drag>,
+    pub(crate) xy_drag: Option<XyDrag>,
+    pub(crate) undo: UndoStack,
+    pub(crate) shortcuts: Shortcuts,
+    pub(crate) storage: UiStorage,
+    /// The zoom of the UI on top of the hidpi factor.
+    pub(crate) ui_scale: f32,
+    pub(crate) ui_scale_request: Option<f32>,
+    pub(crate) reduced_motion: bool,
+    pub(crate) scaling: PlatformScaling,
+    pub(crate) help: Help,
+    pub(crate) locale: Locale,
+    pub(crate) text_direction: TextDirection,
+    pub(crate) window_style: WindowStyle,
+    pub(crate) urls: UrlOpener,
+    pub(crate) native_children: NativeChildren,
+    #[cfg(feature = "webview")]
+    pub(crate) web_views: crate::webview::WebViews,
+    /// The backdrop and the texture it is blurred into.
+    pub(crate) backdrop: Option<(Backdrop, imgui::TextureId)>,
+    /// The new ids of the textures, in the frame the context was recreated.
+    pub(crate) texture_remap: HashMap<usize, imgui::TextureId>,
+    pub(crate) textures: TextureRegistry,
+    pub(crate) focus_ring: Option<FocusRing>,
+    pub(crate) breakpoints: Breakpoints,
+    /// Where the content ends, marked with `fit_to_item` in this frame.
+    pub(crate) content_size: Option<[f32; 2]>,
+    pub(crate) notifications: Notifications,
+    pub(crate) tasks: TaskRegistry,
+    /// What the loader of `Settings::splash` returned, until `take_loaded` takes it.
+    pub(crate) loaded: Option<Box<dyn Any + Send>>,
+    pub(crate) context_restored: Option<RestoreCallback>,
+    pub(crate) on_destroy: Option<DestroyCallback>,
+    pub(crate) frame_hooks: FrameHooks,
+    pub(crate) frame_watchdog: FrameWatchdog,
+    pub(crate) state_tracker: StateTracker,
+    pub(crate) input: InputState,
+    #[cfg(feature = "accessibility")]
+    pub(crate) accessibility: Option<crate::accessibility::Accessibility>,
+    #[cfg(feature = "file_dialog")]
+    pub(crate) file_dialogs: crate::file_dialog::FileDialogs,
+    #[cfg(feature = "remote_control")]
+    pub(crate) remote_control: Option<crate::remote_control::RemoteControlServer>,
+}
+
+impl WindowContext {
+    /// A context with the defaults of `Settings`, which the window changes to its own.
+    pub(crate) fn new(
+        renderer: Renderer,
+        native_window: NativeWindow,
+        fonts: FontAtlas,
+        handle: Handle,
+    ) -> Self {
+        Self {
+            renderer,
+            native_window,
+            fonts,
+            instance: None,
+            undo: UndoStack::new(handle.clone()),
+            shortcuts: Shortcuts::default(),
+            storage: UiStorage::default(),
+            ui_scale: 1.0,
+            ui_scale_request: None,
+            reduced_motion: false,
+            scaling: PlatformScaling::default(),
+            help: Help::new(Duration::default()),
+            handle,
+            midi_learn: None,
+            param_menu: ParamMenu::default(),
+            interaction: Interaction::default(),
+            drag: None,
+            xy_drag: None,
+            locale: Locale::default(),
+            text_direction: TextDirection::default(),
+            window_style: WindowStyle::default(),
+            urls: UrlOpener::default(),
+            native_children: NativeChildren::default(),
+            #[cfg(feature = "webview")]
+            web_views: Default::default(),
+            backdrop: None,
+            texture_remap: HashMap::new(),
+            textures: TextureRegistry::default(),
+            focus_ring: None,
+            breakpoints: Breakpoints::default(),
+            content_size: None,
+            notifications: Notifications::default(),
+            tasks: TaskRegistry::default(),
+            loaded: None,
+            context_restored: None,
+            on_destroy: None,
+            frame_hooks: FrameHooks::default(),
+            frame_watchdog: FrameWatchdog::default(),
+            state_tracker: StateTracker::default(),
+            input: InputState::default(),
+            #[cfg(feature = "accessibility")]
+            accessibility: None,
+            #[cfg(feature = "file_dialog")]
+            file_dialogs: Default::default(),
+            #[cfg(feature = "remote_control")]
+            remote_control: None,
+        }
+    }
+
+    /// The process-wide unique id of this window, if it was registered with
+    /// `Settings::instance_key`.
+    pub fn instance_id(&self) -> Option<u64> {
+        self.instance.as_ref().map(|instance| instance.id())
+    }
+
+    /// The connection to the host.
+    pub fn handle(&self) -> &Handle {
+        &self.handle
+    }
+
+    /// The undo history, which the param-bound widgets record their edits in.
+    pub fn undo_stack(&mut self) -> &mut UndoStack {
+        &mut self.undo
+    }
+
+    /// Values of custom widgets that are saved with the UI state of the window.
+    pub fn storage(&mut self) -> &mut UiStorage {
+        &mut self.storage
+    }
+
+    /// The zoom of the UI on top of the hidpi factor of the monitor, 1 for 100%.
+    pub fn ui_scale(&self) -> f32 {
+        self.ui_scale_request.unwrap_or(self.ui_scale)
+    }
+
+    /// Zoom the UI, for a "UI size" option, from 0.5 to 3. From the next frame on the fonts
+    /// are rasterized at the new size and the window is resized to keep its layout; an
+    /// embedded window asks the host with `UiEvent::RequestResize`. The scale is saved with
+    /// the UI state and restored when the window opens again.
+    pub fn set_ui_scale(&mut self, scale: f32) {
+        let scale = scale.max(0.5).min(3.0);
+        self.ui_scale_request = Some(scale);
+        self.storage.set_id(storage::UI_SCALE_ID, &scale);
+    }
+
+    /// The keyboard shortcuts of the window.
+    pub fn shortcuts(&mut self) -> &mut Shortcuts {
+        &mut self.shortcuts
+    }
+
+    /// Make the widget that was just built the scope `name`, so the shortcuts registered
+    /// with `Scope::Widget(name)` are active while it has keyboard focus.
+    pub fn shortcut_scope(&mut self, ui: &imgui::Ui, name: &str) {
+        self.shortcuts.mark_scope(ui, name);
+    }
+
+    /// Let `Settings::focus_ring_style` draw its ring around the item that was just built
+    /// when it has keyboard focus.
+    pub fn focus_item(&mut self, ui: &imgui::Ui) {
+        if let Some(focus_ring) = &mut self.focus_ring {
+            focus_ring.item(ui);
+        }
+    }
+
+    /// The size class of the whole window by `Settings::breakpoints`, which changes as the
+    /// host resizes it.
+    pub fn size_class(&self, ui: &imgui::Ui) -> SizeClass {
+        self.breakpoints.size_class(ui.io().display_size[0])
+    }
+
+    /// Show `text` in a corner of the window for `duration`, over the UI. Clicking it hides
+    /// it early.
+    pub fn notify(
+        &mut self,
+        level: NotificationLevel,
+        text: impl Into<String>,
+        duration: Duration,
+    ) {
+        self.notifications.push(level, text.into(), duration);
+        self.handle.request_frame();
+    }
+
+    /// Mark the item that was just built, usually a group with the content of the main
+    /// window, as the end of the content that `Settings::auto_resize` fits the window to.
+    /// The window padding is kept around it.
+    pub fn fit_to_item(&mut self, ui: &imgui::Ui) {
+        let max = ui.item_rect_max();
+        let padding = ui.clone_style().window_padding;
+        self.content_size = Some([max[0] + padding[0], max[1] + padding[1]]);
+    }
+
+    /// The breakpoints of the window, for `layout::container_class`.
+    pub fn breakpoints(&self) -> &Breakpoints {
+        &self.breakpoints
+    }
+
+    /// The jobs of the window that report their progress. Clone it to spawn jobs from
+    /// elsewhere.
+    pub fn tasks(&self) -> &TaskRegistry {
+        &self.tasks
+    }
+
+    /// Take what the loader of `Settings::splash` returned. `None` if it was taken already,
+    /// the loader panicked or it returned another type.
+    pub fn take_loaded<T: 'static>(&mut self) -> Option<T> {
+        match self.loaded.take()?.downcast::<T>() {
+            Ok(loaded) => Some(*loaded),
+            Err(loaded) => {
+                self.loaded = Some(loaded);
+                None
+            }
+        }
+    }
+
+    /// Show a window that lists the registered shortcuts by scope.
+    pub fn shortcuts_help(&self, ui: &imgui::Ui, opened: &mut bool) {
+        self.shortcuts.help_window(ui, &self.locale, opened);
+    }
+
+    /// The interaction policy of the param-bound widgets.
+    pub fn interaction(&self) -> &Interaction {
+        &self.interaction
+    }
+
+    pub fn set_interaction(&mut self, interaction: Interaction) {
+        self.interaction = interaction;
+    }
+
+    /// Set the help text of the widget with the given id.
+    pub fn register_help(&mut self, id: &str, text: impl Into<String>) {
+        self.help.register(id, text.into());
+    }
+
+    /// Show the help text registered for `id` as a tooltip for the widget that was just
+    /// built.
+    pub fn help(&mut self, ui: &imgui::Ui, id: &str) {
+        self.help.item(ui, id);
+    }
+
+    /// Whether help mode is on, which highlights every widget with a help text and shows the
+    /// tooltips without delay.
+    pub fn help_mode(&self) -> bool {
+        self.help.help_mode()
+    }
+
+    pub fn set_help_mode(&mut self, help_mode: bool) {
+        self.help.set_help_mode(help_mode);
+    }
+
+    /// Make sure every character of `text` can be displayed.
+    ///
+    /// Characters that are not covered by the glyph ranges in `Settings::fonts` are added to
+    /// the font atlas, which is then rebuilt after the current frame. Call this when loading a
+    /// translation instead of shipping an atlas with every glyph up front.
+    pub fn ensure_glyphs(&mut self, text: &str) {
+        self.fonts.ensure_glyphs(text);
+    }
+
+    /// Rebuild the font atlas after the current frame.
+    pub fn rebuild_fonts(&mut self) {
+        self.fonts.set_dirty();
+    }
+
+    /// Whether the driver only has OpenGL 2, so the window is drawn by the legacy renderer.
+    /// It has no `ui_shader_rect` quads, render callbacks, blurred backdrops, rounded corners
+    /// or transparency, which a UI can leave out of its options then.
+    pub fn is_legacy_renderer(&self) -> bool {
+        self.renderer.is_legacy()
+    }
+
+    /// What the GPU and its driver tell about themselves, to include in support requests.
+    pub fn adapter_info(&self) -> AdapterInfo {
+        self.renderer.adapter_info()
+    }
+
+    /// Create a texture to render your own content into, like a 3D visualizer, and show with
+    /// `imgui::Image`.
+    ///
+    /// Set what is rendered with `set_render_callback`. The texture is rendered bottom up, so
+    /// show it with `uv0([0.0, 1.0])` and `uv1([1.0, 0.0])`.
+    pub fn create_render_target(
+        &mut self,
+        width: u32,
+        height: u32,
+    ) -> (imgui::TextureId, TargetHandle) {
+        self.renderer.create_render_target(width, height)
+    }
+
+    /// Resize a render target, for example when the image it is shown in changes size. The
+    /// texture id stays the same.
+    pub fn resize_render_target(&mut self, target: TargetHandle, width: u32, height: u32) {
+        self.renderer.resize_render_target(target, width, height);
+    }
+
+    /// Call `callback` with the state of the window every frame after the update closure, to
+    /// render into the target with OpenGL before the UI is drawn. It is only called if the
+    /// state is of type `S`.
+    pub fn set_render_callback<S, F>(&mut self, target: TargetHandle, callback: F)
+    where
+        S: 'static,
+        F: FnMut(&mut S, &TargetFrame) + 'static,
+    {
+        self.renderer.set_render_callback(target, callback);
+    }
+
+    /// Delete a render target and its texture.
+    pub fn delete_render_target(&mut self, target: TargetHandle) {
+        self.renderer.delete_render_target(target);
+    }
+
+    /// Add a texture that is uploaded when it is first shown, and can be evicted within
+    /// `Settings::texture_budget`. `loader` gives the pixels every time it is uploaded, or
+    /// `None` if they can't be loaded.
+    pub fn add_texture<F>(&mut self, loader: F) -> TextureKey
+    where
+        F: FnMut() -> Option<TexturePixels> + 'static,
+    {
+        self.textures.add(Box::new(loader))
+    }
+
+    /// The texture to show with `imgui::Image` this frame, uploading it if it was evicted.
+    /// Only valid in this frame.
+    pub fn texture(&mut self, key: TextureKey) -> Option<imgui::TextureId> {
+        self.textures.show(&mut self.renderer, key)
+    }
+
+    /// Add a texture like `add_texture` that is loaded on a thread of its own, and uploaded
+    /// over a few frames. A placeholder is shown until then. `loader` runs again on another
+    /// thread if the texture was evicted, and isn't run again once it returns `None`.
+    pub fn add_texture_async<F>(&mut self, loader: F) -> TextureKey
+    where
+        F: Fn() -> Option<TexturePixels> + Send + Sync + 'static,
+    {
+        self.textures.add_async(Arc::new(loader))
+    }
+
+    /// Whether the texture is loading in the background, so `texture` gives the placeholder.
+    pub fn is_texture_loading(&self, key: TextureKey) -> bool {
+        self.textures.is_loading(key)
+    }
+
+    pub fn remove_texture(&mut self, key: TextureKey) {
+        self.textures.remove(&mut self.renderer, key);
+    }
+
+    pub fn texture_memory(&self) -> TextureMemory {
+        self.textures.memory()
+    }
+
+    /// Blur `backdrop` every frame for the glass windows, or stop blurring.
+    pub fn set_backdrop(&mut self, backdrop: Option<Backdrop>) {
+        self.backdrop = match backdrop {
+            Some(backdrop) => {
+                let blurred = self.renderer.set_blur_source(
+                    backdrop.texture,
+                    backdrop.size,
+                    backdrop.passes,
+                    backdrop.offset,
+                );
+                Some((backdrop, blurred))
+            }
+            None => {
+                if self.backdrop.is_some() {
+                    self.renderer.clear_blur_source();
+                }
+                None
+            }
+        };
+    }
+
+    /// Call `callback` with the state of the window after the OpenGL context was lost and
+    /// created again, before the next frame. It is only called if the state is of type `S`.
+    ///
+    /// The font atlas, the textures of the crate and the render targets are uploaded again
+    /// by then, but most of them get new ids. Look them up with `restored_texture`, and
+    /// create the OpenGL objects of the render callbacks again.
+    pub fn on_context_restored<S, F>(&mut self, mut callback: F)
+    where
+        S: 'static,
+        F: FnMut(&mut S, &mut WindowContext) + 'static,
+    {
+        self.context_restored = Some(Box::new(move |state, context| {
+            if let Some(state) = state.downcast_mut() {
+                callback(state, context)
+            }
+        }));
+    }
+
+    /// Call `callback` with the state of the window once when it closes, before the renderer
+    /// deletes its textures and drops the OpenGL context. It is only called if the state is
+    /// of type `S`, and replaces the callback set before.
+    ///
+    /// Textures and render targets can still be deleted with the `WindowContext` then, and
+    /// OpenGL objects of render callbacks should be deleted here.
+    pub fn on_destroy<S, F>(&mut self, callback: F)
+    where
+        S: 'static,
+        F: FnOnce(&mut S, &mut WindowContext) + 'static,
+    {
+        self.on_destroy = Some(Box::new(move |state, context| {
+            if let Some(state) = state.downcast_mut() {
+                callback(state, context)
+            }
+        }));
+    }
+
+    /// Call `callback` with the state of the window at the start of every frame, before the
+    /// update closure builds the UI. It is only called if the state is of type `S`, and
+    /// replaces the callback set before. The first call is in the next frame.
+    pub fn on_before_frame<S, F>(&mut self, callback: F)
+    where
+        S: 'static,
+        F: FnMut(&mut S) + 'static,
+    {
+        self.frame_hooks.set_before(callback);
+    }
+
+    /// Call `callback` with the state of the window and what was drawn at the end of every
+    /// frame, after the UI was rendered, like `on_before_frame`.
+    pub fn on_after_frame<S, F>(&mut self, callback: F)
+    where
+        S: 'static,
+        F: FnMut(&mut S, &DrawStats) + 'static,
+    {
+        self.frame_hooks.set_after(callback);
+    }
+
+    /// Whether the window lightened its rendering, because frames took longer than
+    /// `Settings::frame_budget`. UIs can skip their own expensive drawing then as well.
+    pub fn is_degraded(&self) -> bool {
+        self.frame_watchdog.is_degraded()
+    }
+
+    /// The factors between baseview, imgui and physical pixels in this frame, to convert
+    /// positions from events or native windows for hit-testing.
+    pub fn scaling(&self) -> PlatformScaling {
+        self.scaling
+    }
+
+    /// Whether animations that are only for show should be left out, from
+    /// `Settings::reduced_motion` or the accessibility settings of the system. Smooth
+    /// scrolling is off then, and animated images don't start playing by themselves.
+    pub fn prefers_reduced_motion(&self) -> bool {
+        self.reduced_motion
+    }
+
+    /// Send the fields of the state that changed in every frame as `UiEvent::StateChanged`,
+    /// starting with the next frame. Only done if the state is of type `S`.
+    pub fn diff_state<S: StateDiff + 'static>(&mut self) {
+        self.state_tracker.set::<S>();
+    }
+
+    /// Call `callback` with the state of the window and the draw data of every frame, right
+    /// before it is rendered, like `on_before_frame`. The draw data is in logical pixels, and
+    /// what the callback changes in it is drawn.
+    pub fn on_draw_data<S, F>(&mut self, callback: F)
+    where
+        S: 'static,
+        F: FnMut(&mut S, &mut imgui::DrawData) + 'static,
+    {
+        self.frame_hooks.set_draw_data(callback);
+    }
+
+    /// The id a texture has after the context was created again, or the same id if it
+    /// didn't change. Only valid in the frame the context was restored in.
+    pub fn restored_texture(&self, texture: imgui::TextureId) -> imgui::TextureId {
+        self.texture_remap
+            .get(&texture.id())
+            .copied()
+            .unwrap_or(texture)
+    }
+
+    /// The input of the mouse since the last frame, including the scrolling imgui used for
+    /// its own windows.
+    pub fn input(&self) -> &InputState {
+        &self.input
+    }
+
+    /// Take the mouse wheel from imgui while the last item is hovered, for a knob that is
+    /// turned with the wheel. With `ScrollPriority::HoveredWidget` the windows of imgui don't
+    /// scroll from the next frame on, for as long as this is called every frame. The steps
+    /// are still in `input`.
+    pub fn claim_scroll(&mut self, ui: &imgui::Ui) {
+        if ui.is_item_hovered() {
+            self.input.claim_scroll = true;
+        }
+    }
+
+    /// Use the new texture ids after the context was recreated.
+    pub(crate) fn restore(&mut self, texture_remap: HashMap<usize, imgui::TextureId>) {
+        self.texture_remap = texture_remap;
+        self.textures.restore(&self.texture_remap);
+        if let Some((mut backdrop, _)) = self.backdrop.take() {
+            backdrop.texture = self.restored_texture(backdrop.texture);
+            self.set_backdrop(Some(backdrop));
+        }
+    }
+
+    /// Make the current window look like frosted glass by drawing the blurred backdrop
+    /// behind it. Call this first thing in the window, and give the window a transparent
+    /// background with `imgui::Window::bg_alpha`. Does nothing without a backdrop.
+    pub fn glass(&self, ui: &imgui::Ui, style: &GlassStyle) {
+        if let Some((backdrop, blurred)) = &self.backdrop {
+            crate::glass::draw(ui, backdrop, *blurred, style);
+        }
+    }
+
+    /// The translations of the text shown by the crate.
+    pub fn locale(&self) -> &Locale {
+        &self.locale
+    }
+
+    /// Switch to another translation, adding any glyphs it needs to the font atlas.
+    pub fn set_locale(&mut self, locale: Locale) {
+        for translation in locale.translations() {
+            self.fonts.ensure_glyphs(translation);
+        }
+        self.locale = locale;
+    }
+
+    pub fn text_direction(&self) -> TextDirection {
+        self.text_direction
+    }
+
+    /// Change the text direction.
+    ///
+    /// The style is only configured for the new direction when the window is opened, so
+    /// call `TextDirection::apply` on the style as well when switching at runtime.
+    pub fn set_text_direction(&mut self, text_direction: TextDirection) {
+        self.text_direction = text_direction;
+    }
+
+    pub fn window_style(&self) -> WindowStyle {
+        self.window_style
+    }
+
+    /// Change how the window behaves among the other windows. Does nothing for windows opened
+    /// with `open_parented`.
+    pub fn set_window_style(&mut self, window_style: WindowStyle) {
+        self.window_style = window_style;
+        self.native_window.set_style(window_style);
+    }
+
+    /// Move the window with the mouse until the button is released. Call this when a custom
+    /// title bar is clicked, for windows without decorations.
+    ///
+    /// It does nothing for windows opened with `open_parented`.
+    pub fn begin_window_drag(&self) {
+        self.native_window.begin_drag();
+    }
+
+    /// Resize the window from `edge` with the mouse until the button is released. Call this
+    /// when a custom resize grip is clicked.
+    ///
+    /// It does nothing for windows opened with `open_parented`, and on macOS, which has no
+    /// way to start a resize for a window.
+    pub fn begin_window_resize(&self, edge: ResizeEdge) {
+        self.native_window.begin_resize(edge);
+    }
+
+    /// Show a native window of the application, like a web view, inside this window.
+    ///
+    /// The child is hidden until a region is reserved for it with `native_child`. Returns
+    /// `None` if the handle is not a window of the platform this window runs on.
+    pub fn attach_native_child(&mut self, child: RawWindowHandle) -> Option<NativeChildId> {
+        self.native_children.attach(&self.native_window, &child)
+    }
+
+    /// Reserve `size` at the cursor for a native child, like `imgui::Ui::dummy`. The child is
+    /// moved over the region after the frame, clipped to the visible part. Children that are
+    /// not reserved in a frame are hidden.
+    pub fn native_child(&mut self, ui: &imgui::Ui, child: NativeChildId, size: [f32; 2]) {
+        self.native_children.reserve(ui, child, size);
+    }
+
+    /// Give a native child back to the application. It is hidden and no longer a child of
+    /// the window.
+    pub fn detach_native_child(&mut self, child: NativeChildId) {
+        self.native_children.detach(child);
+    }
+
+    /// Create a hidden web view showing `url`, to be placed with `web_view`. A web view with
+    /// the same name is replaced.
+    #[cfg(feature = "webview")]
+    pub fn create_web_view(&mut self, name: &str, url: &str) -> Result<(), wry::Error> {
+        self.web_views
+            .create(self.native_window.handle(), &self.handle, name, url)
+    }
+
+    /// Reserve `size` at the cursor for the web view `name`, like `native_child`.
+    #[cfg(feature = "webview")]
+    pub fn web_view(&mut self, ui: &imgui::Ui, name: &str, size: [f32; 2]) {
+        self.web_views.reserve(ui, name, size);
+    }
+
+    #[cfg(feature = "webview")]
+    pub fn close_web_view(&mut self, name: &str) {
+        self.web_views.close(name);
+    }
+
+    /// Ask the user to look at the window, for example when a long job has finished while it
+    /// is in the background.
+    ///
+    /// This flashes the taskbar button on Windows, bounces the dock icon on macOS and sets the
+    /// urgency hint on X11. It does nothing for windows opened with `open_parented`, as those
+    /// belong to the host.
+    pub fn request_attention(&self) {
+        self.native_window.request_attention();
+    }
+
+    /// Open a web or mail link in the browser of the user, without blocking the window.
+    ///
+    /// Only `http`, `https` and `mailto` links are opened. If it fails, the error is returned by
+    /// `take_url_errors` in a later frame.
+    pub fn open_url(&mut self, url: &str) {
+        self.urls.open(url);
+    }
+
+    /// The links that could not be opened since the last call.
+    pub fn take_url_errors(&mut self) -> Vec<OpenUrlError> {
+        self.urls.take_errors()
+    }
+
+    /// Let `Settings::remote_control` read and set a parameter that has no widget. The
+    /// parameters of param-bound widgets are added when they are built.
+    #[cfg(feature = "remote_control")]
+    pub fn expose_param(&mut self, param: &Arc<crate::params::Param>) {
+        if let Some(server) = &mut self.remote_control {
+            server.expose(param);
+        }
+    }
+
+    /// Describe the item that was just built to assistive technology.
+    ///
+    /// Only annotated items are part of the accessibility tree. If the screen reader moves
+    /// the focus to this item, it gets keyboard focus in imgui as well. Does nothing when no
+    /// adapter was set in `Settings::accessibility_adapter`.
+    #[cfg(feature = "accessibility")]
+    pub fn accessible(
+        &mut self,
+        ui: &imgui::Ui,
+        role: crate::accessibility::AccessibleRole,
+        label: &str,
+        value: Option<&str>,
+    ) {
+        if let Some(accessibility) = &mut self.accessibility {
+            accessibility.item(ui, role, label, value);
+        }
+    }
+
+    /// Show a native dialog to choose a file to open, without blocking the window.
+    ///
+    /// `callback` is called with the state of the window and the chosen path, or `None` if
+    /// the dialog was cancelled, at the start of the frame after the dialog was closed. It
+    /// is only called if the state is of type `S`.
+    #[cfg(feature = "file_dialog")]
+    pub fn open_file_dialog<S, F>(&mut self, filters: &[crate::FileFilter], callback: F)
+    where
+        S: 'static,
+        F: FnOnce(&mut S, Option<std::path::PathBuf>) + 'static,
+    {
+        self.file_dialogs.open(
+            crate::file_dialog::DialogKind::Open,
+            filters,
+            None,
+            callback,
+        );
+    }
+
+    /// Show a native dialog to choose where to save a file, like `open_file_dialog`.
+    #[cfg(feature = "file_dialog")]
+    pub fn save_file_dialog<S, F>(
+        &mut self,
+        filters: &[crate::FileFilter],
+        file_name: Option<&str>,
+        callback: F,
+    ) where
+        S: 'static,
+        F: FnOnce(&mut S, Option<std::path::PathBuf>) + 'static,
+    {
+        self.file_dialogs.open(
+            crate::file_dialog::DialogKind::Save,
+            filters,
+            file_name,
+            callback,
+        );
+    }
+}
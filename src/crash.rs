@@ -0,0 +1,159 @@
+//! Reports of panics in the update closure.
+//!
+//! A panic that unwinds out of a plugin editor takes the host down with it. With
+//! `Settings::crash_handler` the window catches the panics of the update closure instead: it
+//! stops calling the closure, shows that the editor crashed in its place, and calls the
+//! handler with a `CrashReport`. The report has what a vendor needs to look into the crash,
+//! so the plugin can save it or ask the user to send it.
+
+use std::backtrace::Backtrace;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Once};
+
+use crate::frame::DrawStats;
+use crate::renderer::AdapterInfo;
+use crate::HiDpiMode;
+
+/// Called on the thread of the window with the report of a panic in the update closure.
+pub type CrashHandler = Arc<dyn Fn(&CrashReport) + Send + Sync>;
+
+/// What is known about a panic of the update closure and the window it happened in.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CrashReport {
+    pub message: String,
+    /// The file, line and column of the panic.
+    pub location: Option<String>,
+    pub backtrace: String,
+    /// The title of the window.
+    pub window: String,
+    /// The stats of the last frame that was rendered before the panic.
+    pub last_frame: Option<DrawStats>,
+    /// How many frames the imgui context built.
+    pub frames: u64,
+    pub scale_factor: f64,
+    pub hidpi_mode: HiDpiMode,
+    pub hidpi_factor: f64,
+    pub ui_scale: f32,
+    pub renderer: String,
+    pub adapter: AdapterInfo,
+    pub os: String,
+    pub arch: String,
+    /// The version of this crate.
+    pub version: String,
+}
+
+impl fmt::Display for CrashReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} panicked: {}", self.window, self.message)?;
+        if let Some(location) = &self.location {
+            writeln!(f, "at {}", location)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "imgui-baseview {}", self.version)?;
+        writeln!(f, "os: {} {}", self.os, self.arch)?;
+        writeln!(f, "renderer: {}", self.renderer)?;
+        writeln!(
+            f,
+            "gpu: {} ({}), driver: {}",
+            self.adapter.renderer, self.adapter.vendor, self.adapter.version
+        )?;
+        if !self.adapter.extensions.is_empty() {
+            writeln!(f, "extensions: {}", self.adapter.extensions.join(" "))?;
+        }
+        writeln!(
+            f,
+            "scale factor: {}, {:?} hidpi factor: {}, ui scale: {}",
+            self.scale_factor, self.hidpi_mode, self.hidpi_factor, self.ui_scale
+        )?;
+        writeln!(f, "frames: {}", self.frames)?;
+        if let Some(stats) = &self.last_frame {
+            writeln!(f, "last frame: {:?}", stats)?;
+        }
+        writeln!(f)?;
+        write!(f, "{}", self.backtrace)
+    }
+}
+
+/// The parts of a report that come from the panic itself.
+pub(crate) struct Panic {
+    pub message: String,
+    pub location: Option<String>,
+    pub backtrace: String,
+}
+
+thread_local! {
+    /// Whether a panic on this thread is caught by `catch`, so the hook records it.
+    static CATCHING: Cell<bool> = Cell::new(false);
+    static LAST_PANIC: RefCell<Option<Panic>> = RefCell::new(None);
+}
+
+static HOOK: Once = Once::new();
+
+/// Record the location and backtrace of the panics that `catch` catches, which are gone once
+/// the panic unwound. Other panics go to the hook that was installed before.
+fn install_hook() {
+    HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !CATCHING.with(Cell::get) {
+                previous(info);
+                return;
+            }
+
+            let message = match info.payload().downcast_ref::<&str>() {
+                Some(message) => message.to_string(),
+                None => match info.payload().downcast_ref::<String>() {
+                    Some(message) => message.clone(),
+                    None => String::from("Box<dyn Any>"),
+                },
+            };
+            let panic = Panic {
+                message,
+                location: info.location().map(|location| location.to_string()),
+                backtrace: Backtrace::force_capture().to_string(),
+            };
+            LAST_PANIC.with(|last| *last.borrow_mut() = Some(panic));
+        }));
+    });
+}
+
+/// Call `f`, and catch its panic.
+pub(crate) fn catch<R>(f: impl FnOnce() -> R) -> Result<R, Panic> {
+    install_hook();
+
+    // `catch` can be nested, when a window opens another one from its update closure.
+    let outer = CATCHING.with(|catching| catching.replace(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING.with(|catching| catching.set(outer));
+
+    result.map_err(|_| {
+        LAST_PANIC
+            .with(|last| last.borrow_mut().take())
+            .unwrap_or_else(|| Panic {
+                message: String::from("unknown panic"),
+                location: None,
+                backtrace: String::new(),
+            })
+    })
+}
+
+/// Draw what the window shows in place of the UI after a crash.
+pub(crate) fn draw_notice(ui: &imgui::Ui) {
+    let display_size = ui.io().display_size;
+    imgui::Window::new("##crashed")
+        .position(
+            [display_size[0] * 0.5, display_size[1] * 0.5],
+            imgui::Condition::Always,
+        )
+        .position_pivot([0.5, 0.5])
+        .always_auto_resize(true)
+        .title_bar(false)
+        .movable(false)
+        .build(ui, || {
+            ui.text_colored([0.9, 0.3, 0.3, 1.0], "The editor crashed.");
+            ui.text("Close and open it again to continue.");
+        });
+}
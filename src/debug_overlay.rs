@@ -0,0 +1,280 @@
+//! Opt-in overlay for diagnosing host-specific issues.
+//!
+//! The overlay also measures the input latency of the window: the time from when an input
+//! event arrives to the end of the buffer swap of the frame that is the first to show its
+//! effect. The compositor of the system adds its own latency after that, which isn't
+//! counted, so the numbers are for comparing settings like `Settings::latency_mode`.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::locale::{Locale, Text};
+use crate::renderer::AdapterInfo;
+use crate::settings::Hotkey;
+use crate::theme::ColorVision;
+use crate::HiDpiMode;
+
+const MAX_EVENTS: usize = 200;
+/// How many latencies the percentiles are taken over.
+const MAX_LATENCIES: usize = 240;
+
+/// Values shown in the overlay that are owned by the window.
+pub(crate) struct DebugInfo {
+    pub scale_factor: f64,
+    pub hidpi_mode: HiDpiMode,
+    pub hidpi_factor: f64,
+    pub renderer_name: String,
+    pub adapter: AdapterInfo,
+    pub texture_count: usize,
+}
+
+pub(crate) struct DebugOverlay {
+    visible: bool,
+    show_metrics: bool,
+    paused: bool,
+    color_vision: Option<ColorVision>,
+    hotkey: Option<Hotkey>,
+    started: Instant,
+    events: VecDeque<(Duration, String)>,
+    /// When the oldest input that no frame showed yet arrived.
+    pending_input: Option<Instant>,
+    latencies: VecDeque<Duration>,
+}
+
+impl DebugOverlay {
+    pub fn new(visible: bool, hotkey: Option<Hotkey>) -> Self {
+        Self {
+            visible,
+            show_metrics: true,
+            paused: false,
+            color_vision: None,
+            hotkey,
+            started: Instant::now(),
+            events: VecDeque::new(),
+            pending_input: None,
+            latencies: VecDeque::new(),
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// The deficiency the frames are rendered with. It stays on while the overlay is hidden.
+    pub fn color_vision(&self) -> Option<ColorVision> {
+        self.color_vision
+    }
+
+    /// Record the event, and toggle the overlay if it is the hotkey.
+    pub fn on_event(&mut self, event: &baseview::Event) {
+        if let baseview::Event::Keyboard(event) = event {
+            if self.hotkey.map_or(false, |hotkey| hotkey.matches(event)) {
+                self.visible = !self.visible;
+            }
+        }
+
+        // Only keep a history if the overlay can actually be shown.
+        if !self.visible && self.hotkey.is_none() {
+            return;
+        }
+
+        let is_input = matches!(
+            event,
+            baseview::Event::Mouse(_) | baseview::Event::Keyboard(_)
+        );
+        if is_input && self.pending_input.is_none() {
+            self.pending_input = Some(Instant::now());
+        }
+
+        if self.paused {
+            return;
+        }
+
+        let time = self.started.elapsed();
+        let description = format!("{:?}", event);
+
+        // Cursor movement would flood the log, so only keep the latest one in a row.
+        let is_cursor_move = matches!(
+            event,
+            baseview::Event::Mouse(baseview::MouseEvent::CursorMoved { .. })
+        );
+        if is_cursor_move {
+            if let Some((last_time, last)) = self.events.back_mut() {
+                if last.starts_with("Mouse(CursorMoved") {
+                    *last_time = time;
+                    *last = description;
+                    return;
+                }
+            }
+        }
+
+        if self.events.len() == MAX_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back((time, description));
+    }
+
+    /// Record the latency of the input a frame that was just presented is the first to show.
+    pub fn frame_presented(&mut self) -> Option<Duration> {
+        let latency = self.pending_input.take()?.elapsed();
+        if self.latencies.len() == MAX_LATENCIES {
+            self.latencies.pop_front();
+        }
+        self.latencies.push_back(latency);
+        Some(latency)
+    }
+
+    /// The 50th, 90th and 99th percentile of the recent input latencies.
+    fn latency_percentiles(&self) -> Option<[Duration; 3]> {
+        if self.latencies.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<Duration> = self.latencies.iter().copied().collect();
+        sorted.sort_unstable();
+        let percentile = |p: usize| sorted[(sorted.len() - 1) * p / 100];
+        Some([percentile(50), percentile(90), percentile(99)])
+    }
+
+    pub fn draw(&mut self, ui: &imgui::Ui, info: &DebugInfo, locale: &Locale) {
+        use imgui::{Condition, Window};
+
+        if !self.visible {
+            return;
+        }
+
+        if self.show_metrics {
+            ui.show_metrics_window(&mut self.show_metrics);
+        }
+
+        let mut visible = self.visible;
+        let io = ui.io();
+        let latencies = self.latency_percentiles();
+
+        Window::new("imgui-baseview debug")
+            .opened(&mut visible)
+            .size([420.0, 360.0], Condition::FirstUseEver)
+            .position([20.0, 20.0], Condition::FirstUseEver)
+            .build(ui, || {
+                ui.text(format!(
+                    "{:.1} fps ({:.2} ms)",
+                    io.framerate,
+                    io.delta_time * 1000.0
+                ));
+                if let Some([p50, p90, p99]) = latencies {
+                    ui.text(format!(
+                        "{}: {:.1} / {:.1} / {:.1} ms (p50 / p90 / p99)",
+                        locale.get(Text::InputLatency),
+                        p50.as_secs_f64() * 1000.0,
+                        p90.as_secs_f64() * 1000.0,
+                        p99.as_secs_f64() * 1000.0
+                    ));
+                }
+                ui.separator();
+
+                ui.text(format!(
+                    "{}: {}",
+                    locale.get(Text::ScaleFactor),
+                    info.scale_factor
+                ));
+                ui.text(format!(
+                    "{}: {:?}",
+                    locale.get(Text::HiDpiMode),
+                    info.hidpi_mode
+                ));
+                ui.text(format!(
+                    "{}: {}",
+                    locale.get(Text::HiDpiFactor),
+                    info.hidpi_factor
+                ));
+                ui.text(format!(
+                    "{}: {:.1} x {:.1}",
+                    locale.get(Text::DisplaySize),
+                    io.display_size[0],
+                    io.display_size[1]
+                ));
+                ui.text(format!(
+                    "{}: {} x {}",
+                    locale.get(Text::FramebufferScale),
+                    io.display_framebuffer_scale[0],
+                    io.display_framebuffer_scale[1]
+                ));
+                ui.separator();
+
+                ui.text(format!(
+                    "{}: {}",
+                    locale.get(Text::Renderer),
+                    info.renderer_name
+                ));
+                if !info.adapter.renderer.is_empty() {
+                    ui.text(format!(
+                        "{}: {} ({})",
+                        locale.get(Text::Gpu),
+                        info.adapter.renderer,
+                        info.adapter.vendor
+                    ));
+                    ui.text(format!(
+                        "{}: {}",
+                        locale.get(Text::Driver),
+                        info.adapter.version
+                    ));
+                    ui.text(format!(
+                        "{}: {}",
+                        locale.get(Text::Extensions),
+                        info.adapter.extensions.join(" ")
+                    ));
+                }
+                ui.text(format!(
+                    "{}: {}",
+                    locale.get(Text::Textures),
+                    info.texture_count
+                ));
+                ui.checkbox(locale.get(Text::MetricsWindow), &mut self.show_metrics);
+
+                let preview = match self.color_vision {
+                    Some(vision) => vision.name(),
+                    None => locale.get(Text::NormalVision),
+                };
+                if let Some(_combo) = imgui::ComboBox::new(locale.get(Text::SimulateColorVision))
+                    .preview_value(preview)
+                    .begin(ui)
+                {
+                    let normal = locale.get(Text::NormalVision);
+                    if imgui::Selectable::new(normal)
+                        .selected(self.color_vision.is_none())
+                        .build(ui)
+                    {
+                        self.color_vision = None;
+                    }
+                    for vision in ColorVision::ALL {
+                        if imgui::Selectable::new(vision.name())
+                            .selected(self.color_vision == Some(*vision))
+                            .build(ui)
+                        {
+                            self.color_vision = Some(*vision);
+                        }
+                    }
+                }
+                ui.separator();
+
+                ui.checkbox(locale.get(Text::PauseEventLog), &mut self.paused);
+                ui.same_line();
+                if ui.button(locale.get(Text::Clear)) {
+                    self.events.clear();
+                }
+
+                let events = &self.events;
+                imgui::ChildWindow::new("events")
+                    .horizontal_scrollbar(true)
+                    .build(ui, || {
+                        for (time, description) in events {
+                            ui.text(format!("{:>9.3} {}", time.as_secs_f64(), description));
+                        }
+                        if ui.scroll_y() >= ui.scroll_max_y() {
+                            ui.set_scroll_here_y_with_ratio(1.0);
+                        }
+                    });
+            });
+
+        self.visible = visible;
+    }
+}
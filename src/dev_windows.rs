@@ -0,0 +1,61 @@
+//! imgui's own tool windows, for developing a UI.
+
+use crate::settings::{DevWindows, Hotkey};
+
+pub(crate) struct DevWindowsState {
+    shown: DevWindows,
+    /// Whether the panel that toggles the windows is open.
+    panel: bool,
+    hotkey: Option<Hotkey>,
+}
+
+impl DevWindowsState {
+    pub fn new(shown: DevWindows, hotkey: Option<Hotkey>) -> Self {
+        Self {
+            shown,
+            panel: false,
+            hotkey,
+        }
+    }
+
+    /// Open or close the panel if the event is the hotkey.
+    pub fn on_event(&mut self, event: &baseview::Event) {
+        if let baseview::Event::Keyboard(event) = event {
+            if self.hotkey.map_or(false, |hotkey| hotkey.matches(event)) {
+                self.panel = !self.panel;
+            }
+        }
+    }
+
+    /// Draw the windows that are shown, over the UI of the update closure.
+    pub fn draw(&mut self, ui: &imgui::Ui) {
+        let shown = &mut self.shown;
+
+        if self.panel {
+            imgui::Window::new("Dev windows")
+                .opened(&mut self.panel)
+                .always_auto_resize(true)
+                .build(ui, || {
+                    ui.checkbox("Demo", &mut shown.demo);
+                    ui.checkbox("Metrics", &mut shown.metrics);
+                    ui.checkbox("Style editor", &mut shown.style_editor);
+                    ui.checkbox("About", &mut shown.about);
+                });
+        }
+
+        if shown.demo {
+            ui.show_demo_window(&mut shown.demo);
+        }
+        if shown.metrics {
+            ui.show_metrics_window(&mut shown.metrics);
+        }
+        if shown.style_editor {
+            imgui::Window::new("Style editor")
+                .opened(&mut shown.style_editor)
+                .build(ui, || ui.show_default_style_editor());
+        }
+        if shown.about {
+            ui.show_about_window(&mut shown.about);
+        }
+    }
+}
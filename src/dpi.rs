@@ -28,25 +28,55 @@ SOFTWARE.
 /// **Note: if you use a mode other than default and the DPI factor is adjusted, baseview and imgui-rs
 /// will use different logical coordinates, so be careful if you pass around logical size or
 /// position values.**
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum HiDpiMode {
     /// The DPI factor from baseview is used directly without adjustment
     Default,
-    /// The DPI factor from baseview is rounded to an integer value.
+    /// The DPI factor from baseview is rounded to the nearest integer value, and at least 1.
     ///
-    /// This prevents the user interface from becoming blurry with non-integer scaling.
+    /// This prevents the user interface from becoming blurry with non-integer scaling, like
+    /// the 1.25 and 1.5 of Windows.
     Rounded,
+    /// The DPI factor from baseview is passed through a function, for example to round only
+    /// factors above 1.5 or to snap to halves. Like with `Rounded` the result is at least 1.
+    ///
+    /// Functions can't be compared reliably, so a `Custom` mode is never equal to another, or
+    /// to itself.
+    ///
+    /// Can't be written to a configuration file with the `serde` feature.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(fn(f64) -> f64),
 }
 
 impl HiDpiMode {
     pub(crate) fn apply(&self, hidpi_factor: f64) -> f64 {
         match *self {
             HiDpiMode::Default => hidpi_factor,
-            HiDpiMode::Rounded => hidpi_factor.round(),
+            HiDpiMode::Rounded => at_least_one(hidpi_factor.round()),
+            HiDpiMode::Custom(strategy) => at_least_one(strategy(hidpi_factor)),
         }
     }
 }
 
+/// `factor`, or 1 if it is smaller or isn't finite.
+fn at_least_one(factor: f64) -> f64 {
+    if factor.is_finite() {
+        factor.max(1.0)
+    } else {
+        1.0
+    }
+}
+
+impl PartialEq for HiDpiMode {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (HiDpiMode::Default, HiDpiMode::Default) | (HiDpiMode::Rounded, HiDpiMode::Rounded)
+        )
+    }
+}
+
 impl Default for HiDpiMode {
     fn default() -> Self {
         HiDpiMode::Default
@@ -0,0 +1,89 @@
+//! Native open and save dialogs that don't block the window.
+//!
+//! The dialog runs on a worker thread through the `tasks` module, and the callback is called
+//! with the chosen path at the start of the frame after the dialog was closed, right before
+//! the update closure.
+//!
+//! macOS only allows dialogs on the main thread, where the host runs its own event loop, so
+//! the dialog is not available there from a plugin window.
+
+use std::any::Any;
+use std::path::PathBuf;
+
+use crate::tasks::{self, Task};
+
+type Callback = Box<dyn FnOnce(&mut dyn Any, Option<PathBuf>)>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DialogKind {
+    Open,
+    Save,
+}
+
+/// A filter of the file types shown in a dialog, for example `("Audio", &["wav", "flac"])`.
+pub type FileFilter<'a> = (&'a str, &'a [&'a str]);
+
+#[derive(Default)]
+pub(crate) struct FileDialogs {
+    pending: Vec<(Task<Option<PathBuf>>, Callback)>,
+}
+
+impl FileDialogs {
+    pub fn open<S, F>(
+        &mut self,
+        kind: DialogKind,
+        filters: &[FileFilter],
+        file_name: Option<&str>,
+        callback: F,
+    ) where
+        S: 'static,
+        F: FnOnce(&mut S, Option<PathBuf>) + 'static,
+    {
+        let filters: Vec<(String, Vec<String>)> = filters
+            .iter()
+            .map(|(name, extensions)| {
+                let extensions = extensions.iter().map(|e| e.to_string()).collect();
+                (name.to_string(), extensions)
+            })
+            .collect();
+        let file_name = file_name.map(str::to_string);
+
+        // The dialog is created on the worker thread, as it is not `Send` on every platform.
+        let task = tasks::spawn(move || {
+            let mut dialog = rfd::FileDialog::new();
+            for (name, extensions) in &filters {
+                let extensions: Vec<&str> = extensions.iter().map(String::as_str).collect();
+                dialog = dialog.add_filter(name, &extensions);
+            }
+            if let Some(file_name) = &file_name {
+                dialog = dialog.set_file_name(file_name);
+            }
+
+            match kind {
+                DialogKind::Open => dialog.pick_file(),
+                DialogKind::Save => dialog.save_file(),
+            }
+        });
+
+        let callback: Callback = Box::new(move |state, path| {
+            if let Some(state) = state.downcast_mut() {
+                callback(state, path)
+            }
+        });
+        self.pending.push((task, callback));
+    }
+
+    /// Call the callbacks of the dialogs that were closed.
+    pub fn poll(&mut self, state: &mut dyn Any) {
+        let mut i = 0;
+        while i < self.pending.len() {
+            if self.pending[i].0.is_running() {
+                i += 1;
+                continue;
+            }
+
+            let (mut task, callback) = self.pending.remove(i);
+            callback(state, task.take().flatten());
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! A ring around the item that has keyboard focus.
+//!
+//! imgui's own navigation highlight is a thin outline in the colors of the theme, which is
+//! easy to lose on a busy plugin UI. With `Settings::focus_ring_style` the window turns on
+//! keyboard navigation and draws a ring in the color of the style around the focused item,
+//! over everything else, while the focus is moved with the keyboard. Items take part by
+//! calling `WindowContext::focus_item` after they are built, which the built-in widgets do.
+
+use crate::settings::FocusRingStyle;
+
+pub(crate) struct FocusRing {
+    style: FocusRingStyle,
+    /// The rectangle of the focused item in this frame.
+    focused: Option<([f32; 2], [f32; 2])>,
+}
+
+impl FocusRing {
+    pub fn new(style: FocusRingStyle) -> Self {
+        Self {
+            style,
+            focused: None,
+        }
+    }
+
+    /// Note the item that was just built if it has keyboard focus.
+    pub fn item(&mut self, ui: &imgui::Ui) {
+        if ui.is_item_focused() {
+            self.focused = Some((ui.item_rect_min(), ui.item_rect_max()));
+        }
+    }
+
+    /// Draw the ring over the UI of the frame.
+    pub fn draw(&mut self, ui: &imgui::Ui) {
+        let (min, max) = match self.focused.take() {
+            Some(rect) => rect,
+            None => return,
+        };
+        // The focus was last moved with the mouse.
+        if !ui.io().nav_visible {
+            return;
+        }
+
+        let style = &self.style;
+        let padding = style.padding + style.thickness * 0.5;
+        ui.get_foreground_draw_list()
+            .add_rect(
+                [min[0] - padding, min[1] - padding],
+                [max[0] + padding, max[1] + padding],
+                style.color,
+            )
+            .rounding(style.rounding)
+            .thickness(style.thickness)
+            .build();
+    }
+}
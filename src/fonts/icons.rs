@@ -0,0 +1,65 @@
+//! Codepoints of commonly used Font Awesome 5 icons.
+//!
+//! The icon font itself is not bundled with this crate. Merge it into your main font with
+//! [`FontSource::icons`](super::FontSource::icons) and [`FONT_AWESOME_RANGES`].
+//!
+//! ```ignore
+//! ui.button(format!("{} Save", icons::SAVE));
+//! ```
+
+/// The glyph ranges covering the Font Awesome 5 icons.
+pub const FONT_AWESOME_RANGES: &[u32] = &[0xe005, 0xf8ff, 0];
+
+pub const PLAY: &str = "\u{f04b}";
+pub const PAUSE: &str = "\u{f04c}";
+pub const STOP: &str = "\u{f04d}";
+pub const BACKWARD: &str = "\u{f04a}";
+pub const FORWARD: &str = "\u{f04e}";
+pub const STEP_BACKWARD: &str = "\u{f048}";
+pub const STEP_FORWARD: &str = "\u{f051}";
+pub const CIRCLE: &str = "\u{f111}";
+pub const RANDOM: &str = "\u{f074}";
+pub const SYNC: &str = "\u{f021}";
+
+pub const MUSIC: &str = "\u{f001}";
+pub const HEADPHONES: &str = "\u{f025}";
+pub const MICROPHONE: &str = "\u{f130}";
+pub const VOLUME_OFF: &str = "\u{f026}";
+pub const VOLUME_UP: &str = "\u{f028}";
+pub const VOLUME_MUTE: &str = "\u{f6a9}";
+pub const WAVE_SQUARE: &str = "\u{f83e}";
+pub const SLIDERS_H: &str = "\u{f1de}";
+
+pub const FILE: &str = "\u{f15b}";
+pub const FOLDER: &str = "\u{f07b}";
+pub const FOLDER_OPEN: &str = "\u{f07c}";
+pub const SAVE: &str = "\u{f0c7}";
+pub const TRASH: &str = "\u{f1f8}";
+pub const COPY: &str = "\u{f0c5}";
+pub const CUT: &str = "\u{f0c4}";
+pub const PASTE: &str = "\u{f0ea}";
+pub const EDIT: &str = "\u{f044}";
+pub const UNDO: &str = "\u{f0e2}";
+pub const REDO: &str = "\u{f01e}";
+pub const SEARCH: &str = "\u{f002}";
+pub const LINK: &str = "\u{f0c1}";
+pub const LOCK: &str = "\u{f023}";
+pub const UNLOCK: &str = "\u{f09c}";
+
+pub const PLUS: &str = "\u{f067}";
+pub const MINUS: &str = "\u{f068}";
+pub const TIMES: &str = "\u{f00d}";
+pub const CHECK: &str = "\u{f00c}";
+pub const STAR: &str = "\u{f005}";
+pub const HEART: &str = "\u{f004}";
+pub const COG: &str = "\u{f013}";
+pub const BARS: &str = "\u{f0c9}";
+pub const POWER_OFF: &str = "\u{f011}";
+pub const INFO_CIRCLE: &str = "\u{f05a}";
+pub const QUESTION_CIRCLE: &str = "\u{f059}";
+pub const EXCLAMATION_TRIANGLE: &str = "\u{f071}";
+
+pub const CHEVRON_LEFT: &str = "\u{f053}";
+pub const CHEVRON_RIGHT: &str = "\u{f054}";
+pub const CHEVRON_UP: &str = "\u{f077}";
+pub const CHEVRON_DOWN: &str = "\u{f078}";
@@ -0,0 +1,652 @@
+//! Configure the fonts of your application.
+//!
+//! Fonts listed in `Settings::fonts` are rasterized at the hidpi factor of the window, and
+//! the font atlas is rebuilt whenever that factor changes.
+
+pub mod icons;
+
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+use std::sync::Mutex;
+
+use bitflags::bitflags;
+use imgui::{FontConfig, FontGlyphRanges};
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum FontData {
+    Default,
+    Ttf(Cow<'static, [u8]>),
+}
+
+/// The set of characters to rasterize from a font.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GlyphRanges {
+    /// Basic Latin and Latin-1 Supplement.
+    Default,
+    /// Default + Latin Extended-A and Latin Extended-B.
+    LatinExtended,
+    /// Default + Cyrillic.
+    Cyrillic,
+    /// Default + Thai.
+    Thai,
+    /// Default + Vietnamese.
+    Vietnamese,
+    /// Default + Korean characters.
+    Korean,
+    /// Default + Hiragana, Katakana and a subset of the most common Kanji.
+    Japanese,
+    /// Default + Half-Width characters and a subset of the most common simplified Chinese
+    /// ideograms.
+    ChineseSimplifiedCommon,
+    /// Default + Half-Width characters, Hiragana, Katakana and all CJK Unified Ideographs.
+    ChineseFull,
+    /// A zero-terminated list of inclusive `[first, last]` codepoint pairs, for example
+    /// [`icons::FONT_AWESOME_RANGES`].
+    ///
+    /// These are written like `Custom` ranges, and read back as them.
+    #[cfg_attr(
+        feature = "serde",
+        serde(skip_deserializing, serialize_with = "serialize_static_ranges")
+    )]
+    Static(&'static [u32]),
+    /// A list of inclusive `(first, last)` codepoint pairs, usually built with a
+    /// [`GlyphRangesBuilder`].
+    #[cfg_attr(feature = "serde", serde(alias = "Static"))]
+    Custom(Vec<(u32, u32)>),
+}
+
+const DEFAULT_RANGES: &[(u32, u32)] = &[(0x0020, 0x00ff)];
+
+impl GlyphRanges {
+    /// Whether the given character is part of the ranges.
+    ///
+    /// For `Japanese` and `ChineseSimplifiedCommon` the ideograms are not considered part of
+    /// the ranges, so missing ones will be picked up by `WindowContext::ensure_glyphs`.
+    pub fn contains(&self, c: char) -> bool {
+        let c = c as u32;
+        let in_ranges =
+            |ranges: &[(u32, u32)]| ranges.iter().any(|&(first, last)| first <= c && c <= last);
+
+        match self {
+            GlyphRanges::Default => in_ranges(DEFAULT_RANGES),
+            GlyphRanges::LatinExtended => in_ranges(&[(0x0020, 0x024f)]),
+            GlyphRanges::Cyrillic => in_ranges(&[
+                (0x0020, 0x00ff),
+                (0x0400, 0x052f),
+                (0x2de0, 0x2dff),
+                (0xa640, 0xa69f),
+            ]),
+            GlyphRanges::Thai => in_ranges(&[(0x0020, 0x00ff), (0x2010, 0x205e), (0x0e00, 0x0e7f)]),
+            GlyphRanges::Vietnamese => in_ranges(&[
+                (0x0020, 0x00ff),
+                (0x0102, 0x0103),
+                (0x0110, 0x0111),
+                (0x0128, 0x0129),
+                (0x0168, 0x0169),
+                (0x01a0, 0x01a1),
+                (0x01af, 0x01b0),
+                (0x1ea0, 0x1ef9),
+            ]),
+            GlyphRanges::Korean => {
+                in_ranges(&[(0x0020, 0x00ff), (0x3131, 0x3163), (0xac00, 0xd7a3)])
+            }
+            GlyphRanges::Japanese | GlyphRanges::ChineseSimplifiedCommon => in_ranges(&[
+                (0x0020, 0x00ff),
+                (0x3000, 0x30ff),
+                (0x31f0, 0x31ff),
+                (0xff00, 0xffef),
+            ]),
+            GlyphRanges::ChineseFull => in_ranges(&[
+                (0x0020, 0x00ff),
+                (0x2000, 0x206f),
+                (0x3000, 0x30ff),
+                (0x31f0, 0x31ff),
+                (0xff00, 0xffef),
+                (0x4e00, 0x9faf),
+            ]),
+            GlyphRanges::Static(ranges) => ranges
+                .chunks_exact(2)
+                .any(|pair| pair[0] <= c && c <= pair[1]),
+            GlyphRanges::Custom(ranges) => in_ranges(ranges),
+        }
+    }
+
+    fn to_imgui(&self) -> FontGlyphRanges {
+        match self {
+            GlyphRanges::Default => FontGlyphRanges::default(),
+            GlyphRanges::LatinExtended => FontGlyphRanges::from_slice(&[0x0020, 0x024f, 0]),
+            GlyphRanges::Cyrillic => FontGlyphRanges::cyrillic(),
+            GlyphRanges::Thai => FontGlyphRanges::thai(),
+            GlyphRanges::Vietnamese => FontGlyphRanges::vietnamese(),
+            GlyphRanges::Korean => FontGlyphRanges::korean(),
+            GlyphRanges::Japanese => FontGlyphRanges::japanese(),
+            GlyphRanges::ChineseSimplifiedCommon => FontGlyphRanges::chinese_simplified_common(),
+            GlyphRanges::ChineseFull => FontGlyphRanges::chinese_full(),
+            GlyphRanges::Static(ranges) => FontGlyphRanges::from_slice(ranges),
+            GlyphRanges::Custom(ranges) => FontGlyphRanges::from_slice(intern_ranges(ranges)),
+        }
+    }
+}
+
+impl Default for GlyphRanges {
+    fn default() -> Self {
+        GlyphRanges::Default
+    }
+}
+
+impl From<&'static [u32]> for GlyphRanges {
+    fn from(ranges: &'static [u32]) -> Self {
+        GlyphRanges::Static(ranges)
+    }
+}
+
+/// imgui keeps a pointer to the glyph ranges for as long as the atlas lives, so custom ranges
+/// are leaked. They are interned so that rebuilding the atlas does not leak them again.
+fn intern_ranges(ranges: &[(u32, u32)]) -> &'static [u32] {
+    static INTERNED: Mutex<Vec<&'static [u32]>> = Mutex::new(Vec::new());
+
+    let mut flat: Vec<u32> = ranges
+        .iter()
+        .flat_map(|&(first, last)| std::iter::once(first).chain(std::iter::once(last)))
+        .collect();
+    flat.push(0);
+
+    let mut interned = INTERNED.lock().unwrap();
+    if let Some(existing) = interned.iter().find(|existing| ***existing == flat[..]) {
+        return existing;
+    }
+
+    let leaked: &'static [u32] = Box::leak(flat.into_boxed_slice());
+    interned.push(leaked);
+    leaked
+}
+
+/// Builds a custom set of glyph ranges, for example from the contents of a translation file.
+#[derive(Clone, Debug, Default)]
+pub struct GlyphRangesBuilder {
+    ranges: Vec<(u32, u32)>,
+}
+
+impl GlyphRangesBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a single character.
+    pub fn add_char(&mut self, c: char) -> &mut Self {
+        self.add_range(c as u32, c as u32)
+    }
+
+    /// Add every character used in the given text.
+    pub fn add_text(&mut self, text: &str) -> &mut Self {
+        for c in text.chars() {
+            self.add_char(c);
+        }
+        self
+    }
+
+    /// Add an inclusive range of codepoints.
+    pub fn add_range(&mut self, first: u32, last: u32) -> &mut Self {
+        // Codepoint 0 terminates the list on the imgui side.
+        let first = first.max(1);
+        if first <= last {
+            self.ranges.push((first, last));
+        }
+        self
+    }
+
+    /// Whether the given character has been added.
+    pub fn contains(&self, c: char) -> bool {
+        let c = c as u32;
+        self.ranges
+            .iter()
+            .any(|&(first, last)| first <= c && c <= last)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The number of ranges added so far.
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Build the ranges, sorted and with overlapping ranges merged.
+    pub fn build(&self) -> GlyphRanges {
+        let mut sorted = self.ranges.clone();
+        sorted.sort_unstable();
+
+        let mut merged: Vec<(u32, u32)> = Vec::with_capacity(sorted.len());
+        for (first, last) in sorted {
+            match merged.last_mut() {
+                Some(prev) if first <= prev.1.saturating_add(1) => prev.1 = prev.1.max(last),
+                _ => merged.push((first, last)),
+            }
+        }
+
+        GlyphRanges::Custom(merged)
+    }
+}
+
+bitflags! {
+    /// Hinting and rendering options for the FreeType rasterizer.
+    ///
+    /// These only have an effect when the `freetype` feature is enabled, in which case the
+    /// whole font atlas is built with FreeType instead of stb_truetype.
+    #[derive(Default)]
+    pub struct FreeTypeFlags: u32 {
+        /// Disable hinting. This generally generates "blurrier" bitmap glyphs.
+        const NO_HINTING = 1 << 0;
+        /// Disable the auto-hinter.
+        const NO_AUTO_HINT = 1 << 1;
+        /// Prefer the auto-hinter over the font's native hinter.
+        const FORCE_AUTO_HINT = 1 << 2;
+        /// A lighter hinting algorithm for gray-level modes. Many generated glyphs are fuzzier
+        /// but better resemble their original shape.
+        const LIGHT_HINTING = 1 << 3;
+        /// Strong hinting algorithm that should only be used for monochrome output.
+        const MONO_HINTING = 1 << 4;
+        /// Artificially embolden the font.
+        const BOLD = 1 << 5;
+        /// Artificially slant the font.
+        const OBLIQUE = 1 << 6;
+        /// Disable anti-aliasing. Combine with `MONO_HINTING` for best results.
+        const MONOCHROME = 1 << 7;
+        /// Enable FreeType color-layered glyphs.
+        const LOAD_COLOR = 1 << 8;
+        /// Enable FreeType bitmap glyphs.
+        const BITMAP = 1 << 9;
+    }
+}
+
+#[cfg(feature = "serde")]
+fn serialize_static_ranges<S>(ranges: &&'static [u32], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    let pairs = ranges
+        .chunks_exact(2)
+        .take_while(|pair| pair[0] != 0)
+        .map(|pair| (pair[0], pair[1]));
+    serializer.collect_seq(pairs)
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for FreeTypeFlags {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&self.bits(), serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for FreeTypeFlags {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bits = u32::deserialize(deserializer)?;
+        FreeTypeFlags::from_bits(bits)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown FreeType flags {:#x}", bits)))
+    }
+}
+
+/// A font to add to the font atlas.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FontSource {
+    data: FontData,
+    size_pixels: f32,
+    glyph_ranges: GlyphRanges,
+    merge: bool,
+    freetype_flags: FreeTypeFlags,
+}
+
+impl FontSource {
+    /// The default imgui font (ProggyClean).
+    ///
+    /// * `size_pixels` - The logical size of the font.
+    pub fn default_font(size_pixels: f32) -> Self {
+        Self {
+            data: FontData::Default,
+            size_pixels,
+            glyph_ranges: GlyphRanges::Default,
+            merge: false,
+            freetype_flags: FreeTypeFlags::empty(),
+        }
+    }
+
+    /// A TrueType or OpenType font.
+    ///
+    /// * `data` - The contents of the font file.
+    /// * `size_pixels` - The logical size of the font.
+    pub fn ttf(data: impl Into<Cow<'static, [u8]>>, size_pixels: f32) -> Self {
+        Self {
+            data: FontData::Ttf(data.into()),
+            size_pixels,
+            glyph_ranges: GlyphRanges::Default,
+            merge: false,
+            freetype_flags: FreeTypeFlags::empty(),
+        }
+    }
+
+    /// An icon font that is merged into the preceding font.
+    ///
+    /// The icons are sized to match the font they are merged into.
+    ///
+    /// * `data` - The contents of the font file.
+    /// * `ranges` - The codepoint ranges to take from the icon font, for example
+    /// [`icons::FONT_AWESOME_RANGES`].
+    pub fn icons(data: impl Into<Cow<'static, [u8]>>, ranges: impl Into<GlyphRanges>) -> Self {
+        Self {
+            data: FontData::Ttf(data.into()),
+            size_pixels: 0.0,
+            glyph_ranges: ranges.into(),
+            merge: true,
+            freetype_flags: FreeTypeFlags::empty(),
+        }
+    }
+
+    /// Set the characters to rasterize from this font.
+    pub fn with_glyph_ranges(mut self, glyph_ranges: GlyphRanges) -> Self {
+        self.glyph_ranges = glyph_ranges;
+        self
+    }
+
+    /// Set the hinting and rendering options used when the atlas is built with FreeType.
+    ///
+    /// Small fonts in dense parameter grids usually look best with `LIGHT_HINTING`.
+    pub fn with_freetype_flags(mut self, freetype_flags: FreeTypeFlags) -> Self {
+        self.freetype_flags = freetype_flags;
+        self
+    }
+}
+
+/// How the font atlas is shared between windows.
+///
+/// Hosts often open many instances of the same plugin, and building an identical font atlas
+/// for every one of them wastes memory and startup time.
+#[derive(Clone, Copy, Debug)]
+pub enum FontAtlasSharing {
+    /// Every window builds its own font atlas.
+    Owned,
+    /// Windows on the same thread with identical `Settings::fonts` that open at the same
+    /// hidpi factor share one atlas, which is only rasterized again when the requested glyphs
+    /// change. When the factor of a window changes while others still use the atlas, it is
+    /// only rasterized again for a higher factor, and windows at a lower one scale it down.
+    /// So no window scales text up, which would make it blurry.
+    ///
+    /// This falls back to `Owned` when `Settings::fonts` is empty. Every window still uploads
+    /// its own copy of the texture, as `raw-gl-context` does not create shared contexts.
+    Cached,
+    /// Use the atlas returned by the given function.
+    ///
+    /// `imgui::SharedFontAtlas` can't be sent between threads, so the function is called on
+    /// the thread of the window. Fonts can be added to the atlas up front, or through
+    /// `Settings::fonts`.
+    Custom(fn() -> Rc<RefCell<imgui::SharedFontAtlas>>),
+}
+
+impl Default for FontAtlasSharing {
+    fn default() -> Self {
+        FontAtlasSharing::Owned
+    }
+}
+
+/// The state of an atlas that may be shared between windows.
+struct AtlasState {
+    atlas: Option<Rc<RefCell<imgui::SharedFontAtlas>>>,
+    extra_glyphs: RefCell<GlyphRangesBuilder>,
+    /// The hidpi factor the atlas is rasterized at, which windows opening at the same factor
+    /// share it by.
+    hidpi_factor: Cell<f64>,
+    /// The hidpi factor and the number of extra glyph ranges the atlas was last built with.
+    built_with: Cell<Option<(f64, usize)>>,
+    /// Incremented every time the atlas is rebuilt, so that every window knows when to
+    /// upload the texture again.
+    generation: Cell<u64>,
+}
+
+impl AtlasState {
+    fn new(atlas: Option<Rc<RefCell<imgui::SharedFontAtlas>>>, hidpi_factor: f64) -> Self {
+        Self {
+            atlas,
+            extra_glyphs: RefCell::new(GlyphRangesBuilder::new()),
+            hidpi_factor: Cell::new(hidpi_factor),
+            built_with: Cell::new(None),
+            generation: Cell::new(0),
+        }
+    }
+}
+
+// `imgui::SharedFontAtlas` is `!Send`, so every thread keeps its own atlases.
+thread_local! {
+    static CACHED_ATLASES: RefCell<Vec<(Vec<FontSource>, Weak<AtlasState>)>> =
+        RefCell::new(Vec::new());
+}
+
+/// Find the cached atlas for the given sources at `hidpi_factor`, or create it.
+fn cached_state(sources: &[FontSource], hidpi_factor: f64) -> Rc<AtlasState> {
+    CACHED_ATLASES.with(|cache| {
+        let mut cache = cache.borrow_mut();
+
+        // Atlases are freed once the last window using them is closed.
+        cache.retain(|(_, state)| state.strong_count() > 0);
+
+        let existing = cache
+            .iter()
+            .filter(|(cached, _)| cached[..] == *sources)
+            .filter_map(|(_, state)| state.upgrade())
+            .find(|state| state.hidpi_factor.get() == hidpi_factor);
+        if let Some(state) = existing {
+            return state;
+        }
+
+        let atlas = Rc::new(RefCell::new(imgui::SharedFontAtlas::create()));
+        let state = Rc::new(AtlasState::new(Some(atlas), hidpi_factor));
+        cache.push((sources.to_vec(), Rc::downgrade(&state)));
+        state
+    })
+}
+
+/// The fonts of a window, and the glyphs requested at runtime.
+pub(crate) struct FontAtlas {
+    sources: Vec<FontSource>,
+    state: Rc<AtlasState>,
+    uploaded_generation: u64,
+    dirty: bool,
+}
+
+impl FontAtlas {
+    pub fn new(sources: Vec<FontSource>, sharing: FontAtlasSharing, hidpi_factor: f64) -> Self {
+        let state = match sharing {
+            FontAtlasSharing::Cached if !sources.is_empty() => cached_state(&sources, hidpi_factor),
+            FontAtlasSharing::Custom(create) => {
+                Rc::new(AtlasState::new(Some(create()), hidpi_factor))
+            }
+            _ => Rc::new(AtlasState::new(None, hidpi_factor)),
+        };
+
+        Self {
+            sources,
+            state,
+            uploaded_generation: 0,
+            dirty: false,
+        }
+    }
+
+    /// Create the imgui context, using the shared atlas if there is one.
+    pub fn create_context(&self) -> imgui::SuspendedContext {
+        match &self.state.atlas {
+            Some(atlas) => imgui::SuspendedContext::create_with_shared_font_atlas(atlas.clone()),
+            None => imgui::SuspendedContext::create(),
+        }
+    }
+
+    /// Request every character of `text` that is not in the atlas yet.
+    pub fn ensure_glyphs(&mut self, text: &str) {
+        if self.sources.is_empty() {
+            return;
+        }
+
+        let mut extra_glyphs = self.state.extra_glyphs.borrow_mut();
+        for c in text.chars() {
+            if c.is_control()
+                || extra_glyphs.contains(c)
+                || self
+                    .sources
+                    .iter()
+                    .any(|source| source.glyph_ranges.contains(c))
+            {
+                continue;
+            }
+            extra_glyphs.add_char(c);
+            self.dirty = true;
+        }
+    }
+
+    pub fn set_dirty(&mut self) {
+        self.dirty = !self.sources.is_empty();
+    }
+
+    /// Whether the atlas needs to be rebuilt, clearing the flag.
+    pub fn take_dirty(&mut self) -> bool {
+        std::mem::replace(&mut self.dirty, false)
+    }
+
+    /// Whether the atlas was rebuilt since this window last uploaded it.
+    pub fn needs_upload(&self) -> bool {
+        self.uploaded_generation != self.state.generation.get()
+    }
+
+    pub fn mark_uploaded(&mut self) {
+        self.uploaded_generation = self.state.generation.get();
+    }
+
+    /// Rebuild the font atlas at the given hidpi factor.
+    ///
+    /// Does nothing if there are no sources, so fonts added manually in the `build` closure
+    /// are left alone. A shared atlas is only rebuilt if another window hasn't already built
+    /// it with the same glyphs, and only for a higher hidpi factor while other windows use it.
+    pub fn build(&self, context: &mut imgui::Context, hidpi_factor: f64) {
+        if self.sources.is_empty() {
+            return;
+        }
+
+        let hidpi_factor = if Rc::strong_count(&self.state) > 1 {
+            hidpi_factor.max(self.state.hidpi_factor.get())
+        } else {
+            hidpi_factor
+        };
+        self.state.hidpi_factor.set(hidpi_factor);
+
+        let extra_glyphs = self.state.extra_glyphs.borrow();
+        let built_with = (hidpi_factor, extra_glyphs.len());
+
+        if self.state.built_with.get() != Some(built_with) {
+            self.rasterize(context, hidpi_factor, &extra_glyphs);
+
+            self.state.built_with.set(Some(built_with));
+            self.state.generation.set(self.state.generation.get() + 1);
+        }
+
+        context.io_mut().font_global_scale = self.global_scale();
+    }
+
+    /// The `font_global_scale` for the hidpi factor the atlas is rasterized at.
+    pub fn global_scale(&self) -> f32 {
+        1.0 / self.state.hidpi_factor.get() as f32
+    }
+
+    fn rasterize(
+        &self,
+        context: &mut imgui::Context,
+        hidpi_factor: f64,
+        extra_glyphs: &GlyphRangesBuilder,
+    ) {
+        let scale = hidpi_factor as f32;
+        let extra_glyphs = if extra_glyphs.is_empty() {
+            None
+        } else {
+            Some(extra_glyphs.build())
+        };
+
+        // Group the sources into fonts. Every non-merged source starts a new font.
+        let mut groups: Vec<Vec<&FontSource>> = Vec::new();
+        for source in &self.sources {
+            match groups.last_mut() {
+                Some(group) if source.merge => group.push(source),
+                _ => groups.push(vec![source]),
+            }
+        }
+
+        let mut fonts = context.fonts();
+        fonts.clear();
+
+        for group in groups {
+            // A merged source at the start of the list is merged into the default font.
+            let base_size = if group[0].merge {
+                13.0
+            } else {
+                group[0].size_pixels
+            };
+            let size_pixels = base_size * scale;
+
+            let mut font_sources = Vec::with_capacity(group.len() + 2);
+            if group[0].merge {
+                font_sources.push(imgui::FontSource::DefaultFontData {
+                    config: Some(FontConfig {
+                        size_pixels,
+                        ..FontConfig::default()
+                    }),
+                });
+            }
+
+            for source in group {
+                let mut config = FontConfig {
+                    size_pixels,
+                    glyph_ranges: source.glyph_ranges.to_imgui(),
+                    font_builder_flags: source.freetype_flags.bits(),
+                    ..FontConfig::default()
+                };
+                if source.merge {
+                    // Keep icons monospaced so they line up in toolbars.
+                    config.glyph_min_advance_x = size_pixels;
+                    config.pixel_snap_h = true;
+                }
+
+                match &source.data {
+                    FontData::Default => {
+                        font_sources.push(imgui::FontSource::DefaultFontData {
+                            config: Some(config),
+                        });
+                    }
+                    FontData::Ttf(data) => {
+                        font_sources.push(imgui::FontSource::TtfData {
+                            data: &data[..],
+                            size_pixels,
+                            config: Some(config),
+                        });
+
+                        // Merge the glyphs requested at runtime from the main font of the
+                        // group. Icon fonts won't have them anyway.
+                        if let (false, Some(extra_glyphs)) = (source.merge, &extra_glyphs) {
+                            font_sources.push(imgui::FontSource::TtfData {
+                                data: &data[..],
+                                size_pixels,
+                                config: Some(FontConfig {
+                                    size_pixels,
+                                    glyph_ranges: extra_glyphs.to_imgui(),
+                                    font_builder_flags: source.freetype_flags.bits(),
+                                    ..FontConfig::default()
+                                }),
+                            });
+                        }
+                    }
+                }
+            }
+
+            fonts.add_font(&font_sources);
+        }
+    }
+}
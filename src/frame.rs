@@ -0,0 +1,237 @@
+//! Callbacks around the frame of a window.
+//!
+//! Plugins often have work that belongs next to the UI but not in it, like draining the
+//! parameter changes the audio thread queued before the UI is built, and marking what the
+//! UI changed after it is rendered. `WindowContext::on_before_frame` and
+//! `WindowContext::on_after_frame` register callbacks for that, which get the state of the
+//! window like the update closure.
+//!
+//! `WindowContext::on_draw_data` shows the draw data of the frame to a callback before it is
+//! rendered, to keep statistics or a capture of the draw lists, or to change it. The renderer
+//! builds the draw data from the `imgui::Ui` itself, so the callback is hooked into imgui for
+//! the time the renderer runs, and called when imgui has built what the renderer draws.
+//!
+//! The frames are also timed against `Settings::frame_budget` here.
+
+use std::any::Any;
+use std::ffi::c_void;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Duration;
+
+use imgui::sys::{ImGuiContext, ImGuiContextHook};
+
+use crate::settings::FrameBudget;
+
+type BeforeFrame = Box<dyn FnMut(&mut dyn Any)>;
+type AfterFrame = Box<dyn FnMut(&mut dyn Any, &DrawStats)>;
+type DrawDataHook = Box<dyn FnMut(&mut dyn Any, &mut imgui::DrawData)>;
+
+/// What a frame drew.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DrawStats {
+    pub draw_lists: usize,
+    pub draw_commands: usize,
+    pub vertices: usize,
+    pub indices: usize,
+    /// How long it took to build and render the frame.
+    pub frame_time: Duration,
+    /// Whether the window lightened its rendering, because frames took longer than
+    /// `Settings::frame_budget`.
+    pub degraded: bool,
+    /// How long the oldest input this frame is the first to show waited for it, up to the end
+    /// of the buffer swap. Only measured while the debug overlay can be shown.
+    pub input_latency: Option<Duration>,
+}
+
+impl DrawStats {
+    /// The stats of the draw data of the frame that was just rendered.
+    pub(crate) fn current(frame_time: Duration) -> Self {
+        let mut stats = DrawStats {
+            frame_time,
+            ..DrawStats::default()
+        };
+
+        unsafe {
+            let draw_data = imgui::sys::igGetDrawData();
+            if draw_data.is_null() {
+                return stats;
+            }
+
+            let draw_data = &*draw_data;
+            stats.draw_lists = draw_data.CmdListsCount as usize;
+            stats.vertices = draw_data.TotalVtxCount as usize;
+            stats.indices = draw_data.TotalIdxCount as usize;
+            for i in 0..stats.draw_lists {
+                let list = &**draw_data.CmdLists.add(i);
+                stats.draw_commands += list.CmdBuffer.Size as usize;
+            }
+        }
+        stats
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct FrameHooks {
+    before: Option<BeforeFrame>,
+    after: Option<AfterFrame>,
+    draw_data: Option<DrawDataHook>,
+}
+
+impl FrameHooks {
+    pub fn set_before<S, F>(&mut self, mut callback: F)
+    where
+        S: 'static,
+        F: FnMut(&mut S) + 'static,
+    {
+        self.before = Some(Box::new(move |state| {
+            if let Some(state) = state.downcast_mut() {
+                callback(state)
+            }
+        }));
+    }
+
+    pub fn set_after<S, F>(&mut self, mut callback: F)
+    where
+        S: 'static,
+        F: FnMut(&mut S, &DrawStats) + 'static,
+    {
+        self.after = Some(Box::new(move |state, stats| {
+            if let Some(state) = state.downcast_mut() {
+                callback(state, stats)
+            }
+        }));
+    }
+
+    pub fn set_draw_data<S, F>(&mut self, mut callback: F)
+    where
+        S: 'static,
+        F: FnMut(&mut S, &mut imgui::DrawData) + 'static,
+    {
+        self.draw_data = Some(Box::new(move |state, draw_data| {
+            if let Some(state) = state.downcast_mut() {
+                callback(state, draw_data)
+            }
+        }));
+    }
+
+    pub fn before_frame(&mut self, state: &mut dyn Any) {
+        if let Some(before) = &mut self.before {
+            before(state);
+        }
+    }
+
+    /// Run `render` with the state, while the draw data callback is called when imgui builds
+    /// the draw data. The renderer builds it right before it draws it, so the callback changes
+    /// what is drawn.
+    pub fn render_with<R>(
+        &mut self,
+        state: &mut dyn Any,
+        render: impl FnOnce(&mut dyn Any) -> R,
+    ) -> R {
+        let callback = match &mut self.draw_data {
+            Some(callback) => callback,
+            None => return render(state),
+        };
+
+        let state: *mut dyn Any = state;
+        let mut hooked = Hooked {
+            callback,
+            state,
+            panic: None,
+        };
+        let result = unsafe {
+            let context = imgui::sys::igGetCurrentContext();
+            let hook = ImGuiContextHook {
+                HookId: 0,
+                Type: imgui::sys::ImGuiContextHookType_RenderPost,
+                Owner: 0,
+                Callback: Some(call_draw_data),
+                UserData: &mut hooked as *mut Hooked<'_> as *mut c_void,
+            };
+            let id = imgui::sys::igAddContextHook(context, &hook);
+            // The renderer is done with the state by the time it builds the draw data.
+            let result = render(&mut *state);
+            imgui::sys::igRemoveContextHook(context, id);
+            result
+        };
+
+        // A panic can't unwind through imgui, so it continues from here.
+        if let Some(payload) = hooked.panic {
+            panic::resume_unwind(payload);
+        }
+        result
+    }
+
+    pub fn after_frame(&mut self, state: &mut dyn Any, stats: &DrawStats) {
+        if let Some(after) = &mut self.after {
+            after(state, stats);
+        }
+    }
+}
+
+/// What the draw data callback needs while it is hooked into imgui.
+struct Hooked<'a> {
+    callback: &'a mut DrawDataHook,
+    state: *mut dyn Any,
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+unsafe extern "C" fn call_draw_data(_context: *mut ImGuiContext, hook: *mut ImGuiContextHook) {
+    let hooked = &mut *((*hook).UserData as *mut Hooked<'_>);
+    let draw_data = imgui::sys::igGetDrawData();
+    if draw_data.is_null() || hooked.panic.is_some() {
+        return;
+    }
+
+    let draw_data = &mut *(draw_data as *mut imgui::DrawData);
+    let state = &mut *hooked.state;
+    let callback = &mut *hooked.callback;
+    if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| callback(state, draw_data))) {
+        hooked.panic = Some(payload);
+    }
+}
+
+/// Counts the frames in a row that took longer than the budget.
+#[derive(Default)]
+pub(crate) struct FrameWatchdog {
+    budget: Option<FrameBudget>,
+    over: u32,
+    degraded: bool,
+}
+
+impl FrameWatchdog {
+    pub fn new(budget: Option<FrameBudget>) -> Self {
+        Self {
+            budget,
+            ..Self::default()
+        }
+    }
+
+    /// Time a frame, returning whether the window has to degrade with it.
+    pub fn record(&mut self, frame_time: Duration) -> bool {
+        let budget = match &self.budget {
+            Some(budget) if !self.degraded => budget,
+            _ => return false,
+        };
+        if frame_time <= budget.budget {
+            self.over = 0;
+            return false;
+        }
+        self.over += 1;
+        self.degraded = self.over >= budget.frames;
+        self.degraded
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        self.degraded
+    }
+
+    /// The frame rate the window is held to, once it degraded.
+    pub fn fps(&self) -> Option<f32> {
+        match &self.budget {
+            Some(budget) if self.degraded => Some(budget.fps),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! Frosted glass behind translucent windows.
+//!
+//! imgui draws everything in one pass, so what is behind a window can't be read back while
+//! it is drawn. Instead the backdrop of the UI, like a background image or a render target,
+//! is set with `WindowContext::set_backdrop`. It is blurred on the GPU every frame before the
+//! UI is rendered, and `WindowContext::glass` draws the blurred part behind the current
+//! window.
+
+/// The texture the UI is drawn on top of.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Backdrop {
+    pub texture: imgui::TextureId,
+    /// The size of the texture in pixels.
+    pub size: [u32; 2],
+    /// Where the texture is drawn, in logical coordinates.
+    pub position: [f32; 2],
+    pub display_size: [f32; 2],
+    /// The texture coordinates it is drawn with, flipped for render targets.
+    pub uv0: [f32; 2],
+    pub uv1: [f32; 2],
+    /// How strong the blur is, from 1 to 6. Every pass halves the resolution.
+    pub passes: u32,
+    /// How far apart the blurred samples are, in pixels of each pass.
+    pub offset: f32,
+}
+
+impl Backdrop {
+    pub fn new(
+        texture: imgui::TextureId,
+        size: [u32; 2],
+        position: [f32; 2],
+        display_size: [f32; 2],
+    ) -> Self {
+        Self {
+            texture,
+            size,
+            position,
+            display_size,
+            uv0: [0.0, 0.0],
+            uv1: [1.0, 1.0],
+            passes: 4,
+            offset: 1.0,
+        }
+    }
+}
+
+/// How a glass window is drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlassStyle {
+    /// The color drawn over the blurred backdrop.
+    pub tint: [f32; 4],
+    /// The rounding of the corners. Uses the window rounding of the style when not set.
+    pub rounding: Option<f32>,
+}
+
+impl Default for GlassStyle {
+    fn default() -> Self {
+        Self {
+            tint: [1.0, 1.0, 1.0, 0.08],
+            rounding: None,
+        }
+    }
+}
+
+/// Draw the blurred backdrop behind the contents of the current window, below its title
+/// bar.
+pub(crate) fn draw(
+    ui: &imgui::Ui,
+    backdrop: &Backdrop,
+    blurred: imgui::TextureId,
+    style: &GlassStyle,
+) {
+    let position = ui.window_pos();
+    let size = ui.window_size();
+    let padding = ui.clone_style().window_padding;
+    let top = position[1] + (ui.cursor_start_pos()[1] - padding[1]).max(0.0);
+    let min = [position[0], top];
+    let max = [position[0] + size[0], position[1] + size[1]];
+
+    let uv = |point: [f32; 2]| {
+        let x = (point[0] - backdrop.position[0]) / backdrop.display_size[0];
+        let y = (point[1] - backdrop.position[1]) / backdrop.display_size[1];
+        [
+            backdrop.uv0[0] + (backdrop.uv1[0] - backdrop.uv0[0]) * x,
+            backdrop.uv0[1] + (backdrop.uv1[1] - backdrop.uv0[1]) * y,
+        ]
+    };
+
+    let rounding = style
+        .rounding
+        .unwrap_or_else(|| ui.clone_style().window_rounding);
+    // With a title bar above, only the bottom corners are rounded.
+    let round_top = top <= position[1];
+
+    let draw_list = ui.get_window_draw_list();
+    draw_list.with_clip_rect(min, max, || {
+        draw_list
+            .add_image_rounded(blurred, min, max, rounding)
+            .uv_min(uv(min))
+            .uv_max(uv(max))
+            .round_top_left(round_top)
+            .round_top_right(round_top)
+            .build();
+        draw_list
+            .add_rect(min, max, style.tint)
+            .filled(true)
+            .rounding(rounding)
+            .round_top_left(round_top)
+            .round_top_right(round_top)
+            .build();
+    });
+}
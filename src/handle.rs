@@ -0,0 +1,222 @@
+//! Communication between a window and its host.
+//!
+//! The crate's widgets report what the user did as `UiEvent`s, which the host or plugin reads
+//! from a `Handle`. The host keeps a clone of the `Handle` passed in `Settings::handle`, and
+//! can use it to send information back to the window, such as completed MIDI mappings.
+
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::params::ParamId;
+use crate::state_diff::StateChange;
+use crate::widgets::AbSlots;
+
+/// Something the user did in the window.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UiEvent {
+    /// The user started editing a parameter, a host automation gesture should begin.
+    BeginEdit(ParamId),
+    /// The user changed a parameter.
+    SetParam { id: ParamId, value: f32 },
+    /// The user finished editing a parameter.
+    EndEdit(ParamId),
+    /// The user wants the parameter to follow the next MIDI controller that is moved.
+    ///
+    /// Complete the mapping with `Handle::set_midi_mapping`.
+    MidiLearn(ParamId),
+    /// The user wants to remove the MIDI mapping of the parameter.
+    MidiUnlearn(ParamId),
+    /// The user wants to see the automation of the parameter in the host.
+    ShowAutomation(ParamId),
+    /// The user wants to remove the automation of the parameter in the host.
+    ClearAutomation(ParamId),
+    /// The user pressed a key of a piano keyboard, with a velocity from `0.0` to `1.0`.
+    NoteOn { note: u8, velocity: f32 },
+    /// The user released a key of a piano keyboard.
+    NoteOff { note: u8 },
+    /// The user wants to load the preset file.
+    LoadPreset(PathBuf),
+    /// The user wants to save the current settings to the preset file.
+    SavePreset(PathBuf),
+    /// The layout of the imgui windows or the `UiStorage` changed. Keep the string and
+    /// pass it in `Settings::ui_state` to restore them when the window is opened again.
+    SaveUiState(String),
+    /// An embedded window wants to be resized to a logical size, because the UI scale
+    /// changed. Standalone windows resize themselves.
+    RequestResize { width: f64, height: f64 },
+    /// Fields of the state changed in the last frame. Only sent for a state that is watched
+    /// with `WindowContext::diff_state`.
+    StateChanged(Vec<StateChange>),
+    /// The user switched between the slots of an `AbCompare`, copied one to the other, or
+    /// undid that. Keep both slots and pass them to `AbCompare::restore` when the window is
+    /// opened again.
+    AbCompare(AbSlots),
+    /// A page in the web view `view` called `window.ipc.postMessage(message)`.
+    #[cfg(feature = "webview")]
+    WebMessage { view: String, message: String },
+}
+
+/// A MIDI controller a parameter is mapped to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MidiMapping {
+    pub channel: u8,
+    pub cc: u8,
+}
+
+/// The logical sizes the host allows the window to have.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SizeLimits {
+    pub min: Option<[f64; 2]>,
+    pub max: Option<[f64; 2]>,
+}
+
+impl SizeLimits {
+    /// A size the host can't change.
+    pub fn fixed(width: f64, height: f64) -> Self {
+        Self {
+            min: Some([width, height]),
+            max: Some([width, height]),
+        }
+    }
+
+    pub fn clamp(&self, size: [f64; 2]) -> [f64; 2] {
+        let mut size = size;
+        for i in 0..2 {
+            if let Some(max) = self.max {
+                size[i] = size[i].min(max[i]);
+            }
+            if let Some(min) = self.min {
+                size[i] = size[i].max(min[i]);
+            }
+        }
+        size
+    }
+}
+
+#[derive(Default)]
+struct Shared {
+    events: Mutex<VecDeque<UiEvent>>,
+    midi_mappings: Mutex<HashMap<ParamId, MidiMapping>>,
+    hidden: AtomicBool,
+    reset_input: AtomicBool,
+    external_frame: AtomicBool,
+    frame_requested: AtomicBool,
+    size_limits: Mutex<SizeLimits>,
+    #[cfg(feature = "webview")]
+    web_messages: Mutex<Vec<(String, String)>>,
+}
+
+/// A connection between a window and its host.
+///
+/// Cloning gives another reference to the same connection.
+#[derive(Clone, Default)]
+#[allow(missing_debug_implementations)]
+pub struct Handle {
+    shared: Arc<Shared>,
+}
+
+impl Handle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take the oldest event that has not been read yet.
+    pub fn poll_event(&self) -> Option<UiEvent> {
+        self.shared.events.lock().unwrap().pop_front()
+    }
+
+    /// Take all events that have not been read yet.
+    pub fn drain_events(&self) -> Vec<UiEvent> {
+        self.shared.events.lock().unwrap().drain(..).collect()
+    }
+
+    pub(crate) fn emit(&self, event: UiEvent) {
+        self.shared.events.lock().unwrap().push_back(event);
+    }
+
+    /// Tell the window which MIDI controller a parameter is mapped to, or that it isn't mapped.
+    pub fn set_midi_mapping(&self, id: ParamId, mapping: Option<MidiMapping>) {
+        let mut mappings = self.shared.midi_mappings.lock().unwrap();
+        match mapping {
+            Some(mapping) => mappings.insert(id, mapping),
+            None => mappings.remove(&id),
+        };
+    }
+
+    pub fn midi_mapping(&self, id: ParamId) -> Option<MidiMapping> {
+        self.shared.midi_mappings.lock().unwrap().get(&id).copied()
+    }
+
+    /// Tell the window whether the host shows it, for hosts that hide the editor without
+    /// hiding its window. Hidden windows don't render, but still handle what is sent to them.
+    pub fn set_visible(&self, visible: bool) {
+        self.shared.hidden.store(!visible, Ordering::Relaxed);
+    }
+
+    pub fn is_visible(&self) -> bool {
+        !self.shared.hidden.load(Ordering::Relaxed)
+    }
+
+    /// Release every key and mouse button the window thinks is held down, before the next
+    /// frame. Use this when the host is known to have swallowed release events, for example
+    /// after it showed a modal dialog over the window.
+    pub fn reset_input_state(&self) {
+        self.shared.reset_input.store(true, Ordering::Relaxed);
+    }
+
+    pub(crate) fn take_input_reset(&self) -> bool {
+        self.shared.reset_input.swap(false, Ordering::Relaxed)
+    }
+
+    /// Render a frame with `FrameMode::OnDemand`, the next time baseview asks the window for
+    /// one. Can be called from any thread.
+    pub fn request_frame(&self) {
+        self.shared.frame_requested.store(true, Ordering::Relaxed);
+    }
+
+    pub(crate) fn take_frame_request(&self) -> bool {
+        self.shared.frame_requested.swap(false, Ordering::Relaxed)
+    }
+
+    /// Run a frame of a window with `GlContextMode::External`, rendering into the context
+    /// that is current on its thread. Keep the context current until the frame ran, which
+    /// is the next time baseview asks the window for one.
+    pub fn render_external(&self) {
+        self.shared.external_frame.store(true, Ordering::Relaxed);
+    }
+
+    pub(crate) fn take_external_frame(&self) -> bool {
+        self.shared.external_frame.swap(false, Ordering::Relaxed)
+    }
+
+    /// Tell the window which sizes the host allows, before it is opened. The size restored
+    /// with `Settings::restore_size` is kept within them, and so is the size that is saved.
+    /// Hosts that don't let the user resize the window should pass `SizeLimits::fixed`.
+    pub fn set_size_limits(&self, limits: SizeLimits) {
+        *self.shared.size_limits.lock().unwrap() = limits;
+    }
+
+    pub fn size_limits(&self) -> SizeLimits {
+        *self.shared.size_limits.lock().unwrap()
+    }
+
+    /// Send a message to the pages of the web view `view`, as a `message` event on their
+    /// `window`. Can be called from any thread.
+    #[cfg(feature = "webview")]
+    pub fn post_web_message(&self, view: &str, message: impl Into<String>) {
+        self.shared
+            .web_messages
+            .lock()
+            .unwrap()
+            .push((view.to_string(), message.into()));
+        // Windows with `FrameMode::OnDemand` would not get to the message.
+        self.request_frame();
+    }
+
+    #[cfg(feature = "webview")]
+    pub(crate) fn take_web_messages(&self) -> Vec<(String, String)> {
+        std::mem::take(&mut *self.shared.web_messages.lock().unwrap())
+    }
+}
@@ -0,0 +1,110 @@
+//! Consistent tooltips and a help mode.
+//!
+//! Help texts are registered once per widget id with `WindowContext::register_help`, and
+//! shown with `WindowContext::help` right after the widget is built. The tooltip appears
+//! after `Settings::tooltip_delay`, or immediately in help mode, which also highlights every
+//! annotated widget so users can discover them.
+//!
+//! Help texts support a small subset of markdown: lines starting with `# ` are headings,
+//! lines starting with `- ` are bullet points and empty lines separate paragraphs.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// The width of tooltips in multiples of the font size, so they scale with the hidpi factor.
+const TOOLTIP_WIDTH: f32 = 30.0;
+
+pub(crate) struct Help {
+    texts: HashMap<String, String>,
+    delay: Duration,
+    help_mode: bool,
+    hovered: Option<String>,
+    hovered_time: Duration,
+    hovered_this_frame: bool,
+}
+
+impl Help {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            texts: HashMap::new(),
+            delay,
+            help_mode: false,
+            hovered: None,
+            hovered_time: Duration::default(),
+            hovered_this_frame: false,
+        }
+    }
+
+    pub fn register(&mut self, id: &str, text: String) {
+        self.texts.insert(id.to_string(), text);
+    }
+
+    pub fn help_mode(&self) -> bool {
+        self.help_mode
+    }
+
+    pub fn set_help_mode(&mut self, help_mode: bool) {
+        self.help_mode = help_mode;
+    }
+
+    pub fn begin_frame(&mut self) {
+        // Restart the delay once the pointer leaves the annotated widgets.
+        if !self.hovered_this_frame {
+            self.hovered = None;
+        }
+        self.hovered_this_frame = false;
+    }
+
+    /// Show the help of the item that was just built.
+    pub fn item(&mut self, ui: &imgui::Ui, id: &str) {
+        let text = match self.texts.get(id) {
+            Some(text) => text,
+            None => return,
+        };
+
+        if self.help_mode {
+            let color = ui.style_color(imgui::StyleColor::NavHighlight);
+            ui.get_window_draw_list()
+                .add_rect(ui.item_rect_min(), ui.item_rect_max(), color)
+                .rounding(ui.clone_style().frame_rounding)
+                .build();
+        }
+
+        if !ui.is_item_hovered() {
+            return;
+        }
+
+        self.hovered_this_frame = true;
+        if self.hovered.as_deref() != Some(id) {
+            self.hovered = Some(id.to_string());
+            self.hovered_time = Duration::default();
+        }
+        self.hovered_time += Duration::from_secs_f32(ui.io().delta_time);
+
+        if self.help_mode || self.hovered_time >= self.delay {
+            ui.tooltip(|| {
+                let _wrap = ui.push_text_wrap_pos_with_pos(ui.current_font_size() * TOOLTIP_WIDTH);
+                draw_text(ui, text);
+            });
+        }
+    }
+}
+
+/// Draw text formatted with the supported markdown subset.
+pub fn draw_text(ui: &imgui::Ui, text: &str) {
+    let heading_color = ui.style_color(imgui::StyleColor::PlotHistogram);
+
+    for line in text.lines() {
+        let line = line.trim_end();
+        if let Some(heading) = line.strip_prefix("# ") {
+            ui.text_colored(heading_color, heading);
+        } else if let Some(bullet) = line.strip_prefix("- ") {
+            ui.bullet();
+            ui.text_wrapped(bullet);
+        } else if line.is_empty() {
+            ui.spacing();
+        } else {
+            ui.text_wrapped(line);
+        }
+    }
+}
@@ -0,0 +1,290 @@
+//! Reloading the update closure from a dynamic library while the window is open.
+//!
+//! Rebuilding the plugin and restarting the host for every tweak to the layout is slow. With
+//! the `hot_reload` feature the update closure can instead be a function of a `cdylib`, made
+//! with `hot_reload::update`. The window loads the library when it opens, and loads it again
+//! every time the file changes, so `cargo build` of the library shows the new UI in the
+//! running window.
+//!
+//! The library links its own copy of imgui, which has no context and allocates from its own
+//! heap. Before every call of the function the window hands the library its context and
+//! allocator through a second function, `imgui_baseview_hot_reload_context`. Both are
+//! exported with `hot_reload_export!`, once per library, which needs the `hot_reload`
+//! feature in the library too:
+//!
+//! ```ignore
+//! fn update(run: &mut bool, ui: &imgui::Ui, ctx: &mut WindowContext, state: &mut State) {
+//!     ui.text("Edit me");
+//! }
+//!
+//! imgui_baseview::hot_reload_export!(update = update, State);
+//! ```
+//!
+//! The state stays in the window. Before a library is unloaded it is serialized and read
+//! back, so nothing in it points into the code of the old library, like a boxed closure the
+//! library made. The state type has to be the same in the window and the library, changing
+//! it needs a restart. The library is loaded from a copy, so the build can replace the file
+//! while it is in use.
+//!
+//! This is meant for development only: the function is called without any checks that the
+//! library was built with the same compiler and crates as the window.
+
+use std::ffi::c_void;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use imgui::sys::{ImGuiContext, ImGuiMemAllocFunc, ImGuiMemFreeFunc};
+use libloading::Library;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::WindowContext;
+
+/// How often the file is checked for changes.
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The function `hot_reload_export!` exports to make the library use the imgui context of
+/// the window.
+const CONTEXT_SYMBOL: &str = "imgui_baseview_hot_reload_context";
+
+/// For `hot_reload_export!`.
+#[doc(hidden)]
+pub use imgui;
+
+type UpdateFn<State> = fn(&mut bool, &imgui::Ui, &mut WindowContext, &mut State);
+type ContextFn =
+    unsafe extern "C" fn(*mut ImGuiContext, ImGuiMemAllocFunc, ImGuiMemFreeFunc, *mut c_void);
+
+struct Loaded<State> {
+    update: UpdateFn<State>,
+    set_context: ContextFn,
+    /// Keeps the code of `update` loaded.
+    _library: Library,
+    copy: PathBuf,
+}
+
+/// Reloads the update function from a library as it is rebuilt.
+struct HotReloader<State> {
+    path: PathBuf,
+    symbol: String,
+    loaded: Option<Loaded<State>>,
+    modified: Option<SystemTime>,
+    last_check: Option<Instant>,
+    loads: u32,
+    /// Why the last load failed, shown in place of the UI until one succeeds.
+    error: Option<String>,
+}
+
+impl<State> HotReloader<State>
+where
+    State: Serialize + DeserializeOwned,
+{
+    fn new(path: PathBuf, symbol: String) -> Self {
+        Self {
+            path,
+            symbol,
+            loaded: None,
+            modified: None,
+            last_check: None,
+            loads: 0,
+            error: None,
+        }
+    }
+
+    /// Load the library again if it changed since it was last loaded.
+    fn check(&mut self, state: &mut State) -> bool {
+        if let Some(last_check) = self.last_check {
+            if last_check.elapsed() < CHECK_INTERVAL {
+                return false;
+            }
+        }
+        self.last_check = Some(Instant::now());
+
+        let modified = fs::metadata(&self.path).and_then(|metadata| metadata.modified());
+        let modified = match modified {
+            Ok(modified) => modified,
+            Err(error) => {
+                if self.loaded.is_none() {
+                    self.error = Some(format!("{}: {}", self.path.display(), error));
+                }
+                return false;
+            }
+        };
+        if self.modified == Some(modified) {
+            return false;
+        }
+
+        match self.load() {
+            Ok(loaded) => {
+                self.modified = Some(modified);
+                self.error = None;
+                if let Some(old) = self.loaded.take() {
+                    reset_state(state);
+                    unload(old);
+                }
+                self.loaded = Some(loaded);
+                true
+            }
+            // The build may still be writing the file. It is tried again at the next check,
+            // and the old library stays loaded until then.
+            Err(error) => {
+                self.error = Some(error);
+                false
+            }
+        }
+    }
+
+    fn load(&mut self) -> Result<Loaded<State>, String> {
+        self.loads += 1;
+        let file_name = self
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let copy = std::env::temp_dir().join(format!(
+            "imgui-baseview-{}-{}-{}",
+            std::process::id(),
+            self.loads,
+            file_name
+        ));
+        fs::copy(&self.path, &copy).map_err(|error| format!("{}: {}", copy.display(), error))?;
+
+        let loaded = unsafe { load_copy(&copy, &self.symbol) };
+        if loaded.is_err() {
+            let _ = fs::remove_file(&copy);
+        }
+        loaded
+    }
+
+    fn update(
+        &mut self,
+        run: &mut bool,
+        ui: &imgui::Ui,
+        ctx: &mut WindowContext,
+        state: &mut State,
+    ) {
+        if self.check(state) {
+            ctx.handle().request_frame();
+        }
+
+        if let Some(error) = &self.error {
+            ui.text_colored([1.0, 0.4, 0.4, 1.0], format!("Hot reload: {}", error));
+        }
+        if let Some(loaded) = &self.loaded {
+            unsafe { share_context(loaded.set_context) };
+            (loaded.update)(run, ui, ctx, state);
+        }
+    }
+}
+
+impl<State> Drop for HotReloader<State> {
+    fn drop(&mut self) {
+        if let Some(loaded) = self.loaded.take() {
+            unload(loaded);
+        }
+    }
+}
+
+unsafe fn load_copy<State>(copy: &Path, symbol: &str) -> Result<Loaded<State>, String> {
+    let library = Library::new(copy).map_err(|error| error.to_string())?;
+    let update = *library
+        .get::<UpdateFn<State>>(symbol.as_bytes())
+        .map_err(|error| error.to_string())?;
+    let set_context = *library
+        .get::<ContextFn>(CONTEXT_SYMBOL.as_bytes())
+        .map_err(|error| format!("{}, export it with `hot_reload_export!`", error))?;
+    Ok(Loaded {
+        update,
+        set_context,
+        _library: library,
+        copy: copy.to_path_buf(),
+    })
+}
+
+/// Hand the current imgui context of the window, and the allocator it was made with, to the
+/// imgui of the library.
+unsafe fn share_context(set_context: ContextFn) {
+    let mut alloc = None;
+    let mut free = None;
+    let mut user_data = std::ptr::null_mut();
+    imgui::sys::igGetAllocatorFunctions(&mut alloc, &mut free, &mut user_data);
+    set_context(imgui::sys::igGetCurrentContext(), alloc, free, user_data);
+}
+
+/// Make the imgui of this library use the context and allocator of the window that loaded
+/// it. Called through the function `hot_reload_export!` exports.
+#[doc(hidden)]
+pub unsafe fn set_context(
+    context: *mut ImGuiContext,
+    alloc: ImGuiMemAllocFunc,
+    free: ImGuiMemFreeFunc,
+    user_data: *mut c_void,
+) {
+    imgui::sys::igSetAllocatorFunctions(alloc, free, user_data);
+    imgui::sys::igSetCurrentContext(context);
+}
+
+fn unload<State>(loaded: Loaded<State>) {
+    let copy = loaded.copy.clone();
+    drop(loaded);
+    let _ = fs::remove_file(copy);
+}
+
+/// Serialize the state and read it back, so it no longer refers to the old library.
+fn reset_state<State>(state: &mut State)
+where
+    State: Serialize + DeserializeOwned,
+{
+    let reset = serde_json::to_value(&*state).and_then(serde_json::from_value);
+    if let Ok(reset) = reset {
+        *state = reset;
+    }
+}
+
+/// An update closure that calls the function `symbol` of the library at `path`, and loads
+/// the library again when the file changes. Pass it as the `update` of `ImguiWindow`.
+///
+/// Until the library is loaded, and when loading a new one fails, the window shows why.
+pub fn update<State>(
+    path: impl Into<PathBuf>,
+    symbol: &str,
+) -> impl FnMut(&mut bool, &imgui::Ui, &mut WindowContext, &mut State) + Send + 'static
+where
+    State: Serialize + DeserializeOwned + 'static,
+{
+    let mut reloader = HotReloader::<State>::new(path.into(), symbol.to_string());
+    move |run, ui, ctx, state| reloader.update(run, ui, ctx, state)
+}
+
+/// Export `$update` from a `cdylib` as the function `$symbol` for `hot_reload::update`, with
+/// the function that hands the library the imgui context of the window. Use it once per
+/// library.
+///
+/// ```ignore
+/// imgui_baseview::hot_reload_export!(update = draw_ui, MyState);
+/// ```
+#[macro_export]
+macro_rules! hot_reload_export {
+    ($symbol:ident = $update:path, $state:ty) => {
+        #[no_mangle]
+        pub fn $symbol(
+            run: &mut bool,
+            ui: &$crate::hot_reload::imgui::Ui,
+            ctx: &mut $crate::WindowContext,
+            state: &mut $state,
+        ) {
+            $update(run, ui, ctx, state)
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn imgui_baseview_hot_reload_context(
+            context: *mut $crate::hot_reload::imgui::sys::ImGuiContext,
+            alloc: $crate::hot_reload::imgui::sys::ImGuiMemAllocFunc,
+            free: $crate::hot_reload::imgui::sys::ImGuiMemFreeFunc,
+            user_data: *mut ::std::ffi::c_void,
+        ) {
+            $crate::hot_reload::set_context(context, alloc, free, user_data)
+        }
+    };
+}
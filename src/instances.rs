@@ -0,0 +1,159 @@
+//! Track all open windows of a plugin in the process.
+//!
+//! Windows opened with `Settings::instance_key` set to the same key are registered together,
+//! so that one instance can, for example, apply a theme to all instances or show frame
+//! statistics of every instance for diagnostics.
+//!
+//! The registry only holds weak references, so closed windows disappear from it
+//! automatically.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+type Command = Arc<dyn Fn(&mut imgui::Context) + Send + Sync>;
+
+/// Frame statistics of an instance.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct InstanceStats {
+    /// The number of frames rendered so far.
+    pub frame_count: u64,
+    /// How long the last frame took to build and render.
+    pub last_frame_time: Duration,
+    /// A moving average of the frame time.
+    pub average_frame_time: Duration,
+    /// The logical size of the imgui display.
+    pub display_size: [f32; 2],
+    /// The current hidpi factor.
+    pub hidpi_factor: f64,
+    /// Whether the window has the keyboard focus or the mouse over it, and renders at the full
+    /// rate.
+    pub active: bool,
+}
+
+/// Information about an open instance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InstanceInfo {
+    /// A process-wide unique id of the instance.
+    pub id: u64,
+    pub stats: InstanceStats,
+}
+
+pub(crate) struct Instance {
+    key: String,
+    id: u64,
+    stats: Mutex<InstanceStats>,
+    commands: Mutex<Vec<Command>>,
+    background_fps: Mutex<Option<f32>>,
+}
+
+static REGISTRY: Mutex<Vec<Weak<Instance>>> = Mutex::new(Vec::new());
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Register a new instance under the given key.
+pub(crate) fn register(key: String, background_fps: Option<f32>) -> Arc<Instance> {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+
+    let instance = Arc::new(Instance {
+        key,
+        id,
+        stats: Mutex::new(InstanceStats::default()),
+        commands: Mutex::new(Vec::new()),
+        background_fps: Mutex::new(background_fps),
+    });
+
+    let mut registry = REGISTRY.lock().unwrap();
+    registry.retain(|instance| instance.strong_count() > 0);
+    registry.push(Arc::downgrade(&instance));
+
+    instance
+}
+
+fn with_instances<F: FnMut(&Instance)>(key: &str, mut f: F) {
+    let registry = REGISTRY.lock().unwrap();
+    for instance in registry.iter().filter_map(Weak::upgrade) {
+        if instance.key == key {
+            f(&instance);
+        }
+    }
+}
+
+/// The open instances registered under the given key.
+pub fn instances(key: &str) -> Vec<InstanceInfo> {
+    let mut infos = Vec::new();
+    with_instances(key, |instance| {
+        infos.push(InstanceInfo {
+            id: instance.id,
+            stats: *instance.stats.lock().unwrap(),
+        })
+    });
+    infos
+}
+
+/// The number of open instances registered under the given key.
+pub fn count(key: &str) -> usize {
+    let mut count = 0;
+    with_instances(key, |_| count += 1);
+    count
+}
+
+/// Run `f` on the imgui context of every instance registered under the given key.
+///
+/// `f` is called before the next frame of each instance, on the thread of its window. Use
+/// this to apply a theme to all instances, for example.
+pub fn broadcast<F>(key: &str, f: F)
+where
+    F: Fn(&mut imgui::Context) + Send + Sync + 'static,
+{
+    let command: Command = Arc::new(f);
+    with_instances(key, |instance| {
+        instance.commands.lock().unwrap().push(command.clone())
+    });
+}
+
+/// Change the frame rate of the instances registered under the given key while they are not
+/// focused or under the mouse, like `Settings::background_fps`.
+pub fn set_background_fps(key: &str, background_fps: Option<f32>) {
+    with_instances(key, |instance| {
+        *instance.background_fps.lock().unwrap() = background_fps
+    });
+}
+
+impl Instance {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn background_fps(&self) -> Option<f32> {
+        *self.background_fps.lock().unwrap()
+    }
+
+    /// Run the commands broadcast to this instance since the last frame.
+    pub fn run_commands(&self, context: &mut imgui::Context) {
+        let commands = std::mem::take(&mut *self.commands.lock().unwrap());
+        for command in commands {
+            (command)(context);
+        }
+    }
+
+    pub fn record_frame(
+        &self,
+        frame_time: Duration,
+        display_size: [f32; 2],
+        hidpi_factor: f64,
+        active: bool,
+    ) {
+        let mut stats = self.stats.lock().unwrap();
+
+        stats.average_frame_time = if stats.frame_count == 0 {
+            frame_time
+        } else {
+            (stats.average_frame_time * 15 + frame_time) / 16
+        };
+        stats.frame_count += 1;
+        stats.last_frame_time = frame_time;
+        stats.display_size = display_size;
+        stats.hidpi_factor = hidpi_factor;
+        stats.active = active;
+    }
+}
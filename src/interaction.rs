@@ -0,0 +1,187 @@
+//! How the param-bound widgets respond to the mouse and the modifier keys.
+//!
+//! Every param-bound widget of the `widgets` module, from sliders and knobs to the nodes of
+//! the XY pad, goes through the same policy, set in `Settings::interaction`: dragging changes
+//! the value relative to where the drag started, holding the fine modifier slows it down, and
+//! clicking with a modifier types in a value or resets the parameter to its default.
+
+use std::sync::Arc;
+
+use crate::handle::UiEvent;
+use crate::params::{Param, ParamId};
+use crate::WindowContext;
+
+/// A modifier key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Modifier {
+    /// Never held, to disable a gesture.
+    None,
+    Shift,
+    Ctrl,
+    Alt,
+    /// The command key on macOS, the Windows key elsewhere.
+    Super,
+}
+
+impl Modifier {
+    pub(crate) fn is_held(self, io: &imgui::Io) -> bool {
+        match self {
+            Modifier::None => false,
+            Modifier::Shift => io.key_shift,
+            Modifier::Ctrl => io.key_ctrl,
+            Modifier::Alt => io.key_alt,
+            Modifier::Super => io.key_super,
+        }
+    }
+}
+
+/// The interaction policy of the param-bound widgets.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Interaction {
+    /// Hold while dragging to adjust the value more precisely.
+    pub fine: Modifier,
+    /// How much slower the value changes while the fine modifier is held.
+    pub fine_factor: f32,
+    /// Hold while clicking to type in a value.
+    pub type_in: Modifier,
+    /// Hold while clicking to reset the parameter to its default.
+    pub reset: Modifier,
+    /// Reset the parameter to its default on a double click.
+    pub double_click_reset: bool,
+    /// How far to drag in logical pixels to go through the whole range.
+    pub drag_distance: f32,
+    /// Smooth out the mouse movement while dragging, from `0.0` for none to just below `1.0`.
+    pub smoothing: f32,
+}
+
+impl Default for Interaction {
+    fn default() -> Self {
+        Self {
+            fine: Modifier::Shift,
+            fine_factor: 0.1,
+            type_in: Modifier::Ctrl,
+            reset: Modifier::Alt,
+            double_click_reset: true,
+            drag_distance: 200.0,
+            smoothing: 0.3,
+        }
+    }
+}
+
+impl Interaction {
+    /// How much the mouse movement is scaled, depending on the fine modifier.
+    pub(crate) fn factor(&self, io: &imgui::Io) -> f32 {
+        if self.fine.is_held(io) {
+            self.fine_factor
+        } else {
+            1.0
+        }
+    }
+
+    /// Smooth the per-frame change `target`, given the change of the previous frame.
+    pub(crate) fn smooth(&self, previous: f32, target: f32) -> f32 {
+        let smoothing = self.smoothing.max(0.0).min(0.99);
+        previous * smoothing + target * (1.0 - smoothing)
+    }
+
+    /// Whether the item that was just activated should be reset to its default.
+    pub(crate) fn wants_reset(&self, ui: &imgui::Ui) -> bool {
+        let double_clicked = ui.is_mouse_double_clicked(imgui::MouseButton::Left);
+        self.reset.is_held(ui.io()) || (self.double_click_reset && double_clicked)
+    }
+}
+
+/// The direction a widget is dragged in to increase its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+/// The drag in progress. Only one widget can be dragged at a time.
+pub(crate) struct Drag {
+    id: ParamId,
+    /// The normalized value, without the rounding of the parameter.
+    normalized: f32,
+    velocity: f32,
+    /// Whether the drag is an edit, and not a click that reset the value or opened the text
+    /// field.
+    editing: bool,
+}
+
+/// Apply the policy to the widget that was just built for `param`, with an item that can be
+/// activated such as an invisible button. Returns whether the value changed.
+pub(crate) fn drag(
+    ui: &imgui::Ui,
+    ctx: &mut WindowContext,
+    param: &Arc<Param>,
+    axis: Axis,
+) -> bool {
+    let io = ui.io();
+    let policy = &ctx.interaction;
+
+    if ui.is_item_activated() {
+        let reset = policy.wants_reset(ui);
+        let type_in = policy.type_in.is_held(io);
+
+        ctx.drag = Some(Drag {
+            id: param.id(),
+            normalized: param.normalized(),
+            velocity: 0.0,
+            editing: !reset && !type_in,
+        });
+
+        if reset {
+            crate::widgets::set_param(ctx, param, param.default_value());
+            return true;
+        }
+        if type_in {
+            crate::widgets::open_type_in(ui, param);
+            return false;
+        }
+
+        ctx.undo.begin_param_edit(param);
+        ctx.handle.emit(UiEvent::BeginEdit(param.id()));
+    }
+
+    let mut changed = false;
+    let drag = match &mut ctx.drag {
+        Some(drag) if drag.id == param.id() => drag,
+        _ => return false,
+    };
+
+    if ui.is_item_active() && drag.editing {
+        let policy = &ctx.interaction;
+        let delta = match axis {
+            Axis::Horizontal => io.mouse_delta[0],
+            Axis::Vertical => -io.mouse_delta[1],
+        };
+        let target = delta * policy.factor(io) / policy.drag_distance.max(1.0);
+        drag.velocity = policy.smooth(drag.velocity, target);
+        drag.normalized = (drag.normalized + drag.velocity).max(0.0).min(1.0);
+
+        let before = param.get();
+        param.set_normalized(drag.normalized);
+        if param.get() != before {
+            ctx.handle.emit(UiEvent::SetParam {
+                id: param.id(),
+                value: param.get(),
+            });
+            changed = true;
+        }
+    }
+
+    if ui.is_item_deactivated() {
+        let editing = drag.editing;
+        ctx.drag = None;
+
+        if editing {
+            ctx.undo.end_param_edit(param.id());
+            ctx.handle.emit(UiEvent::EndEdit(param.id()));
+        }
+    }
+
+    changed
+}
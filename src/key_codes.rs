@@ -0,0 +1,159 @@
+//! The keys that shortcuts and imgui see for a key press.
+//!
+//! `Code` is the physical key, which is what navigation wants: the arrows and Tab are in
+//! the same place on every keyboard. Shortcuts are named after letters, though, and with
+//! `ShortcutMatching::Layout` a letter or digit is matched by the character the layout types
+//! instead, so `Ctrl+Z` undoes on a German keyboard where Z and Y are swapped, and `Ctrl+A`
+//! selects all on a French one. The keys of the numeric keypad type digits with num lock on
+//! and navigate with it off, so with num lock off imgui sees the arrow or Home key they stand
+//! for.
+
+use keyboard_types::{Code, Key, KeyState, KeyboardEvent};
+
+use crate::settings::ShortcutMatching;
+
+const LETTERS: [Code; 26] = [
+    Code::KeyA,
+    Code::KeyB,
+    Code::KeyC,
+    Code::KeyD,
+    Code::KeyE,
+    Code::KeyF,
+    Code::KeyG,
+    Code::KeyH,
+    Code::KeyI,
+    Code::KeyJ,
+    Code::KeyK,
+    Code::KeyL,
+    Code::KeyM,
+    Code::KeyN,
+    Code::KeyO,
+    Code::KeyP,
+    Code::KeyQ,
+    Code::KeyR,
+    Code::KeyS,
+    Code::KeyT,
+    Code::KeyU,
+    Code::KeyV,
+    Code::KeyW,
+    Code::KeyX,
+    Code::KeyY,
+    Code::KeyZ,
+];
+
+const DIGITS: [Code; 10] = [
+    Code::Digit0,
+    Code::Digit1,
+    Code::Digit2,
+    Code::Digit3,
+    Code::Digit4,
+    Code::Digit5,
+    Code::Digit6,
+    Code::Digit7,
+    Code::Digit8,
+    Code::Digit9,
+];
+
+/// The key of the letter or digit the layout types with the key, if it types one.
+fn layout_code(key: &Key) -> Option<Code> {
+    let written = match key {
+        Key::Character(written) => written,
+        _ => return None,
+    };
+    let mut chars = written.chars();
+    let c = match (chars.next(), chars.next()) {
+        (Some(c), None) => c,
+        _ => return None,
+    };
+
+    match c {
+        'a'..='z' => Some(LETTERS[c as usize - 'a' as usize]),
+        'A'..='Z' => Some(LETTERS[c as usize - 'A' as usize]),
+        '0'..='9' => Some(DIGITS[c as usize - '0' as usize]),
+        // Ctrl and a letter types its control character on Windows.
+        '\u{1}'..='\u{1a}' => Some(LETTERS[c as usize - 1]),
+        _ => None,
+    }
+}
+
+/// The navigation key a key of the numeric keypad stands for with num lock off.
+fn navigation_code(event: &KeyboardEvent) -> Option<Code> {
+    let numpad = matches!(
+        event.code,
+        Code::Numpad0
+            | Code::Numpad1
+            | Code::Numpad2
+            | Code::Numpad3
+            | Code::Numpad4
+            | Code::Numpad6
+            | Code::Numpad7
+            | Code::Numpad8
+            | Code::Numpad9
+            | Code::NumpadDecimal
+    );
+    if !numpad {
+        return None;
+    }
+
+    match event.key {
+        Key::ArrowLeft => Some(Code::ArrowLeft),
+        Key::ArrowRight => Some(Code::ArrowRight),
+        Key::ArrowUp => Some(Code::ArrowUp),
+        Key::ArrowDown => Some(Code::ArrowDown),
+        Key::Home => Some(Code::Home),
+        Key::End => Some(Code::End),
+        Key::PageUp => Some(Code::PageUp),
+        Key::PageDown => Some(Code::PageDown),
+        Key::Insert => Some(Code::Insert),
+        Key::Delete => Some(Code::Delete),
+        _ => None,
+    }
+}
+
+pub(crate) struct KeyCodes {
+    matching: ShortcutMatching,
+    /// The keys that are down with the key imgui saw for them, so a key is released under
+    /// the same key even if the layout types something else by then.
+    held: Vec<(Code, Code)>,
+}
+
+impl KeyCodes {
+    pub fn new(matching: ShortcutMatching) -> Self {
+        Self {
+            matching,
+            held: Vec::new(),
+        }
+    }
+
+    /// The key a shortcut has to name to match the event.
+    pub fn shortcut_code(&self, event: &KeyboardEvent) -> Code {
+        match self.matching {
+            ShortcutMatching::Physical => event.code,
+            ShortcutMatching::Layout => layout_code(&event.key).unwrap_or(event.code),
+        }
+    }
+
+    /// The key imgui sees as pressed or released.
+    pub fn imgui_code(&mut self, event: &KeyboardEvent) -> Code {
+        let held = self.held.iter().position(|(code, _)| *code == event.code);
+        match event.state {
+            KeyState::Down => {
+                if let Some(i) = held {
+                    return self.held[i].1;
+                }
+                let code = navigation_code(event).unwrap_or_else(|| self.shortcut_code(event));
+                self.held.push((event.code, code));
+                code
+            }
+            KeyState::Up => match held {
+                Some(i) => self.held.remove(i).1,
+                None => event.code,
+            },
+        }
+    }
+
+    /// Forget the keys that are down, after the window released them in imgui.
+    pub fn release_all(&mut self) {
+        self.held.clear();
+    }
+}
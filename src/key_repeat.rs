@@ -0,0 +1,103 @@
+//! Repeating held keys for hosts that don't.
+//!
+//! Some hosts send a single key press while a key is held, so holding a letter types it
+//! once. With `Settings::key_repeat` the window sends the last key that was pressed again
+//! from the frame clock, as `repeat` events, until it is released. imgui repeats its own
+//! keys like Backspace and the arrows while they are down, so the repeats add the typed
+//! characters and the shortcuts. Once the host repeats a key itself, nothing is repeated
+//! anymore.
+
+use std::time::Duration;
+
+use keyboard_types::{Key, KeyState, KeyboardEvent};
+
+use crate::settings::KeyRepeat;
+
+/// The most repeats sent in a frame, so a stalled frame doesn't type a burst of them.
+const MAX_REPEATS: usize = 4;
+
+struct Held {
+    event: KeyboardEvent,
+    /// When the key repeats next, on the frame clock.
+    next: Duration,
+}
+
+pub(crate) struct KeyRepeater {
+    repeat: KeyRepeat,
+    held: Option<Held>,
+    /// Whether the host sent a repeat, so it repeats keys itself.
+    host_repeats: bool,
+}
+
+impl KeyRepeater {
+    pub fn new(repeat: KeyRepeat) -> Self {
+        Self {
+            repeat,
+            held: None,
+            host_repeats: false,
+        }
+    }
+
+    /// Note an event from the host.
+    pub fn on_event(&mut self, event: &baseview::Event, now: Duration) {
+        let event = match event {
+            baseview::Event::Keyboard(event) => event,
+            baseview::Event::Window(baseview::WindowEvent::Unfocused) => {
+                self.held = None;
+                return;
+            }
+            _ => return,
+        };
+
+        let held_code = self.held.as_ref().map(|held| held.event.code);
+        match event.state {
+            KeyState::Down => {
+                if event.repeat || held_code == Some(event.code) {
+                    self.host_repeats = true;
+                    self.held = None;
+                    return;
+                }
+                let modifier = matches!(
+                    event.key,
+                    Key::Shift | Key::Control | Key::Alt | Key::AltGraph | Key::Meta
+                );
+                if !modifier && !self.host_repeats {
+                    self.held = Some(Held {
+                        event: event.clone(),
+                        next: now + self.repeat.delay,
+                    });
+                }
+            }
+            KeyState::Up => {
+                if held_code == Some(event.code) {
+                    self.held = None;
+                }
+            }
+        }
+    }
+
+    /// Whether a key is held that repeats, so frames are needed to repeat it.
+    pub fn is_held(&self) -> bool {
+        self.held.is_some()
+    }
+
+    /// The repeats that are due on the frame clock.
+    pub fn poll(&mut self, now: Duration) -> Vec<KeyboardEvent> {
+        let held = match &mut self.held {
+            Some(held) => held,
+            None => return Vec::new(),
+        };
+
+        let mut repeats = Vec::new();
+        while held.next <= now && repeats.len() < MAX_REPEATS {
+            let mut event = held.event.clone();
+            event.repeat = true;
+            repeats.push(event);
+            held.next += self.repeat.interval.max(Duration::from_millis(1));
+        }
+        if held.next <= now {
+            held.next = now + self.repeat.interval;
+        }
+        repeats
+    }
+}
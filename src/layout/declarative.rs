@@ -0,0 +1,133 @@
+//! Describing a UI in data instead of code.
+//!
+//! Simple plugin UIs are rows and columns of param-bound widgets. With the `layout` feature
+//! they can be described in a JSON file, and drawn every frame with `Layout::draw`. The Rust
+//! side only passes the parameters the widgets are bound to:
+//!
+//! ```json
+//! {
+//!     "title": "Synth",
+//!     "root": { "column": [
+//!         { "text": "Oscillator" },
+//!         { "row": [{ "knob": 0 }, { "knob": 1 }] },
+//!         "separator",
+//!         { "grid": { "columns": 2, "items": [{ "slider": 2 }, { "slider": 3 }] } }
+//!     ] }
+//! }
+//! ```
+//!
+//! Widgets bound to a parameter that wasn't passed are left out.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::params::{Param, ParamId};
+use crate::WindowContext;
+
+/// A part of a layout.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Node {
+    /// Side by side, sharing the width of the window.
+    Row(Vec<Node>),
+    /// One below the other.
+    Column(Vec<Node>),
+    /// In a table, filled row by row.
+    Grid {
+        columns: usize,
+        items: Vec<Node>,
+    },
+    Text(String),
+    Separator,
+    /// A slider bound to the parameter with the id.
+    Slider(ParamId),
+    /// A knob bound to the parameter with the id.
+    Knob(ParamId),
+}
+
+/// A UI described in data.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Layout {
+    /// The title of the window the layout is drawn in, or `None` to draw it into the
+    /// current window.
+    #[serde(default)]
+    pub title: Option<String>,
+    pub root: Node,
+}
+
+impl Layout {
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Read a layout from a JSON file.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let json = fs::read_to_string(path)?;
+        Self::from_json(&json).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    /// Draw the layout with its widgets bound to `params`.
+    pub fn draw(&self, ui: &imgui::Ui, ctx: &mut WindowContext, params: &[Arc<Param>]) {
+        match &self.title {
+            Some(title) => {
+                imgui::Window::new(title).build(ui, || draw_node(ui, ctx, params, &self.root));
+            }
+            None => draw_node(ui, ctx, params, &self.root),
+        }
+    }
+}
+
+fn draw_node(ui: &imgui::Ui, ctx: &mut WindowContext, params: &[Arc<Param>], node: &Node) {
+    let param = |id: &ParamId| params.iter().find(|param| param.id() == *id);
+
+    match node {
+        Node::Row(children) => {
+            let count = children.len().max(1) as f32;
+            let spacing = ui.clone_style().item_spacing[0];
+            let width = (ui.content_region_avail()[0] - spacing * (count - 1.0)) / count;
+            for (i, child) in children.iter().enumerate() {
+                if i > 0 {
+                    ui.same_line();
+                }
+                let _id = ui.push_id(i as i32);
+                ui.group(|| {
+                    let _width = ui.push_item_width(width.max(1.0));
+                    draw_node(ui, ctx, params, child);
+                });
+            }
+        }
+        Node::Column(children) => ui.group(|| {
+            for (i, child) in children.iter().enumerate() {
+                let _id = ui.push_id(i as i32);
+                draw_node(ui, ctx, params, child);
+            }
+        }),
+        Node::Grid { columns, items } => {
+            let columns = (*columns).max(1);
+            if let Some(_table) = ui.begin_table("##grid", columns) {
+                for (i, item) in items.iter().enumerate() {
+                    ui.table_next_column();
+                    let _id = ui.push_id(i as i32);
+                    let _width = ui.push_item_width(-1.0);
+                    draw_node(ui, ctx, params, item);
+                }
+            }
+        }
+        Node::Text(text) => ui.text(text),
+        Node::Separator => ui.separator(),
+        Node::Slider(id) => {
+            if let Some(param) = param(id) {
+                crate::widgets::param_slider(ui, ctx, param);
+            }
+        }
+        Node::Knob(id) => {
+            if let Some(param) = param(id) {
+                crate::widgets::param_knob(ui, ctx, param);
+            }
+        }
+    }
+}
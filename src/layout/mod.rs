@@ -0,0 +1,78 @@
+//! Layouts that adapt to the size of the window.
+//!
+//! Hosts let the user resize a plugin editor down to a fraction of the size it was designed
+//! for. The size classes of `Settings::breakpoints` tell a UI which layout fits: `compact`
+//! stacks everything in one column, `regular` is the layout it was designed for and `large`
+//! has room to spare. `WindowContext::size_class` is the class of the whole window, and
+//! `container_class` the class of the space left in the current window or child, for parts
+//! of a UI that are laid out on their own. Both follow the size as the host resizes it.
+//!
+//! With the `layout` feature a UI can also be described in a JSON file with `Layout`.
+
+#[cfg(feature = "layout")]
+mod declarative;
+
+#[cfg(feature = "layout")]
+pub use declarative::{Layout, Node};
+
+/// How much room a window or a container has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SizeClass {
+    Compact,
+    Regular,
+    Large,
+}
+
+impl SizeClass {
+    /// A number of columns for widgets of the same kind, like a bank of knobs.
+    pub fn columns(self) -> usize {
+        match self {
+            SizeClass::Compact => 1,
+            SizeClass::Regular => 2,
+            SizeClass::Large => 4,
+        }
+    }
+}
+
+/// The widths in logical pixels where the size classes begin.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Breakpoints {
+    pub regular: f32,
+    pub large: f32,
+}
+
+impl Default for Breakpoints {
+    fn default() -> Self {
+        Self {
+            regular: 480.0,
+            large: 960.0,
+        }
+    }
+}
+
+impl Breakpoints {
+    pub fn size_class(&self, width: f32) -> SizeClass {
+        if width >= self.large {
+            SizeClass::Large
+        } else if width >= self.regular {
+            SizeClass::Regular
+        } else {
+            SizeClass::Compact
+        }
+    }
+}
+
+/// The size class of the width left in the current window or child.
+pub fn container_class(ui: &imgui::Ui, breakpoints: &Breakpoints) -> SizeClass {
+    breakpoints.size_class(ui.content_region_avail()[0])
+}
+
+/// How many columns at least `min_width` wide fit in the width left in the current window or
+/// child, with the item spacing between them. At least one.
+pub fn columns(ui: &imgui::Ui, min_width: f32) -> usize {
+    let spacing = ui.clone_style().item_spacing[0];
+    let width = ui.content_region_avail()[0];
+    ((width + spacing) / (min_width.max(1.0) + spacing)).max(1.0) as usize
+}
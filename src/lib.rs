@@ -1,11 +1,91 @@
+#[cfg(feature = "accessibility")]
+pub mod accessibility;
+#[cfg(feature = "animated_image")]
+pub mod animated_image;
+mod auto_resize;
+mod clipboard;
+mod context;
+mod debug_overlay;
+mod dev_windows;
 mod dpi;
+#[cfg(feature = "file_dialog")]
+mod file_dialog;
+mod focus_ring;
+mod frame;
+#[cfg(feature = "hot_reload")]
+pub mod hot_reload;
+mod key_codes;
+mod key_repeat;
+mod midi_learn;
 mod mouse;
+mod native_child;
+mod open_url;
+mod pan;
+mod passthrough;
+mod pixel_snap;
+mod platform;
+#[cfg(feature = "event_recorder")]
+pub mod recorder;
+#[cfg(feature = "remote_control")]
+pub mod remote_control;
+#[cfg(feature = "remote_debug")]
+pub mod remote_debug;
 mod renderer;
+#[cfg(feature = "scripting")]
+pub mod script;
+mod scroll;
+#[cfg(feature = "svg")]
+mod svg;
+mod text_input;
+#[cfg(feature = "webview")]
+mod webview;
 mod window;
 
+pub mod activation;
+pub mod clock;
+pub mod color;
+pub mod crash;
+pub mod fonts;
+pub mod glass;
+pub mod handle;
+pub mod help;
+pub mod instances;
+pub mod interaction;
+pub mod layout;
+pub mod locale;
+pub mod monitor;
+pub mod node_graph;
+pub mod notifications;
+pub mod params;
+pub mod scaling;
 pub mod settings;
+pub mod shader;
+pub mod shortcuts;
+pub mod splash;
+pub mod state_diff;
+pub mod storage;
+pub mod tasks;
+pub mod textures;
+pub mod theme;
+pub mod undo;
+pub mod widgets;
 
+pub use context::{ResizeEdge, WindowContext};
 pub use dpi::HiDpiMode;
-pub use renderer::RenderSettings;
+#[cfg(feature = "file_dialog")]
+pub use file_dialog::FileFilter;
+pub use frame::DrawStats;
+pub use handle::{Handle, SizeLimits};
+pub use mouse::{InputState, RawButtonEvent};
+pub use native_child::NativeChildId;
+pub use open_url::OpenUrlError;
+#[cfg(feature = "software_renderer")]
+pub use renderer::software_backend;
+pub use renderer::{
+    AdapterInfo, BackendFactory, GlContextMode, RenderSettings, RendererBackend, SharedContext,
+    TargetFrame, TargetHandle,
+};
 pub use settings::Settings;
-pub use window::ImguiWindow;
+#[cfg(feature = "svg")]
+pub use svg::IconSet;
+pub use window::{ImguiWindow, OpenError};
@@ -0,0 +1,221 @@
+//! Localize the text shown by the crate itself.
+//!
+//! Widgets and overlays provided by this crate look up their strings in the `Locale` of the
+//! window instead of hard-coding English, so plugins can ship translations without forking.
+//! Strings that are not translated fall back to English.
+
+use std::collections::HashMap;
+
+/// The direction text is read in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TextDirection {
+    LeftToRight,
+    /// Mirror the layout for right-to-left locales such as Arabic and Hebrew.
+    ///
+    /// imgui does not shape or reorder text itself, so strings have to be passed in visual
+    /// order. This aligns window titles, selectables and the helper widgets to the right and
+    /// moves the window menu and color buttons to the other side.
+    RightToLeft,
+}
+
+impl Default for TextDirection {
+    fn default() -> Self {
+        TextDirection::LeftToRight
+    }
+}
+
+impl TextDirection {
+    pub fn is_rtl(self) -> bool {
+        self == TextDirection::RightToLeft
+    }
+
+    /// Configure the style for this direction.
+    pub fn apply(self, style: &mut imgui::Style) {
+        use imgui::Direction;
+
+        match self {
+            TextDirection::LeftToRight => {
+                style.window_title_align = [0.0, 0.5];
+                style.selectable_text_align = [0.0, 0.0];
+                style.window_menu_button_position = Direction::Left;
+                style.color_button_position = Direction::Right;
+            }
+            TextDirection::RightToLeft => {
+                style.window_title_align = [1.0, 0.5];
+                style.selectable_text_align = [1.0, 0.0];
+                style.window_menu_button_position = Direction::Right;
+                style.color_button_position = Direction::Left;
+            }
+        }
+    }
+}
+
+/// The strings shown by the crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Text {
+    MetricsWindow,
+    PauseEventLog,
+    Clear,
+    ScaleFactor,
+    HiDpiMode,
+    HiDpiFactor,
+    DisplaySize,
+    FramebufferScale,
+    Renderer,
+    Gpu,
+    Driver,
+    Extensions,
+    Textures,
+    InputLatency,
+    MidiLearn,
+    MidiLearning,
+    MidiUnlearn,
+    TypeValue,
+    Search,
+    Rescan,
+    Scanning,
+    Favorites,
+    PresetName,
+    Save,
+    ResetToDefault,
+    CopyValue,
+    PasteValue,
+    ShowAutomation,
+    ClearAutomation,
+    KeyboardShortcuts,
+    GlobalShortcuts,
+    PickFromScreen,
+    PickingColor,
+    SimulateColorVision,
+    NormalVision,
+    CopySlot,
+}
+
+impl Text {
+    /// All strings, for example to check a translation for completeness.
+    pub const ALL: &'static [Text] = &[
+        Text::MetricsWindow,
+        Text::PauseEventLog,
+        Text::Clear,
+        Text::ScaleFactor,
+        Text::HiDpiMode,
+        Text::HiDpiFactor,
+        Text::DisplaySize,
+        Text::FramebufferScale,
+        Text::Renderer,
+        Text::Gpu,
+        Text::Driver,
+        Text::Extensions,
+        Text::Textures,
+        Text::InputLatency,
+        Text::MidiLearn,
+        Text::MidiLearning,
+        Text::MidiUnlearn,
+        Text::TypeValue,
+        Text::Search,
+        Text::Rescan,
+        Text::Scanning,
+        Text::Favorites,
+        Text::PresetName,
+        Text::Save,
+        Text::ResetToDefault,
+        Text::CopyValue,
+        Text::PasteValue,
+        Text::ShowAutomation,
+        Text::ClearAutomation,
+        Text::KeyboardShortcuts,
+        Text::GlobalShortcuts,
+        Text::PickFromScreen,
+        Text::PickingColor,
+        Text::SimulateColorVision,
+        Text::NormalVision,
+        Text::CopySlot,
+    ];
+
+    /// The English text.
+    pub fn default_text(self) -> &'static str {
+        match self {
+            Text::MetricsWindow => "Metrics window",
+            Text::PauseEventLog => "Pause event log",
+            Text::Clear => "Clear",
+            Text::ScaleFactor => "Scale factor",
+            Text::HiDpiMode => "HiDPI mode",
+            Text::HiDpiFactor => "HiDPI factor",
+            Text::DisplaySize => "Display size",
+            Text::FramebufferScale => "Framebuffer scale",
+            Text::Renderer => "Renderer",
+            Text::Gpu => "GPU",
+            Text::Driver => "Driver",
+            Text::Extensions => "Extensions",
+            Text::Textures => "Textures",
+            Text::InputLatency => "Input latency",
+            Text::MidiLearn => "MIDI learn",
+            Text::MidiLearning => "Move a MIDI controller...",
+            Text::MidiUnlearn => "MIDI unlearn",
+            Text::TypeValue => "Type value",
+            Text::Search => "Search",
+            Text::Rescan => "Rescan",
+            Text::Scanning => "Scanning...",
+            Text::Favorites => "Favorites",
+            Text::PresetName => "Preset name",
+            Text::Save => "Save",
+            Text::ResetToDefault => "Reset to default",
+            Text::CopyValue => "Copy value",
+            Text::PasteValue => "Paste value",
+            Text::ShowAutomation => "Show automation",
+            Text::ClearAutomation => "Clear automation",
+            Text::KeyboardShortcuts => "Keyboard shortcuts",
+            Text::GlobalShortcuts => "Global",
+            Text::PickFromScreen => "Pick from screen",
+            Text::PickingColor => "Enter to take the color, Escape to cancel",
+            Text::SimulateColorVision => "Simulate color vision",
+            Text::NormalVision => "Normal",
+            Text::CopySlot => "Copy",
+        }
+    }
+}
+
+/// A table of translated strings.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Locale {
+    strings: HashMap<Text, String>,
+}
+
+impl Locale {
+    /// A locale that uses the English text for every string.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Translate `text`.
+    pub fn with(mut self, text: Text, translation: impl Into<String>) -> Self {
+        self.set(text, translation);
+        self
+    }
+
+    pub fn set(&mut self, text: Text, translation: impl Into<String>) {
+        self.strings.insert(text, translation.into());
+    }
+
+    pub fn get(&self, text: Text) -> &str {
+        self.strings
+            .get(&text)
+            .map(String::as_str)
+            .unwrap_or_else(|| text.default_text())
+    }
+
+    /// The strings that are not translated.
+    pub fn missing(&self) -> impl Iterator<Item = Text> + '_ {
+        Text::ALL
+            .iter()
+            .copied()
+            .filter(move |text| !self.strings.contains_key(text))
+    }
+
+    pub(crate) fn translations(&self) -> impl Iterator<Item = &str> {
+        self.strings.values().map(String::as_str)
+    }
+}
@@ -0,0 +1,50 @@
+//! The MIDI learn items of the param context menu when `Settings::midi_learn` is enabled.
+
+use crate::handle::UiEvent;
+use crate::locale::Text;
+use crate::params::{Param, ParamId};
+use crate::WindowContext;
+
+#[derive(Default)]
+pub(crate) struct MidiLearn {
+    /// The parameter waiting for the host to complete its mapping.
+    learning: Option<ParamId>,
+}
+
+/// Add the MIDI learn items to the open context menu of `param`.
+pub(crate) fn menu_items(ui: &imgui::Ui, ctx: &mut WindowContext, param: &Param) {
+    let state = match &mut ctx.midi_learn {
+        Some(state) => state,
+        None => return,
+    };
+    let mapping = ctx.handle.midi_mapping(param.id());
+
+    if state.learning == Some(param.id()) && mapping.is_some() {
+        state.learning = None;
+    }
+
+    if state.learning == Some(param.id()) {
+        ui.text_disabled(ctx.locale.get(Text::MidiLearning));
+    } else if imgui::MenuItem::new(ctx.locale.get(Text::MidiLearn)).build(ui) {
+        state.learning = Some(param.id());
+        ctx.handle.emit(UiEvent::MidiLearn(param.id()));
+    }
+
+    let unlearn = match mapping {
+        Some(mapping) => format!(
+            "{} (CC {}, ch {})",
+            ctx.locale.get(Text::MidiUnlearn),
+            mapping.cc,
+            mapping.channel + 1
+        ),
+        None => ctx.locale.get(Text::MidiUnlearn).to_string(),
+    };
+    if imgui::MenuItem::new(&unlearn)
+        .enabled(mapping.is_some())
+        .build(ui)
+    {
+        ctx.handle.emit(UiEvent::MidiUnlearn(param.id()));
+    }
+
+    ui.separator();
+}
@@ -0,0 +1,55 @@
+//! The monitors of the desktop, to place standalone windows with `Settings::position`.
+//!
+//! Positions and sizes are in the coordinates windows are placed in on the platform: physical
+//! pixels on Windows and X11, and points on macOS. The origin is the top left of the primary
+//! monitor.
+
+use raw_window_handle::HasRawWindowHandle;
+
+use crate::platform;
+
+/// A monitor of the desktop.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Monitor {
+    pub position: [i32; 2],
+    pub size: [u32; 2],
+    /// The scale factor of the monitor.
+    pub scale: f64,
+    /// Whether it is the main monitor, with the taskbar or the menu bar.
+    pub primary: bool,
+}
+
+impl Monitor {
+    pub fn contains(&self, point: [i32; 2]) -> bool {
+        point[0] >= self.position[0]
+            && point[1] >= self.position[1]
+            && point[0] < self.position[0] + self.size[0] as i32
+            && point[1] < self.position[1] + self.size[1] as i32
+    }
+
+    /// The position that centers something of `size` on the monitor.
+    pub fn center(&self, size: [u32; 2]) -> [i32; 2] {
+        [
+            self.position[0] + (self.size[0] as i32 - size[0] as i32) / 2,
+            self.position[1] + (self.size[1] as i32 - size[1] as i32) / 2,
+        ]
+    }
+}
+
+/// The monitors of the desktop.
+pub fn monitors() -> Vec<Monitor> {
+    platform::monitors()
+}
+
+/// The index in `monitors` of the monitor showing the center of a window, like the window of
+/// the host.
+pub fn monitor_of<W: HasRawWindowHandle>(window: &W) -> Option<usize> {
+    let (position, size) = platform::window_rect(&window.raw_window_handle())?;
+    let center = [
+        position[0] + size[0] as i32 / 2,
+        position[1] + size[1] as i32 / 2,
+    ];
+    monitors()
+        .iter()
+        .position(|monitor| monitor.contains(center))
+}
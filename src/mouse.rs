@@ -47,6 +47,11 @@ impl Button {
             self.pressed_this_frame.set(true);
         }
     }
+    /// Release the button, forgetting a press this frame.
+    pub(crate) fn reset(&self) {
+        self.state.set(false);
+        self.pressed_this_frame.set(false);
+    }
     pub(crate) fn get(&self) -> bool {
         // If we got a press this frame, record it even if we got a release
         // too — this way we don't drop mouse clicks where the release comes
@@ -56,6 +61,56 @@ impl Button {
     }
 }
 
+/// A press or release of a mouse button imgui has no slot for, like the extra buttons of
+/// gaming mice. Bind these yourself with `InputState::raw_buttons`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RawButtonEvent {
+    /// The number baseview gives the button in `MouseButton::Other`.
+    pub button: u8,
+    pub pressed: bool,
+}
+
+/// The input of the mouse since the last frame, as the window got it, before imgui used any
+/// of it. Get it with `WindowContext::input`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InputState {
+    /// The horizontal and vertical steps of the mouse wheel.
+    pub scroll_lines: [f32; 2],
+    /// The horizontal and vertical distance scrolled on a trackpad, in the coordinates of
+    /// imgui.
+    pub scroll_pixels: [f32; 2],
+    /// The presses and releases of buttons imgui has no slot for, in the order they
+    /// happened.
+    pub raw_buttons: Vec<RawButtonEvent>,
+    /// A widget asked for the wheel in this frame.
+    pub(crate) claim_scroll: bool,
+    /// A widget asked for the wheel in the last frame.
+    pub(crate) scroll_claimed: bool,
+}
+
+impl InputState {
+    /// Start collecting the input for the next frame.
+    pub(crate) fn end_frame(&mut self) {
+        self.scroll_lines = [0.0; 2];
+        self.scroll_pixels = [0.0; 2];
+        self.raw_buttons.clear();
+        self.scroll_claimed = std::mem::take(&mut self.claim_scroll);
+    }
+}
+
+/// The slot of `Io::mouse_down` a button goes into, or the number of a button imgui has no
+/// slot for. Back and forward are imgui's two extra buttons.
+pub(crate) fn imgui_button(button: baseview::MouseButton) -> Result<usize, u8> {
+    match button {
+        baseview::MouseButton::Left => Ok(0),
+        baseview::MouseButton::Right => Ok(1),
+        baseview::MouseButton::Middle => Ok(2),
+        baseview::MouseButton::Back => Ok(3),
+        baseview::MouseButton::Forward => Ok(4),
+        baseview::MouseButton::Other(button) => Err(button),
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub(crate) struct CursorSettings {
     pub cursor: Option<imgui::MouseCursor>,
@@ -0,0 +1,133 @@
+//! Native windows of the application shown over a region of the UI, like a web view or a
+//! widget from a vendor SDK.
+//!
+//! The region is reserved like any other item every frame. After the frame the native window
+//! is moved over it and clipped to the visible part, and hidden when the region isn't
+//! reserved or is scrolled out of view. Native windows are always drawn above the UI, so
+//! popups that overlap the region are hidden behind them.
+
+use std::collections::HashMap;
+
+use raw_window_handle::RawWindowHandle;
+
+use crate::platform::{Child, NativeWindow};
+
+/// Identifies a native window attached with `WindowContext::attach_native_child`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NativeChildId(u32);
+
+struct Entry {
+    child: Child,
+    /// The item rectangle and clip rectangle of this frame, in logical coordinates.
+    reserved: Option<([f32; 4], [f32; 4])>,
+    /// The bounds and visible part last applied, in physical pixels.
+    applied: Option<([i32; 4], [i32; 4])>,
+}
+
+#[derive(Default)]
+pub(crate) struct NativeChildren {
+    children: HashMap<NativeChildId, Entry>,
+    next_id: u32,
+}
+
+impl NativeChildren {
+    pub fn attach(
+        &mut self,
+        window: &NativeWindow,
+        child: &RawWindowHandle,
+    ) -> Option<NativeChildId> {
+        let child = window.attach_child(child)?;
+        let id = NativeChildId(self.next_id);
+        self.next_id += 1;
+        self.children.insert(
+            id,
+            Entry {
+                child,
+                reserved: None,
+                applied: None,
+            },
+        );
+        Some(id)
+    }
+
+    pub fn detach(&mut self, id: NativeChildId) {
+        if let Some(entry) = self.children.remove(&id) {
+            entry.child.detach();
+        }
+    }
+
+    /// Reserve `size` at the cursor for the child.
+    pub fn reserve(&mut self, ui: &imgui::Ui, id: NativeChildId, size: [f32; 2]) {
+        let reserved = reserve(ui, size);
+        if let Some(entry) = self.children.get_mut(&id) {
+            entry.reserved = Some(reserved);
+        }
+    }
+
+    /// Move the children over the regions reserved this frame, and hide the others.
+    pub fn end_frame(&mut self, hidpi_factor: f64) {
+        for entry in self.children.values_mut() {
+            let placement = entry
+                .reserved
+                .take()
+                .and_then(|(rect, clip)| placement(rect, clip, hidpi_factor));
+
+            if placement == entry.applied {
+                continue;
+            }
+            match placement {
+                Some((bounds, visible)) => entry.child.set_bounds(bounds, visible),
+                None => entry.child.hide(),
+            }
+            entry.applied = placement;
+        }
+    }
+}
+
+/// Reserve `size` at the cursor, returning the item rectangle and the clip rectangle as
+/// left, top, right and bottom in logical coordinates.
+pub(crate) fn reserve(ui: &imgui::Ui, size: [f32; 2]) -> ([f32; 4], [f32; 4]) {
+    ui.dummy(size);
+
+    let min = ui.item_rect_min();
+    let max = ui.item_rect_max();
+    let (clip_min, clip_max) = unsafe {
+        let draw_list = imgui::sys::igGetWindowDrawList();
+        let mut clip_min = imgui::sys::ImVec2::zero();
+        let mut clip_max = imgui::sys::ImVec2::zero();
+        imgui::sys::ImDrawList_GetClipRectMin(&mut clip_min, draw_list);
+        imgui::sys::ImDrawList_GetClipRectMax(&mut clip_max, draw_list);
+        (clip_min, clip_max)
+    };
+
+    (
+        [min[0], min[1], max[0], max[1]],
+        [clip_min.x, clip_min.y, clip_max.x, clip_max.y],
+    )
+}
+
+/// The bounds of a reserved rectangle and its visible part as x, y, width and height, scaled
+/// by `scale`. `None` if nothing of it is visible.
+pub(crate) fn placement(
+    rect: [f32; 4],
+    clip: [f32; 4],
+    scale: f64,
+) -> Option<([i32; 4], [i32; 4])> {
+    let scaled = |value: f32| (value as f64 * scale).round() as i32;
+
+    let bounds = [
+        scaled(rect[0]),
+        scaled(rect[1]),
+        scaled(rect[2]) - scaled(rect[0]),
+        scaled(rect[3]) - scaled(rect[1]),
+    ];
+    let left = scaled(rect[0].max(clip[0]));
+    let top = scaled(rect[1].max(clip[1]));
+    let right = scaled(rect[2].min(clip[2]));
+    let bottom = scaled(rect[3].min(clip[3]));
+    if right <= left || bottom <= top {
+        None
+    } else {
+        Some((bounds, [left, top, right - left, bottom - top]))
+    }
+}
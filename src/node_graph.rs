@@ -0,0 +1,696 @@
+//! Node graph editors, for the modular routing of synth plugins.
+//!
+//! A `Graph` has nodes with typed input and output pins, the links between them, and where
+//! the nodes are. With the `serde` feature it can be saved to restore the routing and the
+//! layout together.
+//!
+//! The `NodeGraph` editor draws a graph on a `widgets::Canvas`. Drag a node to move it, drag
+//! from a pin to a pin of the same type to link them, and drag from a linked input to move
+//! its link to another output or to remove it. Dragging on the background selects the nodes
+//! in a rectangle, with Shift to add to the selection. Delete removes the selected nodes, and
+//! Ctrl+X, Ctrl+C and Ctrl+V cut, copy and paste them. The editor returns what the user
+//! changed as `NodeGraphEvent`s, so the plugin can route its audio to match.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::widgets::{Canvas, CanvasView};
+
+/// The distance between the border of a node and its content, in canvas units.
+const PADDING: f32 = 8.0;
+/// The space between the names of the inputs and the outputs, in canvas units.
+const GAP: f32 = 24.0;
+const PIN_RADIUS: f32 = 4.5;
+/// How close to a pin a click still hits it, in screen pixels.
+const PIN_GRAB: f32 = 8.0;
+const ROUNDING: f32 = 4.0;
+
+/// Identifies a node of a `Graph`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeId(pub u64);
+
+/// An input or output of a node. Only pins of the same type can be linked.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pin {
+    pub name: String,
+    pub ty: String,
+}
+
+impl Pin {
+    pub fn new(name: impl Into<String>, ty: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ty: ty.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Node {
+    pub id: NodeId,
+    pub title: String,
+    /// The top left corner, in canvas units.
+    pub position: [f32; 2],
+    pub inputs: Vec<Pin>,
+    pub outputs: Vec<Pin>,
+}
+
+impl Node {
+    /// A node without pins. It gets its id when it is added to a graph.
+    pub fn new(title: impl Into<String>, position: [f32; 2]) -> Self {
+        Self {
+            id: NodeId(0),
+            title: title.into(),
+            position,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+
+    pub fn with_input(mut self, name: impl Into<String>, ty: impl Into<String>) -> Self {
+        self.inputs.push(Pin::new(name, ty));
+        self
+    }
+
+    pub fn with_output(mut self, name: impl Into<String>, ty: impl Into<String>) -> Self {
+        self.outputs.push(Pin::new(name, ty));
+        self
+    }
+}
+
+/// An input or an output of a node, by its index among the inputs or the outputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PinRef {
+    pub node: NodeId,
+    pub index: usize,
+}
+
+/// A link from an output to an input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Link {
+    pub from: PinRef,
+    pub to: PinRef,
+}
+
+/// Nodes and the links between them. An input has one link at most, an output any number.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Graph {
+    pub nodes: Vec<Node>,
+    pub links: Vec<Link>,
+}
+
+impl Graph {
+    /// Add a node, and return the id it got.
+    pub fn add_node(&mut self, mut node: Node) -> NodeId {
+        node.id = self.next_id();
+        let id = node.id;
+        self.nodes.push(node);
+        id
+    }
+
+    fn next_id(&self) -> NodeId {
+        let last = self.nodes.iter().map(|node| node.id.0).max();
+        NodeId(last.map_or(1, |last| last + 1))
+    }
+
+    pub fn node(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+        self.nodes.iter_mut().find(|node| node.id == id)
+    }
+
+    /// Remove a node, and return it with the links it had.
+    pub fn remove_node(&mut self, id: NodeId) -> Option<(Node, Vec<Link>)> {
+        let index = self.nodes.iter().position(|node| node.id == id)?;
+        let node = self.nodes.remove(index);
+        let (links, kept) = self
+            .links
+            .drain(..)
+            .partition(|link| link.from.node == id || link.to.node == id);
+        self.links = kept;
+        Some((node, links))
+    }
+
+    fn pin(&self, pin: PinRef, output: bool) -> Option<&Pin> {
+        let node = self.node(pin.node)?;
+        if output {
+            node.outputs.get(pin.index)
+        } else {
+            node.inputs.get(pin.index)
+        }
+    }
+
+    /// Whether the output `from` can be linked to the input `to`.
+    pub fn can_link(&self, from: PinRef, to: PinRef) -> bool {
+        match (self.pin(from, true), self.pin(to, false)) {
+            (Some(output), Some(input)) => from.node != to.node && output.ty == input.ty,
+            _ => false,
+        }
+    }
+
+    /// Link the output `from` to the input `to`, and return the link the input had before.
+    pub fn link(&mut self, from: PinRef, to: PinRef) -> Option<Link> {
+        let index = self.links.iter().position(|link| link.to == to);
+        let old = index.map(|index| self.links.remove(index));
+        self.links.push(Link { from, to });
+        old
+    }
+
+    /// The nodes in `ids`, and the links between them.
+    fn subgraph(&self, ids: &HashSet<NodeId>) -> Graph {
+        Graph {
+            nodes: self
+                .nodes
+                .iter()
+                .filter(|node| ids.contains(&node.id))
+                .cloned()
+                .collect(),
+            links: self
+                .links
+                .iter()
+                .filter(|link| ids.contains(&link.from.node) && ids.contains(&link.to.node))
+                .copied()
+                .collect(),
+        }
+    }
+}
+
+/// What the user changed in a `NodeGraph`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeGraphEvent {
+    Linked(Link),
+    Unlinked(Link),
+    /// The user moved the nodes.
+    Moved(Vec<NodeId>),
+    /// The user deleted or cut the nodes. Their links are reported as `Unlinked` first.
+    Removed(Vec<Node>),
+    /// The user pasted the nodes, with new ids. Their links are reported as `Linked`.
+    Pasted(Vec<NodeId>),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Drag {
+    Nodes {
+        moved: bool,
+    },
+    /// A new link from a pin, which is an output if `output` is set.
+    Link {
+        from: PinRef,
+        output: bool,
+    },
+    Marquee {
+        start: [f32; 2],
+    },
+}
+
+/// Where the parts of a node are, in canvas units.
+struct Layout {
+    min: [f32; 2],
+    max: [f32; 2],
+    header: f32,
+    row: f32,
+}
+
+impl Layout {
+    fn new(ui: &imgui::Ui, node: &Node) -> Self {
+        let font_size = ui.current_font_size();
+        let width = |pins: &[Pin]| {
+            pins.iter()
+                .map(|pin| ui.calc_text_size(&pin.name)[0])
+                .fold(0.0, f32::max)
+        };
+        let pins = width(&node.inputs) + GAP + width(&node.outputs);
+        let content = ui.calc_text_size(&node.title)[0].max(pins);
+        let header = font_size + PADDING;
+        let row = font_size + PADDING * 0.5;
+        let rows = node.inputs.len().max(node.outputs.len()) as f32;
+
+        Self {
+            min: node.position,
+            max: [
+                node.position[0] + content + PADDING * 2.0,
+                node.position[1] + header + rows * row + PADDING * 0.5,
+            ],
+            header,
+            row,
+        }
+    }
+
+    fn contains(&self, pos: [f32; 2]) -> bool {
+        pos[0] >= self.min[0]
+            && pos[0] <= self.max[0]
+            && pos[1] >= self.min[1]
+            && pos[1] <= self.max[1]
+    }
+
+    fn pin(&self, output: bool, index: usize) -> [f32; 2] {
+        let x = if output { self.max[0] } else { self.min[0] };
+        let y = self.min[1] + self.header + PADDING * 0.25 + (index as f32 + 0.5) * self.row;
+        [x, y]
+    }
+}
+
+/// The graph of a `NodeGraph` and what the user is doing with it.
+#[derive(Debug, Default)]
+struct Editor {
+    graph: Graph,
+    selection: HashSet<NodeId>,
+    drag: Option<Drag>,
+    clipboard: Option<Graph>,
+    pin_colors: HashMap<String, [f32; 4]>,
+}
+
+impl Editor {
+    /// The pin under `pos`, and whether it is an output.
+    fn hit_pin(&self, layouts: &[Layout], pos: [f32; 2], radius: f32) -> Option<(PinRef, bool)> {
+        let near = |pin: [f32; 2]| {
+            let dx = pin[0] - pos[0];
+            let dy = pin[1] - pos[1];
+            dx * dx + dy * dy <= radius * radius
+        };
+        for (node, layout) in self.graph.nodes.iter().zip(layouts).rev() {
+            for (output, count) in &[(false, node.inputs.len()), (true, node.outputs.len())] {
+                for index in 0..*count {
+                    if near(layout.pin(*output, index)) {
+                        return Some((
+                            PinRef {
+                                node: node.id,
+                                index,
+                            },
+                            *output,
+                        ));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn hit_node(&self, layouts: &[Layout], pos: [f32; 2]) -> Option<usize> {
+        layouts.iter().rposition(|layout| layout.contains(pos))
+    }
+
+    fn remove_selection(&mut self, events: &mut Vec<NodeGraphEvent>) {
+        let mut ids: Vec<NodeId> = self.selection.drain().collect();
+        ids.sort();
+        let mut removed = Vec::new();
+        for id in ids {
+            if let Some((node, links)) = self.graph.remove_node(id) {
+                events.extend(links.into_iter().map(NodeGraphEvent::Unlinked));
+                removed.push(node);
+            }
+        }
+        if !removed.is_empty() {
+            events.push(NodeGraphEvent::Removed(removed));
+        }
+    }
+
+    /// Add the nodes of the clipboard with new ids, with their top left corner at `at`.
+    fn paste(&mut self, at: [f32; 2], events: &mut Vec<NodeGraphEvent>) {
+        let clipboard = match &self.clipboard {
+            Some(clipboard) if !clipboard.nodes.is_empty() => clipboard.clone(),
+            _ => return,
+        };
+        let mut corner = [f32::MAX, f32::MAX];
+        for node in &clipboard.nodes {
+            corner[0] = corner[0].min(node.position[0]);
+            corner[1] = corner[1].min(node.position[1]);
+        }
+
+        self.selection.clear();
+        let mut ids = HashMap::new();
+        for mut node in clipboard.nodes {
+            node.position = [
+                node.position[0] - corner[0] + at[0],
+                node.position[1] - corner[1] + at[1],
+            ];
+            let old = node.id;
+            let id = self.graph.add_node(node);
+            ids.insert(old, id);
+            self.selection.insert(id);
+        }
+        for link in clipboard.links {
+            let link = Link {
+                from: PinRef {
+                    node: ids[&link.from.node],
+                    index: link.from.index,
+                },
+                to: PinRef {
+                    node: ids[&link.to.node],
+                    index: link.to.index,
+                },
+            };
+            self.graph.links.push(link);
+            events.push(NodeGraphEvent::Linked(link));
+        }
+        let mut pasted: Vec<NodeId> = ids.values().copied().collect();
+        pasted.sort();
+        events.push(NodeGraphEvent::Pasted(pasted));
+    }
+
+    fn handle_keys(&mut self, ui: &imgui::Ui, view: &CanvasView, events: &mut Vec<NodeGraphEvent>) {
+        use imgui::Key;
+
+        let io = ui.io();
+        if !view.hovered || io.want_text_input {
+            return;
+        }
+        if ui.is_key_pressed(Key::Delete) || ui.is_key_pressed(Key::Backspace) {
+            self.remove_selection(events);
+        }
+        if !(io.key_ctrl || io.key_super) {
+            return;
+        }
+        if ui.is_key_pressed(Key::C) || ui.is_key_pressed(Key::X) {
+            self.clipboard = Some(self.graph.subgraph(&self.selection));
+            if ui.is_key_pressed(Key::X) {
+                self.remove_selection(events);
+            }
+        } else if ui.is_key_pressed(Key::V) {
+            self.paste(view.mouse, events);
+        } else if ui.is_key_pressed(Key::A) {
+            self.selection = self.graph.nodes.iter().map(|node| node.id).collect();
+        }
+    }
+
+    fn handle_mouse(
+        &mut self,
+        ui: &imgui::Ui,
+        view: &CanvasView,
+        events: &mut Vec<NodeGraphEvent>,
+    ) {
+        use imgui::MouseButton;
+
+        let io = ui.io();
+        let layouts: Vec<Layout> = self
+            .graph
+            .nodes
+            .iter()
+            .map(|node| Layout::new(ui, node))
+            .collect();
+        let radius = PIN_RADIUS.max(PIN_GRAB / view.zoom);
+
+        if view.hovered && ui.is_mouse_clicked(MouseButton::Left) {
+            if let Some((pin, output)) = self.hit_pin(&layouts, view.mouse, radius) {
+                let linked = self.graph.links.iter().position(|link| link.to == pin);
+                self.drag = Some(match linked {
+                    // Dragging a linked input picks up its link.
+                    Some(index) if !output => {
+                        let link = self.graph.links.remove(index);
+                        events.push(NodeGraphEvent::Unlinked(link));
+                        Drag::Link {
+                            from: link.from,
+                            output: true,
+                        }
+                    }
+                    _ => Drag::Link { from: pin, output },
+                });
+            } else if let Some(index) = self.hit_node(&layouts, view.mouse) {
+                let id = self.graph.nodes[index].id;
+                if io.key_shift || io.key_ctrl {
+                    if !self.selection.remove(&id) {
+                        self.selection.insert(id);
+                    }
+                } else if !self.selection.contains(&id) {
+                    self.selection.clear();
+                    self.selection.insert(id);
+                }
+                // The clicked node is drawn over the others.
+                let node = self.graph.nodes.remove(index);
+                self.graph.nodes.push(node);
+                self.drag = Some(Drag::Nodes { moved: false });
+            } else {
+                if !io.key_shift {
+                    self.selection.clear();
+                }
+                self.drag = Some(Drag::Marquee { start: view.mouse });
+            }
+        }
+
+        if let Some(Drag::Nodes { moved }) = &mut self.drag {
+            let delta = [io.mouse_delta[0] / view.zoom, io.mouse_delta[1] / view.zoom];
+            if delta != [0.0, 0.0] {
+                for node in &mut self.graph.nodes {
+                    if self.selection.contains(&node.id) {
+                        node.position[0] += delta[0];
+                        node.position[1] += delta[1];
+                    }
+                }
+                *moved = true;
+            }
+        }
+
+        if ui.is_mouse_down(MouseButton::Left) {
+            return;
+        }
+        match self.drag.take() {
+            Some(Drag::Nodes { moved: true }) => {
+                let mut ids: Vec<NodeId> = self.selection.iter().copied().collect();
+                ids.sort();
+                events.push(NodeGraphEvent::Moved(ids));
+            }
+            Some(Drag::Link { from, output }) => {
+                let target = self.hit_pin(&layouts, view.mouse, radius);
+                if let Some((pin, pin_output)) = target {
+                    let (from, to) = if output { (from, pin) } else { (pin, from) };
+                    if pin_output != output && self.graph.can_link(from, to) {
+                        if let Some(old) = self.graph.link(from, to) {
+                            events.push(NodeGraphEvent::Unlinked(old));
+                        }
+                        events.push(NodeGraphEvent::Linked(Link { from, to }));
+                    }
+                }
+            }
+            Some(Drag::Marquee { start }) => {
+                let min = [start[0].min(view.mouse[0]), start[1].min(view.mouse[1])];
+                let max = [start[0].max(view.mouse[0]), start[1].max(view.mouse[1])];
+                for (node, layout) in self.graph.nodes.iter().zip(&layouts) {
+                    let overlaps = layout.min[0] <= max[0]
+                        && layout.max[0] >= min[0]
+                        && layout.min[1] <= max[1]
+                        && layout.max[1] >= min[1];
+                    if overlaps {
+                        self.selection.insert(node.id);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn pin_color(&self, ui: &imgui::Ui, ty: &str) -> [f32; 4] {
+        match self.pin_colors.get(ty) {
+            Some(color) => *color,
+            None => ui.style_color(imgui::StyleColor::Text),
+        }
+    }
+
+    fn draw(&self, ui: &imgui::Ui, view: &CanvasView) {
+        use imgui::StyleColor;
+
+        let layouts: Vec<Layout> = self
+            .graph
+            .nodes
+            .iter()
+            .map(|node| Layout::new(ui, node))
+            .collect();
+        let layout_of = |id: NodeId| {
+            self.graph
+                .nodes
+                .iter()
+                .position(|node| node.id == id)
+                .map(|index| &layouts[index])
+        };
+        let thickness = view.scale(2.0).max(1.0);
+
+        {
+            let draw_list = ui.get_window_draw_list();
+            let bezier = |from: [f32; 2], to: [f32; 2], color: [f32; 4]| {
+                let tangent = ((to[0] - from[0]).abs() * 0.5).max(50.0);
+                draw_list
+                    .add_bezier_curve(
+                        view.to_screen(from),
+                        view.to_screen([from[0] + tangent, from[1]]),
+                        view.to_screen([to[0] - tangent, to[1]]),
+                        view.to_screen(to),
+                        color,
+                    )
+                    .thickness(thickness)
+                    .build();
+            };
+
+            for link in &self.graph.links {
+                let (from, to) = match (layout_of(link.from.node), layout_of(link.to.node)) {
+                    (Some(from), Some(to)) => (from, to),
+                    _ => continue,
+                };
+                let color = match self.graph.pin(link.from, true) {
+                    Some(pin) => self.pin_color(ui, &pin.ty),
+                    None => continue,
+                };
+                bezier(
+                    from.pin(true, link.from.index),
+                    to.pin(false, link.to.index),
+                    color,
+                );
+            }
+
+            if let Some(Drag::Link { from, output }) = self.drag {
+                if let (Some(layout), Some(pin)) =
+                    (layout_of(from.node), self.graph.pin(from, output))
+                {
+                    let pin_pos = layout.pin(output, from.index);
+                    let color = self.pin_color(ui, &pin.ty);
+                    if output {
+                        bezier(pin_pos, view.mouse, color);
+                    } else {
+                        bezier(view.mouse, pin_pos, color);
+                    }
+                }
+            }
+        }
+
+        let text_color = ui.style_color(StyleColor::Text);
+        let border = ui.style_color(StyleColor::Border);
+        let selected_border = ui.style_color(StyleColor::ButtonActive);
+        let header_color = ui.style_color(StyleColor::TitleBgActive);
+        let body_color = ui.style_color(StyleColor::PopupBg);
+        let rounding = view.scale(ROUNDING);
+
+        for (node, layout) in self.graph.nodes.iter().zip(&layouts) {
+            let min = view.to_screen(layout.min);
+            let max = view.to_screen(layout.max);
+            let header = view.to_screen([layout.max[0], layout.min[1] + layout.header]);
+            let selected = self.selection.contains(&node.id);
+
+            {
+                let draw_list = ui.get_window_draw_list();
+                draw_list
+                    .add_rect(min, max, body_color)
+                    .filled(true)
+                    .rounding(rounding)
+                    .build();
+                draw_list
+                    .add_rect(min, header, header_color)
+                    .filled(true)
+                    .rounding(rounding)
+                    .round_bot_left(false)
+                    .round_bot_right(false)
+                    .build();
+                draw_list
+                    .add_rect(min, max, if selected { selected_border } else { border })
+                    .rounding(rounding)
+                    .thickness(if selected { 2.0 } else { 1.0 })
+                    .build();
+
+                for (output, pins) in &[(false, &node.inputs), (true, &node.outputs)] {
+                    for (index, pin) in pins.iter().enumerate() {
+                        let center = view.to_screen(layout.pin(*output, index));
+                        draw_list
+                            .add_circle(center, view.scale(PIN_RADIUS), self.pin_color(ui, &pin.ty))
+                            .filled(true)
+                            .build();
+                    }
+                }
+            }
+
+            let top = layout.min[1] + PADDING * 0.5;
+            view.text(ui, [layout.min[0] + PADDING, top], text_color, &node.title);
+            let half_row = ui.current_font_size() * 0.5;
+            for (index, pin) in node.inputs.iter().enumerate() {
+                let pos = layout.pin(false, index);
+                view.text(
+                    ui,
+                    [pos[0] + PADDING, pos[1] - half_row],
+                    text_color,
+                    &pin.name,
+                );
+            }
+            for (index, pin) in node.outputs.iter().enumerate() {
+                let pos = layout.pin(true, index);
+                let width = ui.calc_text_size(&pin.name)[0];
+                let at = [pos[0] - PADDING - width, pos[1] - half_row];
+                view.text(ui, at, text_color, &pin.name);
+            }
+        }
+
+        if let Some(Drag::Marquee { start }) = self.drag {
+            let min = view.to_screen(start);
+            let max = view.to_screen(view.mouse);
+            let color = ui.style_color(StyleColor::TextSelectedBg);
+            let draw_list = ui.get_window_draw_list();
+            draw_list.add_rect(min, max, color).filled(true).build();
+            draw_list.add_rect(min, max, selected_border).build();
+        }
+    }
+}
+
+/// An editor for a `Graph` on a pannable, zoomable canvas.
+///
+/// Keep it in the application state and call `build` every frame.
+#[derive(Debug, Default)]
+pub struct NodeGraph {
+    canvas: Canvas,
+    editor: Editor,
+}
+
+impl NodeGraph {
+    pub fn new(graph: Graph) -> Self {
+        Self {
+            canvas: Canvas::new(),
+            editor: Editor {
+                graph,
+                ..Editor::default()
+            },
+        }
+    }
+
+    /// Draw the pins of type `ty` and the links between them in `color`. Other types are drawn
+    /// in the text color.
+    pub fn pin_color(mut self, ty: impl Into<String>, color: [f32; 4]) -> Self {
+        self.editor.pin_colors.insert(ty.into(), color);
+        self
+    }
+
+    pub fn graph(&self) -> &Graph {
+        &self.editor.graph
+    }
+
+    pub fn graph_mut(&mut self) -> &mut Graph {
+        &mut self.editor.graph
+    }
+
+    /// The canvas the graph is drawn on, to change or restore its view.
+    pub fn canvas_mut(&mut self) -> &mut Canvas {
+        &mut self.canvas
+    }
+
+    pub fn selection(&self) -> Vec<NodeId> {
+        let mut ids: Vec<NodeId> = self.editor.selection.iter().copied().collect();
+        ids.sort();
+        ids
+    }
+
+    pub fn set_selection(&mut self, ids: &[NodeId]) {
+        self.editor.selection = ids.iter().copied().collect();
+    }
+
+    /// Draw the editor at the cursor, and return what the user changed this frame.
+    pub fn build(&mut self, ui: &imgui::Ui, label: &str, size: [f32; 2]) -> Vec<NodeGraphEvent> {
+        let editor = &mut self.editor;
+        self.canvas.build(ui, label, size, |view| {
+            let mut events = Vec::new();
+            editor.handle_keys(ui, view, &mut events);
+            editor.handle_mouse(ui, view, &mut events);
+            editor.draw(ui, view);
+            events
+        })
+    }
+}
@@ -0,0 +1,145 @@
+//! Short messages shown in a corner of the window.
+//!
+//! Plugins tell the user that a preset was saved or a file couldn't be loaded. Instead of a
+//! status line of their own, they can queue a notification with `WindowContext::notify`.
+//! Notifications are drawn in the bottom right corner over the UI of the update closure,
+//! newest at the bottom, fade in and out, and go away when their time is up or they are
+//! clicked.
+
+use std::time::Duration;
+
+use imgui::{Condition, StyleColor, StyleVar, WindowFlags};
+
+/// How long notifications fade in and out, unless motion is reduced.
+const FADE_IN: f32 = 0.15;
+const FADE_OUT: f32 = 0.3;
+/// The most notifications shown at once. Older ones wait until newer ones are gone.
+const MAX_VISIBLE: usize = 5;
+/// The distance from the corner and between notifications, in logical pixels.
+const MARGIN: f32 = 12.0;
+/// Where the text of a notification wraps, in logical pixels.
+const WRAP_WIDTH: f32 = 320.0;
+
+/// How important a notification is, which sets its color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NotificationLevel {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl NotificationLevel {
+    fn color(self) -> [f32; 4] {
+        match self {
+            NotificationLevel::Info => [0.26, 0.59, 0.98, 1.0],
+            NotificationLevel::Success => [0.30, 0.75, 0.40, 1.0],
+            NotificationLevel::Warning => [0.95, 0.70, 0.20, 1.0],
+            NotificationLevel::Error => [0.90, 0.30, 0.30, 1.0],
+        }
+    }
+}
+
+struct Notification {
+    id: u64,
+    level: NotificationLevel,
+    text: String,
+    duration: f32,
+    /// How long it has been shown, in seconds.
+    age: f32,
+    /// The height it had in the last frame, to stack the older ones above it.
+    height: f32,
+}
+
+#[derive(Default)]
+pub(crate) struct Notifications {
+    queue: Vec<Notification>,
+    next_id: u64,
+}
+
+impl Notifications {
+    pub fn push(&mut self, level: NotificationLevel, text: String, duration: Duration) {
+        self.next_id += 1;
+        self.queue.push(Notification {
+            id: self.next_id,
+            level,
+            text,
+            duration: duration.as_secs_f32(),
+            age: 0.0,
+            height: 0.0,
+        });
+    }
+
+    /// Whether notifications are shown, so frames are needed to fade them and time them out.
+    pub fn is_active(&self) -> bool {
+        !self.queue.is_empty()
+    }
+
+    /// Draw the notifications over the UI, and drop the ones whose time is up or that were
+    /// clicked.
+    pub fn draw(&mut self, ui: &imgui::Ui, reduced_motion: bool) {
+        let delta_time = ui.io().delta_time;
+        let display_size = ui.io().display_size;
+        let start = self.queue.len().saturating_sub(MAX_VISIBLE);
+
+        let mut bottom = display_size[1] - MARGIN;
+        let mut dismissed = Vec::new();
+        for notification in self.queue[start..].iter_mut().rev() {
+            notification.age += delta_time;
+            let remaining = notification.duration - notification.age;
+            let alpha = if reduced_motion {
+                1.0
+            } else {
+                (notification.age / FADE_IN)
+                    .min(remaining / FADE_OUT)
+                    .max(0.0)
+                    .min(1.0)
+            };
+
+            let _alpha = ui.push_style_var(StyleVar::Alpha(alpha));
+            let _border = ui.push_style_color(StyleColor::Border, notification.level.color());
+            let flags = WindowFlags::NO_DECORATION
+                | WindowFlags::ALWAYS_AUTO_RESIZE
+                | WindowFlags::NO_SAVED_SETTINGS
+                | WindowFlags::NO_FOCUS_ON_APPEARING
+                | WindowFlags::NO_NAV
+                | WindowFlags::NO_MOVE;
+            imgui::Window::new(&format!("##notification{}", notification.id))
+                .position([display_size[0] - MARGIN, bottom], Condition::Always)
+                .position_pivot([1.0, 1.0])
+                .flags(flags)
+                .build(ui, || {
+                    let wrap = ui.cursor_pos()[0] + WRAP_WIDTH;
+                    let _wrap = ui.push_text_wrap_pos_with_pos(wrap);
+                    // A bar in the color of the level, next to the first line.
+                    let pos = ui.cursor_screen_pos();
+                    let size = [3.0, ui.text_line_height()];
+                    ui.get_window_draw_list()
+                        .add_rect(
+                            pos,
+                            [pos[0] + size[0], pos[1] + size[1]],
+                            notification.level.color(),
+                        )
+                        .filled(true)
+                        .build();
+                    ui.dummy(size);
+                    ui.same_line();
+                    ui.text(&notification.text);
+
+                    notification.height = ui.window_size()[1];
+                    if ui.is_window_hovered() && ui.is_mouse_clicked(imgui::MouseButton::Left) {
+                        dismissed.push(notification.id);
+                    }
+                });
+
+            bottom -= notification.height + MARGIN * 0.5;
+            if remaining <= 0.0 {
+                dismissed.push(notification.id);
+            }
+        }
+
+        self.queue
+            .retain(|notification| !dismissed.contains(&notification.id));
+    }
+}
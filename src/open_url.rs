@@ -0,0 +1,83 @@
+//! Opening links in the browser of the user.
+//!
+//! Launching the browser can block, and some platforms don't like it being done from the
+//! event handler of a child window, so it runs on a worker thread through the `tasks` module.
+//! Failures are collected at the start of the frame after they happened.
+
+use std::process::Command;
+
+use crate::tasks::{self, Task};
+
+/// A link that could not be opened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenUrlError {
+    pub url: String,
+    pub message: String,
+}
+
+#[derive(Default)]
+pub(crate) struct UrlOpener {
+    pending: Vec<(String, Task<Result<(), String>>)>,
+    errors: Vec<OpenUrlError>,
+}
+
+impl UrlOpener {
+    pub fn open(&mut self, url: &str) {
+        // Only web and mail links, so a link can't be used to launch a program.
+        let allowed = ["http://", "https://", "mailto:"];
+        if !allowed.iter().any(|scheme| url.starts_with(scheme)) {
+            self.errors.push(OpenUrlError {
+                url: url.to_string(),
+                message: String::from("unsupported URL scheme"),
+            });
+            return;
+        }
+
+        let owned = url.to_string();
+        let task = tasks::spawn(move || launch(&owned));
+        self.pending.push((url.to_string(), task));
+    }
+
+    /// Collect the failures of the links that finished opening.
+    pub fn poll(&mut self) {
+        let mut i = 0;
+        while i < self.pending.len() {
+            if self.pending[i].1.is_running() {
+                i += 1;
+                continue;
+            }
+
+            let (url, mut task) = self.pending.remove(i);
+            if let Some(Err(message)) = task.take() {
+                self.errors.push(OpenUrlError { url, message });
+            }
+        }
+    }
+
+    pub fn take_errors(&mut self) -> Vec<OpenUrlError> {
+        std::mem::take(&mut self.errors)
+    }
+}
+
+fn launch(url: &str) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler");
+        command
+    };
+    #[cfg(target_os = "macos")]
+    let mut command = Command::new("open");
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut command = Command::new("xdg-open");
+
+    let status = command
+        .arg(url)
+        .status()
+        .map_err(|error| error.to_string())?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("the browser could not be launched ({})", status))
+    }
+}
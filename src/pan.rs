@@ -0,0 +1,171 @@
+//! Scrolling a UI that is larger than its window.
+//!
+//! Hosts can limit an editor to less than the size its UI was designed for. With
+//! `Settings::scroll_container` the update closure still lays the UI out at the design size,
+//! and the window shows the part of it under a scroll offset. Dragging with Space held pans
+//! the UI, and so does scrolling with Space held or outside the imgui windows, like with two
+//! fingers on a trackpad. The offset is kept in the UI state, so the editor opens where it
+//! was left.
+//!
+//! The whole frame is moved, so the notifications and overlays of the crate pan with the UI.
+
+use crate::scroll;
+use crate::settings::ScrollContainer;
+use crate::storage::{self, UiStorage};
+
+/// The thickness of the bars that show the offset, in logical pixels.
+const INDICATOR: f32 = 3.0;
+
+pub(crate) struct PanContainer {
+    size: [f32; 2],
+    offset: [f32; 2],
+    /// The size of the window, while the frame is laid out at the size of the UI.
+    viewport: [f32; 2],
+    /// The mouse position of the last frame of a drag.
+    drag: Option<[f32; 2]>,
+}
+
+impl PanContainer {
+    pub fn new(settings: ScrollContainer, storage: &UiStorage) -> Self {
+        Self {
+            size: settings.size,
+            offset: storage
+                .get_id(storage::SCROLL_OFFSET_ID)
+                .unwrap_or([0.0, 0.0]),
+            viewport: [0.0, 0.0],
+            drag: None,
+        }
+    }
+
+    /// Pan for the input since the last frame, and lay the frame out at the size of the UI.
+    pub fn begin_frame(&mut self, io: &mut imgui::Io, line_height: f32, storage: &mut UiStorage) {
+        self.viewport = io.display_size;
+        let mouse = io.mouse_pos;
+        let has_mouse = mouse[0].is_finite() && mouse[1].is_finite();
+        let panning = io.keys_down[io[imgui::Key::Space] as usize];
+
+        let mut offset = self.offset;
+        if panning && io.mouse_down[0] && has_mouse {
+            if let Some(last) = self.drag {
+                offset[0] -= mouse[0] - last[0];
+                offset[1] -= mouse[1] - last[1];
+            }
+            self.drag = Some(mouse);
+            // The widgets under the mouse don't see the drag.
+            io.mouse_down[0] = false;
+        } else {
+            self.drag = None;
+        }
+
+        // The windows of the UI had the mouse in the last frame, and scroll themselves.
+        if panning || !io.want_capture_mouse {
+            let step = line_height * scroll::LINES_PER_STEP;
+            offset[0] -= io.mouse_wheel_h * step;
+            offset[1] -= io.mouse_wheel * step;
+            io.mouse_wheel_h = 0.0;
+            io.mouse_wheel = 0.0;
+        }
+
+        let content = self.content_size();
+        for i in 0..2 {
+            offset[i] = offset[i].max(0.0).min(content[i] - self.viewport[i]);
+        }
+        if offset != self.offset {
+            self.offset = offset;
+            storage.set_id(storage::SCROLL_OFFSET_ID, &offset);
+        }
+
+        io.display_size = content;
+        if has_mouse {
+            io.mouse_pos = [mouse[0] + offset[0], mouse[1] + offset[1]];
+        }
+    }
+
+    /// The UI is shown at its size, or stretched to the window if that is larger.
+    fn content_size(&self) -> [f32; 2] {
+        [
+            self.size[0].max(self.viewport[0]),
+            self.size[1].max(self.viewport[1]),
+        ]
+    }
+
+    /// Draw bars along the right and bottom edge of the window that show which part of the
+    /// UI it shows.
+    pub fn draw_indicators(&self, ui: &imgui::Ui) {
+        let content = self.content_size();
+        let draw_list = ui.get_foreground_draw_list();
+        let color = ui.style_color(imgui::StyleColor::ScrollbarGrab);
+        let [x, y] = self.offset;
+        let [width, height] = self.viewport;
+
+        if content[1] > height {
+            let top = y + y / content[1] * height;
+            let bottom = top + height / content[1] * height;
+            draw_list
+                .add_rect([x + width - INDICATOR, top], [x + width, bottom], color)
+                .filled(true)
+                .build();
+        }
+        if content[0] > width {
+            let left = x + x / content[0] * width;
+            let right = left + width / content[0] * width;
+            draw_list
+                .add_rect([left, y + height - INDICATOR], [right, y + height], color)
+                .filled(true)
+                .build();
+        }
+    }
+
+    /// Move the frame that was just built by the offset, and render it at the size of the
+    /// window.
+    ///
+    /// The renderer builds the draw data from the `imgui::Ui` itself, so like for pixel
+    /// snapping the vertices and clip rectangles of the draw lists are moved, and the main
+    /// viewport gets the size of the window to give the draw data that size.
+    pub fn translate(&self) {
+        let [x, y] = self.offset;
+
+        unsafe {
+            let viewport = &mut *imgui::sys::igGetMainViewport();
+            viewport.Size.x = self.viewport[0];
+            viewport.Size.y = self.viewport[1];
+
+            // Building the draw data twice in a frame only collects the draw lists again.
+            imgui::sys::igRender();
+            let draw_data = imgui::sys::igGetDrawData();
+            if draw_data.is_null() || (x == 0.0 && y == 0.0) {
+                return;
+            }
+
+            let draw_data = &*draw_data;
+            for i in 0..draw_data.CmdListsCount as usize {
+                let list = &mut **draw_data.CmdLists.add(i);
+                let buffer = &list.VtxBuffer;
+                let vertices = std::slice::from_raw_parts_mut(buffer.Data, buffer.Size as usize);
+                for vertex in vertices {
+                    vertex.pos.x -= x;
+                    vertex.pos.y -= y;
+                }
+                let buffer = &list.CmdBuffer;
+                let commands = std::slice::from_raw_parts_mut(buffer.Data, buffer.Size as usize);
+                for command in commands {
+                    command.ClipRect.x -= x;
+                    command.ClipRect.y -= y;
+                    command.ClipRect.z -= x;
+                    command.ClipRect.w -= y;
+                }
+            }
+        }
+    }
+
+    /// Give the window its own size and mouse position again after a frame.
+    pub fn end_frame(&self, io: &mut imgui::Io) {
+        io.display_size = self.viewport;
+        if io.mouse_pos[0].is_finite() && io.mouse_pos[1].is_finite() {
+            io.mouse_pos = [
+                io.mouse_pos[0] - self.offset[0],
+                io.mouse_pos[1] - self.offset[1],
+            ];
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! Parameters shared between the UI and the audio thread.
+//!
+//! A `Param` stores its value in an atomic, so it can be put in an `Arc` and read and written
+//! from the audio thread while the param-bound widgets in the `widgets` module edit it.
+//!
+//! Values the audio thread publishes once a block or less, like meter levels, step visibly
+//! when they are shown as they are. A `SmoothedValue` moves the displayed value towards the
+//! latest one a bit every frame instead.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+/// Identifies a parameter, usually the index the host knows it by.
+pub type ParamId = u32;
+
+/// A continuous parameter.
+#[derive(Debug)]
+pub struct Param {
+    id: ParamId,
+    name: String,
+    unit: String,
+    min: f32,
+    max: f32,
+    default: f32,
+    value: AtomicU32,
+}
+
+impl Param {
+    /// Create a parameter that starts at its default value.
+    pub fn new(id: ParamId, name: impl Into<String>, min: f32, max: f32, default: f32) -> Self {
+        let default = default.max(min).min(max);
+
+        Self {
+            id,
+            name: name.into(),
+            unit: String::new(),
+            min,
+            max,
+            default,
+            value: AtomicU32::new(default.to_bits()),
+        }
+    }
+
+    /// Show `unit` after the value, for example "dB" or "Hz".
+    pub fn with_unit(mut self, unit: impl Into<String>) -> Self {
+        self.unit = unit.into();
+        self
+    }
+
+    pub fn id(&self) -> ParamId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn unit(&self) -> &str {
+        &self.unit
+    }
+
+    pub fn min(&self) -> f32 {
+        self.min
+    }
+
+    pub fn max(&self) -> f32 {
+        self.max
+    }
+
+    pub fn default_value(&self) -> f32 {
+        self.default
+    }
+
+    pub fn get(&self) -> f32 {
+        f32::from_bits(self.value.load(Ordering::Relaxed))
+    }
+
+    /// Set the value, clamped to the range of the parameter.
+    pub fn set(&self, value: f32) {
+        let value = value.max(self.min).min(self.max);
+        self.value.store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    /// The value mapped to `0.0..=1.0`.
+    pub fn normalized(&self) -> f32 {
+        if self.max > self.min {
+            (self.get() - self.min) / (self.max - self.min)
+        } else {
+            0.0
+        }
+    }
+
+    pub fn set_normalized(&self, normalized: f32) {
+        self.set(self.min + normalized * (self.max - self.min));
+    }
+
+    pub fn reset(&self) {
+        self.set(self.default);
+    }
+
+    /// Format the value together with its unit.
+    pub fn format(&self) -> String {
+        self.format_value(self.get())
+    }
+
+    pub fn format_value(&self, value: f32) -> String {
+        if self.unit.is_empty() {
+            format!("{:.2}", value)
+        } else {
+            format!("{:.2} {}", value, self.unit)
+        }
+    }
+
+    /// Parse a value typed by the user, with or without the unit.
+    pub fn parse(&self, text: &str) -> Option<f32> {
+        let text = text.trim();
+        let text = text.strip_suffix(self.unit.as_str()).unwrap_or(text);
+        text.trim().parse().ok()
+    }
+}
+
+/// A displayed value that follows a target smoothly, from frame to frame.
+#[derive(Debug, Clone)]
+pub struct SmoothedValue {
+    value: Option<f32>,
+    attack: f32,
+    release: f32,
+}
+
+impl SmoothedValue {
+    /// A value that covers about two thirds of the distance to its target in `time`.
+    pub fn new(time: Duration) -> Self {
+        Self {
+            value: None,
+            attack: time.as_secs_f32(),
+            release: time.as_secs_f32(),
+        }
+    }
+
+    /// Fall towards lower targets in `time` instead, so meters rise quickly and fall slowly.
+    pub fn release(mut self, time: Duration) -> Self {
+        self.release = time.as_secs_f32();
+        self
+    }
+
+    /// Move towards `target` by a frame that took `delta_time` seconds, usually
+    /// `ui.io().delta_time`, and return the value to show. The first target is taken as it is.
+    pub fn update(&mut self, target: f32, delta_time: f32) -> f32 {
+        let value = match self.value {
+            Some(value) => value,
+            None => target,
+        };
+        let time = if target >= value {
+            self.attack
+        } else {
+            self.release
+        };
+
+        let value = if time > 0.0 {
+            value + (target - value) * (1.0 - (-delta_time / time).exp())
+        } else {
+            target
+        };
+        self.value = Some(value);
+        value
+    }
+
+    /// The value that was last shown.
+    pub fn value(&self) -> Option<f32> {
+        self.value
+    }
+
+    /// Jump to `value`, for example when a preset is loaded.
+    pub fn set(&mut self, value: f32) {
+        self.value = Some(value);
+    }
+
+    /// Whether the value is within `epsilon` of `target`, so no more frames are needed to
+    /// follow it.
+    pub fn is_settled(&self, target: f32, epsilon: f32) -> bool {
+        self.value
+            .map_or(false, |value| (value - target).abs() <= epsilon)
+    }
+}
@@ -0,0 +1,112 @@
+//! Input that goes to the host even over the window.
+//!
+//! Hosts have gestures that should work over a plugin UI too, like Alt+click or the space
+//! bar for the transport. Events that match a rule of `Settings::host_passthrough` skip imgui
+//! and are returned as ignored, so the host handles them. What belongs to a gesture follows
+//! it: the release of a key or button that went to the host goes there as well, and so does
+//! the mouse movement while such a button is held.
+
+use keyboard_types::{Code, KeyState, Modifiers};
+
+use crate::settings::{PassthroughButton, PassthroughInput, PassthroughRule};
+
+impl PassthroughButton {
+    fn matches(self, button: baseview::MouseButton) -> bool {
+        use baseview::MouseButton;
+
+        match (self, button) {
+            (PassthroughButton::Left, MouseButton::Left)
+            | (PassthroughButton::Right, MouseButton::Right)
+            | (PassthroughButton::Middle, MouseButton::Middle)
+            | (PassthroughButton::Back, MouseButton::Back)
+            | (PassthroughButton::Forward, MouseButton::Forward) => true,
+            (PassthroughButton::Other(a), MouseButton::Other(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Passthrough {
+    rules: Vec<PassthroughRule>,
+    /// The modifiers of the last key event, since mouse events don't have them.
+    modifiers: Modifiers,
+    /// The keys and buttons held down that went to the host.
+    keys: Vec<Code>,
+    buttons: Vec<baseview::MouseButton>,
+}
+
+impl Passthrough {
+    pub fn new(rules: Vec<PassthroughRule>) -> Self {
+        Self {
+            rules,
+            ..Self::default()
+        }
+    }
+
+    fn matches(&self, input: impl Fn(&PassthroughInput) -> bool) -> bool {
+        let mask = Modifiers::SHIFT | Modifiers::CONTROL | Modifiers::ALT | Modifiers::META;
+
+        self.rules
+            .iter()
+            .any(|rule| rule.modifiers & mask == self.modifiers & mask && input(&rule.input))
+    }
+
+    /// Whether the event goes to the host instead of the window.
+    pub fn passes(&mut self, event: &baseview::Event) -> bool {
+        match event {
+            baseview::Event::Keyboard(event) => {
+                self.modifiers = event.modifiers;
+                let held = self.keys.iter().position(|code| *code == event.code);
+                match (event.state, held) {
+                    (KeyState::Down, Some(_)) => true,
+                    (KeyState::Down, None) => {
+                        let passes =
+                            self.matches(|input| *input == PassthroughInput::Key(event.code));
+                        if passes {
+                            self.keys.push(event.code);
+                        }
+                        passes
+                    }
+                    (KeyState::Up, Some(i)) => {
+                        self.keys.swap_remove(i);
+                        true
+                    }
+                    (KeyState::Up, None) => false,
+                }
+            }
+            baseview::Event::Mouse(baseview::MouseEvent::ButtonPressed(button)) => {
+                let passes = self.matches(|input| match input {
+                    PassthroughInput::MouseButton(rule) => rule.matches(*button),
+                    _ => false,
+                });
+                if passes {
+                    self.buttons.push(*button);
+                }
+                passes
+            }
+            baseview::Event::Mouse(baseview::MouseEvent::ButtonReleased(button)) => {
+                match self.buttons.iter().position(|held| held == button) {
+                    Some(i) => {
+                        self.buttons.swap_remove(i);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            baseview::Event::Mouse(baseview::MouseEvent::CursorMoved { .. }) => {
+                !self.buttons.is_empty()
+            }
+            baseview::Event::Mouse(baseview::MouseEvent::WheelScrolled(_)) => {
+                self.matches(|input| *input == PassthroughInput::Wheel)
+            }
+            // The modifiers may have been released outside of the window.
+            baseview::Event::Window(baseview::WindowEvent::Unfocused) => {
+                self.modifiers = Modifiers::empty();
+                self.keys.clear();
+                false
+            }
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,38 @@
+//! Snapping the vertices of the UI to physical pixels.
+//!
+//! imgui lays out the UI in logical pixels, so at a fractional hidpi factor like 1.25 a
+//! separator at a logical y of 10 lands on a physical y of 12.5 and is smeared over two rows.
+//! With `Settings::pixel_snapping` every vertex is moved to the nearest physical pixel before
+//! the UI is rendered, which keeps borders and separators crisp.
+//!
+//! The renderer builds the draw data from the `imgui::Ui` itself, so the draw data is built
+//! here first and the vertices are changed in the draw lists it points to. When the renderer
+//! builds it again it uses the same lists, with the snapped vertices.
+
+/// Snap the vertices of the frame that was just built to multiples of `1 / hidpi_factor` logical pixels. Does
+/// nothing at integer factors, where imgui's own rounding already lands on pixels.
+pub(crate) fn snap(hidpi_factor: f32) {
+    if hidpi_factor <= 0.0 || hidpi_factor.fract() == 0.0 {
+        return;
+    }
+
+    // Building the draw data twice in a frame only collects the draw lists again.
+    unsafe {
+        imgui::sys::igRender();
+        let draw_data = imgui::sys::igGetDrawData();
+        if draw_data.is_null() {
+            return;
+        }
+
+        let draw_data = &*draw_data;
+        for i in 0..draw_data.CmdListsCount as usize {
+            let list = &mut **draw_data.CmdLists.add(i);
+            let buffer = &list.VtxBuffer;
+            let vertices = std::slice::from_raw_parts_mut(buffer.Data, buffer.Size as usize);
+            for vertex in vertices {
+                vertex.pos.x = (vertex.pos.x * hidpi_factor).round() / hidpi_factor;
+                vertex.pos.y = (vertex.pos.y * hidpi_factor).round() / hidpi_factor;
+            }
+        }
+    }
+}
@@ -0,0 +1,550 @@
+use objc::runtime::Object;
+use objc::{class, msg_send, sel, sel_impl};
+use raw_window_handle::RawWindowHandle;
+
+use crate::monitor::Monitor;
+use crate::settings::WindowStyle;
+use crate::ResizeEdge;
+
+/// `NSInformationalRequest`, which bounces the dock icon once.
+const INFORMATIONAL_REQUEST: isize = 10;
+
+fn ns_window(handle: &RawWindowHandle) -> Option<*mut Object> {
+    match handle {
+        RawWindowHandle::MacOS(handle) if !handle.ns_window.is_null() => {
+            Some(handle.ns_window as *mut Object)
+        }
+        _ => None,
+    }
+}
+
+pub fn set_transparent(handle: &RawWindowHandle) {
+    if let Some(ns_window) = ns_window(handle) {
+        unsafe {
+            let clear: *mut Object = msg_send![class!(NSColor), clearColor];
+            let _: () = msg_send![ns_window, setOpaque: false];
+            let _: () = msg_send![ns_window, setBackgroundColor: clear];
+        }
+    }
+}
+
+/// `NSNormalWindowLevel` and `NSFloatingWindowLevel`.
+const NORMAL_LEVEL: isize = 0;
+const FLOATING_LEVEL: isize = 3;
+
+/// `NSWindowCollectionBehaviorTransient` and `NSWindowCollectionBehaviorIgnoresCycle`.
+const TOOL_BEHAVIOR: usize = 1 << 3 | 1 << 6;
+
+/// There is no taskbar entry per window on macOS, so `skip_taskbar` does nothing.
+pub fn set_style(handle: &RawWindowHandle, style: WindowStyle) {
+    if let Some(ns_window) = ns_window(handle) {
+        unsafe {
+            let level = if style.always_on_top {
+                FLOATING_LEVEL
+            } else {
+                NORMAL_LEVEL
+            };
+            let _: () = msg_send![ns_window, setLevel: level];
+
+            let behavior: usize = msg_send![ns_window, collectionBehavior];
+            let behavior = if style.tool_window {
+                behavior | TOOL_BEHAVIOR
+            } else {
+                behavior & !TOOL_BEHAVIOR
+            };
+            let _: () = msg_send![ns_window, setCollectionBehavior: behavior];
+        }
+    }
+}
+
+/// `NSOpenGLContextParameterSurfaceOpacity`.
+const SURFACE_OPACITY: isize = 236;
+
+pub fn make_current_context_transparent() {
+    unsafe {
+        let context: *mut Object = msg_send![class!(NSOpenGLContext), currentContext];
+        if !context.is_null() {
+            let opacity: i32 = 0;
+            let _: () = msg_send![context, setValues: &opacity forParameter: SURFACE_OPACITY];
+        }
+    }
+}
+
+/// `NSWindowOcclusionStateVisible`.
+const OCCLUSION_VISIBLE: usize = 1 << 1;
+
+/// Whether the view or one of its parents is hidden, or its window is minimized, covered by
+/// other windows or on another space.
+pub fn is_hidden(handle: &RawWindowHandle) -> bool {
+    let ns_view = match handle {
+        RawWindowHandle::MacOS(handle) if !handle.ns_view.is_null() => {
+            handle.ns_view as *mut Object
+        }
+        _ => return false,
+    };
+    unsafe {
+        let hidden: bool = msg_send![ns_view, isHiddenOrHasHiddenAncestor];
+        let window: *mut Object = msg_send![ns_view, window];
+        if hidden || window.is_null() {
+            return true;
+        }
+        let occlusion: usize = msg_send![window, occlusionState];
+        occlusion & OCCLUSION_VISIBLE == 0
+    }
+}
+
+pub fn request_attention(handle: &RawWindowHandle) {
+    if ns_window(handle).is_some() {
+        unsafe {
+            let app: *mut Object = msg_send![class!(NSApplication), sharedApplication];
+            let _: isize = msg_send![app, requestUserAttention: INFORMATIONAL_REQUEST];
+        }
+    }
+}
+
+pub fn begin_drag(handle: &RawWindowHandle) {
+    if let Some(ns_window) = ns_window(handle) {
+        unsafe {
+            let app: *mut Object = msg_send![class!(NSApplication), sharedApplication];
+            let event: *mut Object = msg_send![app, currentEvent];
+            if !event.is_null() {
+                let _: () = msg_send![ns_window, performWindowDragWithEvent: event];
+            }
+        }
+    }
+}
+
+/// AppKit has no way to hand a resize over to the window server, so windows without a frame
+/// can't be resized from their edges.
+pub fn begin_resize(_handle: &RawWindowHandle, _edge: ResizeEdge) {}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct NSPoint {
+    x: f64,
+    y: f64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct NSSize {
+    width: f64,
+    height: f64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct NSRect {
+    origin: NSPoint,
+    size: NSSize,
+}
+
+/// The height of the main screen, to flip the bottom-up AppKit coordinates.
+unsafe fn primary_height() -> f64 {
+    let screens: *mut Object = msg_send![class!(NSScreen), screens];
+    let count: usize = msg_send![screens, count];
+    if count == 0 {
+        return 0.0;
+    }
+    let primary: *mut Object = msg_send![screens, objectAtIndex: 0usize];
+    let frame: NSRect = msg_send![primary, frame];
+    frame.size.height
+}
+
+/// Convert a frame to a top-left position and size in points.
+unsafe fn flip(frame: NSRect) -> ([i32; 2], [u32; 2]) {
+    let top = primary_height() - (frame.origin.y + frame.size.height);
+    (
+        [frame.origin.x.round() as i32, top.round() as i32],
+        [
+            frame.size.width.round() as u32,
+            frame.size.height.round() as u32,
+        ],
+    )
+}
+
+/// The screens in points, with the menu bar screen first.
+pub fn monitors() -> Vec<Monitor> {
+    let mut monitors = Vec::new();
+    unsafe {
+        let screens: *mut Object = msg_send![class!(NSScreen), screens];
+        let count: usize = msg_send![screens, count];
+        for i in 0..count {
+            let screen: *mut Object = msg_send![screens, objectAtIndex: i];
+            let frame: NSRect = msg_send![screen, frame];
+            let scale: f64 = msg_send![screen, backingScaleFactor];
+            let (position, size) = flip(frame);
+            monitors.push(Monitor {
+                position,
+                size,
+                scale,
+                primary: i == 0,
+            });
+        }
+    }
+    monitors
+}
+
+pub fn window_rect(handle: &RawWindowHandle) -> Option<([i32; 2], [u32; 2])> {
+    let ns_window = ns_window(handle)?;
+    unsafe {
+        let frame: NSRect = msg_send![ns_window, frame];
+        Some(flip(frame))
+    }
+}
+
+pub fn set_position(handle: &RawWindowHandle, position: [i32; 2]) {
+    if let Some(ns_window) = ns_window(handle) {
+        unsafe {
+            let top_left = NSPoint {
+                x: position[0] as f64,
+                y: primary_height() - position[1] as f64,
+            };
+            let _: () = msg_send![ns_window, setFrameTopLeftPoint: top_left];
+        }
+    }
+}
+
+/// Resize the window so its content has `size` physical pixels, keeping the top left corner
+/// in place.
+pub fn set_size(handle: &RawWindowHandle, size: [u32; 2]) {
+    if let Some(ns_window) = ns_window(handle) {
+        unsafe {
+            let scale: f64 = msg_send![ns_window, backingScaleFactor];
+            let frame: NSRect = msg_send![ns_window, frame];
+            let top_left = NSPoint {
+                x: frame.origin.x,
+                y: frame.origin.y + frame.size.height,
+            };
+            let content = NSSize {
+                width: size[0] as f64 / scale,
+                height: size[1] as f64 / scale,
+            };
+            let _: () = msg_send![ns_window, setContentSize: content];
+            let _: () = msg_send![ns_window, setFrameTopLeftPoint: top_left];
+        }
+    }
+}
+
+/// `NSWindowAbove`.
+const WINDOW_ABOVE: isize = 1;
+
+/// A native view of the application shown inside the window, in a container view that clips
+/// it.
+pub struct Child {
+    parent: *mut Object,
+    container: *mut Object,
+    view: *mut Object,
+}
+
+pub fn attach_child(parent: &RawWindowHandle, child: &RawWindowHandle) -> Option<Child> {
+    let view = |handle: &RawWindowHandle| match handle {
+        RawWindowHandle::MacOS(handle) if !handle.ns_view.is_null() => {
+            Some(handle.ns_view as *mut Object)
+        }
+        _ => None,
+    };
+    let parent = view(parent)?;
+    let view = view(child)?;
+
+    unsafe {
+        let zero = NSRect {
+            origin: NSPoint { x: 0.0, y: 0.0 },
+            size: NSSize {
+                width: 0.0,
+                height: 0.0,
+            },
+        };
+        let container: *mut Object = msg_send![class!(NSView), alloc];
+        let container: *mut Object = msg_send![container, initWithFrame: zero];
+        // Views only clip their subviews by default before macOS 14.
+        let clips: bool = msg_send![container, respondsToSelector: sel!(setClipsToBounds:)];
+        if clips {
+            let _: () = msg_send![container, setClipsToBounds: true];
+        }
+        let _: () = msg_send![container, setHidden: true];
+
+        let nil: *mut Object = std::ptr::null_mut();
+        let _: () =
+            msg_send![parent, addSubview: container positioned: WINDOW_ABOVE relativeTo: nil];
+        let _: () = msg_send![container, addSubview: view];
+
+        Some(Child {
+            parent,
+            container,
+            view,
+        })
+    }
+}
+
+impl Child {
+    /// Place the child at `bounds` and only show the part in `visible`, both as x, y, width
+    /// and height in physical pixels from the top left of the parent.
+    pub fn set_bounds(&self, bounds: [i32; 4], visible: [i32; 4]) {
+        unsafe {
+            let window: *mut Object = msg_send![self.parent, window];
+            let scale: f64 = if window.is_null() {
+                1.0
+            } else {
+                msg_send![window, backingScaleFactor]
+            };
+            let parent_bounds: NSRect = msg_send![self.parent, bounds];
+            let flipped: bool = msg_send![self.parent, isFlipped];
+
+            let points = |value: i32| value as f64 / scale;
+            let container_y = if flipped {
+                points(visible[1])
+            } else {
+                parent_bounds.size.height - points(visible[1] + visible[3])
+            };
+            let container_frame = NSRect {
+                origin: NSPoint {
+                    x: points(visible[0]),
+                    y: container_y,
+                },
+                size: NSSize {
+                    width: points(visible[2]),
+                    height: points(visible[3]),
+                },
+            };
+
+            // The container is not flipped, so the child is placed from its bottom.
+            let view_frame = NSRect {
+                origin: NSPoint {
+                    x: points(bounds[0] - visible[0]),
+                    y: points((visible[1] + visible[3]) - (bounds[1] + bounds[3])),
+                },
+                size: NSSize {
+                    width: points(bounds[2]),
+                    height: points(bounds[3]),
+                },
+            };
+
+            let _: () = msg_send![self.container, setFrame: container_frame];
+            let _: () = msg_send![self.view, setFrame: view_frame];
+            let _: () = msg_send![self.container, setHidden: false];
+        }
+    }
+
+    pub fn hide(&self) {
+        unsafe {
+            let _: () = msg_send![self.container, setHidden: true];
+        }
+    }
+
+    pub fn detach(self) {
+        unsafe {
+            let _: () = msg_send![self.view, removeFromSuperview];
+            let _: () = msg_send![self.container, removeFromSuperview];
+            let _: () = msg_send![self.container, release];
+        }
+    }
+}
+
+#[cfg(feature = "eyedropper")]
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGWindowListCreateImage(
+        bounds: NSRect,
+        options: u32,
+        window: u32,
+        image_options: u32,
+    ) -> *mut std::ffi::c_void;
+    fn CGImageGetBitsPerPixel(image: *mut std::ffi::c_void) -> usize;
+    fn CGImageGetDataProvider(image: *mut std::ffi::c_void) -> *mut std::ffi::c_void;
+    fn CGDataProviderCopyData(provider: *mut std::ffi::c_void) -> *mut std::ffi::c_void;
+}
+
+#[cfg(feature = "eyedropper")]
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    fn CFDataGetBytePtr(data: *mut std::ffi::c_void) -> *const u8;
+    fn CFDataGetLength(data: *mut std::ffi::c_void) -> isize;
+    fn CFRelease(object: *mut std::ffi::c_void);
+}
+
+/// `kCGWindowListOptionOnScreenOnly`.
+#[cfg(feature = "eyedropper")]
+const ON_SCREEN_ONLY: u32 = 1;
+
+/// The color of the screen under the mouse. Without the screen recording permission, macOS
+/// only shows the desktop and the windows of the application.
+#[cfg(feature = "eyedropper")]
+pub fn pixel_under_cursor(_handle: &RawWindowHandle) -> Option<[u8; 3]> {
+    unsafe {
+        let location: NSPoint = msg_send![class!(NSEvent), mouseLocation];
+        let bounds = NSRect {
+            origin: NSPoint {
+                x: location.x.floor(),
+                y: (primary_height() - location.y).floor(),
+            },
+            size: NSSize {
+                width: 1.0,
+                height: 1.0,
+            },
+        };
+
+        let image = CGWindowListCreateImage(bounds, ON_SCREEN_ONLY, 0, 0);
+        if image.is_null() {
+            return None;
+        }
+        let mut color = None;
+        let data = CGDataProviderCopyData(CGImageGetDataProvider(image));
+        if !data.is_null() {
+            // Screen captures are 32-bit BGRA.
+            if CGImageGetBitsPerPixel(image) == 32 && CFDataGetLength(data) >= 4 {
+                let bytes = CFDataGetBytePtr(data);
+                color = Some([*bytes.add(2), *bytes.add(1), *bytes]);
+            }
+            CFRelease(data);
+        }
+        CFRelease(image);
+        color
+    }
+}
+
+/// The keyboard events already have the characters that dead keys compose.
+pub fn dead_key(
+    _handle: &RawWindowHandle,
+    _code: keyboard_types::Code,
+    _modifiers: keyboard_types::Modifiers,
+) -> Option<char> {
+    None
+}
+
+/// The delay and interval of the key repeat in the keyboard settings.
+pub fn key_repeat() -> Option<(std::time::Duration, std::time::Duration)> {
+    let (delay, interval): (f64, f64) = unsafe {
+        (
+            msg_send![class!(NSEvent), keyRepeatDelay],
+            msg_send![class!(NSEvent), keyRepeatInterval],
+        )
+    };
+    if !(delay > 0.0 && interval > 0.0) {
+        return None;
+    }
+    Some((
+        std::time::Duration::from_secs_f64(delay),
+        std::time::Duration::from_secs_f64(interval),
+    ))
+}
+
+pub fn prefers_reduced_motion() -> Option<bool> {
+    unsafe {
+        let workspace: *mut Object = msg_send![class!(NSWorkspace), sharedWorkspace];
+        // The setting is there from macOS 10.12 on.
+        let known: objc::runtime::BOOL = msg_send![
+            workspace,
+            respondsToSelector: sel!(accessibilityDisplayShouldReduceMotion)
+        ];
+        if workspace.is_null() || known == objc::runtime::NO {
+            return None;
+        }
+        let reduce: objc::runtime::BOOL =
+            msg_send![workspace, accessibilityDisplayShouldReduceMotion];
+        Some(reduce != objc::runtime::NO)
+    }
+}
+
+pub fn double_click_time() -> Option<std::time::Duration> {
+    let interval: f64 = unsafe { msg_send![class!(NSEvent), doubleClickInterval] };
+    if !(interval > 0.0) {
+        return None;
+    }
+    Some(std::time::Duration::from_secs_f64(interval))
+}
+
+/// AppKit has no setting for how far the mouse moves before a press becomes a drag.
+pub fn drag_threshold() -> Option<f32> {
+    None
+}
+
+#[cfg(feature = "software_renderer")]
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGColorSpaceCreateDeviceRGB() -> *mut std::ffi::c_void;
+    fn CGColorSpaceRelease(space: *mut std::ffi::c_void);
+    fn CGDataProviderCreateWithData(
+        info: *mut std::ffi::c_void,
+        data: *const std::ffi::c_void,
+        size: usize,
+        release: extern "C" fn(*mut std::ffi::c_void, *const std::ffi::c_void, usize),
+    ) -> *mut std::ffi::c_void;
+    fn CGDataProviderRelease(provider: *mut std::ffi::c_void);
+    fn CGImageCreate(
+        width: usize,
+        height: usize,
+        bits_per_component: usize,
+        bits_per_pixel: usize,
+        bytes_per_row: usize,
+        space: *mut std::ffi::c_void,
+        bitmap_info: u32,
+        provider: *mut std::ffi::c_void,
+        decode: *const f64,
+        should_interpolate: bool,
+        intent: i32,
+    ) -> *mut std::ffi::c_void;
+    fn CGImageRelease(image: *mut std::ffi::c_void);
+}
+
+/// `kCGImageAlphaNoneSkipFirst | kCGBitmapByteOrder32Little`, which is BGRX in memory.
+#[cfg(feature = "software_renderer")]
+const BGRX: u32 = 6 | 2 << 12;
+
+#[cfg(feature = "software_renderer")]
+extern "C" fn release_pixels(
+    info: *mut std::ffi::c_void,
+    _data: *const std::ffi::c_void,
+    _size: usize,
+) {
+    unsafe {
+        drop(Box::from_raw(info as *mut Vec<u8>));
+    }
+}
+
+/// Show a BGRA image as the contents of the layer of the view. The layer keeps the image
+/// until the next one, so it gets a copy of the pixels.
+#[cfg(feature = "software_renderer")]
+pub fn present_pixels(handle: &RawWindowHandle, size: [u32; 2], bgra: &mut [u8]) {
+    let ns_view = match handle {
+        RawWindowHandle::MacOS(handle) if !handle.ns_view.is_null() => {
+            handle.ns_view as *mut Object
+        }
+        _ => return,
+    };
+    unsafe {
+        let pixels = Box::into_raw(Box::new(bgra.to_vec()));
+        let provider = CGDataProviderCreateWithData(
+            pixels as *mut _,
+            (*pixels).as_ptr() as *const _,
+            (*pixels).len(),
+            release_pixels,
+        );
+        let space = CGColorSpaceCreateDeviceRGB();
+        let image = CGImageCreate(
+            size[0] as usize,
+            size[1] as usize,
+            8,
+            32,
+            size[0] as usize * 4,
+            space,
+            BGRX,
+            provider,
+            std::ptr::null(),
+            false,
+            0,
+        );
+        CGColorSpaceRelease(space);
+        CGDataProviderRelease(provider);
+        if image.is_null() {
+            return;
+        }
+
+        let _: () = msg_send![ns_view, setWantsLayer: true];
+        let layer: *mut Object = msg_send![ns_view, layer];
+        if !layer.is_null() {
+            let _: () = msg_send![layer, setContents: image];
+        }
+        CGImageRelease(image);
+    }
+}
@@ -0,0 +1,229 @@
+//! Native window calls that baseview doesn't provide.
+//!
+//! Windows that are embedded in a host window belong to the host, so the calls that would
+//! change the top-level window do nothing for them.
+
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "windows")]
+mod windows;
+#[cfg(all(unix, not(target_os = "macos")))]
+mod x11;
+
+#[cfg(target_os = "macos")]
+use macos as imp;
+#[cfg(target_os = "windows")]
+use windows as imp;
+#[cfg(all(unix, not(target_os = "macos")))]
+use x11 as imp;
+
+pub(crate) use imp::Child;
+#[cfg(all(unix, not(target_os = "macos")))]
+pub(crate) use x11::PrimarySelection;
+
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+use raw_window_handle::RawWindowHandle;
+
+use crate::monitor::Monitor;
+use crate::settings::{WindowPosition, WindowStyle};
+use crate::ResizeEdge;
+
+/// Make the surface of the current OpenGL context keep its alpha channel.
+pub(crate) fn make_current_context_transparent() {
+    #[cfg(target_os = "macos")]
+    macos::make_current_context_transparent();
+}
+
+/// Share the objects of the current OpenGL context with `shared`. Only possible on Windows,
+/// which `GlContextMode::is_supported` tells.
+pub(crate) fn share_current_context(shared: *mut std::ffi::c_void) {
+    #[cfg(target_os = "windows")]
+    windows::share_current_context(shared);
+    #[cfg(not(target_os = "windows"))]
+    let _ = shared;
+}
+
+/// The primary selection of X11, which the other platforms don't have.
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+pub(crate) struct PrimarySelection;
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+impl PrimarySelection {
+    pub fn new() -> Option<Self> {
+        None
+    }
+
+    pub fn request(&self) -> Option<std::sync::mpsc::Receiver<Option<String>>> {
+        None
+    }
+
+    pub fn set(&self, _text: String) {}
+}
+
+pub(crate) fn monitors() -> Vec<Monitor> {
+    imp::monitors()
+}
+
+/// The delay before a held key repeats and the time between repeats, from the keyboard
+/// settings of the system.
+pub(crate) fn key_repeat() -> Option<(std::time::Duration, std::time::Duration)> {
+    imp::key_repeat()
+}
+
+/// Whether the accessibility settings of the system ask for less motion, or `None` if they
+/// can't be read.
+pub(crate) fn prefers_reduced_motion() -> Option<bool> {
+    imp::prefers_reduced_motion()
+}
+
+/// How long two clicks can be apart to be a double click, from the mouse settings of the
+/// system.
+pub(crate) fn double_click_time() -> Option<std::time::Duration> {
+    imp::double_click_time()
+}
+
+/// How far the mouse moves while a button is held before it drags, in physical pixels.
+pub(crate) fn drag_threshold() -> Option<f32> {
+    imp::drag_threshold()
+}
+
+/// Copy a BGRA image of `size` physical pixels into the window, for the software renderer.
+#[cfg(feature = "software_renderer")]
+pub(crate) fn present_pixels(handle: &RawWindowHandle, size: [u32; 2], bgra: &mut [u8]) {
+    imp::present_pixels(handle, size, bgra)
+}
+
+/// The position and size of the top-level window, in the coordinates of `monitors`.
+pub(crate) fn window_rect(handle: &RawWindowHandle) -> Option<([i32; 2], [u32; 2])> {
+    imp::window_rect(handle)
+}
+
+/// How long `NativeWindow::is_hidden` answers with the last query, which on X11 is a round
+/// trip to the server every frame otherwise.
+const HIDDEN_INTERVAL: Duration = Duration::from_millis(100);
+
+pub(crate) struct NativeWindow {
+    handle: RawWindowHandle,
+    embedded: bool,
+    /// When the window was last asked whether it is hidden, and the answer.
+    hidden: Cell<Option<(Instant, bool)>>,
+}
+
+impl NativeWindow {
+    pub fn new(handle: RawWindowHandle, embedded: bool) -> Self {
+        Self {
+            handle,
+            embedded,
+            hidden: Cell::new(None),
+        }
+    }
+
+    pub fn handle(&self) -> &RawWindowHandle {
+        &self.handle
+    }
+
+    /// Whether the window was opened with `open_parented`.
+    pub fn is_embedded(&self) -> bool {
+        self.embedded
+    }
+
+    /// Let the alpha channel of the framebuffer show through the window.
+    pub fn set_transparent(&self) {
+        if !self.embedded {
+            imp::set_transparent(&self.handle);
+        }
+    }
+
+    pub fn set_style(&self, style: WindowStyle) {
+        if !self.embedded {
+            imp::set_style(&self.handle, style);
+        }
+    }
+
+    pub fn set_position(&self, position: WindowPosition) {
+        if self.embedded {
+            return;
+        }
+
+        let size = match imp::window_rect(&self.handle) {
+            Some((_, size)) => size,
+            None => return,
+        };
+        let monitors = imp::monitors();
+        let monitor = match position {
+            WindowPosition::AtPoint(point) => return imp::set_position(&self.handle, point),
+            WindowPosition::Centered => monitors
+                .iter()
+                .find(|monitor| monitor.primary)
+                .or_else(|| monitors.first()),
+            WindowPosition::OnMonitor(index) => monitors.get(index),
+        };
+        if let Some(monitor) = monitor {
+            imp::set_position(&self.handle, monitor.center(size));
+        }
+    }
+
+    /// Resize the window to `size` physical pixels.
+    pub fn set_size(&self, size: [u32; 2]) {
+        if !self.embedded {
+            imp::set_size(&self.handle, size);
+        }
+    }
+
+    /// Make a native window of the application a child of this window.
+    pub fn attach_child(&self, child: &RawWindowHandle) -> Option<Child> {
+        imp::attach_child(&self.handle, child)
+    }
+
+    /// Let the window manager move the window with the mouse until the button is released.
+    pub fn begin_drag(&self) {
+        if !self.embedded {
+            imp::begin_drag(&self.handle);
+        }
+    }
+
+    /// Let the window manager resize the window from `edge` until the button is released.
+    pub fn begin_resize(&self, edge: ResizeEdge) {
+        if !self.embedded {
+            imp::begin_resize(&self.handle, edge);
+        }
+    }
+
+    /// Whether the window can't be seen, because it or the host window is hidden, minimized or
+    /// covered where the platform reports it. The answer can be up to `HIDDEN_INTERVAL` old.
+    pub fn is_hidden(&self) -> bool {
+        match self.hidden.get() {
+            Some((asked, hidden)) if asked.elapsed() < HIDDEN_INTERVAL => hidden,
+            _ => {
+                let hidden = imp::is_hidden(&self.handle);
+                self.hidden.set(Some((Instant::now(), hidden)));
+                hidden
+            }
+        }
+    }
+
+    /// The color of the screen under the mouse, anywhere on the desktop.
+    #[cfg(feature = "eyedropper")]
+    pub fn pixel_under_cursor(&self) -> Option<[u8; 3]> {
+        imp::pixel_under_cursor(&self.handle)
+    }
+
+    /// The accent of a dead key that was pressed, on the platforms that leave composing it
+    /// with the next key to the window.
+    pub fn dead_key(
+        &self,
+        code: keyboard_types::Code,
+        modifiers: keyboard_types::Modifiers,
+    ) -> Option<char> {
+        imp::dead_key(&self.handle, code, modifiers)
+    }
+
+    /// Flash the taskbar button, bounce the dock icon or set the urgency hint.
+    pub fn request_attention(&self) {
+        if !self.embedded {
+            imp::request_attention(&self.handle);
+        }
+    }
+}
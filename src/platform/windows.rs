@@ -0,0 +1,463 @@
+use raw_window_handle::RawWindowHandle;
+
+use crate::monitor::Monitor;
+use crate::settings::WindowStyle;
+use crate::ResizeEdge;
+use winapi::shared::minwindef::{BOOL, FALSE, LPARAM, TRUE, WPARAM};
+use winapi::shared::windef::{HDC, HMONITOR, HWND, LPRECT, POINT, RECT};
+use winapi::um::{dwmapi, shellscalingapi, wingdi, winuser};
+
+fn hwnd(handle: &RawWindowHandle) -> Option<HWND> {
+    match handle {
+        RawWindowHandle::Windows(handle) if !handle.hwnd.is_null() => Some(handle.hwnd as HWND),
+        _ => None,
+    }
+}
+
+pub fn share_current_context(shared: *mut std::ffi::c_void) {
+    unsafe {
+        let current = wingdi::wglGetCurrentContext();
+        if !current.is_null() && !shared.is_null() {
+            wingdi::wglShareLists(shared as _, current);
+        }
+    }
+}
+
+pub fn set_transparent(handle: &RawWindowHandle) {
+    if let Some(hwnd) = hwnd(handle) {
+        unsafe {
+            // Blur behind an empty region makes DWM use the alpha channel without any blur.
+            let region = wingdi::CreateRectRgn(0, 0, -1, -1);
+            let blur_behind = dwmapi::DWM_BLURBEHIND {
+                dwFlags: dwmapi::DWM_BB_ENABLE | dwmapi::DWM_BB_BLURREGION,
+                fEnable: TRUE,
+                hRgnBlur: region,
+                fTransitionOnMaximized: FALSE,
+            };
+            dwmapi::DwmEnableBlurBehindWindow(hwnd, &blur_behind);
+            wingdi::DeleteObject(region as _);
+        }
+    }
+}
+
+pub fn set_style(handle: &RawWindowHandle, style: WindowStyle) {
+    if let Some(hwnd) = hwnd(handle) {
+        unsafe {
+            let hwnd = winuser::GetAncestor(hwnd, winuser::GA_ROOT);
+
+            // Tool windows are left out of the taskbar as well.
+            let mut ex_style = winuser::GetWindowLongPtrW(hwnd, winuser::GWL_EXSTYLE) as u32;
+            if style.tool_window || style.skip_taskbar {
+                ex_style = (ex_style | winuser::WS_EX_TOOLWINDOW) & !winuser::WS_EX_APPWINDOW;
+            } else {
+                ex_style = (ex_style | winuser::WS_EX_APPWINDOW) & !winuser::WS_EX_TOOLWINDOW;
+            }
+            winuser::SetWindowLongPtrW(hwnd, winuser::GWL_EXSTYLE, ex_style as _);
+
+            let insert_after = if style.always_on_top {
+                winuser::HWND_TOPMOST
+            } else {
+                winuser::HWND_NOTOPMOST
+            };
+            winuser::SetWindowPos(
+                hwnd,
+                insert_after,
+                0,
+                0,
+                0,
+                0,
+                winuser::SWP_NOMOVE
+                    | winuser::SWP_NOSIZE
+                    | winuser::SWP_NOACTIVATE
+                    | winuser::SWP_FRAMECHANGED,
+            );
+        }
+    }
+}
+
+/// Whether the window or one of its parents is hidden, minimized, or cloaked by DWM, which
+/// it is on another virtual desktop.
+pub fn is_hidden(handle: &RawWindowHandle) -> bool {
+    let hwnd = match hwnd(handle) {
+        Some(hwnd) => hwnd,
+        None => return false,
+    };
+    unsafe {
+        let root = winuser::GetAncestor(hwnd, winuser::GA_ROOT);
+        if winuser::IsWindowVisible(hwnd) == FALSE || winuser::IsIconic(root) != FALSE {
+            return true;
+        }
+
+        let mut cloaked: u32 = 0;
+        let result = dwmapi::DwmGetWindowAttribute(
+            root,
+            dwmapi::DWMWA_CLOAKED,
+            &mut cloaked as *mut u32 as _,
+            std::mem::size_of::<u32>() as u32,
+        );
+        result == 0 && cloaked != 0
+    }
+}
+
+pub fn request_attention(handle: &RawWindowHandle) {
+    if let Some(hwnd) = hwnd(handle) {
+        unsafe {
+            let mut info = winuser::FLASHWINFO {
+                cbSize: std::mem::size_of::<winuser::FLASHWINFO>() as _,
+                hwnd: winuser::GetAncestor(hwnd, winuser::GA_ROOT),
+                dwFlags: winuser::FLASHW_ALL | winuser::FLASHW_TIMERNOFG,
+                uCount: 0,
+                dwTimeout: 0,
+            };
+            winuser::FlashWindowEx(&mut info);
+        }
+    }
+}
+
+/// Start a move or resize as if the mouse was pressed on the frame at `hit`.
+fn begin_frame_drag(handle: &RawWindowHandle, hit: isize) {
+    if let Some(hwnd) = hwnd(handle) {
+        unsafe {
+            let hwnd = winuser::GetAncestor(hwnd, winuser::GA_ROOT);
+            let mut point: POINT = std::mem::zeroed();
+            winuser::GetCursorPos(&mut point);
+            let position = (point.y as u16 as u32) << 16 | point.x as u16 as u32;
+
+            // Posted rather than sent, as the modal move loop would call back into the window
+            // in the middle of the frame.
+            winuser::ReleaseCapture();
+            winuser::PostMessageW(
+                hwnd,
+                winuser::WM_NCLBUTTONDOWN,
+                hit as WPARAM,
+                position as LPARAM,
+            );
+        }
+    }
+}
+
+pub fn begin_drag(handle: &RawWindowHandle) {
+    begin_frame_drag(handle, winuser::HTCAPTION);
+}
+
+pub fn begin_resize(handle: &RawWindowHandle, edge: ResizeEdge) {
+    let hit = match edge {
+        ResizeEdge::Top => winuser::HTTOP,
+        ResizeEdge::Bottom => winuser::HTBOTTOM,
+        ResizeEdge::Left => winuser::HTLEFT,
+        ResizeEdge::Right => winuser::HTRIGHT,
+        ResizeEdge::TopLeft => winuser::HTTOPLEFT,
+        ResizeEdge::TopRight => winuser::HTTOPRIGHT,
+        ResizeEdge::BottomLeft => winuser::HTBOTTOMLEFT,
+        ResizeEdge::BottomRight => winuser::HTBOTTOMRIGHT,
+    };
+    begin_frame_drag(handle, hit);
+}
+
+unsafe extern "system" fn collect_monitor(
+    monitor: HMONITOR,
+    _dc: HDC,
+    _rect: LPRECT,
+    monitors: LPARAM,
+) -> BOOL {
+    let monitors = &mut *(monitors as *mut Vec<Monitor>);
+
+    let mut info: winuser::MONITORINFO = std::mem::zeroed();
+    info.cbSize = std::mem::size_of::<winuser::MONITORINFO>() as _;
+    if winuser::GetMonitorInfoW(monitor, &mut info) != 0 {
+        let (mut dpi_x, mut dpi_y) = (96, 96);
+        shellscalingapi::GetDpiForMonitor(
+            monitor,
+            shellscalingapi::MDT_EFFECTIVE_DPI,
+            &mut dpi_x,
+            &mut dpi_y,
+        );
+
+        let rect = info.rcMonitor;
+        monitors.push(Monitor {
+            position: [rect.left, rect.top],
+            size: [
+                (rect.right - rect.left) as u32,
+                (rect.bottom - rect.top) as u32,
+            ],
+            scale: dpi_x as f64 / 96.0,
+            primary: info.dwFlags & winuser::MONITORINFOF_PRIMARY != 0,
+        });
+    }
+
+    TRUE
+}
+
+pub fn monitors() -> Vec<Monitor> {
+    let mut monitors: Vec<Monitor> = Vec::new();
+    unsafe {
+        winuser::EnumDisplayMonitors(
+            std::ptr::null_mut(),
+            std::ptr::null(),
+            Some(collect_monitor),
+            &mut monitors as *mut Vec<Monitor> as LPARAM,
+        );
+    }
+    monitors
+}
+
+pub fn window_rect(handle: &RawWindowHandle) -> Option<([i32; 2], [u32; 2])> {
+    let hwnd = hwnd(handle)?;
+    unsafe {
+        let mut rect: RECT = std::mem::zeroed();
+        if winuser::GetWindowRect(winuser::GetAncestor(hwnd, winuser::GA_ROOT), &mut rect) == 0 {
+            return None;
+        }
+        Some((
+            [rect.left, rect.top],
+            [
+                (rect.right - rect.left) as u32,
+                (rect.bottom - rect.top) as u32,
+            ],
+        ))
+    }
+}
+
+pub fn set_position(handle: &RawWindowHandle, position: [i32; 2]) {
+    if let Some(hwnd) = hwnd(handle) {
+        unsafe {
+            winuser::SetWindowPos(
+                winuser::GetAncestor(hwnd, winuser::GA_ROOT),
+                std::ptr::null_mut(),
+                position[0],
+                position[1],
+                0,
+                0,
+                winuser::SWP_NOSIZE | winuser::SWP_NOZORDER | winuser::SWP_NOACTIVATE,
+            );
+        }
+    }
+}
+
+/// Resize the top-level window so its client area has `size` physical pixels.
+pub fn set_size(handle: &RawWindowHandle, size: [u32; 2]) {
+    if let Some(hwnd) = hwnd(handle) {
+        unsafe {
+            let root = winuser::GetAncestor(hwnd, winuser::GA_ROOT);
+            let mut window: RECT = std::mem::zeroed();
+            let mut client: RECT = std::mem::zeroed();
+            if winuser::GetWindowRect(root, &mut window) == 0
+                || winuser::GetClientRect(hwnd, &mut client) == 0
+            {
+                return;
+            }
+
+            // Keep the frame around the client area.
+            let frame_width = (window.right - window.left) - (client.right - client.left);
+            let frame_height = (window.bottom - window.top) - (client.bottom - client.top);
+            winuser::SetWindowPos(
+                root,
+                std::ptr::null_mut(),
+                0,
+                0,
+                size[0] as i32 + frame_width,
+                size[1] as i32 + frame_height,
+                winuser::SWP_NOMOVE | winuser::SWP_NOZORDER | winuser::SWP_NOACTIVATE,
+            );
+        }
+    }
+}
+
+/// A native window of the application shown inside the window.
+pub struct Child {
+    hwnd: HWND,
+}
+
+pub fn attach_child(parent: &RawWindowHandle, child: &RawWindowHandle) -> Option<Child> {
+    let parent = hwnd(parent)?;
+    let child = hwnd(child)?;
+    unsafe {
+        // OpenGL would draw over the child otherwise.
+        let parent_style = winuser::GetWindowLongPtrW(parent, winuser::GWL_STYLE) as u32;
+        winuser::SetWindowLongPtrW(
+            parent,
+            winuser::GWL_STYLE,
+            (parent_style | winuser::WS_CLIPCHILDREN) as _,
+        );
+
+        let style = winuser::GetWindowLongPtrW(child, winuser::GWL_STYLE) as u32;
+        let style = (style & !winuser::WS_POPUP) | winuser::WS_CHILD | winuser::WS_CLIPSIBLINGS;
+        winuser::SetWindowLongPtrW(child, winuser::GWL_STYLE, style as _);
+        winuser::SetParent(child, parent);
+    }
+    Some(Child { hwnd: child })
+}
+
+impl Child {
+    /// Place the child at `bounds` and only show the part in `visible`, both as x, y, width
+    /// and height in physical pixels of the parent.
+    pub fn set_bounds(&self, bounds: [i32; 4], visible: [i32; 4]) {
+        unsafe {
+            winuser::SetWindowPos(
+                self.hwnd,
+                winuser::HWND_TOP,
+                bounds[0],
+                bounds[1],
+                bounds[2],
+                bounds[3],
+                winuser::SWP_NOACTIVATE | winuser::SWP_SHOWWINDOW,
+            );
+
+            let left = visible[0] - bounds[0];
+            let top = visible[1] - bounds[1];
+            let region = wingdi::CreateRectRgn(left, top, left + visible[2], top + visible[3]);
+            // The window owns the region from now on.
+            winuser::SetWindowRgn(self.hwnd, region, TRUE);
+        }
+    }
+
+    pub fn hide(&self) {
+        unsafe {
+            winuser::ShowWindow(self.hwnd, winuser::SW_HIDE);
+        }
+    }
+
+    pub fn detach(self) {
+        self.hide();
+        unsafe {
+            winuser::SetParent(self.hwnd, std::ptr::null_mut());
+        }
+    }
+}
+
+/// The color of the screen under the mouse.
+#[cfg(feature = "eyedropper")]
+pub fn pixel_under_cursor(_handle: &RawWindowHandle) -> Option<[u8; 3]> {
+    unsafe {
+        let mut point: POINT = std::mem::zeroed();
+        if winuser::GetCursorPos(&mut point) == FALSE {
+            return None;
+        }
+        let screen = winuser::GetDC(std::ptr::null_mut());
+        if screen.is_null() {
+            return None;
+        }
+        let color = wingdi::GetPixel(screen, point.x, point.y);
+        winuser::ReleaseDC(std::ptr::null_mut(), screen);
+
+        if color == wingdi::CLR_INVALID {
+            return None;
+        }
+        Some([
+            wingdi::GetRValue(color),
+            wingdi::GetGValue(color),
+            wingdi::GetBValue(color),
+        ])
+    }
+}
+
+/// The keyboard events already have the characters that dead keys compose.
+pub fn dead_key(
+    _handle: &RawWindowHandle,
+    _code: keyboard_types::Code,
+    _modifiers: keyboard_types::Modifiers,
+) -> Option<char> {
+    None
+}
+
+/// The delay and interval of the key repeat in the keyboard settings.
+pub fn key_repeat() -> Option<(std::time::Duration, std::time::Duration)> {
+    let (mut delay, mut speed): (u32, u32) = (0, 0);
+    unsafe {
+        if winuser::SystemParametersInfoW(
+            winuser::SPI_GETKEYBOARDDELAY,
+            0,
+            &mut delay as *mut u32 as *mut _,
+            0,
+        ) == FALSE
+            || winuser::SystemParametersInfoW(
+                winuser::SPI_GETKEYBOARDSPEED,
+                0,
+                &mut speed as *mut u32 as *mut _,
+                0,
+            ) == FALSE
+        {
+            return None;
+        }
+    }
+
+    // The delay goes from 250 ms to 1 s, the speed from about 2.5 to 30 repeats a second.
+    let delay = std::time::Duration::from_millis(250 * (delay.min(3) as u64 + 1));
+    let rate = 2.5 + speed.min(31) as f64 * 27.5 / 31.0;
+    Some((delay, std::time::Duration::from_secs_f64(1.0 / rate)))
+}
+
+pub fn prefers_reduced_motion() -> Option<bool> {
+    let mut animations: BOOL = TRUE;
+    unsafe {
+        if winuser::SystemParametersInfoW(
+            winuser::SPI_GETCLIENTAREAANIMATION,
+            0,
+            &mut animations as *mut BOOL as *mut _,
+            0,
+        ) == FALSE
+        {
+            return None;
+        }
+    }
+    Some(animations == FALSE)
+}
+
+pub fn double_click_time() -> Option<std::time::Duration> {
+    let millis = unsafe { winuser::GetDoubleClickTime() };
+    if millis == 0 {
+        return None;
+    }
+    Some(std::time::Duration::from_millis(millis as u64))
+}
+
+pub fn drag_threshold() -> Option<f32> {
+    let (x, y) = unsafe {
+        (
+            winuser::GetSystemMetrics(winuser::SM_CXDRAG),
+            winuser::GetSystemMetrics(winuser::SM_CYDRAG),
+        )
+    };
+    // The metrics are the size of the rectangle the mouse can move in around the press.
+    let threshold = x.max(y) as f32 * 0.5;
+    Some(threshold).filter(|threshold| *threshold > 0.0)
+}
+
+/// Copy a top-down BGRA image into the window with GDI.
+#[cfg(feature = "software_renderer")]
+pub fn present_pixels(handle: &RawWindowHandle, size: [u32; 2], bgra: &mut [u8]) {
+    let hwnd = match hwnd(handle) {
+        Some(hwnd) => hwnd,
+        None => return,
+    };
+    unsafe {
+        let mut info: wingdi::BITMAPINFO = std::mem::zeroed();
+        info.bmiHeader.biSize = std::mem::size_of::<wingdi::BITMAPINFOHEADER>() as u32;
+        info.bmiHeader.biWidth = size[0] as i32;
+        // A negative height starts at the top row.
+        info.bmiHeader.biHeight = -(size[1] as i32);
+        info.bmiHeader.biPlanes = 1;
+        info.bmiHeader.biBitCount = 32;
+        info.bmiHeader.biCompression = wingdi::BI_RGB;
+
+        let dc = winuser::GetDC(hwnd);
+        if dc.is_null() {
+            return;
+        }
+        wingdi::SetDIBitsToDevice(
+            dc,
+            0,
+            0,
+            size[0],
+            size[1],
+            0,
+            0,
+            0,
+            size[1],
+            bgra.as_ptr() as *const _,
+            &info,
+            wingdi::DIB_RGB_COLORS,
+        );
+        winuser::ReleaseDC(hwnd, dc);
+    }
+}
@@ -0,0 +1,882 @@
+use std::ffi::CString;
+use std::os::raw::c_long;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use std::{ptr, slice};
+
+use raw_window_handle::RawWindowHandle;
+use x11::{xinerama, xlib};
+
+use crate::monitor::Monitor;
+use crate::settings::WindowStyle;
+use crate::ResizeEdge;
+
+fn window(handle: &RawWindowHandle) -> Option<(*mut xlib::Display, xlib::Window)> {
+    match handle {
+        RawWindowHandle::Xlib(handle) if !handle.display.is_null() && handle.window != 0 => {
+            Some((handle.display as *mut xlib::Display, handle.window))
+        }
+        _ => None,
+    }
+}
+
+/// The window would need a visual with an alpha channel, which baseview doesn't choose, so
+/// the background stays opaque.
+pub fn set_transparent(_handle: &RawWindowHandle) {}
+
+unsafe fn atom(display: *mut xlib::Display, name: &str) -> xlib::Atom {
+    let name = CString::new(name).unwrap();
+    xlib::XInternAtom(display, name.as_ptr(), xlib::False)
+}
+
+/// Ask the window manager to add or remove a `_NET_WM_STATE` of a mapped window.
+unsafe fn set_state(display: *mut xlib::Display, window: xlib::Window, state: &str, on: bool) {
+    let mut event: xlib::XEvent = std::mem::zeroed();
+    event.client_message = xlib::XClientMessageEvent {
+        type_: xlib::ClientMessage,
+        serial: 0,
+        send_event: xlib::True,
+        display,
+        window,
+        message_type: atom(display, "_NET_WM_STATE"),
+        format: 32,
+        data: xlib::ClientMessageData::new(),
+    };
+    event.client_message.data.set_long(0, on as _);
+    event
+        .client_message
+        .data
+        .set_long(1, atom(display, state) as _);
+    event.client_message.data.set_long(3, 1);
+
+    xlib::XSendEvent(
+        display,
+        xlib::XDefaultRootWindow(display),
+        xlib::False,
+        xlib::SubstructureRedirectMask | xlib::SubstructureNotifyMask,
+        &mut event,
+    );
+}
+
+pub fn set_style(handle: &RawWindowHandle, style: WindowStyle) {
+    if let Some((display, window)) = window(handle) {
+        unsafe {
+            set_state(display, window, "_NET_WM_STATE_ABOVE", style.always_on_top);
+            set_state(
+                display,
+                window,
+                "_NET_WM_STATE_SKIP_TASKBAR",
+                style.skip_taskbar,
+            );
+
+            let window_type = if style.tool_window {
+                atom(display, "_NET_WM_WINDOW_TYPE_UTILITY")
+            } else {
+                atom(display, "_NET_WM_WINDOW_TYPE_NORMAL")
+            };
+            xlib::XChangeProperty(
+                display,
+                window,
+                atom(display, "_NET_WM_WINDOW_TYPE"),
+                xlib::XA_ATOM,
+                32,
+                xlib::PropModeReplace,
+                &window_type as *const xlib::Atom as *const u8,
+                1,
+            );
+            xlib::XFlush(display);
+        }
+    }
+}
+
+/// Whether the window or one of its parents is unmapped, which includes minimized windows.
+/// Being covered by other windows is only reported through events that baseview handles.
+pub fn is_hidden(handle: &RawWindowHandle) -> bool {
+    let (display, window) = match window(handle) {
+        Some(window) => window,
+        None => return false,
+    };
+    unsafe {
+        let mut attributes: xlib::XWindowAttributes = std::mem::zeroed();
+        if xlib::XGetWindowAttributes(display, window, &mut attributes) == 0 {
+            return false;
+        }
+        attributes.map_state != xlib::IsViewable
+    }
+}
+
+pub fn request_attention(handle: &RawWindowHandle) {
+    if let Some((display, window)) = window(handle) {
+        unsafe {
+            let mut hints = xlib::XGetWMHints(display, window);
+            if hints.is_null() {
+                hints = xlib::XAllocWMHints();
+            }
+            if hints.is_null() {
+                return;
+            }
+
+            (*hints).flags |= xlib::XUrgencyHint;
+            xlib::XSetWMHints(display, window, hints);
+            xlib::XFree(hints as *mut _);
+            xlib::XFlush(display);
+        }
+    }
+}
+
+/// The `_NET_WM_MOVERESIZE` direction of a move.
+const MOVE: i64 = 8;
+
+/// Ask the window manager to move or resize the window with the pointer.
+fn move_resize(handle: &RawWindowHandle, direction: i64) {
+    if let Some((display, window)) = window(handle) {
+        unsafe {
+            let root = xlib::XDefaultRootWindow(display);
+            let (mut root_return, mut child) = (0, 0);
+            let (mut x, mut y, mut window_x, mut window_y) = (0, 0, 0, 0);
+            let mut mask = 0;
+            xlib::XQueryPointer(
+                display,
+                window,
+                &mut root_return,
+                &mut child,
+                &mut x,
+                &mut y,
+                &mut window_x,
+                &mut window_y,
+                &mut mask,
+            );
+
+            // The window manager can only grab the pointer once the press is let go of.
+            xlib::XUngrabPointer(display, xlib::CurrentTime);
+
+            let mut event: xlib::XEvent = std::mem::zeroed();
+            event.client_message = xlib::XClientMessageEvent {
+                type_: xlib::ClientMessage,
+                serial: 0,
+                send_event: xlib::True,
+                display,
+                window,
+                message_type: atom(display, "_NET_WM_MOVERESIZE"),
+                format: 32,
+                data: xlib::ClientMessageData::new(),
+            };
+            event.client_message.data.set_long(0, x as _);
+            event.client_message.data.set_long(1, y as _);
+            event.client_message.data.set_long(2, direction as _);
+            event.client_message.data.set_long(3, xlib::Button1 as _);
+            event.client_message.data.set_long(4, 1);
+
+            xlib::XSendEvent(
+                display,
+                root,
+                xlib::False,
+                xlib::SubstructureRedirectMask | xlib::SubstructureNotifyMask,
+                &mut event,
+            );
+            xlib::XFlush(display);
+        }
+    }
+}
+
+pub fn begin_drag(handle: &RawWindowHandle) {
+    move_resize(handle, MOVE);
+}
+
+pub fn begin_resize(handle: &RawWindowHandle, edge: ResizeEdge) {
+    let direction = match edge {
+        ResizeEdge::TopLeft => 0,
+        ResizeEdge::Top => 1,
+        ResizeEdge::TopRight => 2,
+        ResizeEdge::Right => 3,
+        ResizeEdge::BottomRight => 4,
+        ResizeEdge::Bottom => 5,
+        ResizeEdge::BottomLeft => 6,
+        ResizeEdge::Left => 7,
+    };
+    move_resize(handle, direction);
+}
+
+/// The scale from the `Xft.dpi` resource, which desktops set for the whole screen.
+unsafe fn xft_scale(display: *mut xlib::Display) -> f64 {
+    let resources = xlib::XResourceManagerString(display);
+    if resources.is_null() {
+        return 1.0;
+    }
+
+    std::ffi::CStr::from_ptr(resources)
+        .to_string_lossy()
+        .lines()
+        .find_map(|line| line.strip_prefix("Xft.dpi:"))
+        .and_then(|dpi| dpi.trim().parse::<f64>().ok())
+        .map_or(1.0, |dpi| dpi / 96.0)
+}
+
+/// The Xinerama screens, with the primary output first when RandR is in use. X11 has one
+/// DPI setting, so every monitor gets the same scale.
+pub fn monitors() -> Vec<Monitor> {
+    let mut monitors = Vec::new();
+    unsafe {
+        let display = xlib::XOpenDisplay(std::ptr::null());
+        if display.is_null() {
+            return monitors;
+        }
+
+        let scale = xft_scale(display);
+        let mut count = 0;
+        let screens = xinerama::XineramaQueryScreens(display, &mut count);
+        if screens.is_null() {
+            // Without Xinerama the whole screen is one monitor.
+            let screen = xlib::XDefaultScreen(display);
+            monitors.push(Monitor {
+                position: [0, 0],
+                size: [
+                    xlib::XDisplayWidth(display, screen) as u32,
+                    xlib::XDisplayHeight(display, screen) as u32,
+                ],
+                scale,
+                primary: true,
+            });
+        } else {
+            for (i, screen) in std::slice::from_raw_parts(screens, count as usize)
+                .iter()
+                .enumerate()
+            {
+                monitors.push(Monitor {
+                    position: [screen.x_org as i32, screen.y_org as i32],
+                    size: [screen.width as u32, screen.height as u32],
+                    scale,
+                    primary: i == 0,
+                });
+            }
+            xlib::XFree(screens as *mut _);
+        }
+
+        xlib::XCloseDisplay(display);
+    }
+    monitors
+}
+
+pub fn window_rect(handle: &RawWindowHandle) -> Option<([i32; 2], [u32; 2])> {
+    let (display, window) = window(handle)?;
+    unsafe {
+        let mut attributes: xlib::XWindowAttributes = std::mem::zeroed();
+        if xlib::XGetWindowAttributes(display, window, &mut attributes) == 0 {
+            return None;
+        }
+
+        let (mut x, mut y, mut child) = (0, 0, 0);
+        xlib::XTranslateCoordinates(
+            display,
+            window,
+            xlib::XDefaultRootWindow(display),
+            0,
+            0,
+            &mut x,
+            &mut y,
+            &mut child,
+        );
+        Some(([x, y], [attributes.width as u32, attributes.height as u32]))
+    }
+}
+
+pub fn set_position(handle: &RawWindowHandle, position: [i32; 2]) {
+    if let Some((display, window)) = window(handle) {
+        unsafe {
+            xlib::XMoveWindow(display, window, position[0], position[1]);
+            xlib::XFlush(display);
+        }
+    }
+}
+
+pub fn set_size(handle: &RawWindowHandle, size: [u32; 2]) {
+    if let Some((display, window)) = window(handle) {
+        unsafe {
+            xlib::XResizeWindow(display, window, size[0].max(1), size[1].max(1));
+            xlib::XFlush(display);
+        }
+    }
+}
+
+/// A native window of the application shown inside the window, in a container window that
+/// clips it.
+pub struct Child {
+    display: *mut xlib::Display,
+    container: xlib::Window,
+    window: xlib::Window,
+}
+
+pub fn attach_child(parent: &RawWindowHandle, child: &RawWindowHandle) -> Option<Child> {
+    let (display, parent) = window(parent)?;
+    let window = match child {
+        RawWindowHandle::Xlib(handle) if handle.window != 0 => handle.window,
+        RawWindowHandle::Xcb(handle) if handle.window != 0 => handle.window as xlib::Window,
+        _ => return None,
+    };
+
+    unsafe {
+        let container = xlib::XCreateSimpleWindow(display, parent, 0, 0, 1, 1, 0, 0, 0);
+        xlib::XReparentWindow(display, window, container, 0, 0);
+        xlib::XMapWindow(display, window);
+        xlib::XFlush(display);
+
+        Some(Child {
+            display,
+            container,
+            window,
+        })
+    }
+}
+
+impl Child {
+    /// Place the child at `bounds` and only show the part in `visible`, both as x, y, width
+    /// and height in physical pixels of the parent.
+    pub fn set_bounds(&self, bounds: [i32; 4], visible: [i32; 4]) {
+        unsafe {
+            xlib::XMoveResizeWindow(
+                self.display,
+                self.container,
+                visible[0],
+                visible[1],
+                visible[2] as _,
+                visible[3] as _,
+            );
+            xlib::XMoveResizeWindow(
+                self.display,
+                self.window,
+                bounds[0] - visible[0],
+                bounds[1] - visible[1],
+                bounds[2] as _,
+                bounds[3] as _,
+            );
+            xlib::XMapRaised(self.display, self.container);
+            xlib::XFlush(self.display);
+        }
+    }
+
+    pub fn hide(&self) {
+        unsafe {
+            xlib::XUnmapWindow(self.display, self.container);
+            xlib::XFlush(self.display);
+        }
+    }
+
+    pub fn detach(self) {
+        unsafe {
+            xlib::XUnmapWindow(self.display, self.window);
+            let root = xlib::XDefaultRootWindow(self.display);
+            xlib::XReparentWindow(self.display, self.window, root, 0, 0);
+            xlib::XDestroyWindow(self.display, self.container);
+            xlib::XFlush(self.display);
+        }
+    }
+}
+
+/// How long a paste waits for the owner of the selection to send it.
+const SELECTION_TIMEOUT: Duration = Duration::from_millis(200);
+/// How often the selection thread looks for requests while there are no commands.
+const SELECTION_POLL: Duration = Duration::from_millis(10);
+
+enum SelectionCommand {
+    Set(String),
+    Get(Sender<Option<String>>),
+}
+
+/// The `PRIMARY` selection, served on a thread with a connection and window of its own, since
+/// the events of the window are read by baseview. Selections that are sent in increments
+/// aren't read.
+pub struct PrimarySelection {
+    commands: Sender<SelectionCommand>,
+}
+
+struct SelectionAtoms {
+    primary: xlib::Atom,
+    targets: xlib::Atom,
+    utf8: xlib::Atom,
+    /// The property of the window that pasted text is put in.
+    property: xlib::Atom,
+}
+
+impl PrimarySelection {
+    pub fn new() -> Option<Self> {
+        let (commands, receiver) = mpsc::channel();
+        let (opened, open) = mpsc::channel();
+
+        thread::spawn(move || unsafe {
+            let display = xlib::XOpenDisplay(ptr::null());
+            if display.is_null() {
+                let _ = opened.send(false);
+                return;
+            }
+            let root = xlib::XDefaultRootWindow(display);
+            let window = xlib::XCreateSimpleWindow(display, root, 0, 0, 1, 1, 0, 0, 0);
+            let _ = opened.send(true);
+
+            serve_selection(display, window, receiver);
+
+            xlib::XDestroyWindow(display, window);
+            xlib::XCloseDisplay(display);
+        });
+
+        match open.recv() {
+            Ok(true) => Some(Self { commands }),
+            _ => None,
+        }
+    }
+
+    /// Ask for the text of the selection, from this window or another application. It
+    /// arrives on the receiver once the owner sent it, which is dropped if that takes longer
+    /// than `SELECTION_TIMEOUT`.
+    pub fn request(&self) -> Option<Receiver<Option<String>>> {
+        let (reply, text) = mpsc::channel();
+        self.commands.send(SelectionCommand::Get(reply)).ok()?;
+        Some(text)
+    }
+
+    /// Own the selection with `text`.
+    pub fn set(&self, text: String) {
+        let _ = self.commands.send(SelectionCommand::Set(text));
+    }
+}
+
+/// Answer the requests of other applications and run the commands of the window until it
+/// drops the selection.
+unsafe fn serve_selection(
+    display: *mut xlib::Display,
+    window: xlib::Window,
+    commands: Receiver<SelectionCommand>,
+) {
+    let atoms = SelectionAtoms {
+        primary: xlib::XA_PRIMARY,
+        targets: atom(display, "TARGETS"),
+        utf8: atom(display, "UTF8_STRING"),
+        property: atom(display, "IMGUI_BASEVIEW_SELECTION"),
+    };
+    let mut text: Option<String> = None;
+    // A paste that waits for the owner to answer. Dropping the sender answers it with nothing.
+    let mut paste: Option<(Sender<Option<String>>, Instant)> = None;
+
+    loop {
+        while xlib::XPending(display) > 0 {
+            let mut event: xlib::XEvent = std::mem::zeroed();
+            xlib::XNextEvent(display, &mut event);
+            match event.get_type() {
+                xlib::SelectionRequest => {
+                    answer_selection(display, &atoms, &event.selection_request, text.as_deref())
+                }
+                xlib::SelectionClear => text = None,
+                xlib::SelectionNotify => {
+                    if let Some((reply, _)) = paste.take() {
+                        let _ =
+                            reply.send(read_selection(display, window, &atoms, &event.selection));
+                    }
+                }
+                _ => {}
+            }
+        }
+        if paste
+            .as_ref()
+            .map_or(false, |(_, since)| since.elapsed() > SELECTION_TIMEOUT)
+        {
+            paste = None;
+        }
+
+        match commands.recv_timeout(SELECTION_POLL) {
+            Ok(SelectionCommand::Set(new_text)) => {
+                xlib::XSetSelectionOwner(display, atoms.primary, window, xlib::CurrentTime);
+                let owned = xlib::XGetSelectionOwner(display, atoms.primary) == window;
+                text = if owned { Some(new_text) } else { None };
+                xlib::XFlush(display);
+            }
+            Ok(SelectionCommand::Get(reply)) => match &text {
+                Some(text) => {
+                    let _ = reply.send(Some(text.clone()));
+                }
+                None => {
+                    xlib::XConvertSelection(
+                        display,
+                        atoms.primary,
+                        atoms.utf8,
+                        atoms.property,
+                        window,
+                        xlib::CurrentTime,
+                    );
+                    xlib::XFlush(display);
+                    paste = Some((reply, Instant::now()));
+                }
+            },
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+    }
+}
+
+unsafe fn answer_selection(
+    display: *mut xlib::Display,
+    atoms: &SelectionAtoms,
+    request: &xlib::XSelectionRequestEvent,
+    text: Option<&str>,
+) {
+    // Old clients leave the property out and expect the target to be used instead.
+    let mut property = if request.property == 0 {
+        request.target
+    } else {
+        request.property
+    };
+
+    match text {
+        Some(_) if request.target == atoms.targets => {
+            let targets = [atoms.targets, atoms.utf8, xlib::XA_STRING];
+            xlib::XChangeProperty(
+                display,
+                request.requestor,
+                property,
+                xlib::XA_ATOM,
+                32,
+                xlib::PropModeReplace,
+                targets.as_ptr() as *const u8,
+                targets.len() as i32,
+            );
+        }
+        Some(text) if request.target == atoms.utf8 || request.target == xlib::XA_STRING => {
+            xlib::XChangeProperty(
+                display,
+                request.requestor,
+                property,
+                request.target,
+                8,
+                xlib::PropModeReplace,
+                text.as_ptr(),
+                text.len() as i32,
+            );
+        }
+        // Refused.
+        _ => property = 0,
+    }
+
+    let mut event = xlib::XEvent::from(xlib::XSelectionEvent {
+        type_: xlib::SelectionNotify,
+        serial: 0,
+        send_event: xlib::True,
+        display,
+        requestor: request.requestor,
+        selection: request.selection,
+        target: request.target,
+        property,
+        time: request.time,
+    });
+    xlib::XSendEvent(display, request.requestor, xlib::False, 0, &mut event);
+    xlib::XFlush(display);
+}
+
+unsafe fn read_selection(
+    display: *mut xlib::Display,
+    window: xlib::Window,
+    atoms: &SelectionAtoms,
+    event: &xlib::XSelectionEvent,
+) -> Option<String> {
+    // The owner refused, or there is none.
+    if event.property == 0 {
+        return None;
+    }
+
+    let mut kind = 0;
+    let mut format = 0;
+    let mut length = 0;
+    let mut remaining = 0;
+    let mut data = ptr::null_mut();
+    xlib::XGetWindowProperty(
+        display,
+        window,
+        atoms.property,
+        0,
+        c_long::MAX / 4,
+        xlib::True,
+        xlib::AnyPropertyType as xlib::Atom,
+        &mut kind,
+        &mut format,
+        &mut length,
+        &mut remaining,
+        &mut data,
+    );
+    if data.is_null() {
+        return None;
+    }
+
+    let text = if format == 8 {
+        let bytes = slice::from_raw_parts(data, length as usize);
+        Some(String::from_utf8_lossy(bytes).into_owned())
+    } else {
+        None
+    };
+    xlib::XFree(data as *mut _);
+    text
+}
+
+/// The color of the screen under the mouse, from the root window.
+#[cfg(feature = "eyedropper")]
+pub fn pixel_under_cursor(handle: &RawWindowHandle) -> Option<[u8; 3]> {
+    let (display, _) = window(handle)?;
+    unsafe {
+        let root = xlib::XDefaultRootWindow(display);
+        let (mut root_return, mut child) = (0, 0);
+        let (mut x, mut y, mut window_x, mut window_y) = (0, 0, 0, 0);
+        let mut mask = 0;
+        if xlib::XQueryPointer(
+            display,
+            root,
+            &mut root_return,
+            &mut child,
+            &mut x,
+            &mut y,
+            &mut window_x,
+            &mut window_y,
+            &mut mask,
+        ) == xlib::False
+        {
+            return None;
+        }
+
+        let image = xlib::XGetImage(display, root, x, y, 1, 1, xlib::XAllPlanes(), xlib::ZPixmap);
+        if image.is_null() {
+            return None;
+        }
+        let pixel = (*image)
+            .funcs
+            .get_pixel
+            .map(|get_pixel| get_pixel(image, 0, 0));
+        let masks = [(*image).red_mask, (*image).green_mask, (*image).blue_mask];
+        if let Some(destroy_image) = (*image).funcs.destroy_image {
+            destroy_image(image);
+        }
+
+        let pixel = pixel?;
+        let channel = |mask: std::os::raw::c_ulong| {
+            if mask == 0 {
+                return 0;
+            }
+            let value = (pixel & mask) >> mask.trailing_zeros();
+            let max = mask >> mask.trailing_zeros();
+            (value * 255 / max) as u8
+        };
+        Some([channel(masks[0]), channel(masks[1]), channel(masks[2])])
+    }
+}
+
+/// `XkbUseCoreKbd`.
+const CORE_KEYBOARD: std::os::raw::c_uint = 0x0100;
+
+/// The X keycodes of the keys that have dead keys on common layouts.
+fn keycode(code: keyboard_types::Code) -> Option<xlib::KeyCode> {
+    use keyboard_types::Code;
+
+    Some(match code {
+        Code::Digit1 => 10,
+        Code::Digit2 => 11,
+        Code::Digit3 => 12,
+        Code::Digit4 => 13,
+        Code::Digit5 => 14,
+        Code::Digit6 => 15,
+        Code::Digit7 => 16,
+        Code::Digit8 => 17,
+        Code::Digit9 => 18,
+        Code::Digit0 => 19,
+        Code::Minus => 20,
+        Code::Equal => 21,
+        Code::BracketLeft => 34,
+        Code::BracketRight => 35,
+        Code::Semicolon => 47,
+        Code::Quote => 48,
+        Code::Backquote => 49,
+        Code::Backslash => 51,
+        Code::Comma => 59,
+        Code::Period => 60,
+        Code::Slash => 61,
+        Code::IntlBackslash => 94,
+        _ => return None,
+    })
+}
+
+/// The accent of a dead key, as the character it types on its own. baseview reports dead
+/// keys without saying which one, so the keysym is looked up in the current layout group.
+pub fn dead_key(
+    handle: &RawWindowHandle,
+    code: keyboard_types::Code,
+    modifiers: keyboard_types::Modifiers,
+) -> Option<char> {
+    use keyboard_types::Modifiers;
+
+    let (display, _) = window(handle)?;
+    let keycode = keycode(code)?;
+    let level = modifiers.contains(Modifiers::SHIFT) as u32
+        + 2 * modifiers.contains(Modifiers::ALT_GRAPH) as u32;
+    let keysym = unsafe {
+        let mut state: xlib::XkbStateRec = std::mem::zeroed();
+        xlib::XkbGetState(display, CORE_KEYBOARD, &mut state);
+        xlib::XkbKeycodeToKeysym(display, keycode, state.group as _, level)
+    };
+
+    Some(match keysym {
+        0xfe50 => '`',
+        0xfe51 => '´',
+        0xfe52 => '^',
+        0xfe53 => '~',
+        0xfe57 => '¨',
+        0xfe58 => '°',
+        0xfe5a => 'ˇ',
+        0xfe5b => '¸',
+        _ => return None,
+    })
+}
+
+/// The delay and interval of the key repeat of the X server.
+pub fn key_repeat() -> Option<(Duration, Duration)> {
+    unsafe {
+        let display = xlib::XOpenDisplay(ptr::null());
+        if display.is_null() {
+            return None;
+        }
+        let (mut delay, mut interval) = (0, 0);
+        let ok = xlib::XkbGetAutoRepeatRate(display, CORE_KEYBOARD, &mut delay, &mut interval);
+        xlib::XCloseDisplay(display);
+
+        if ok == xlib::False || interval == 0 {
+            return None;
+        }
+        Some((
+            Duration::from_millis(delay as u64),
+            Duration::from_millis(interval as u64),
+        ))
+    }
+}
+
+/// A setting of GNOME and the desktops that follow it, for what X11 has no setting for.
+fn gsetting(schema: &str, key: &str) -> Option<String> {
+    let output = std::process::Command::new("gsettings")
+        .args(&["get", schema, key])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // Numbers of some types are written with the type first, like `uint32 400`.
+    let value = String::from_utf8_lossy(&output.stdout);
+    value.split_whitespace().last().map(str::to_string)
+}
+
+/// The GNOME settings the windows use. Every read starts `gsettings`, so they are read once
+/// per process, when the first window opens.
+#[derive(Clone)]
+struct GnomeSettings {
+    enable_animations: Option<String>,
+    double_click: Option<String>,
+    drag_threshold: Option<String>,
+}
+
+impl GnomeSettings {
+    fn get() -> Self {
+        static SETTINGS: Mutex<Option<GnomeSettings>> = Mutex::new(None);
+        let mut settings = SETTINGS.lock().unwrap();
+        settings
+            .get_or_insert_with(|| GnomeSettings {
+                enable_animations: gsetting("org.gnome.desktop.interface", "enable-animations"),
+                double_click: gsetting("org.gnome.desktop.peripherals.mouse", "double-click"),
+                drag_threshold: gsetting("org.gnome.desktop.peripherals.mouse", "drag-threshold"),
+            })
+            .clone()
+    }
+}
+
+pub fn prefers_reduced_motion() -> Option<bool> {
+    match GnomeSettings::get().enable_animations?.as_str() {
+        "true" => Some(false),
+        "false" => Some(true),
+        _ => None,
+    }
+}
+
+/// An X resource that applies to every program, like `*multiClickTime: 400`.
+fn x_resource(option: &str) -> Option<String> {
+    unsafe {
+        let display = xlib::XOpenDisplay(ptr::null());
+        if display.is_null() {
+            return None;
+        }
+        let program = CString::new("imgui-baseview").unwrap();
+        let option = CString::new(option).unwrap();
+        let value = xlib::XGetDefault(display, program.as_ptr(), option.as_ptr());
+        let resource = if value.is_null() {
+            None
+        } else {
+            Some(
+                std::ffi::CStr::from_ptr(value)
+                    .to_string_lossy()
+                    .into_owned(),
+            )
+        };
+        xlib::XCloseDisplay(display);
+        resource
+    }
+}
+
+/// The `multiClickTime` X resource of Xt applications, or the double-click time of GNOME.
+pub fn double_click_time() -> Option<Duration> {
+    let millis: u64 = x_resource("multiClickTime")
+        .and_then(|value| value.trim().parse().ok())
+        .or_else(|| GnomeSettings::get().double_click?.parse().ok())?;
+    Some(Duration::from_millis(millis)).filter(|time| *time > Duration::default())
+}
+
+pub fn drag_threshold() -> Option<f32> {
+    GnomeSettings::get()
+        .drag_threshold?
+        .parse()
+        .ok()
+        .filter(|threshold: &f32| *threshold > 0.0)
+}
+
+/// Copy a BGRA image into the window with `XPutImage`, which matches the 24-bit visuals of
+/// little-endian X servers.
+#[cfg(feature = "software_renderer")]
+pub fn present_pixels(handle: &RawWindowHandle, size: [u32; 2], bgra: &mut [u8]) {
+    let (display, window) = match window(handle) {
+        Some(window) => window,
+        None => return,
+    };
+    unsafe {
+        let screen = xlib::XDefaultScreen(display);
+        let image = xlib::XCreateImage(
+            display,
+            xlib::XDefaultVisual(display, screen),
+            xlib::XDefaultDepth(display, screen) as u32,
+            xlib::ZPixmap,
+            0,
+            bgra.as_mut_ptr() as *mut _,
+            size[0],
+            size[1],
+            32,
+            0,
+        );
+        if image.is_null() {
+            return;
+        }
+        xlib::XPutImage(
+            display,
+            window,
+            xlib::XDefaultGC(display, screen),
+            image,
+            0,
+            0,
+            0,
+            0,
+            size[0],
+            size[1],
+        );
+        // The pixels belong to the renderer, so only the image is freed.
+        (*image).data = ptr::null_mut();
+        xlib::XFree(image as *mut _);
+        xlib::XFlush(display);
+    }
+}
@@ -0,0 +1,429 @@
+//! Record and replay the input of a window.
+//!
+//! A recording captures the baseview events of a window grouped by the frame they arrived
+//! in. Replaying it feeds the events back through the window at the same frames with a fixed
+//! delta time, so host-specific bugs can be reproduced and UIs can be regression tested.
+//! Frames without events aren't stored, the next frame with events counts them instead.
+
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// What to do with the input of a window.
+#[derive(Clone)]
+pub enum EventRecorder {
+    /// Append the events of the window to the recording.
+    Record(Arc<Mutex<Recording>>),
+    /// Ignore the mouse and keyboard input of the window and replay the recording instead.
+    ///
+    /// Once the recording runs out the window continues with live input.
+    Replay {
+        recording: Recording,
+        /// The delta time given to imgui for every replayed frame.
+        delta_time: Duration,
+    },
+}
+
+/// A recorded stream of events.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    pub frames: Vec<RecordedFrame>,
+}
+
+impl Recording {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+/// The events that arrived before a frame.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    /// The time of the frame since the start of the recording.
+    pub time: Duration,
+    /// The frames without events between the previous frame and this one.
+    #[serde(default)]
+    pub skipped: u32,
+    pub events: Vec<RecordedEvent>,
+}
+
+/// A serializable baseview event.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RecordedEvent {
+    CursorMoved {
+        x: f64,
+        y: f64,
+    },
+    ButtonPressed(RecordedButton),
+    ButtonReleased(RecordedButton),
+    WheelLines {
+        x: f32,
+        y: f32,
+    },
+    WheelPixels {
+        x: f32,
+        y: f32,
+    },
+    Key {
+        down: bool,
+        /// The logical key, as formatted by `keyboard_types::Key`.
+        key: String,
+        /// The physical key, as formatted by `keyboard_types::Code`.
+        code: String,
+        modifiers: u32,
+        repeat: bool,
+        is_composing: bool,
+    },
+    /// The window was resized to the given logical size.
+    Resized {
+        width: f64,
+        height: f64,
+        scale: f64,
+    },
+    Focused,
+    Unfocused,
+    WillClose,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RecordedButton {
+    Left,
+    Middle,
+    Right,
+    Back,
+    Forward,
+    Other(u8),
+}
+
+impl RecordedEvent {
+    /// Convert a baseview event. Returns `None` for events that are not recorded.
+    pub fn from_event(event: &baseview::Event) -> Option<Self> {
+        use baseview::{Event, MouseEvent, ScrollDelta, WindowEvent};
+
+        Some(match event {
+            Event::Mouse(event) => match event {
+                MouseEvent::CursorMoved { position } => RecordedEvent::CursorMoved {
+                    x: position.x,
+                    y: position.y,
+                },
+                MouseEvent::ButtonPressed(button) => {
+                    RecordedEvent::ButtonPressed(RecordedButton::from_button(*button))
+                }
+                MouseEvent::ButtonReleased(button) => {
+                    RecordedEvent::ButtonReleased(RecordedButton::from_button(*button))
+                }
+                MouseEvent::WheelScrolled(ScrollDelta::Lines { x, y }) => {
+                    RecordedEvent::WheelLines { x: *x, y: *y }
+                }
+                MouseEvent::WheelScrolled(ScrollDelta::Pixels { x, y }) => {
+                    RecordedEvent::WheelPixels { x: *x, y: *y }
+                }
+                _ => return None,
+            },
+            Event::Keyboard(event) => RecordedEvent::Key {
+                down: event.state == keyboard_types::KeyState::Down,
+                key: event.key.to_string(),
+                code: event.code.to_string(),
+                modifiers: event.modifiers.bits(),
+                repeat: event.repeat,
+                is_composing: event.is_composing,
+            },
+            Event::Window(event) => match event {
+                WindowEvent::Resized(window_info) => {
+                    let size = window_info.logical_size();
+                    RecordedEvent::Resized {
+                        width: size.width,
+                        height: size.height,
+                        scale: window_info.scale(),
+                    }
+                }
+                WindowEvent::Focused => RecordedEvent::Focused,
+                WindowEvent::Unfocused => RecordedEvent::Unfocused,
+                WindowEvent::WillClose => RecordedEvent::WillClose,
+            },
+        })
+    }
+
+    /// Convert back to a baseview event.
+    pub fn to_event(&self) -> baseview::Event {
+        use baseview::{Event, MouseEvent, Point, ScrollDelta, Size, WindowEvent, WindowInfo};
+
+        match self {
+            RecordedEvent::CursorMoved { x, y } => Event::Mouse(MouseEvent::CursorMoved {
+                position: Point::new(*x, *y),
+            }),
+            RecordedEvent::ButtonPressed(button) => {
+                Event::Mouse(MouseEvent::ButtonPressed(button.to_button()))
+            }
+            RecordedEvent::ButtonReleased(button) => {
+                Event::Mouse(MouseEvent::ButtonReleased(button.to_button()))
+            }
+            RecordedEvent::WheelLines { x, y } => {
+                Event::Mouse(MouseEvent::WheelScrolled(ScrollDelta::Lines {
+                    x: *x,
+                    y: *y,
+                }))
+            }
+            RecordedEvent::WheelPixels { x, y } => {
+                Event::Mouse(MouseEvent::WheelScrolled(ScrollDelta::Pixels {
+                    x: *x,
+                    y: *y,
+                }))
+            }
+            RecordedEvent::Key {
+                down,
+                key,
+                code,
+                modifiers,
+                repeat,
+                is_composing,
+            } => Event::Keyboard(keyboard_types::KeyboardEvent {
+                state: if *down {
+                    keyboard_types::KeyState::Down
+                } else {
+                    keyboard_types::KeyState::Up
+                },
+                key: keyboard_types::Key::from_str(key)
+                    .unwrap_or(keyboard_types::Key::Unidentified),
+                code: keyboard_types::Code::from_str(code)
+                    .unwrap_or(keyboard_types::Code::Unidentified),
+                location: keyboard_types::Location::Standard,
+                modifiers: keyboard_types::Modifiers::from_bits_truncate(*modifiers),
+                repeat: *repeat,
+                is_composing: *is_composing,
+            }),
+            RecordedEvent::Resized {
+                width,
+                height,
+                scale,
+            } => Event::Window(WindowEvent::Resized(WindowInfo::from_logical_size(
+                Size::new(*width, *height),
+                *scale,
+            ))),
+            RecordedEvent::Focused => Event::Window(WindowEvent::Focused),
+            RecordedEvent::Unfocused => Event::Window(WindowEvent::Unfocused),
+            RecordedEvent::WillClose => Event::Window(WindowEvent::WillClose),
+        }
+    }
+
+    fn is_input(&self) -> bool {
+        !matches!(
+            self,
+            RecordedEvent::Resized { .. }
+                | RecordedEvent::Focused
+                | RecordedEvent::Unfocused
+                | RecordedEvent::WillClose
+        )
+    }
+}
+
+impl RecordedButton {
+    fn from_button(button: baseview::MouseButton) -> Self {
+        match button {
+            baseview::MouseButton::Left => RecordedButton::Left,
+            baseview::MouseButton::Middle => RecordedButton::Middle,
+            baseview::MouseButton::Right => RecordedButton::Right,
+            baseview::MouseButton::Back => RecordedButton::Back,
+            baseview::MouseButton::Forward => RecordedButton::Forward,
+            baseview::MouseButton::Other(n) => RecordedButton::Other(n),
+        }
+    }
+
+    fn to_button(self) -> baseview::MouseButton {
+        match self {
+            RecordedButton::Left => baseview::MouseButton::Left,
+            RecordedButton::Middle => baseview::MouseButton::Middle,
+            RecordedButton::Right => baseview::MouseButton::Right,
+            RecordedButton::Back => baseview::MouseButton::Back,
+            RecordedButton::Forward => baseview::MouseButton::Forward,
+            RecordedButton::Other(n) => baseview::MouseButton::Other(n),
+        }
+    }
+}
+
+pub(crate) struct RecorderState {
+    mode: EventRecorder,
+    started: Instant,
+    pending: Vec<RecordedEvent>,
+    next_frame: usize,
+    /// The frames without events since the last recorded one, or that were replayed before
+    /// the next one.
+    skipped: u32,
+}
+
+impl RecorderState {
+    pub fn new(mode: EventRecorder) -> Self {
+        Self {
+            mode,
+            started: Instant::now(),
+            pending: Vec::new(),
+            next_frame: 0,
+            skipped: 0,
+        }
+    }
+
+    fn is_replaying(&self) -> bool {
+        match &self.mode {
+            EventRecorder::Record(_) => false,
+            EventRecorder::Replay { recording, .. } => self.next_frame < recording.frames.len(),
+        }
+    }
+
+    /// Record a live event. Returns whether the window should handle it.
+    pub fn on_event(&mut self, event: &baseview::Event) -> bool {
+        let recorded = RecordedEvent::from_event(event);
+
+        if self.is_replaying() {
+            // Live input would make the replay nondeterministic.
+            return recorded.map_or(true, |recorded| !recorded.is_input());
+        }
+
+        if let (EventRecorder::Record(_), Some(recorded)) = (&self.mode, recorded) {
+            self.pending.push(recorded);
+        }
+
+        true
+    }
+
+    /// Finish the current frame of the recording, or get the events to replay before the next
+    /// frame together with the fixed delta time.
+    pub fn on_frame(&mut self) -> Option<(Vec<baseview::Event>, Duration)> {
+        match &self.mode {
+            EventRecorder::Record(recording) => {
+                if self.pending.is_empty() {
+                    self.skipped += 1;
+                    return None;
+                }
+                let frame = RecordedFrame {
+                    time: self.started.elapsed(),
+                    skipped: std::mem::replace(&mut self.skipped, 0),
+                    events: std::mem::take(&mut self.pending),
+                };
+                recording.lock().unwrap().frames.push(frame);
+
+                None
+            }
+            EventRecorder::Replay {
+                recording,
+                delta_time,
+            } => {
+                let frame = recording.frames.get(self.next_frame)?;
+                if self.skipped < frame.skipped {
+                    self.skipped += 1;
+                    return Some((Vec::new(), *delta_time));
+                }
+                self.skipped = 0;
+                self.next_frame += 1;
+
+                Some((
+                    frame.events.iter().map(RecordedEvent::to_event).collect(),
+                    *delta_time,
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use baseview::{Event, MouseButton, MouseEvent, Point};
+
+    fn frames() -> Vec<Vec<Event>> {
+        let moved = |x, y| {
+            Event::Mouse(MouseEvent::CursorMoved {
+                position: Point::new(x, y),
+            })
+        };
+        let key = Event::Keyboard(keyboard_types::KeyboardEvent {
+            key: keyboard_types::Key::Character(String::from("a")),
+            code: keyboard_types::Code::KeyA,
+            ..keyboard_types::KeyboardEvent::default()
+        });
+        vec![
+            vec![moved(1.0, 2.0)],
+            Vec::new(),
+            Vec::new(),
+            vec![
+                Event::Mouse(MouseEvent::ButtonPressed(MouseButton::Left)),
+                Event::Mouse(MouseEvent::ButtonReleased(MouseButton::Left)),
+            ],
+            vec![key],
+            Vec::new(),
+            vec![moved(3.5, -4.0)],
+        ]
+    }
+
+    fn recorded(events: &[Event]) -> Vec<RecordedEvent> {
+        events
+            .iter()
+            .filter_map(RecordedEvent::from_event)
+            .collect()
+    }
+
+    #[test]
+    fn empty_frames_are_counted_instead_of_stored() {
+        let recording = Arc::new(Mutex::new(Recording::new()));
+        let mut recorder = RecorderState::new(EventRecorder::Record(recording.clone()));
+        for frame in frames() {
+            for event in &frame {
+                assert!(recorder.on_event(event));
+            }
+            assert!(recorder.on_frame().is_none());
+        }
+        for _ in 0..100 {
+            recorder.on_frame();
+        }
+
+        let skipped: Vec<u32> = recording
+            .lock()
+            .unwrap()
+            .frames
+            .iter()
+            .map(|frame| frame.skipped)
+            .collect();
+        assert_eq!(skipped, [0, 2, 0, 1]);
+    }
+
+    #[test]
+    fn record_and_replay_round_trip() {
+        let recording = Arc::new(Mutex::new(Recording::new()));
+        let mut recorder = RecorderState::new(EventRecorder::Record(recording.clone()));
+        for frame in frames() {
+            for event in &frame {
+                recorder.on_event(event);
+            }
+            recorder.on_frame();
+        }
+
+        let json = recording.lock().unwrap().to_json().unwrap();
+        let recording = Recording::from_json(&json).unwrap();
+        let delta_time = Duration::from_millis(16);
+        let mut replayer = RecorderState::new(EventRecorder::Replay {
+            recording,
+            delta_time,
+        });
+
+        // Live input is ignored while the recording plays.
+        let click = Event::Mouse(MouseEvent::ButtonPressed(MouseButton::Right));
+        assert!(!replayer.on_event(&click));
+
+        for frame in frames() {
+            let (events, replayed_delta_time) = replayer.on_frame().unwrap();
+            assert_eq!(recorded(&events), recorded(&frame));
+            assert_eq!(replayed_delta_time, delta_time);
+        }
+        assert!(replayer.on_frame().is_none());
+        assert!(replayer.on_event(&click));
+    }
+}
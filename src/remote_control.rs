@@ -0,0 +1,358 @@
+//! Controlling the parameters of a window over OSC.
+//!
+//! Hardware controllers and tablet apps like TouchOSC can drive a plugin over the network.
+//! With `Settings::remote_control` the window listens for OSC messages on a UDP address, and
+//! the parameters of the param-bound widgets it has built can be read and set with them.
+//! Parameters that have no widget can be added with `WindowContext::expose_param`.
+//!
+//! Remote edits are made on the thread of the window like edits with the mouse, so they are
+//! reported through the `Handle` as `BeginEdit`, `SetParam` and `EndEdit`, and can be undone.
+//!
+//! The messages, with the arguments and their OSC types:
+//!
+//! * `/param/set id:i value:f` sets a parameter to a plain value.
+//! * `/param/set_normalized id:i value:f` sets it to a value from `0.0` to `1.0`.
+//! * `/param/get id:i` answers with `/param/value id:i value:f text:s`.
+//! * `/param/list` answers with `/param/info id:i name:s min:f max:f` for every parameter.
+//! * `/param/subscribe` sends the sender a `/param/value` every time a parameter changes,
+//!   from the UI, the host or remotely. `/param/unsubscribe` stops it.
+//!
+//! A subscription ends after a minute without a message from the subscriber, so controllers
+//! subscribe again from time to time. There are at most eight at once, and more are ignored.
+
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::handle::Handle;
+use crate::params::{Param, ParamId};
+use crate::WindowContext;
+
+/// How often the receiving thread checks whether the window closed.
+const RECEIVE_TIMEOUT: Duration = Duration::from_millis(100);
+/// The largest message that is read. Longer ones are cut off and ignored.
+const MAX_MESSAGE: usize = 1024;
+/// How many senders can subscribe at once.
+const MAX_SUBSCRIBERS: usize = 8;
+/// How long a subscription lasts without a message from the subscriber.
+const SUBSCRIPTION_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Where the window listens for OSC messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RemoteControl {
+    /// The UDP address to listen on. The default `127.0.0.1:9000` can only be reached from
+    /// this machine. `0.0.0.0:9000` lets controllers on other machines in, and anyone else on
+    /// the network too.
+    pub address: String,
+}
+
+impl Default for RemoteControl {
+    fn default() -> Self {
+        Self {
+            address: String::from("127.0.0.1:9000"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Command {
+    Set { id: ParamId, value: f32 },
+    SetNormalized { id: ParamId, value: f32 },
+    Get(ParamId),
+    List,
+    Subscribe,
+    Unsubscribe,
+}
+
+/// An argument of an OSC message.
+#[derive(Debug, Clone, PartialEq)]
+enum Arg<'a> {
+    Int(i32),
+    Float(f32),
+    Str(&'a str),
+}
+
+struct Exposed {
+    param: Arc<Param>,
+    /// The value the subscribers were last sent.
+    sent: f32,
+}
+
+/// The server of a window. Messages are received on a thread of their own, and handled
+/// before the next frame.
+pub(crate) struct RemoteControlServer {
+    socket: UdpSocket,
+    commands: Receiver<(Command, SocketAddr)>,
+    closed: Arc<AtomicBool>,
+    params: BTreeMap<ParamId, Exposed>,
+    /// The subscribers, and when they last sent a message.
+    subscribers: Vec<(SocketAddr, Instant)>,
+}
+
+impl RemoteControlServer {
+    pub fn start(settings: &RemoteControl, handle: Handle) -> io::Result<Self> {
+        let socket = UdpSocket::bind(&settings.address)?;
+        socket.set_read_timeout(Some(RECEIVE_TIMEOUT))?;
+        let receiver = socket.try_clone()?;
+
+        let closed = Arc::new(AtomicBool::new(false));
+        let (sender, commands) = mpsc::channel();
+
+        let thread_closed = closed.clone();
+        thread::spawn(move || {
+            let mut buffer = [0; MAX_MESSAGE];
+            while !thread_closed.load(Ordering::Relaxed) {
+                let (length, from) = match receiver.recv_from(&mut buffer) {
+                    Ok(received) => received,
+                    Err(_) => continue,
+                };
+                if let Some(command) = parse_command(&buffer[..length]) {
+                    if sender.send((command, from)).is_err() {
+                        return;
+                    }
+                    // Windows with `FrameMode::OnDemand` would not get to the message.
+                    handle.request_frame();
+                }
+            }
+        });
+
+        Ok(Self {
+            socket,
+            commands,
+            closed,
+            params: BTreeMap::new(),
+            subscribers: Vec::new(),
+        })
+    }
+
+    pub fn expose(&mut self, param: &Arc<Param>) {
+        self.params.entry(param.id()).or_insert_with(|| Exposed {
+            param: param.clone(),
+            sent: param.get(),
+        });
+    }
+
+    fn param(&self, id: ParamId) -> Option<Arc<Param>> {
+        self.params.get(&id).map(|exposed| exposed.param.clone())
+    }
+
+    fn send(&self, to: SocketAddr, address: &str, args: &[Arg]) {
+        let _ = self.socket.send_to(&encode_message(address, args), to);
+    }
+
+    fn send_value(&self, to: SocketAddr, param: &Param) {
+        let text = param.format();
+        let args = [
+            Arg::Int(param.id() as i32),
+            Arg::Float(param.get()),
+            Arg::Str(&text),
+        ];
+        self.send(to, "/param/value", &args);
+    }
+
+    fn send_list(&self, to: SocketAddr) {
+        for exposed in self.params.values() {
+            let param = &exposed.param;
+            let args = [
+                Arg::Int(param.id() as i32),
+                Arg::Str(param.name()),
+                Arg::Float(param.min()),
+                Arg::Float(param.max()),
+            ];
+            self.send(to, "/param/info", &args);
+        }
+    }
+
+    /// Send the parameters that changed since the last frame to the subscribers.
+    fn send_changes(&mut self) {
+        for exposed in self.params.values_mut() {
+            let value = exposed.param.get();
+            if value == exposed.sent {
+                continue;
+            }
+            exposed.sent = value;
+            for (subscriber, _) in &self.subscribers {
+                let text = exposed.param.format();
+                let args = [
+                    Arg::Int(exposed.param.id() as i32),
+                    Arg::Float(value),
+                    Arg::Str(&text),
+                ];
+                let _ = self
+                    .socket
+                    .send_to(&encode_message("/param/value", &args), subscriber);
+            }
+        }
+    }
+}
+
+impl Drop for RemoteControlServer {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Handle the messages that arrived since the last frame, and send the changes.
+pub(crate) fn poll(ctx: &mut WindowContext) {
+    let commands: Vec<_> = match &ctx.remote_control {
+        Some(server) => server.commands.try_iter().collect(),
+        None => return,
+    };
+
+    let now = Instant::now();
+    if let Some(server) = &mut ctx.remote_control {
+        server
+            .subscribers
+            .retain(|(_, heard)| now.duration_since(*heard) < SUBSCRIPTION_TIMEOUT);
+    }
+
+    for (command, from) in commands {
+        let server = match &mut ctx.remote_control {
+            Some(server) => server,
+            None => return,
+        };
+        let subscriber = server.subscribers.iter_mut().find(|(to, _)| *to == from);
+        if let Some((_, heard)) = subscriber {
+            *heard = now;
+        }
+        match command {
+            Command::Set { id, value } => {
+                if let Some(param) = server.param(id) {
+                    crate::widgets::set_param(ctx, &param, value);
+                }
+            }
+            Command::SetNormalized { id, value } => {
+                if let Some(param) = server.param(id) {
+                    let value = param.min() + value.max(0.0).min(1.0) * (param.max() - param.min());
+                    crate::widgets::set_param(ctx, &param, value);
+                }
+            }
+            Command::Get(id) => {
+                if let Some(param) = server.param(id) {
+                    server.send_value(from, &param);
+                }
+            }
+            Command::List => server.send_list(from),
+            Command::Subscribe => {
+                let subscribed = server.subscribers.iter().any(|(to, _)| *to == from);
+                if !subscribed && server.subscribers.len() < MAX_SUBSCRIBERS {
+                    server.subscribers.push((from, now));
+                }
+            }
+            Command::Unsubscribe => server.subscribers.retain(|(to, _)| *to != from),
+        }
+    }
+
+    if let Some(server) = &mut ctx.remote_control {
+        server.send_changes();
+    }
+}
+
+fn parse_command(packet: &[u8]) -> Option<Command> {
+    let (address, args) = parse_message(packet)?;
+    let id = || match args.first() {
+        Some(Arg::Int(id)) if *id >= 0 => Some(*id as ParamId),
+        _ => None,
+    };
+    let value = || match args.get(1) {
+        Some(Arg::Float(value)) => Some(*value),
+        Some(Arg::Int(value)) => Some(*value as f32),
+        _ => None,
+    };
+
+    match address {
+        "/param/set" => Some(Command::Set {
+            id: id()?,
+            value: value()?,
+        }),
+        "/param/set_normalized" => Some(Command::SetNormalized {
+            id: id()?,
+            value: value()?,
+        }),
+        "/param/get" => Some(Command::Get(id()?)),
+        "/param/list" => Some(Command::List),
+        "/param/subscribe" => Some(Command::Subscribe),
+        "/param/unsubscribe" => Some(Command::Unsubscribe),
+        _ => None,
+    }
+}
+
+/// Read a null-terminated string padded to four bytes, returning it and the rest.
+fn read_string(data: &[u8]) -> Option<(&str, &[u8])> {
+    let end = data.iter().position(|byte| *byte == 0)?;
+    let string = std::str::from_utf8(&data[..end]).ok()?;
+    let padded = (end + 4) & !3;
+    Some((string, data.get(padded..)?))
+}
+
+/// Parse an OSC message. Bundles and argument types other than `i`, `f` and `s` are not
+/// supported.
+fn parse_message(packet: &[u8]) -> Option<(&str, Vec<Arg>)> {
+    let (address, rest) = read_string(packet)?;
+    if !address.starts_with('/') {
+        return None;
+    }
+    // Old senders leave the type tags out of messages without arguments.
+    if rest.is_empty() {
+        return Some((address, Vec::new()));
+    }
+
+    let (tags, mut rest) = read_string(rest)?;
+    let mut args = Vec::new();
+    for tag in tags.strip_prefix(',')?.chars() {
+        match tag {
+            'i' | 'f' => {
+                let bytes: [u8; 4] = rest.get(..4)?.try_into().ok()?;
+                rest = &rest[4..];
+                args.push(if tag == 'i' {
+                    Arg::Int(i32::from_be_bytes(bytes))
+                } else {
+                    Arg::Float(f32::from_be_bytes(bytes))
+                });
+            }
+            's' => {
+                let (string, after) = read_string(rest)?;
+                rest = after;
+                args.push(Arg::Str(string));
+            }
+            _ => return None,
+        }
+    }
+    Some((address, args))
+}
+
+fn write_string(packet: &mut Vec<u8>, string: &str) {
+    packet.extend_from_slice(string.as_bytes());
+    let padded = (string.len() + 4) & !3;
+    packet.resize(packet.len() + padded - string.len(), 0);
+}
+
+fn encode_message(address: &str, args: &[Arg]) -> Vec<u8> {
+    let mut tags = String::from(",");
+    for arg in args {
+        tags.push(match arg {
+            Arg::Int(_) => 'i',
+            Arg::Float(_) => 'f',
+            Arg::Str(_) => 's',
+        });
+    }
+
+    let mut packet = Vec::new();
+    write_string(&mut packet, address);
+    write_string(&mut packet, &tags);
+    for arg in args {
+        match arg {
+            Arg::Int(value) => packet.extend_from_slice(&value.to_be_bytes()),
+            Arg::Float(value) => packet.extend_from_slice(&value.to_be_bytes()),
+            Arg::Str(string) => write_string(&mut packet, string),
+        }
+    }
+    packet
+}
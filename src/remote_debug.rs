@@ -0,0 +1,188 @@
+//! Streaming what a window draws to a debugger on another machine.
+//!
+//! Rendering bugs that only show up in one host are hard to look at from inside it. With
+//! `Settings::remote_debug` the window listens on a TCP address, and sends every client that
+//! connects one line of JSON per frame with the `FrameReport` of the frame, for example to
+//! `nc <address> <port>` or a small viewer. The draw lists are only sent when asked for,
+//! since they are large.
+
+use std::io::{self, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::frame::DrawStats;
+
+/// How often the server looks for new clients while no frames arrive.
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(100);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Where the window serves its frames.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RemoteDebug {
+    /// The address to listen on, like `0.0.0.0:7878` to be reached from other machines.
+    pub address: String,
+    /// Send the vertices, indices and commands of every draw list too.
+    pub draw_lists: bool,
+}
+
+/// What a window sends for a frame.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrameReport {
+    /// The title of the window.
+    pub window: String,
+    /// The number of the frame since the window opened.
+    pub frame: u64,
+    pub hidpi_factor: f64,
+    /// The logical size of the UI.
+    pub display_size: [f32; 2],
+    pub stats: DrawStats,
+    pub draw_lists: Option<Vec<DrawListReport>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DrawListReport {
+    pub vertices: Vec<VertexReport>,
+    pub indices: Vec<u16>,
+    pub commands: Vec<CommandReport>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VertexReport {
+    pub pos: [f32; 2],
+    pub uv: [f32; 2],
+    pub col: [u8; 4],
+}
+
+/// A draw command. Callbacks are left out.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CommandReport {
+    pub count: usize,
+    pub clip_rect: [f32; 4],
+    pub texture_id: usize,
+    pub vtx_offset: usize,
+    pub idx_offset: usize,
+}
+
+impl DrawListReport {
+    fn new(list: &imgui::DrawList) -> Self {
+        let vertices = list
+            .vtx_buffer()
+            .iter()
+            .map(|vertex| VertexReport {
+                pos: vertex.pos,
+                uv: vertex.uv,
+                col: vertex.col,
+            })
+            .collect();
+        let commands = list
+            .commands()
+            .filter_map(|command| match command {
+                imgui::DrawCmd::Elements { count, cmd_params } => Some(CommandReport {
+                    count,
+                    clip_rect: cmd_params.clip_rect,
+                    texture_id: cmd_params.texture_id.id(),
+                    vtx_offset: cmd_params.vtx_offset,
+                    idx_offset: cmd_params.idx_offset,
+                }),
+                _ => None,
+            })
+            .collect();
+
+        Self {
+            vertices,
+            indices: list.idx_buffer().to_vec(),
+            commands,
+        }
+    }
+}
+
+/// The server of a window, which writes to the clients on a thread of its own.
+pub(crate) struct RemoteDebugServer {
+    window: String,
+    draw_lists: bool,
+    frame: u64,
+    clients: Arc<AtomicUsize>,
+    sender: Sender<String>,
+}
+
+impl RemoteDebugServer {
+    pub fn start(settings: &RemoteDebug, window: String) -> io::Result<Self> {
+        let listener = TcpListener::bind(&settings.address)?;
+        listener.set_nonblocking(true)?;
+
+        let clients = Arc::new(AtomicUsize::new(0));
+        let (sender, receiver) = mpsc::channel::<String>();
+
+        let client_count = clients.clone();
+        thread::spawn(move || {
+            let mut streams: Vec<TcpStream> = Vec::new();
+            loop {
+                while let Ok((stream, _)) = listener.accept() {
+                    let blocking = stream.set_nonblocking(false).is_ok();
+                    // A client that stops reading is dropped instead of stalling the others.
+                    if blocking && stream.set_write_timeout(Some(WRITE_TIMEOUT)).is_ok() {
+                        streams.push(stream);
+                    }
+                }
+                client_count.store(streams.len(), Ordering::Relaxed);
+
+                // The window closed when the sender is dropped.
+                match receiver.recv_timeout(ACCEPT_INTERVAL) {
+                    Ok(line) => streams.retain_mut(|stream| {
+                        stream
+                            .write_all(line.as_bytes())
+                            .and_then(|_| stream.write_all(b"\n"))
+                            .is_ok()
+                    }),
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+        });
+
+        Ok(Self {
+            window,
+            draw_lists: settings.draw_lists,
+            frame: 0,
+            clients,
+            sender,
+        })
+    }
+
+    /// Send the frame that was just rendered, if anyone is listening.
+    pub fn send_frame(&mut self, stats: &DrawStats, hidpi_factor: f64, display_size: [f32; 2]) {
+        self.frame += 1;
+        if self.clients.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+
+        let draw_lists = if self.draw_lists {
+            let draw_data = unsafe { imgui::sys::igGetDrawData() };
+            (!draw_data.is_null()).then(|| {
+                let draw_data = unsafe { &*(draw_data as *const imgui::DrawData) };
+                draw_data.draw_lists().map(DrawListReport::new).collect()
+            })
+        } else {
+            None
+        };
+
+        let report = FrameReport {
+            window: self.window.clone(),
+            frame: self.frame,
+            hidpi_factor,
+            display_size,
+            stats: *stats,
+            draw_lists,
+        };
+        if let Ok(line) = serde_json::to_string(&report) {
+            let _ = self.sender.send(line);
+        }
+    }
+}
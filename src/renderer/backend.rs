@@ -0,0 +1,158 @@
+//! Custom renderers.
+//!
+//! Applications that already have an engine can draw the UI with it instead of the built-in
+//! OpenGL renderer, by setting `Settings::renderer_backend` to a function that creates their
+//! `RendererBackend` for the window. What needs OpenGL is left out with a custom backend:
+//! `ui_shader_rect` stays empty, render targets are empty textures whose callbacks aren't
+//! called, glass windows show their backdrop without blurring it, and the window has no
+//! rounded corners or transparency.
+
+use std::collections::HashMap;
+
+use baseview::Window;
+
+use super::render_targets::TargetHandle;
+use super::AdapterInfo;
+use crate::settings::LatencyMode;
+
+/// Creates the backend of a window, once the imgui context and its font atlas are built.
+pub type BackendFactory = fn(&Window, &mut imgui::Context) -> Box<dyn RendererBackend>;
+
+/// Draws the UI of a window.
+///
+/// The methods are called on the thread of the window, with the imgui context current.
+pub trait RendererBackend {
+    /// Upload the font atlas of `context`, returning the texture it is in. Called when the
+    /// backend is created and every time the atlas is rebuilt.
+    fn upload_font_atlas(&mut self, context: &mut imgui::Context) -> imgui::TextureId;
+
+    /// Clear the window with `clear_color` and draw the frame. `draw_data` is in logical
+    /// pixels, with `framebuffer_scale` physical pixels to each.
+    fn render(&mut self, draw_data: &imgui::DrawData, clear_color: (f32, f32, f32));
+
+    /// The window was resized to `physical_size` pixels.
+    fn resize(&mut self, _physical_size: [u32; 2]) {}
+
+    /// Upload an RGBA8 image into a new texture usable with `imgui::Image`.
+    fn create_texture(&mut self, width: u32, height: u32, rgba: &[u8]) -> imgui::TextureId;
+
+    fn delete_texture(&mut self, texture: imgui::TextureId);
+
+    /// Wait on the swap chain after every frame with `LatencyMode::LowLatency`, for less
+    /// latency at the cost of frame rate.
+    fn set_latency_mode(&mut self, _mode: LatencyMode) {}
+
+    /// The window is closing. Free what the backend holds on the GPU while its context or
+    /// device is still usable. Nothing is rendered afterwards.
+    fn destroy(&mut self) {}
+
+    /// The name shown in the debug overlay and given to imgui.
+    fn name(&self) -> String {
+        String::from("custom")
+    }
+
+    /// What the GPU and its driver tell about themselves, like the adapter info of wgpu.
+    fn adapter_info(&self) -> AdapterInfo {
+        AdapterInfo::default()
+    }
+}
+
+/// A user backend with what the crate needs around it.
+pub(crate) struct CustomRenderer {
+    backend: Box<dyn RendererBackend>,
+    font_texture: imgui::TextureId,
+    texture_count: usize,
+    /// The textures shown in place of render targets.
+    targets: HashMap<TargetHandle, imgui::TextureId>,
+    next_target: u32,
+}
+
+impl CustomRenderer {
+    pub fn new(mut backend: Box<dyn RendererBackend>, imgui_context: &mut imgui::Context) -> Self {
+        let font_texture = backend.upload_font_atlas(imgui_context);
+        imgui_context.fonts().tex_id = font_texture;
+
+        Self {
+            backend,
+            font_texture,
+            texture_count: 0,
+            targets: HashMap::new(),
+            next_target: 0,
+        }
+    }
+
+    pub fn render(&mut self, imgui_ui: imgui::Ui, clear_color: (f32, f32, f32)) {
+        let draw_data = imgui_ui.render();
+        self.backend.render(draw_data, clear_color);
+    }
+
+    pub fn resize(&mut self, physical_size: [u32; 2]) {
+        self.backend.resize(physical_size);
+    }
+
+    pub fn reload_font_texture(&mut self, imgui_context: &mut imgui::Context) {
+        self.backend.delete_texture(self.font_texture);
+        self.font_texture = self.backend.upload_font_atlas(imgui_context);
+        imgui_context.fonts().tex_id = self.font_texture;
+    }
+
+    pub fn font_texture(&self) -> imgui::TextureId {
+        self.font_texture
+    }
+
+    pub fn create_texture(&mut self, width: u32, height: u32, rgba: &[u8]) -> imgui::TextureId {
+        self.texture_count += 1;
+        self.backend.create_texture(width, height, rgba)
+    }
+
+    pub fn delete_texture(&mut self, texture: imgui::TextureId) {
+        self.texture_count = self.texture_count.saturating_sub(1);
+        self.backend.delete_texture(texture);
+    }
+
+    pub fn texture_count(&self) -> usize {
+        self.texture_count
+    }
+
+    pub fn create_render_target(
+        &mut self,
+        width: u32,
+        height: u32,
+    ) -> (imgui::TextureId, TargetHandle) {
+        let pixels = vec![0; width as usize * height as usize * 4];
+        let texture = self.backend.create_texture(width, height, &pixels);
+
+        self.next_target += 1;
+        let handle = TargetHandle(self.next_target);
+        self.targets.insert(handle, texture);
+
+        (texture, handle)
+    }
+
+    pub fn delete_render_target(&mut self, target: TargetHandle) {
+        if let Some(texture) = self.targets.remove(&target) {
+            self.backend.delete_texture(texture);
+        }
+    }
+
+    /// Delete the font atlas and the render targets, then let the backend free the rest.
+    pub fn destroy(&mut self) {
+        for (_, texture) in self.targets.drain() {
+            self.backend.delete_texture(texture);
+        }
+        self.backend.delete_texture(self.font_texture);
+        self.backend.destroy();
+    }
+
+    pub fn name(&self) -> String {
+        self.backend.name()
+    }
+
+    pub fn adapter_info(&self) -> AdapterInfo {
+        self.backend.adapter_info()
+    }
+
+    pub fn set_latency_mode(&mut self, mode: LatencyMode) {
+        self.backend.set_latency_mode(mode);
+    }
+}
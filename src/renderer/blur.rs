@@ -0,0 +1,244 @@
+use std::ffi::CString;
+
+use super::shaders::{link, VERTEX_SHADER};
+
+const DOWNSAMPLE: &str = "#version 150
+in vec2 v_uv;
+uniform sampler2D u_texture;
+uniform vec2 u_half_pixel;
+uniform float u_offset;
+out vec4 out_color;
+void main() {
+    vec2 offset = u_half_pixel * u_offset;
+    vec4 sum = texture(u_texture, v_uv) * 4.0;
+    sum += texture(u_texture, v_uv - offset);
+    sum += texture(u_texture, v_uv + offset);
+    sum += texture(u_texture, v_uv + vec2(offset.x, -offset.y));
+    sum += texture(u_texture, v_uv - vec2(offset.x, -offset.y));
+    out_color = sum / 8.0;
+}
+";
+
+const UPSAMPLE: &str = "#version 150
+in vec2 v_uv;
+uniform sampler2D u_texture;
+uniform vec2 u_half_pixel;
+uniform float u_offset;
+out vec4 out_color;
+void main() {
+    vec2 offset = u_half_pixel * u_offset;
+    vec4 sum = texture(u_texture, v_uv + vec2(-offset.x * 2.0, 0.0));
+    sum += texture(u_texture, v_uv + vec2(-offset.x, offset.y)) * 2.0;
+    sum += texture(u_texture, v_uv + vec2(0.0, offset.y * 2.0));
+    sum += texture(u_texture, v_uv + vec2(offset.x, offset.y)) * 2.0;
+    sum += texture(u_texture, v_uv + vec2(offset.x * 2.0, 0.0));
+    sum += texture(u_texture, v_uv + vec2(offset.x, -offset.y)) * 2.0;
+    sum += texture(u_texture, v_uv + vec2(0.0, -offset.y * 2.0));
+    sum += texture(u_texture, v_uv + vec2(-offset.x, -offset.y)) * 2.0;
+    out_color = sum / 12.0;
+}
+";
+
+/// The most levels the source is downsampled by.
+const MAX_PASSES: u32 = 6;
+
+struct Program {
+    program: u32,
+    texture: i32,
+    half_pixel: i32,
+    offset: i32,
+}
+
+/// A downsampled copy of the source, which is blurred back up into the larger levels.
+struct Level {
+    texture: u32,
+    framebuffer: u32,
+    size: [u32; 2],
+}
+
+/// Blurs a texture with the dual Kawase filter every frame. Everything must be called with
+/// the context current.
+#[derive(Default)]
+pub(crate) struct Blur {
+    programs: Option<(Program, Program)>,
+    vertex_array: u32,
+    source: Option<u32>,
+    offset: f32,
+    levels: Vec<Level>,
+    /// Whether the source was blurred since it was set.
+    blurred: bool,
+}
+
+impl Blur {
+    /// Blur `source` from now on, returning the texture the result is rendered into. More
+    /// passes blur more, at half the resolution each.
+    pub fn set_source(
+        &mut self,
+        source: u32,
+        size: [u32; 2],
+        passes: u32,
+        offset: f32,
+    ) -> imgui::TextureId {
+        if self.programs.is_none() {
+            unsafe {
+                gl::GenVertexArrays(1, &mut self.vertex_array);
+                self.programs = Some((Program::new(DOWNSAMPLE), Program::new(UPSAMPLE)));
+            }
+        }
+
+        let passes = passes.max(1).min(MAX_PASSES) as usize;
+        let sizes: Vec<[u32; 2]> = (1..=passes)
+            .map(|level| [(size[0] >> level).max(1), (size[1] >> level).max(1)])
+            .collect();
+        let same_levels = self.levels.len() == sizes.len()
+            && self
+                .levels
+                .iter()
+                .zip(&sizes)
+                .all(|(level, size)| level.size == *size);
+        if !same_levels {
+            self.clear_levels();
+            self.levels = sizes.into_iter().map(Level::new).collect();
+        }
+
+        self.source = Some(source);
+        self.offset = offset;
+        self.blurred = false;
+
+        imgui::TextureId::new(self.levels[0].texture as usize)
+    }
+
+    pub fn is_blurred(&self) -> bool {
+        self.blurred
+    }
+
+    pub fn clear_source(&mut self) {
+        self.source = None;
+        self.clear_levels();
+    }
+
+    pub fn delete(&mut self) {
+        self.clear_source();
+        if let Some((downsample, upsample)) = self.programs.take() {
+            unsafe {
+                gl::DeleteProgram(downsample.program);
+                gl::DeleteProgram(upsample.program);
+                gl::DeleteVertexArrays(1, &self.vertex_array);
+            }
+        }
+    }
+
+    fn clear_levels(&mut self) {
+        for level in self.levels.drain(..) {
+            unsafe {
+                gl::DeleteFramebuffers(1, &level.framebuffer);
+                gl::DeleteTextures(1, &level.texture);
+            }
+        }
+    }
+
+    /// Downsample the source through the levels, then upsample back to the first level.
+    pub fn render(&mut self) {
+        let (source, (down, up)) = match (self.source, &self.programs) {
+            (Some(source), Some(programs)) => (source, programs),
+            _ => return,
+        };
+        self.blurred = true;
+
+        unsafe {
+            let mut viewport = [0; 4];
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+            gl::Disable(gl::BLEND);
+            gl::Disable(gl::SCISSOR_TEST);
+            gl::BindVertexArray(self.vertex_array);
+            gl::ActiveTexture(gl::TEXTURE0);
+
+            let mut input = source;
+            for level in &self.levels {
+                down.draw(input, level, self.offset);
+                input = level.texture;
+            }
+            for i in (0..self.levels.len() - 1).rev() {
+                up.draw(self.levels[i + 1].texture, &self.levels[i], self.offset);
+            }
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            gl::BindVertexArray(0);
+            gl::UseProgram(0);
+            gl::Viewport(viewport[0], viewport[1], viewport[2], viewport[3]);
+        }
+    }
+}
+
+impl Program {
+    unsafe fn new(fragment: &str) -> Self {
+        let program = link(VERTEX_SHADER, fragment)
+            .unwrap_or_else(|log| panic!("failed to build the blur shader: {}", log));
+        let location = |name: &str| {
+            let name = CString::new(name).unwrap();
+            gl::GetUniformLocation(program, name.as_ptr())
+        };
+
+        Self {
+            program,
+            texture: location("u_texture"),
+            half_pixel: location("u_half_pixel"),
+            offset: location("u_offset"),
+        }
+    }
+
+    unsafe fn draw(&self, input: u32, output: &Level, offset: f32) {
+        gl::BindFramebuffer(gl::FRAMEBUFFER, output.framebuffer);
+        gl::Viewport(0, 0, output.size[0] as _, output.size[1] as _);
+        gl::UseProgram(self.program);
+        gl::BindTexture(gl::TEXTURE_2D, input);
+        gl::Uniform1i(self.texture, 0);
+        gl::Uniform2f(
+            self.half_pixel,
+            0.5 / output.size[0] as f32,
+            0.5 / output.size[1] as f32,
+        );
+        gl::Uniform1f(self.offset, offset);
+        gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
+    }
+}
+
+impl Level {
+    fn new(size: [u32; 2]) -> Self {
+        let mut level = Self {
+            texture: 0,
+            framebuffer: 0,
+            size,
+        };
+        unsafe {
+            gl::GenTextures(1, &mut level.texture);
+            gl::BindTexture(gl::TEXTURE_2D, level.texture);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as _);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as _);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as _);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as _);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA as _,
+                size[0] as _,
+                size[1] as _,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                std::ptr::null(),
+            );
+            gl::GenFramebuffers(1, &mut level.framebuffer);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, level.framebuffer);
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_2D,
+                level.texture,
+                0,
+            );
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+        level
+    }
+}
@@ -0,0 +1,173 @@
+use std::ffi::CString;
+
+use super::shaders::{link, VERTEX_SHADER};
+use crate::theme::ColorVision;
+
+const FRAGMENT_SHADER: &str = "#version 150
+in vec2 v_uv;
+uniform sampler2D u_texture;
+uniform mat3 u_matrix;
+out vec4 out_color;
+vec3 to_linear(vec3 c) {
+    return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(0.04045, c));
+}
+vec3 to_srgb(vec3 c) {
+    return mix(c * 12.92, 1.055 * pow(c, vec3(1.0 / 2.4)) - 0.055, step(0.0031308, c));
+}
+void main() {
+    vec4 color = texture(u_texture, v_uv);
+    vec3 simulated = clamp(u_matrix * to_linear(color.rgb), 0.0, 1.0);
+    out_color = vec4(to_srgb(simulated), color.a);
+}
+";
+
+/// The matrices of Machado, Oliveira and Fernandes (2009) for a full deficiency, by rows, in
+/// linear RGB.
+fn matrix(vision: ColorVision) -> [[f32; 3]; 3] {
+    match vision {
+        ColorVision::Protanopia => [
+            [0.152_286, 1.052_583, -0.204_868],
+            [0.114_503, 0.786_281, 0.099_216],
+            [-0.003_882, -0.048_116, 1.051_998],
+        ],
+        ColorVision::Deuteranopia => [
+            [0.367_322, 0.860_646, -0.227_968],
+            [0.280_085, 0.672_501, 0.047_413],
+            [-0.011_820, 0.042_940, 0.968_881],
+        ],
+        ColorVision::Tritanopia => [
+            [1.255_528, -0.076_749, -0.178_779],
+            [-0.078_411, 0.930_809, 0.147_602],
+            [0.004_733, 0.691_367, 0.303_900],
+        ],
+    }
+}
+
+/// Redraws the framebuffer after the UI is rendered as it looks with a color vision
+/// deficiency. The framebuffer is resolved into a texture first, so it works with
+/// multisampling. Everything must be called with the context current.
+pub(crate) struct ColorFilter {
+    program: u32,
+    vertex_array: u32,
+    texture_location: i32,
+    matrix_location: i32,
+    texture: u32,
+    framebuffer: u32,
+    size: [i32; 2],
+}
+
+impl ColorFilter {
+    pub fn new() -> Self {
+        unsafe {
+            let program = link(VERTEX_SHADER, FRAGMENT_SHADER)
+                .unwrap_or_else(|log| panic!("failed to build the color filter shader: {}", log));
+            let location = |name: &str| {
+                let name = CString::new(name).unwrap();
+                gl::GetUniformLocation(program, name.as_ptr())
+            };
+
+            let mut vertex_array = 0;
+            gl::GenVertexArrays(1, &mut vertex_array);
+
+            Self {
+                program,
+                vertex_array,
+                texture_location: location("u_texture"),
+                matrix_location: location("u_matrix"),
+                texture: 0,
+                framebuffer: 0,
+                size: [0, 0],
+            }
+        }
+    }
+
+    pub fn delete(self) {
+        unsafe {
+            gl::DeleteProgram(self.program);
+            gl::DeleteVertexArrays(1, &self.vertex_array);
+            if self.texture != 0 {
+                gl::DeleteFramebuffers(1, &self.framebuffer);
+                gl::DeleteTextures(1, &self.texture);
+            }
+        }
+    }
+
+    /// Give the copy of the framebuffer the size of the viewport.
+    unsafe fn resize(&mut self, size: [i32; 2]) {
+        if self.size == size {
+            return;
+        }
+        self.size = size;
+
+        if self.texture == 0 {
+            gl::GenTextures(1, &mut self.texture);
+            gl::GenFramebuffers(1, &mut self.framebuffer);
+        }
+        gl::BindTexture(gl::TEXTURE_2D, self.texture);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as _);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as _);
+        gl::TexImage2D(
+            gl::TEXTURE_2D,
+            0,
+            gl::RGBA as _,
+            size[0],
+            size[1],
+            0,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            std::ptr::null(),
+        );
+        gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer);
+        gl::FramebufferTexture2D(
+            gl::FRAMEBUFFER,
+            gl::COLOR_ATTACHMENT0,
+            gl::TEXTURE_2D,
+            self.texture,
+            0,
+        );
+        gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+    }
+
+    /// Filter the whole viewport.
+    pub fn render(&mut self, vision: ColorVision) {
+        unsafe {
+            let mut viewport = [0; 4];
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+            let [x, y, width, height] = viewport;
+            if width <= 0 || height <= 0 {
+                return;
+            }
+            self.resize([width, height]);
+
+            gl::Disable(gl::SCISSOR_TEST);
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, self.framebuffer);
+            gl::BlitFramebuffer(
+                x,
+                y,
+                x + width,
+                y + height,
+                0,
+                0,
+                width,
+                height,
+                gl::COLOR_BUFFER_BIT,
+                gl::NEAREST,
+            );
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+
+            gl::Disable(gl::BLEND);
+            gl::BindVertexArray(self.vertex_array);
+            gl::UseProgram(self.program);
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, self.texture);
+            gl::Uniform1i(self.texture_location, 0);
+            let matrix = matrix(vision);
+            gl::UniformMatrix3fv(self.matrix_location, 1, gl::TRUE, matrix.as_ptr() as _);
+            gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
+
+            gl::UseProgram(0);
+            gl::BindVertexArray(0);
+        }
+    }
+}
@@ -0,0 +1,408 @@
+//! The renderer for drivers without OpenGL 3.
+//!
+//! Some older machines only have OpenGL 2.1, or OpenGL ES 2 through a host context. The
+//! renderer of imgui-opengl-renderer needs vertex array objects and GLSL 1.50, so on those
+//! the UI is drawn with `#version 120` shaders from client-side vertex arrays instead. What
+//! needs framebuffers or newer shaders is left out, like with a custom backend: the quads of
+//! `ui_shader_rect` stay empty, render targets are empty textures, the backdrop is not
+//! blurred, and the window has no rounded corners or transparency.
+
+use std::collections::{HashMap, HashSet};
+use std::ffi::CString;
+use std::mem;
+use std::time::{Duration, Instant};
+
+use super::opengl_renderer::{self, empty_texture, upload_rows, upload_texture, Gl};
+use super::render_targets::TargetHandle;
+use super::shaders::link;
+use super::AdapterInfo;
+use crate::settings::LatencyMode;
+
+const VERTEX_SHADER: &str = "
+uniform mat4 u_matrix;
+attribute vec2 a_pos;
+attribute vec2 a_uv;
+attribute vec4 a_color;
+varying vec2 v_uv;
+varying vec4 v_color;
+void main() {
+    v_uv = a_uv;
+    v_color = a_color;
+    gl_Position = u_matrix * vec4(a_pos, 0.0, 1.0);
+}
+";
+
+const FRAGMENT_SHADER: &str = "
+uniform sampler2D u_texture;
+varying vec2 v_uv;
+varying vec4 v_color;
+void main() {
+    gl_FragColor = v_color * texture2D(u_texture, v_uv);
+}
+";
+
+struct Locations {
+    matrix: i32,
+    texture: i32,
+    pos: u32,
+    uv: u32,
+    color: u32,
+}
+
+pub(crate) struct LegacyRenderer {
+    context: Gl,
+    version: (u32, u32),
+    es: bool,
+    program: u32,
+    locations: Locations,
+    font_texture: imgui::TextureId,
+    /// The textures of `create_texture`, deleted with the renderer.
+    textures: HashSet<u32>,
+    /// The textures shown in place of render targets.
+    targets: HashMap<TargetHandle, u32>,
+    next_target: u32,
+    swap_time: Duration,
+    latency_mode: LatencyMode,
+    adapter: AdapterInfo,
+}
+
+impl LegacyRenderer {
+    /// Create the renderer with a context from `create_context`, which is current.
+    pub fn new(
+        context: Gl,
+        imgui_context: &mut imgui::Context,
+        version: (u32, u32),
+        es: bool,
+    ) -> Self {
+        let header = if es {
+            "#version 100\nprecision mediump float;\n"
+        } else {
+            "#version 120\n"
+        };
+        let vertex = format!("{}{}", header, VERTEX_SHADER);
+        let fragment = format!("{}{}", header, FRAGMENT_SHADER);
+
+        let (program, locations) = unsafe {
+            let program = link(&vertex, &fragment)
+                .unwrap_or_else(|log| panic!("failed to build the legacy UI shader: {}", log));
+            let name = |name: &str| CString::new(name).unwrap();
+            let locations = Locations {
+                matrix: gl::GetUniformLocation(program, name("u_matrix").as_ptr()),
+                texture: gl::GetUniformLocation(program, name("u_texture").as_ptr()),
+                pos: gl::GetAttribLocation(program, name("a_pos").as_ptr()) as u32,
+                uv: gl::GetAttribLocation(program, name("a_uv").as_ptr()) as u32,
+                color: gl::GetAttribLocation(program, name("a_color").as_ptr()) as u32,
+            };
+            (program, locations)
+        };
+        let font_texture = upload_font_atlas(imgui_context);
+        let adapter = opengl_renderer::adapter_info();
+
+        context.make_not_current();
+
+        Self {
+            context,
+            version,
+            es,
+            program,
+            locations,
+            font_texture,
+            textures: HashSet::new(),
+            targets: HashMap::new(),
+            next_target: 0,
+            swap_time: Duration::default(),
+            latency_mode: LatencyMode::default(),
+            adapter,
+        }
+    }
+
+    pub fn render(&mut self, imgui_ui: imgui::Ui, clear_color: (f32, f32, f32)) {
+        self.context.make_current();
+
+        unsafe {
+            gl::ClearColor(clear_color.0, clear_color.1, clear_color.2, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+            self.draw(imgui_ui.render());
+        }
+
+        let swap_start = Instant::now();
+        self.context.swap_buffers();
+        if self.latency_mode == LatencyMode::LowLatency {
+            unsafe {
+                gl::Finish();
+            }
+        }
+        self.swap_time = swap_start.elapsed();
+        self.context.make_not_current();
+    }
+
+    pub fn set_latency_mode(&mut self, mode: LatencyMode) {
+        self.latency_mode = mode;
+    }
+
+    pub fn swap_time(&self) -> Duration {
+        self.swap_time
+    }
+
+    /// Delete the program and the textures with the context current, then drop the context.
+    pub fn destroy(&mut self) {
+        if let Gl::Destroyed = self.context {
+            return;
+        }
+        self.context.make_current();
+
+        let font_texture = self.font_texture.id() as u32;
+        unsafe {
+            gl::DeleteProgram(self.program);
+            gl::DeleteTextures(1, &font_texture);
+            for texture in self.textures.drain() {
+                gl::DeleteTextures(1, &texture);
+            }
+            for (_, texture) in self.targets.drain() {
+                gl::DeleteTextures(1, &texture);
+            }
+        }
+
+        self.context.destroy();
+    }
+
+    unsafe fn set_state(&self, draw_data: &imgui::DrawData, framebuffer_size: [f32; 2]) {
+        gl::Enable(gl::BLEND);
+        gl::BlendEquation(gl::FUNC_ADD);
+        gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+        gl::Disable(gl::CULL_FACE);
+        gl::Disable(gl::DEPTH_TEST);
+        gl::Enable(gl::SCISSOR_TEST);
+        gl::Viewport(0, 0, framebuffer_size[0] as _, framebuffer_size[1] as _);
+
+        let [left, top] = draw_data.display_pos;
+        let right = left + draw_data.display_size[0];
+        let bottom = top + draw_data.display_size[1];
+        let matrix = [
+            [2.0 / (right - left), 0.0, 0.0, 0.0],
+            [0.0, 2.0 / (top - bottom), 0.0, 0.0],
+            [0.0, 0.0, -1.0, 0.0],
+            [
+                (right + left) / (left - right),
+                (top + bottom) / (bottom - top),
+                0.0,
+                1.0,
+            ],
+        ];
+
+        gl::UseProgram(self.program);
+        gl::Uniform1i(self.locations.texture, 0);
+        gl::UniformMatrix4fv(self.locations.matrix, 1, gl::FALSE, matrix.as_ptr() as _);
+        gl::ActiveTexture(gl::TEXTURE0);
+
+        // The vertices and indices are read from the draw lists.
+        gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, 0);
+        gl::EnableVertexAttribArray(self.locations.pos);
+        gl::EnableVertexAttribArray(self.locations.uv);
+        gl::EnableVertexAttribArray(self.locations.color);
+    }
+
+    unsafe fn draw(&self, draw_data: &imgui::DrawData) {
+        let [scale_x, scale_y] = draw_data.framebuffer_scale;
+        let framebuffer_size = [
+            draw_data.display_size[0] * scale_x,
+            draw_data.display_size[1] * scale_y,
+        ];
+        if framebuffer_size[0] <= 0.0 || framebuffer_size[1] <= 0.0 {
+            return;
+        }
+        self.set_state(draw_data, framebuffer_size);
+
+        let stride = mem::size_of::<imgui::DrawVert>() as i32;
+        for list in draw_data.draw_lists() {
+            let vertices = list.vtx_buffer();
+            let indices = list.idx_buffer();
+
+            for command in list.commands() {
+                match command {
+                    imgui::DrawCmd::Elements { count, cmd_params } => {
+                        let clip = cmd_params.clip_rect;
+                        let min = [
+                            (clip[0] - draw_data.display_pos[0]) * scale_x,
+                            (clip[1] - draw_data.display_pos[1]) * scale_y,
+                        ];
+                        let max = [
+                            (clip[2] - draw_data.display_pos[0]) * scale_x,
+                            (clip[3] - draw_data.display_pos[1]) * scale_y,
+                        ];
+                        if max[0] <= min[0] || max[1] <= min[1] {
+                            continue;
+                        }
+                        gl::Scissor(
+                            min[0] as i32,
+                            (framebuffer_size[1] - max[1]) as i32,
+                            (max[0] - min[0]) as i32,
+                            (max[1] - min[1]) as i32,
+                        );
+                        gl::BindTexture(gl::TEXTURE_2D, cmd_params.texture_id.id() as u32);
+
+                        // There is no base vertex before OpenGL 3.2, so the arrays start at it.
+                        let vertex = vertices[cmd_params.vtx_offset..].as_ptr() as *const u8;
+                        gl::VertexAttribPointer(
+                            self.locations.pos,
+                            2,
+                            gl::FLOAT,
+                            gl::FALSE,
+                            stride,
+                            vertex as _,
+                        );
+                        gl::VertexAttribPointer(
+                            self.locations.uv,
+                            2,
+                            gl::FLOAT,
+                            gl::FALSE,
+                            stride,
+                            vertex.add(8) as _,
+                        );
+                        gl::VertexAttribPointer(
+                            self.locations.color,
+                            4,
+                            gl::UNSIGNED_BYTE,
+                            gl::TRUE,
+                            stride,
+                            vertex.add(16) as _,
+                        );
+                        gl::DrawElements(
+                            gl::TRIANGLES,
+                            count as i32,
+                            gl::UNSIGNED_SHORT,
+                            indices[cmd_params.idx_offset..].as_ptr() as _,
+                        );
+                    }
+                    imgui::DrawCmd::ResetRenderState => self.set_state(draw_data, framebuffer_size),
+                    imgui::DrawCmd::RawCallback { callback, raw_cmd } => {
+                        callback(list.raw(), raw_cmd)
+                    }
+                }
+            }
+        }
+
+        gl::DisableVertexAttribArray(self.locations.pos);
+        gl::DisableVertexAttribArray(self.locations.uv);
+        gl::DisableVertexAttribArray(self.locations.color);
+        gl::Disable(gl::SCISSOR_TEST);
+        gl::UseProgram(0);
+    }
+
+    pub fn reload_font_texture(&mut self, imgui_context: &mut imgui::Context) {
+        self.context.make_current();
+
+        let old = self.font_texture.id() as u32;
+        unsafe {
+            gl::DeleteTextures(1, &old);
+        }
+        self.font_texture = upload_font_atlas(imgui_context);
+
+        self.context.make_not_current();
+    }
+
+    pub fn font_texture(&self) -> imgui::TextureId {
+        self.font_texture
+    }
+
+    pub fn create_texture(&mut self, width: u32, height: u32, rgba: &[u8]) -> imgui::TextureId {
+        self.context.make_current();
+        let texture = upload_texture(width, height, rgba);
+        self.context.make_not_current();
+
+        self.textures.insert(texture);
+        imgui::TextureId::new(texture as usize)
+    }
+
+    pub fn create_empty_texture(&mut self, width: u32, height: u32) -> imgui::TextureId {
+        self.context.make_current();
+        let texture = empty_texture(width, height);
+        self.context.make_not_current();
+
+        self.textures.insert(texture);
+        imgui::TextureId::new(texture as usize)
+    }
+
+    pub fn upload_texture_rows(
+        &mut self,
+        texture_id: imgui::TextureId,
+        width: u32,
+        y: u32,
+        rgba: &[u8],
+    ) {
+        self.context.make_current();
+        upload_rows(texture_id.id() as u32, width, y, rgba);
+        self.context.make_not_current();
+    }
+
+    pub fn delete_texture(&mut self, texture_id: imgui::TextureId) {
+        self.context.make_current();
+        let texture = texture_id.id() as u32;
+        unsafe {
+            gl::DeleteTextures(1, &texture);
+        }
+        self.context.make_not_current();
+
+        self.textures.remove(&texture);
+    }
+
+    pub fn texture_count(&self) -> usize {
+        self.textures.len()
+    }
+
+    pub fn create_render_target(
+        &mut self,
+        width: u32,
+        height: u32,
+    ) -> (imgui::TextureId, TargetHandle) {
+        let pixels = vec![0; width as usize * height as usize * 4];
+        self.context.make_current();
+        let texture = upload_texture(width, height, &pixels);
+        self.context.make_not_current();
+
+        self.next_target += 1;
+        let handle = TargetHandle(self.next_target);
+        self.targets.insert(handle, texture);
+
+        (imgui::TextureId::new(texture as usize), handle)
+    }
+
+    pub fn delete_render_target(&mut self, target: TargetHandle) {
+        if let Some(texture) = self.targets.remove(&target) {
+            self.context.make_current();
+            unsafe {
+                gl::DeleteTextures(1, &texture);
+            }
+            self.context.make_not_current();
+        }
+    }
+
+    pub fn name(&self) -> String {
+        let api = if self.es { "OpenGL ES" } else { "OpenGL" };
+        format!("legacy {} {}.{}", api, self.version.0, self.version.1)
+    }
+
+    pub fn adapter_info(&self) -> &AdapterInfo {
+        &self.adapter
+    }
+}
+
+impl Drop for LegacyRenderer {
+    fn drop(&mut self) {
+        // Windows that are dropped without closing still delete their objects in their own
+        // context.
+        self.destroy();
+    }
+}
+
+/// Upload the font atlas as RGBA32 and point the context to it. The context must be current.
+fn upload_font_atlas(imgui_context: &mut imgui::Context) -> imgui::TextureId {
+    let mut fonts = imgui_context.fonts();
+    let atlas = fonts.build_rgba32_texture();
+    let texture = upload_texture(atlas.width, atlas.height, atlas.data);
+
+    let texture = imgui::TextureId::new(texture as usize);
+    fonts.tex_id = texture;
+    texture
+}
@@ -1,6 +1,376 @@
 #[cfg(feature = "opengl")]
+mod backend;
+#[cfg(feature = "opengl")]
+mod blur;
+#[cfg(feature = "opengl")]
+mod color_filter;
+#[cfg(feature = "opengl")]
+mod legacy;
+#[cfg(feature = "opengl")]
 mod opengl_renderer;
 #[cfg(feature = "opengl")]
-pub use opengl_renderer::RenderSettings;
+mod render_targets;
+#[cfg(feature = "opengl")]
+mod shaders;
+#[cfg(feature = "opengl")]
+mod shape_mask;
+#[cfg(feature = "software_renderer")]
+mod software;
+#[cfg(feature = "opengl")]
+pub use backend::{BackendFactory, RendererBackend};
+#[cfg(feature = "opengl")]
+pub use opengl_renderer::{GlContextMode, RenderSettings, SharedContext};
+#[cfg(feature = "opengl")]
+pub use render_targets::{TargetFrame, TargetHandle};
+#[cfg(feature = "software_renderer")]
+pub use software::software_backend;
+
+#[cfg(feature = "opengl")]
+use std::any::Any;
+#[cfg(feature = "opengl")]
+use std::collections::HashMap;
+#[cfg(feature = "opengl")]
+use std::time::Duration;
+
+#[cfg(feature = "opengl")]
+use backend::CustomRenderer;
+#[cfg(feature = "opengl")]
+use legacy::LegacyRenderer;
+#[cfg(feature = "opengl")]
+use opengl_renderer::OpenGlRenderer;
+
+#[cfg(feature = "opengl")]
+use crate::shader::Quad;
+
+/// What the GPU and its driver tell about themselves, for support requests about a window
+/// that stays black or glitches on some machines.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdapterInfo {
+    /// Like `NVIDIA Corporation`.
+    pub vendor: String,
+    /// The GPU, like `NVIDIA GeForce RTX 3060/PCIe/SSE2`.
+    pub renderer: String,
+    /// The version of the API and the driver, like `4.6.0 NVIDIA 535.98`.
+    pub version: String,
+    /// The version of the shading language, empty if the driver doesn't tell.
+    pub shading_language: String,
+    /// The extensions of interest that the driver has, like `GL_KHR_debug`.
+    pub extensions: Vec<String>,
+}
+
+/// The built-in OpenGL renderer, its fallback for OpenGL 2, or a backend from
+/// `Settings::renderer_backend`.
+#[cfg(feature = "opengl")]
+pub(crate) enum Renderer {
+    OpenGl(OpenGlRenderer),
+    Legacy(LegacyRenderer),
+    Custom(CustomRenderer),
+}
+
 #[cfg(feature = "opengl")]
-pub(crate) use opengl_renderer::Renderer;
+impl Renderer {
+    /// The renderer of OpenGL 3, or the legacy one if the context is older. With the
+    /// `software_renderer` feature, the software renderer if there is no usable context.
+    pub fn open_gl(
+        window: &baseview::Window,
+        imgui_context: &mut imgui::Context,
+        mut render_settings: RenderSettings,
+        mode: GlContextMode,
+        shaders: &[crate::shader::FragmentShader],
+        alpha: f32,
+        shape: crate::settings::WindowShape,
+    ) -> Self {
+        // Shaped windows get their corners cut out of the alpha channel.
+        if alpha < 1.0 || shape != crate::settings::WindowShape::Rectangle {
+            render_settings.alpha_bits = render_settings.alpha_bits.max(8);
+        }
+        let context =
+            opengl_renderer::create_context(window, &mut render_settings, mode, alpha, shape);
+        #[cfg(feature = "software_renderer")]
+        let mut context = match context {
+            Some(context) => context,
+            None => return Self::software(window, imgui_context),
+        };
+        #[cfg(not(feature = "software_renderer"))]
+        let context = context.expect("failed to create an OpenGL context");
+
+        // Drivers that don't tell their version are treated as new enough.
+        if let Some((version, es)) = opengl_renderer::context_version() {
+            // Like the OpenGL 1.1 of the Windows GDI in virtual machines.
+            #[cfg(feature = "software_renderer")]
+            if version < (2, 0) && !matches!(mode, GlContextMode::External { .. }) {
+                context.destroy();
+                return Self::software(window, imgui_context);
+            }
+            if version < (3, 0) {
+                let renderer = LegacyRenderer::new(context, imgui_context, version, es);
+                return Renderer::Legacy(renderer);
+            }
+        }
+        Renderer::OpenGl(OpenGlRenderer::new(
+            context,
+            imgui_context,
+            render_settings,
+            mode,
+            shaders,
+            alpha,
+            shape,
+        ))
+    }
+
+    pub fn custom(
+        window: &baseview::Window,
+        imgui_context: &mut imgui::Context,
+        factory: BackendFactory,
+    ) -> Self {
+        let backend = factory(window, imgui_context);
+        Renderer::Custom(CustomRenderer::new(backend, imgui_context))
+    }
+
+    /// A renderer around `backend` without a window, for tests.
+    #[cfg(test)]
+    pub fn headless(imgui_context: &mut imgui::Context, backend: Box<dyn RendererBackend>) -> Self {
+        Renderer::Custom(CustomRenderer::new(backend, imgui_context))
+    }
+
+    #[cfg(feature = "software_renderer")]
+    fn software(window: &baseview::Window, imgui_context: &mut imgui::Context) -> Self {
+        Self::custom(window, imgui_context, software_backend)
+    }
+
+    /// Whether the OpenGL context was reset. Custom backends handle their own device loss.
+    pub fn is_context_lost(&self) -> bool {
+        match self {
+            Renderer::OpenGl(renderer) => renderer.is_context_lost(),
+            Renderer::Legacy(_) | Renderer::Custom(_) => false,
+        }
+    }
+
+    /// How long the last frame waited for the buffer swap. Custom backends count it as
+    /// rendering.
+    pub fn swap_time(&self) -> Duration {
+        match self {
+            Renderer::OpenGl(renderer) => renderer.swap_time(),
+            Renderer::Legacy(renderer) => renderer.swap_time(),
+            Renderer::Custom(_) => Duration::default(),
+        }
+    }
+
+    /// Show the frames as they look with a color vision deficiency. Only the built-in
+    /// renderer for OpenGL 3 can.
+    pub fn set_color_vision(&mut self, vision: Option<crate::theme::ColorVision>) {
+        if let Renderer::OpenGl(renderer) = self {
+            renderer.set_color_vision(vision);
+        }
+    }
+
+    pub fn set_latency_mode(&mut self, mode: crate::settings::LatencyMode) {
+        match self {
+            Renderer::OpenGl(renderer) => renderer.set_latency_mode(mode),
+            Renderer::Legacy(renderer) => renderer.set_latency_mode(mode),
+            Renderer::Custom(renderer) => renderer.set_latency_mode(mode),
+        }
+    }
+
+    /// Render less for `Settings::frame_budget`. Returns whether the context has to be
+    /// created again, which drops what was in it like a lost context.
+    pub fn degrade(&mut self) -> bool {
+        match self {
+            Renderer::OpenGl(renderer) => renderer.degrade(),
+            Renderer::Legacy(_) | Renderer::Custom(_) => false,
+        }
+    }
+
+    /// Delete what the renderer created on the GPU and drop its context, when the window
+    /// closes. Nothing can be rendered afterwards.
+    pub fn destroy(&mut self) {
+        match self {
+            Renderer::OpenGl(renderer) => renderer.destroy(),
+            Renderer::Legacy(renderer) => renderer.destroy(),
+            Renderer::Custom(renderer) => renderer.destroy(),
+        }
+    }
+
+    /// Whether the window is drawn by the renderer for OpenGL 2.
+    pub fn is_legacy(&self) -> bool {
+        matches!(self, Renderer::Legacy(_))
+    }
+
+    pub fn recreate(
+        &mut self,
+        window: &baseview::Window,
+        imgui_context: &mut imgui::Context,
+    ) -> HashMap<usize, imgui::TextureId> {
+        match self {
+            Renderer::OpenGl(renderer) => renderer.recreate(window, imgui_context),
+            Renderer::Legacy(_) | Renderer::Custom(_) => HashMap::new(),
+        }
+    }
+
+    pub fn render(
+        &mut self,
+        imgui_ui: imgui::Ui,
+        clear_color: (f32, f32, f32),
+        quads: &[Quad],
+        state: &mut dyn Any,
+    ) {
+        match self {
+            Renderer::OpenGl(renderer) => renderer.render(imgui_ui, clear_color, quads, state),
+            Renderer::Legacy(renderer) => renderer.render(imgui_ui, clear_color),
+            Renderer::Custom(renderer) => renderer.render(imgui_ui, clear_color),
+        }
+    }
+
+    /// The window was resized. The OpenGL renderer follows the size of the window itself.
+    pub fn resize(&mut self, physical_size: [u32; 2]) {
+        if let Renderer::Custom(renderer) = self {
+            renderer.resize(physical_size);
+        }
+    }
+
+    pub fn reload_font_texture(&mut self, imgui_context: &mut imgui::Context) {
+        match self {
+            Renderer::OpenGl(renderer) => renderer.reload_font_texture(imgui_context),
+            Renderer::Legacy(renderer) => renderer.reload_font_texture(imgui_context),
+            Renderer::Custom(renderer) => renderer.reload_font_texture(imgui_context),
+        }
+    }
+
+    pub fn font_texture(&self) -> imgui::TextureId {
+        match self {
+            Renderer::OpenGl(renderer) => renderer.font_texture(),
+            Renderer::Legacy(renderer) => renderer.font_texture(),
+            Renderer::Custom(renderer) => renderer.font_texture(),
+        }
+    }
+
+    pub fn create_texture(&mut self, width: u32, height: u32, rgba: &[u8]) -> imgui::TextureId {
+        match self {
+            Renderer::OpenGl(renderer) => renderer.create_texture(width, height, rgba),
+            Renderer::Legacy(renderer) => renderer.create_texture(width, height, rgba),
+            Renderer::Custom(renderer) => renderer.create_texture(width, height, rgba),
+        }
+    }
+
+    /// Create a texture without pixels, to fill a few rows at a time with
+    /// `upload_texture_rows`. Custom backends can only upload whole textures, so `None`.
+    pub fn create_empty_texture(&mut self, width: u32, height: u32) -> Option<imgui::TextureId> {
+        match self {
+            Renderer::OpenGl(renderer) => Some(renderer.create_empty_texture(width, height)),
+            Renderer::Legacy(renderer) => Some(renderer.create_empty_texture(width, height)),
+            Renderer::Custom(_) => None,
+        }
+    }
+
+    /// Upload whole rows of a texture from `create_empty_texture`, starting at row `y`.
+    pub fn upload_texture_rows(
+        &mut self,
+        texture_id: imgui::TextureId,
+        width: u32,
+        y: u32,
+        rgba: &[u8],
+    ) {
+        match self {
+            Renderer::OpenGl(renderer) => renderer.upload_texture_rows(texture_id, y, rgba),
+            Renderer::Legacy(renderer) => renderer.upload_texture_rows(texture_id, width, y, rgba),
+            Renderer::Custom(_) => {}
+        }
+    }
+
+    pub fn delete_texture(&mut self, texture_id: imgui::TextureId) {
+        match self {
+            Renderer::OpenGl(renderer) => renderer.delete_texture(texture_id),
+            Renderer::Legacy(renderer) => renderer.delete_texture(texture_id),
+            Renderer::Custom(renderer) => renderer.delete_texture(texture_id),
+        }
+    }
+
+    pub fn shader_targets(&self) -> Vec<imgui::TextureId> {
+        match self {
+            Renderer::OpenGl(renderer) => renderer.shader_targets(),
+            Renderer::Legacy(_) | Renderer::Custom(_) => Vec::new(),
+        }
+    }
+
+    pub fn create_render_target(
+        &mut self,
+        width: u32,
+        height: u32,
+    ) -> (imgui::TextureId, TargetHandle) {
+        match self {
+            Renderer::OpenGl(renderer) => renderer.create_render_target(width, height),
+            Renderer::Legacy(renderer) => renderer.create_render_target(width, height),
+            Renderer::Custom(renderer) => renderer.create_render_target(width, height),
+        }
+    }
+
+    pub fn resize_render_target(&mut self, target: TargetHandle, width: u32, height: u32) {
+        if let Renderer::OpenGl(renderer) = self {
+            renderer.resize_render_target(target, width, height);
+        }
+    }
+
+    pub fn set_render_callback<S, F>(&mut self, target: TargetHandle, callback: F)
+    where
+        S: 'static,
+        F: FnMut(&mut S, &TargetFrame) + 'static,
+    {
+        if let Renderer::OpenGl(renderer) = self {
+            renderer.set_render_callback(target, callback);
+        }
+    }
+
+    pub fn delete_render_target(&mut self, target: TargetHandle) {
+        match self {
+            Renderer::OpenGl(renderer) => renderer.delete_render_target(target),
+            Renderer::Legacy(renderer) => renderer.delete_render_target(target),
+            Renderer::Custom(renderer) => renderer.delete_render_target(target),
+        }
+    }
+
+    /// Blur `texture` every frame, returning the texture the result is rendered into. The
+    /// legacy renderer and custom backends show the texture as it is.
+    pub fn set_blur_source(
+        &mut self,
+        texture: imgui::TextureId,
+        size: [u32; 2],
+        passes: u32,
+        offset: f32,
+    ) -> imgui::TextureId {
+        match self {
+            Renderer::OpenGl(renderer) => renderer.set_blur_source(texture, size, passes, offset),
+            Renderer::Legacy(_) | Renderer::Custom(_) => texture,
+        }
+    }
+
+    pub fn clear_blur_source(&mut self) {
+        if let Renderer::OpenGl(renderer) = self {
+            renderer.clear_blur_source();
+        }
+    }
+
+    pub fn texture_count(&self) -> usize {
+        match self {
+            Renderer::OpenGl(renderer) => renderer.texture_count(),
+            Renderer::Legacy(renderer) => renderer.texture_count(),
+            Renderer::Custom(renderer) => renderer.texture_count(),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Renderer::OpenGl(_) => OpenGlRenderer::name(),
+            Renderer::Legacy(renderer) => renderer.name(),
+            Renderer::Custom(renderer) => renderer.name(),
+        }
+    }
+
+    pub fn adapter_info(&self) -> AdapterInfo {
+        match self {
+            Renderer::OpenGl(renderer) => renderer.adapter_info().clone(),
+            Renderer::Legacy(renderer) => renderer.adapter_info().clone(),
+            Renderer::Custom(renderer) => renderer.adapter_info(),
+        }
+    }
+}
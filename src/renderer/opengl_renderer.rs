@@ -1,54 +1,736 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::time::{Duration, Instant};
+
 use baseview::Window;
 use raw_gl_context::GlContext;
 
+use super::blur::Blur;
+use super::color_filter::ColorFilter;
+use super::render_targets::{RenderTargets, TargetFrame, TargetHandle};
+use super::shaders::ShaderQuads;
+use super::shape_mask::ShapeMask;
+use super::AdapterInfo;
+use crate::settings::{LatencyMode, WindowShape};
+use crate::shader::{FragmentShader, Quad};
+use crate::theme::ColorVision;
+
 pub use raw_gl_context::GlConfig as RenderSettings;
 
-pub struct Renderer {
-    context: GlContext,
-    imgui_renderer: imgui_opengl_renderer::Renderer,
+/// A context of the host to share textures and buffers with, a `HGLRC` on Windows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SharedContext(pub *mut c_void);
+
+// The handle is only used on the thread of the window.
+unsafe impl Send for SharedContext {}
+unsafe impl Sync for SharedContext {}
+
+/// Where the window renders into.
+///
+/// `RenderSettings` is the `GlConfig` of raw-gl-context, so the mode is kept next to it in
+/// `Settings::gl_context`.
+#[derive(Clone, Copy)]
+#[allow(missing_debug_implementations)]
+pub enum GlContextMode {
+    /// Create a context for the window.
+    Owned,
+    /// Create a context for the window that shares its objects with a context of the host,
+    /// so both can use the same textures. Only supported on Windows.
+    Shared(SharedContext),
+    /// Don't create a context, for frameworks that already have one on the window. Frames
+    /// only run when the host asks for one with `Handle::render_external`, and render into
+    /// the context that is current on the thread of the window then, without presenting it.
+    External {
+        /// Looks up the OpenGL functions of the context of the host.
+        get_proc_address: fn(&str) -> *const c_void,
+    },
+}
+
+impl Default for GlContextMode {
+    fn default() -> Self {
+        GlContextMode::Owned
+    }
+}
+
+impl GlContextMode {
+    /// Whether the mode works on this platform.
+    pub fn is_supported(&self) -> bool {
+        match self {
+            GlContextMode::Shared(_) => cfg!(target_os = "windows"),
+            _ => true,
+        }
+    }
+}
+
+/// The context the renderer draws with.
+pub(crate) enum Gl {
+    Owned(GlContext),
+    External(fn(&str) -> *const c_void),
+    /// The window closed, and the objects and the context were destroyed.
+    Destroyed,
+}
+
+impl Gl {
+    pub fn make_current(&self) {
+        if let Gl::Owned(context) = self {
+            context.make_current();
+        }
+    }
+
+    pub fn make_not_current(&self) {
+        if let Gl::Owned(context) = self {
+            context.make_not_current();
+        }
+    }
+
+    /// The host presents the frame of an external context.
+    pub fn swap_buffers(&self) {
+        if let Gl::Owned(context) = self {
+            context.swap_buffers();
+        }
+    }
+
+    pub fn get_proc_address(&self, symbol: &str) -> *const c_void {
+        match self {
+            Gl::Owned(context) => context.get_proc_address(symbol),
+            Gl::External(get_proc_address) => get_proc_address(symbol),
+            Gl::Destroyed => std::ptr::null(),
+        }
+    }
+
+    /// Drop the context once nothing is left in it. The context of the host is left alone.
+    pub fn destroy(&mut self) {
+        self.make_not_current();
+        *self = Gl::Destroyed;
+    }
+}
+
+/// The pixels of a texture created with `create_texture`, to upload it again into a new
+/// context.
+struct KeptTexture {
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
 }
 
-impl Renderer {
+pub struct OpenGlRenderer {
+    context: Gl,
+    /// What the context was created with, to create it again after it is lost.
+    render_settings: RenderSettings,
+    mode: GlContextMode,
+    shaders: Vec<FragmentShader>,
+    shape: WindowShape,
+    /// `None` once the renderer was destroyed.
+    imgui_renderer: Option<imgui_opengl_renderer::Renderer>,
+    font_texture: imgui::TextureId,
+    textures: HashMap<u32, KeptTexture>,
+    /// The alpha the framebuffer is cleared with.
+    alpha: f32,
+    shader_quads: ShaderQuads,
+    render_targets: RenderTargets,
+    blur: Blur,
+    /// The mask and corner radius in logical pixels of a rounded window.
+    shape_mask: Option<(ShapeMask, f32)>,
+    /// The deficiency the debug overlay simulates, with the filter once it was used.
+    color_vision: Option<ColorVision>,
+    color_filter: Option<ColorFilter>,
+    /// Whether frames took too long, so the backdrop is only blurred when it is set.
+    degraded: bool,
+    /// How long the last frame waited for the buffer swap.
+    swap_time: Duration,
+    latency_mode: LatencyMode,
+    adapter: AdapterInfo,
+}
+
+impl OpenGlRenderer {
+    /// Create the renderer with a context from `create_context`, which is current.
     pub fn new(
-        window: &Window,
+        context: Gl,
         imgui_context: &mut imgui::Context,
         render_settings: RenderSettings,
+        mode: GlContextMode,
+        shaders: &[FragmentShader],
+        alpha: f32,
+        shape: WindowShape,
     ) -> Self {
-        let context = GlContext::create(window, render_settings).unwrap();
-
-        context.make_current();
-
-        gl::load_with(|s| context.get_proc_address(s) as _);
-
         let imgui_renderer = imgui_opengl_renderer::Renderer::new(imgui_context, |s| {
             context.get_proc_address(s) as _
         });
+        let shader_quads = ShaderQuads::new(shaders);
+        let shape_mask = create_shape_mask(shape);
+        let adapter = adapter_info();
 
         context.make_not_current();
 
+        let font_texture = imgui_context.fonts().tex_id;
+
         Self {
             context,
-            imgui_renderer,
+            render_settings,
+            mode,
+            shaders: shaders.to_vec(),
+            shape,
+            imgui_renderer: Some(imgui_renderer),
+            font_texture,
+            textures: HashMap::new(),
+            alpha: alpha.max(0.0).min(1.0),
+            shader_quads,
+            render_targets: RenderTargets::default(),
+            blur: Blur::default(),
+            shape_mask,
+            color_vision: None,
+            color_filter: None,
+            degraded: false,
+            swap_time: Duration::default(),
+            latency_mode: LatencyMode::default(),
+            adapter,
+        }
+    }
+
+    /// Whether the driver reset the context, for example after a driver update or when a
+    /// remote desktop session took over the GPU. Everything in it is gone, and nothing drawn
+    /// with it shows up.
+    pub fn is_context_lost(&self) -> bool {
+        // The host takes care of its own context.
+        if matches!(self.context, Gl::External(_) | Gl::Destroyed) {
+            return false;
+        }
+        if !gl::GetGraphicsResetStatus::is_loaded() {
+            return false;
+        }
+
+        self.context.make_current();
+        let status = unsafe { gl::GetGraphicsResetStatus() };
+        self.context.make_not_current();
+
+        status != gl::NO_ERROR
+    }
+
+    /// Replace a lost context with a new one, and upload the font atlas, the textures of
+    /// `create_texture` and the render targets into it again.
+    ///
+    /// Returns the new texture id of every texture whose id changed. The backdrop is not
+    /// blurred anymore, and the render callbacks must create their own objects again.
+    pub fn recreate(
+        &mut self,
+        window: &Window,
+        imgui_context: &mut imgui::Context,
+    ) -> HashMap<usize, imgui::TextureId> {
+        let context = create_context(
+            window,
+            &mut self.render_settings,
+            self.mode,
+            self.alpha,
+            self.shape,
+        )
+        .expect("failed to create the OpenGL context again");
+        let imgui_renderer = imgui_opengl_renderer::Renderer::new(imgui_context, |s| {
+            context.get_proc_address(s) as _
+        });
+        // The new context can be on another GPU, like when a laptop switched to the other one.
+        self.adapter = adapter_info();
+        context.make_not_current();
+
+        // The old renderer deletes its objects in the old context.
+        self.context.make_current();
+        self.imgui_renderer = Some(imgui_renderer);
+        self.context.make_not_current();
+        self.context = context;
+
+        let mut remap = HashMap::new();
+        let font_texture = imgui_context.fonts().tex_id;
+        remap.insert(self.font_texture.id(), font_texture);
+        self.font_texture = font_texture;
+
+        self.context.make_current();
+
+        self.shader_quads = ShaderQuads::new(&self.shaders);
+        self.shape_mask = create_shape_mask(self.shape);
+        self.blur = Blur::default();
+        self.color_filter = None;
+        for (old, new) in self.render_targets.recreate() {
+            remap.insert(old.id(), new);
+        }
+
+        // Uploading in the order they were created gives them the same names on most drivers.
+        let mut textures: Vec<_> = self.textures.drain().collect();
+        textures.sort_by_key(|(texture, _)| *texture);
+        for (old, kept) in textures {
+            let texture = upload_texture(kept.width, kept.height, &kept.rgba);
+            remap.insert(old as usize, imgui::TextureId::new(texture as usize));
+            self.textures.insert(texture, kept);
         }
+
+        self.context.make_not_current();
+
+        remap.retain(|old, new| *old != new.id());
+        remap
     }
 
-    pub fn render(&mut self, imgui_ui: imgui::Ui, clear_color: (f32, f32, f32)) {
+    /// Render the quads of `ui_shader_rect` and the render targets into their textures, blur
+    /// the backdrop, then render the UI.
+    pub fn render(
+        &mut self,
+        imgui_ui: imgui::Ui,
+        clear_color: (f32, f32, f32),
+        quads: &[Quad],
+        state: &mut dyn Any,
+    ) {
         self.context.make_current();
 
+        self.shader_quads.render(quads);
+        let context = &self.context;
+        self.render_targets
+            .render(state, &|s| context.get_proc_address(s) as _);
+        if !(self.degraded && self.blur.is_blurred()) {
+            self.blur.render();
+        }
+
         unsafe {
-            gl::ClearColor(clear_color.0, clear_color.1, clear_color.2, 1.0);
+            gl::ClearColor(clear_color.0, clear_color.1, clear_color.2, self.alpha);
             gl::Clear(gl::COLOR_BUFFER_BIT);
         }
 
-        self.imgui_renderer.render(imgui_ui);
+        let hidpi_factor = imgui_ui.io().display_framebuffer_scale[0];
+        if let Some(imgui_renderer) = &self.imgui_renderer {
+            imgui_renderer.render(imgui_ui);
+        }
+
+        if let Some(vision) = self.color_vision {
+            self.color_filter
+                .get_or_insert_with(ColorFilter::new)
+                .render(vision);
+        }
+
+        if let Some((mask, radius)) = &self.shape_mask {
+            mask.render(radius * hidpi_factor);
+        }
 
         // ...
 
+        let swap_start = Instant::now();
         self.context.swap_buffers();
+        if self.latency_mode == LatencyMode::LowLatency {
+            unsafe {
+                gl::Finish();
+            }
+        }
+        self.swap_time = swap_start.elapsed();
         self.context.make_not_current();
     }
 
+    pub fn set_latency_mode(&mut self, mode: LatencyMode) {
+        self.latency_mode = mode;
+    }
+
+    pub fn swap_time(&self) -> Duration {
+        self.swap_time
+    }
+
+    /// Delete the textures, buffers and programs with the context current, then drop the
+    /// context. Nothing can be rendered afterwards.
+    pub fn destroy(&mut self) {
+        if let Gl::Destroyed = self.context {
+            return;
+        }
+        self.context.make_current();
+
+        // The UI program, its buffers and the font atlas.
+        self.imgui_renderer = None;
+        for (texture, _) in self.textures.drain() {
+            unsafe {
+                gl::DeleteTextures(1, &texture);
+            }
+        }
+        self.render_targets.delete_all();
+        self.blur.delete();
+        self.shader_quads.delete();
+        if let Some((mask, _)) = self.shape_mask.take() {
+            mask.delete();
+        }
+        if let Some(filter) = self.color_filter.take() {
+            filter.delete();
+        }
+
+        self.context.destroy();
+    }
+
+    pub fn set_color_vision(&mut self, vision: Option<ColorVision>) {
+        self.color_vision = vision;
+    }
+
+    /// Stop blurring the backdrop every frame, and multisampling. Returns whether the context
+    /// has to be created again without multisampling.
+    pub fn degrade(&mut self) -> bool {
+        self.degraded = true;
+        self.render_settings.samples.take().is_some()
+    }
+
+    /// Upload the font atlas of the context again after it was rebuilt.
+    ///
+    /// The atlas is always uploaded as RGBA32, so colored glyphs from the FreeType rasterizer
+    /// work the same as the alpha-only glyphs from stb_truetype.
+    pub fn reload_font_texture(&mut self, imgui_context: &mut imgui::Context) {
+        self.context.make_current();
+
+        let context = &self.context;
+        self.imgui_renderer = Some(imgui_opengl_renderer::Renderer::new(imgui_context, |s| {
+            context.get_proc_address(s) as _
+        }));
+        self.font_texture = imgui_context.fonts().tex_id;
+
+        self.context.make_not_current();
+    }
+
+    /// The texture the font atlas was uploaded to.
+    pub fn font_texture(&self) -> imgui::TextureId {
+        self.font_texture
+    }
+
+    /// Upload an RGBA8 image into a new texture usable with `imgui::Image`.
+    ///
+    /// A copy of the pixels is kept to upload them again if the context is lost.
+    pub fn create_texture(&mut self, width: u32, height: u32, rgba: &[u8]) -> imgui::TextureId {
+        self.context.make_current();
+        let texture = upload_texture(width, height, rgba);
+        self.context.make_not_current();
+
+        self.textures.insert(
+            texture,
+            KeptTexture {
+                width,
+                height,
+                rgba: rgba.to_vec(),
+            },
+        );
+
+        imgui::TextureId::new(texture as usize)
+    }
+
+    /// Create a texture to fill with `upload_texture_rows`, for uploads spread over frames.
+    pub fn create_empty_texture(&mut self, width: u32, height: u32) -> imgui::TextureId {
+        self.context.make_current();
+        let texture = empty_texture(width, height);
+        self.context.make_not_current();
+
+        self.textures.insert(
+            texture,
+            KeptTexture {
+                width,
+                height,
+                rgba: vec![0; width as usize * height as usize * 4],
+            },
+        );
+
+        imgui::TextureId::new(texture as usize)
+    }
+
+    pub fn upload_texture_rows(&mut self, texture_id: imgui::TextureId, y: u32, rgba: &[u8]) {
+        let texture = texture_id.id() as u32;
+        let kept = match self.textures.get_mut(&texture) {
+            Some(kept) => kept,
+            None => return,
+        };
+        let start = y as usize * kept.width as usize * 4;
+        let end = (start + rgba.len()).min(kept.rgba.len());
+        kept.rgba[start..end].copy_from_slice(&rgba[..end - start]);
+
+        self.context.make_current();
+        upload_rows(texture, kept.width, y, &rgba[..end - start]);
+        self.context.make_not_current();
+    }
+
+    /// Delete a texture previously created with `create_texture`.
+    pub fn delete_texture(&mut self, texture_id: imgui::TextureId) {
+        self.context.make_current();
+
+        let texture = texture_id.id() as u32;
+        unsafe {
+            gl::DeleteTextures(1, &texture);
+        }
+
+        self.context.make_not_current();
+
+        self.textures.remove(&texture);
+    }
+
+    /// The textures that the quads of the next frame are rendered into.
+    pub fn shader_targets(&self) -> Vec<imgui::TextureId> {
+        self.shader_quads.targets()
+    }
+
+    /// Create a texture with a framebuffer to render into, usable with `imgui::Image`.
+    pub fn create_render_target(
+        &mut self,
+        width: u32,
+        height: u32,
+    ) -> (imgui::TextureId, TargetHandle) {
+        self.context.make_current();
+        let target = self.render_targets.create(width, height);
+        self.context.make_not_current();
+
+        target
+    }
+
+    /// Resize a render target, keeping its texture id.
+    pub fn resize_render_target(&mut self, target: TargetHandle, width: u32, height: u32) {
+        self.context.make_current();
+        self.render_targets.resize(target, width, height);
+        self.context.make_not_current();
+    }
+
+    /// Call `callback` every frame to render into the target, before the UI is drawn. It is
+    /// only called if the state of the window is of type `S`.
+    pub fn set_render_callback<S, F>(&mut self, target: TargetHandle, mut callback: F)
+    where
+        S: 'static,
+        F: FnMut(&mut S, &TargetFrame) + 'static,
+    {
+        self.render_targets.set_callback(
+            target,
+            Box::new(move |state, frame| {
+                if let Some(state) = state.downcast_mut() {
+                    callback(state, frame)
+                }
+            }),
+        );
+    }
+
+    pub fn delete_render_target(&mut self, target: TargetHandle) {
+        self.context.make_current();
+        self.render_targets.delete(target);
+        self.context.make_not_current();
+    }
+
+    /// Blur `texture` every frame, returning the texture the result is rendered into.
+    pub fn set_blur_source(
+        &mut self,
+        texture: imgui::TextureId,
+        size: [u32; 2],
+        passes: u32,
+        offset: f32,
+    ) -> imgui::TextureId {
+        self.context.make_current();
+        let blurred = self
+            .blur
+            .set_source(texture.id() as u32, size, passes, offset);
+        self.context.make_not_current();
+
+        blurred
+    }
+
+    pub fn clear_blur_source(&mut self) {
+        self.context.make_current();
+        self.blur.clear_source();
+        self.context.make_not_current();
+    }
+
+    /// The number of textures created with `create_texture` that are still alive.
+    pub fn texture_count(&self) -> usize {
+        self.textures.len()
+    }
+
     pub fn name() -> String {
         String::from("imgui_opengl_renderer 0.10")
     }
+
+    pub fn adapter_info(&self) -> &AdapterInfo {
+        &self.adapter
+    }
+}
+
+impl Drop for OpenGlRenderer {
+    fn drop(&mut self) {
+        // Windows that are dropped without closing, like when the host destroys them, still
+        // delete their objects in their own context.
+        self.destroy();
+    }
+}
+
+/// Create a context for the window and make it current, or load the functions of the
+/// external one.
+///
+/// Drivers that can't create a context of the version in `render_settings` get an OpenGL 2.1
+/// context, and `render_settings` is changed to it. `None` if that fails too.
+pub(crate) fn create_context(
+    window: &Window,
+    render_settings: &mut RenderSettings,
+    mode: GlContextMode,
+    alpha: f32,
+    shape: WindowShape,
+) -> Option<Gl> {
+    let context = match mode {
+        GlContextMode::External { get_proc_address } => Gl::External(get_proc_address),
+        GlContextMode::Owned | GlContextMode::Shared(_) => {
+            let context = match GlContext::create(window, render_settings.clone()) {
+                Ok(context) => context,
+                Err(_) => {
+                    *render_settings = RenderSettings {
+                        version: (2, 1),
+                        profile: raw_gl_context::Profile::Compatibility,
+                        ..render_settings.clone()
+                    };
+                    GlContext::create(window, render_settings.clone()).ok()?
+                }
+            };
+            context.make_current();
+            // Sharing only works before the new context has objects of its own.
+            if let GlContextMode::Shared(shared) = mode {
+                crate::platform::share_current_context(shared.0);
+            }
+            if alpha < 1.0 || shape != WindowShape::Rectangle {
+                crate::platform::make_current_context_transparent();
+            }
+            Gl::Owned(context)
+        }
+    };
+
+    gl::load_with(|s| context.get_proc_address(s) as _);
+
+    Some(context)
+}
+
+/// The version of the current context, and whether it is OpenGL ES.
+pub(crate) fn context_version() -> Option<((u32, u32), bool)> {
+    let version = unsafe {
+        let version = gl::GetString(gl::VERSION);
+        if version.is_null() {
+            return None;
+        }
+        std::ffi::CStr::from_ptr(version as *const _).to_string_lossy()
+    };
+
+    // Like `4.6.0 NVIDIA 535.98` or `OpenGL ES 2.0 Mesa 23.0`.
+    let (version, es) = match version.strip_prefix("OpenGL ES") {
+        Some(version) => (version.trim_start(), true),
+        None => (&*version, false),
+    };
+    let mut numbers = version
+        .split(|c: char| !c.is_ascii_digit())
+        .map(str::parse::<u32>);
+    let major = numbers.next()?.ok()?;
+    let minor = numbers.next().and_then(Result::ok).unwrap_or(0);
+    Some(((major, minor), es))
+}
+
+/// The extensions that `AdapterInfo` lists when the driver has them.
+const EXTENSIONS_OF_INTEREST: &[&str] = &[
+    "GL_ARB_framebuffer_object",
+    "GL_EXT_framebuffer_object",
+    "GL_ARB_vertex_array_object",
+    "GL_OES_vertex_array_object",
+    "GL_ARB_texture_non_power_of_two",
+    "GL_OES_texture_npot",
+    "GL_ARB_framebuffer_sRGB",
+    "GL_ARB_robustness",
+    "GL_KHR_robustness",
+    "GL_KHR_debug",
+    "GL_ARB_timer_query",
+];
+
+/// What the driver of the current context tells about itself.
+pub(crate) fn adapter_info() -> AdapterInfo {
+    let string = |name| unsafe {
+        let string = gl::GetString(name);
+        if string.is_null() {
+            return String::new();
+        }
+        std::ffi::CStr::from_ptr(string as *const _)
+            .to_string_lossy()
+            .into_owned()
+    };
+
+    // Core profiles only list their extensions one by one.
+    let mut extensions = Vec::new();
+    let mut count = 0;
+    unsafe {
+        gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut count);
+        // Contexts older than OpenGL 3 don't know `NUM_EXTENSIONS`, and leave an error.
+        gl::GetError();
+    }
+    if count > 0 {
+        for i in 0..count as u32 {
+            let extension = unsafe { gl::GetStringi(gl::EXTENSIONS, i) };
+            if !extension.is_null() {
+                let extension = unsafe { std::ffi::CStr::from_ptr(extension as *const _) };
+                extensions.push(extension.to_string_lossy().into_owned());
+            }
+        }
+    } else {
+        extensions = string(gl::EXTENSIONS)
+            .split_whitespace()
+            .map(String::from)
+            .collect();
+    }
+    extensions.retain(|extension| EXTENSIONS_OF_INTEREST.contains(&extension.as_str()));
+
+    AdapterInfo {
+        vendor: string(gl::VENDOR),
+        renderer: string(gl::RENDERER),
+        version: string(gl::VERSION),
+        shading_language: string(gl::SHADING_LANGUAGE_VERSION),
+        extensions,
+    }
+}
+
+fn create_shape_mask(shape: WindowShape) -> Option<(ShapeMask, f32)> {
+    match shape {
+        WindowShape::Rectangle => None,
+        WindowShape::Rounded { radius } => Some((ShapeMask::new(), radius)),
+    }
+}
+
+/// Upload an RGBA8 image into a new texture. The context must be current.
+pub(super) fn upload_texture(width: u32, height: u32, rgba: &[u8]) -> u32 {
+    allocate_texture(width, height, rgba.as_ptr() as _)
+}
+
+/// Create a texture whose pixels are undefined until they are uploaded with `upload_rows`.
+pub(super) fn empty_texture(width: u32, height: u32) -> u32 {
+    allocate_texture(width, height, std::ptr::null())
+}
+
+/// Upload whole rows of RGBA8 pixels into a texture, starting at row `y`.
+pub(super) fn upload_rows(texture: u32, width: u32, y: u32, rgba: &[u8]) {
+    let rows = rgba.len() / (width as usize * 4);
+    unsafe {
+        gl::BindTexture(gl::TEXTURE_2D, texture);
+        gl::PixelStorei(gl::UNPACK_ROW_LENGTH, 0);
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+        gl::TexSubImage2D(
+            gl::TEXTURE_2D,
+            0,
+            0,
+            y as _,
+            width as _,
+            rows as _,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            rgba.as_ptr() as _,
+        );
+    }
+}
+
+fn allocate_texture(width: u32, height: u32, data: *const c_void) -> u32 {
+    let mut texture = 0;
+    unsafe {
+        gl::GenTextures(1, &mut texture);
+        gl::BindTexture(gl::TEXTURE_2D, texture);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as _);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as _);
+        gl::PixelStorei(gl::UNPACK_ROW_LENGTH, 0);
+        gl::TexImage2D(
+            gl::TEXTURE_2D,
+            0,
+            gl::RGBA as _,
+            width as _,
+            height as _,
+            0,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            data,
+        );
+    }
+    texture
 }
@@ -0,0 +1,205 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::ffi::c_void;
+
+type Callback = Box<dyn FnMut(&mut dyn Any, &TargetFrame)>;
+
+/// Identifies a render target created with `WindowContext::create_render_target`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TargetHandle(pub(crate) u32);
+
+/// What a render callback gets to draw into its target.
+///
+/// The framebuffer of the target is bound and the viewport covers it when the callback is
+/// called. It has a depth buffer, and nothing is cleared for you.
+pub struct TargetFrame<'a> {
+    /// The size of the target in pixels.
+    pub size: [u32; 2],
+    get_proc_address: &'a dyn Fn(&str) -> *const c_void,
+}
+
+impl<'a> TargetFrame<'a> {
+    /// Look up an OpenGL function, to load your own bindings with.
+    pub fn get_proc_address(&self, symbol: &str) -> *const c_void {
+        (self.get_proc_address)(symbol)
+    }
+}
+
+struct Target {
+    texture: u32,
+    framebuffer: u32,
+    depth: u32,
+    size: [u32; 2],
+    callback: Option<Callback>,
+}
+
+/// Textures with a framebuffer that user callbacks render into every frame. Everything must
+/// be called with the context current.
+#[derive(Default)]
+pub(crate) struct RenderTargets {
+    targets: HashMap<TargetHandle, Target>,
+    next_handle: u32,
+}
+
+impl RenderTargets {
+    pub fn create(&mut self, width: u32, height: u32) -> (imgui::TextureId, TargetHandle) {
+        let mut target = Target {
+            texture: 0,
+            framebuffer: 0,
+            depth: 0,
+            size: [0, 0],
+            callback: None,
+        };
+        target.generate();
+        target.resize([width, height]);
+
+        // Handles are not GL names, so they stay the same when the context is recreated.
+        self.next_handle += 1;
+        let handle = TargetHandle(self.next_handle);
+        let texture_id = imgui::TextureId::new(target.texture as usize);
+        self.targets.insert(handle, target);
+
+        (texture_id, handle)
+    }
+
+    /// Create the objects of every target again in a new context, keeping their sizes and
+    /// callbacks. Returns the old and new texture of each target.
+    pub fn recreate(&mut self) -> Vec<(imgui::TextureId, imgui::TextureId)> {
+        self.targets
+            .values_mut()
+            .map(|target| {
+                let old = imgui::TextureId::new(target.texture as usize);
+                let size = target.size;
+                target.size = [0, 0];
+                target.generate();
+                target.resize(size);
+                (old, imgui::TextureId::new(target.texture as usize))
+            })
+            .collect()
+    }
+
+    pub fn resize(&mut self, handle: TargetHandle, width: u32, height: u32) {
+        if let Some(target) = self.targets.get_mut(&handle) {
+            target.resize([width, height]);
+        }
+    }
+
+    pub fn set_callback(&mut self, handle: TargetHandle, callback: Callback) {
+        if let Some(target) = self.targets.get_mut(&handle) {
+            target.callback = Some(callback);
+        }
+    }
+
+    pub fn delete(&mut self, handle: TargetHandle) {
+        if let Some(target) = self.targets.remove(&handle) {
+            target.delete();
+        }
+    }
+
+    pub fn delete_all(&mut self) {
+        for (_, target) in self.targets.drain() {
+            target.delete();
+        }
+    }
+
+    /// Call the callback of every target with its framebuffer bound.
+    pub fn render(
+        &mut self,
+        state: &mut dyn Any,
+        get_proc_address: &dyn Fn(&str) -> *const c_void,
+    ) {
+        if self.targets.is_empty() {
+            return;
+        }
+
+        unsafe {
+            let mut viewport = [0; 4];
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+
+            for target in self.targets.values_mut() {
+                if let Some(callback) = &mut target.callback {
+                    gl::BindFramebuffer(gl::FRAMEBUFFER, target.framebuffer);
+                    gl::Viewport(0, 0, target.size[0] as _, target.size[1] as _);
+                    let frame = TargetFrame {
+                        size: target.size,
+                        get_proc_address,
+                    };
+                    callback(state, &frame);
+                }
+            }
+
+            // The imgui renderer sets up the rest of the state it needs.
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            gl::Viewport(viewport[0], viewport[1], viewport[2], viewport[3]);
+            gl::Disable(gl::DEPTH_TEST);
+            gl::UseProgram(0);
+            gl::BindVertexArray(0);
+        }
+    }
+}
+
+impl Target {
+    fn generate(&mut self) {
+        unsafe {
+            gl::GenTextures(1, &mut self.texture);
+            gl::BindTexture(gl::TEXTURE_2D, self.texture);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as _);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as _);
+            gl::GenRenderbuffers(1, &mut self.depth);
+            gl::GenFramebuffers(1, &mut self.framebuffer);
+        }
+    }
+
+    fn resize(&mut self, size: [u32; 2]) {
+        let size = [size[0].max(1), size[1].max(1)];
+        if self.size == size {
+            return;
+        }
+
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, self.texture);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA as _,
+                size[0] as _,
+                size[1] as _,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                std::ptr::null(),
+            );
+            gl::BindRenderbuffer(gl::RENDERBUFFER, self.depth);
+            gl::RenderbufferStorage(
+                gl::RENDERBUFFER,
+                gl::DEPTH_COMPONENT24,
+                size[0] as _,
+                size[1] as _,
+            );
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer);
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_2D,
+                self.texture,
+                0,
+            );
+            gl::FramebufferRenderbuffer(
+                gl::FRAMEBUFFER,
+                gl::DEPTH_ATTACHMENT,
+                gl::RENDERBUFFER,
+                self.depth,
+            );
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+        self.size = size;
+    }
+
+    fn delete(self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.framebuffer);
+            gl::DeleteRenderbuffers(1, &self.depth);
+            gl::DeleteTextures(1, &self.texture);
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+use std::ffi::CString;
+
+use crate::shader::{FragmentShader, Quad, ShaderId, MAX_UNIFORMS};
+
+pub(super) const VERTEX_SHADER: &str = "#version 150
+out vec2 v_uv;
+void main() {
+    vec2 position = vec2(gl_VertexID & 1, gl_VertexID >> 1);
+    v_uv = position;
+    gl_Position = vec4(position * 2.0 - 1.0, 0.0, 1.0);
+}
+";
+
+const FRAGMENT_HEADER: &str = "#version 150
+in vec2 v_uv;
+uniform vec2 u_size;
+uniform float u_values[16];
+out vec4 out_color;
+";
+
+struct Program {
+    program: u32,
+    size: i32,
+    values: i32,
+}
+
+/// A texture with a framebuffer to render a quad into.
+struct Target {
+    texture: u32,
+    framebuffer: u32,
+    size: [u32; 2],
+}
+
+/// The user shaders and the textures the quads of a frame are rendered into. Everything
+/// must be called with the context current.
+pub(crate) struct ShaderQuads {
+    programs: HashMap<ShaderId, Program>,
+    vertex_array: u32,
+    targets: Vec<Target>,
+}
+
+impl ShaderQuads {
+    pub fn new(shaders: &[FragmentShader]) -> Self {
+        let mut vertex_array = 0;
+        unsafe {
+            gl::GenVertexArrays(1, &mut vertex_array);
+        }
+
+        let programs = shaders
+            .iter()
+            .map(|shader| {
+                let source = format!("{}{}", FRAGMENT_HEADER, shader.source);
+                let program = unsafe { link(VERTEX_SHADER, &source) }.unwrap_or_else(|log| {
+                    panic!("failed to build fragment shader {}: {}", shader.id, log)
+                });
+                let location = |name: &str| {
+                    let name = CString::new(name).unwrap();
+                    unsafe { gl::GetUniformLocation(program, name.as_ptr()) }
+                };
+
+                let program = Program {
+                    program,
+                    size: location("u_size"),
+                    values: location("u_values"),
+                };
+                (shader.id, program)
+            })
+            .collect();
+
+        Self {
+            programs,
+            vertex_array,
+            targets: Vec::new(),
+        }
+    }
+
+    pub fn delete(&mut self) {
+        for (_, program) in self.programs.drain() {
+            unsafe {
+                gl::DeleteProgram(program.program);
+            }
+        }
+        for target in self.targets.drain(..) {
+            target.delete();
+        }
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.vertex_array);
+        }
+        self.vertex_array = 0;
+    }
+
+    /// The textures for the quads of the next frame, in order.
+    pub fn targets(&self) -> Vec<imgui::TextureId> {
+        self.targets
+            .iter()
+            .map(|target| imgui::TextureId::new(target.texture as usize))
+            .collect()
+    }
+
+    /// Render the quads into their textures, creating or resizing the textures as needed.
+    pub fn render(&mut self, quads: &[Quad]) {
+        // Textures of quads that are gone are released.
+        for target in self.targets.drain(quads.len().min(self.targets.len())..) {
+            target.delete();
+        }
+        if quads.is_empty() {
+            return;
+        }
+
+        unsafe {
+            let mut viewport = [0; 4];
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+            gl::Disable(gl::BLEND);
+            gl::Disable(gl::SCISSOR_TEST);
+            gl::BindVertexArray(self.vertex_array);
+
+            for (i, quad) in quads.iter().enumerate() {
+                if i == self.targets.len() {
+                    self.targets.push(Target::new());
+                }
+                let target = &mut self.targets[i];
+                target.resize(quad.size);
+
+                gl::BindFramebuffer(gl::FRAMEBUFFER, target.framebuffer);
+                gl::Viewport(0, 0, quad.size[0] as _, quad.size[1] as _);
+                gl::ClearColor(0.0, 0.0, 0.0, 0.0);
+                gl::Clear(gl::COLOR_BUFFER_BIT);
+
+                // Quads of unknown shaders stay transparent.
+                if let Some(program) = self.programs.get(&quad.shader) {
+                    gl::UseProgram(program.program);
+                    gl::Uniform2f(program.size, quad.size[0] as f32, quad.size[1] as f32);
+                    gl::Uniform1fv(program.values, MAX_UNIFORMS as _, quad.uniforms.as_ptr());
+                    gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
+                }
+            }
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            gl::BindVertexArray(0);
+            gl::UseProgram(0);
+            gl::Viewport(viewport[0], viewport[1], viewport[2], viewport[3]);
+        }
+    }
+}
+
+impl Target {
+    fn new() -> Self {
+        let mut texture = 0;
+        let mut framebuffer = 0;
+        unsafe {
+            gl::GenTextures(1, &mut texture);
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as _);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as _);
+            gl::GenFramebuffers(1, &mut framebuffer);
+        }
+
+        Self {
+            texture,
+            framebuffer,
+            size: [0, 0],
+        }
+    }
+
+    fn resize(&mut self, size: [u32; 2]) {
+        if self.size == size {
+            return;
+        }
+
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, self.texture);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA as _,
+                size[0] as _,
+                size[1] as _,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                std::ptr::null(),
+            );
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer);
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_2D,
+                self.texture,
+                0,
+            );
+        }
+        self.size = size;
+    }
+
+    fn delete(self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.framebuffer);
+            gl::DeleteTextures(1, &self.texture);
+        }
+    }
+}
+
+/// Compile and link a program, returning the info log on failure.
+pub(super) unsafe fn link(vertex: &str, fragment: &str) -> Result<u32, String> {
+    let vertex = compile(gl::VERTEX_SHADER, vertex)?;
+    let fragment = compile(gl::FRAGMENT_SHADER, fragment)?;
+
+    let program = gl::CreateProgram();
+    gl::AttachShader(program, vertex);
+    gl::AttachShader(program, fragment);
+    gl::LinkProgram(program);
+    gl::DeleteShader(vertex);
+    gl::DeleteShader(fragment);
+
+    let mut status = 0;
+    gl::GetProgramiv(program, gl::LINK_STATUS, &mut status);
+    if status == 0 {
+        let mut log = vec![0u8; 1024];
+        let mut len = 0;
+        gl::GetProgramInfoLog(program, log.len() as _, &mut len, log.as_mut_ptr() as _);
+        gl::DeleteProgram(program);
+        log.truncate(len as usize);
+        return Err(String::from_utf8_lossy(&log).into_owned());
+    }
+
+    Ok(program)
+}
+
+unsafe fn compile(kind: u32, source: &str) -> Result<u32, String> {
+    let shader = gl::CreateShader(kind);
+    let source = CString::new(source).map_err(|error| error.to_string())?;
+    gl::ShaderSource(shader, 1, &source.as_ptr(), std::ptr::null());
+    gl::CompileShader(shader);
+
+    let mut status = 0;
+    gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut status);
+    if status == 0 {
+        let mut log = vec![0u8; 1024];
+        let mut len = 0;
+        gl::GetShaderInfoLog(shader, log.len() as _, &mut len, log.as_mut_ptr() as _);
+        gl::DeleteShader(shader);
+        log.truncate(len as usize);
+        return Err(String::from_utf8_lossy(&log).into_owned());
+    }
+
+    Ok(shader)
+}
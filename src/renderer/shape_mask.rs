@@ -0,0 +1,75 @@
+use std::ffi::CString;
+
+use super::shaders::{link, VERTEX_SHADER};
+
+const FRAGMENT_SHADER: &str = "#version 150
+uniform vec2 u_size;
+uniform float u_radius;
+out vec4 out_color;
+void main() {
+    vec2 half_size = u_size * 0.5;
+    vec2 corner = abs(gl_FragCoord.xy - half_size) - (half_size - vec2(u_radius));
+    float distance = length(max(corner, 0.0)) - u_radius;
+    out_color = vec4(0.0, 0.0, 0.0, clamp(0.5 - distance, 0.0, 1.0));
+}
+";
+
+/// Cuts the corners out of the framebuffer after the UI is rendered, by multiplying it with
+/// the coverage of a rounded rectangle. Everything must be called with the context current.
+pub(crate) struct ShapeMask {
+    program: u32,
+    vertex_array: u32,
+    size: i32,
+    radius: i32,
+}
+
+impl ShapeMask {
+    pub fn new() -> Self {
+        unsafe {
+            let program = link(VERTEX_SHADER, FRAGMENT_SHADER)
+                .unwrap_or_else(|log| panic!("failed to build the window shape shader: {}", log));
+            let location = |name: &str| {
+                let name = CString::new(name).unwrap();
+                gl::GetUniformLocation(program, name.as_ptr())
+            };
+
+            let mut vertex_array = 0;
+            gl::GenVertexArrays(1, &mut vertex_array);
+
+            Self {
+                program,
+                vertex_array,
+                size: location("u_size"),
+                radius: location("u_radius"),
+            }
+        }
+    }
+
+    pub fn delete(self) {
+        unsafe {
+            gl::DeleteProgram(self.program);
+            gl::DeleteVertexArrays(1, &self.vertex_array);
+        }
+    }
+
+    /// Mask the whole viewport with corners of `radius` pixels.
+    pub fn render(&self, radius: f32) {
+        unsafe {
+            let mut viewport = [0; 4];
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+
+            gl::Disable(gl::SCISSOR_TEST);
+            gl::Enable(gl::BLEND);
+            gl::BlendEquation(gl::FUNC_ADD);
+            gl::BlendFunc(gl::ZERO, gl::SRC_ALPHA);
+            gl::BindVertexArray(self.vertex_array);
+            gl::UseProgram(self.program);
+            gl::Uniform2f(self.size, viewport[2] as f32, viewport[3] as f32);
+            gl::Uniform1f(self.radius, radius);
+            gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
+
+            gl::UseProgram(0);
+            gl::BindVertexArray(0);
+        }
+    }
+}
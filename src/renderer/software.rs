@@ -0,0 +1,266 @@
+//! The renderer for machines without a usable GPU.
+//!
+//! Virtual machines and remote desktop sessions often have no OpenGL driver, or only the
+//! OpenGL 1.1 of the Windows GDI. With the `software_renderer` feature the window falls back
+//! to drawing the UI on the CPU then: the triangles of the draw data are rasterized into a
+//! tiny-skia pixmap, which is copied into the window with the software path of the platform.
+//! It can also be chosen with `Settings::renderer_backend = Some(software_backend)`.
+//!
+//! Like with a custom backend, what needs OpenGL is left out: `ui_shader_rect` stays empty,
+//! render targets are empty textures, the backdrop is not blurred, and the window has no
+//! rounded corners or transparency.
+
+use std::collections::HashMap;
+
+use baseview::Window;
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+use tiny_skia::{Color, Pixmap};
+
+use super::backend::RendererBackend;
+
+/// An RGBA8 image with straight alpha.
+struct Texture {
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+}
+
+impl Texture {
+    /// The color at `uv`, from the nearest pixel.
+    fn sample(&self, uv: [f32; 2]) -> [f32; 4] {
+        let x = ((uv[0] * self.width as f32) as i64)
+            .max(0)
+            .min(self.width as i64 - 1);
+        let y = ((uv[1] * self.height as f32) as i64)
+            .max(0)
+            .min(self.height as i64 - 1);
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        let pixel = &self.rgba[i..i + 4];
+        [
+            pixel[0] as f32 / 255.0,
+            pixel[1] as f32 / 255.0,
+            pixel[2] as f32 / 255.0,
+            pixel[3] as f32 / 255.0,
+        ]
+    }
+}
+
+/// A vertex of the draw data in physical pixels.
+#[derive(Clone, Copy)]
+struct Vertex {
+    pos: [f32; 2],
+    uv: [f32; 2],
+    color: [f32; 4],
+}
+
+pub(crate) struct SoftwareRenderer {
+    window: RawWindowHandle,
+    pixmap: Option<Pixmap>,
+    /// The pixmap in the order of the platform, BGRA.
+    frame: Vec<u8>,
+    textures: HashMap<usize, Texture>,
+    next_texture: usize,
+}
+
+impl SoftwareRenderer {
+    pub fn new(window: &Window) -> Self {
+        Self {
+            window: window.raw_window_handle(),
+            pixmap: None,
+            frame: Vec::new(),
+            textures: HashMap::new(),
+            next_texture: 0,
+        }
+    }
+}
+
+/// A `BackendFactory` for `Settings::renderer_backend` that draws on the CPU.
+pub fn software_backend(
+    window: &Window,
+    _context: &mut imgui::Context,
+) -> Box<dyn RendererBackend> {
+    Box::new(SoftwareRenderer::new(window))
+}
+
+impl RendererBackend for SoftwareRenderer {
+    fn upload_font_atlas(&mut self, context: &mut imgui::Context) -> imgui::TextureId {
+        let mut fonts = context.fonts();
+        let atlas = fonts.build_rgba32_texture();
+        self.create_texture(atlas.width, atlas.height, atlas.data)
+    }
+
+    fn render(&mut self, draw_data: &imgui::DrawData, clear_color: (f32, f32, f32)) {
+        let [scale_x, scale_y] = draw_data.framebuffer_scale;
+        let width = (draw_data.display_size[0] * scale_x).round() as u32;
+        let height = (draw_data.display_size[1] * scale_y).round() as u32;
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let resized = match &self.pixmap {
+            Some(pixmap) => pixmap.width() != width || pixmap.height() != height,
+            None => true,
+        };
+        if resized {
+            self.pixmap = Pixmap::new(width, height);
+        }
+        let pixmap = match &mut self.pixmap {
+            Some(pixmap) => pixmap,
+            None => return,
+        };
+        pixmap.fill(Color::from_rgba8(
+            (clear_color.0 * 255.0) as u8,
+            (clear_color.1 * 255.0) as u8,
+            (clear_color.2 * 255.0) as u8,
+            255,
+        ));
+
+        let [left, top] = draw_data.display_pos;
+        let pixels = pixmap.data_mut();
+        for list in draw_data.draw_lists() {
+            let vertices = list.vtx_buffer();
+            let indices = list.idx_buffer();
+
+            for command in list.commands() {
+                if let imgui::DrawCmd::Elements { count, cmd_params } = command {
+                    let [x0, y0, x1, y1] = cmd_params.clip_rect;
+                    let clip = [
+                        ((x0 - left) * scale_x).max(0.0) as i64,
+                        ((y0 - top) * scale_y).max(0.0) as i64,
+                        ((x1 - left) * scale_x).min(width as f32) as i64,
+                        ((y1 - top) * scale_y).min(height as f32) as i64,
+                    ];
+                    if clip[2] <= clip[0] || clip[3] <= clip[1] {
+                        continue;
+                    }
+                    let texture = self.textures.get(&cmd_params.texture_id.id());
+
+                    let vertex = |index: u16| {
+                        let vertex = &vertices[cmd_params.vtx_offset + index as usize];
+                        Vertex {
+                            pos: [
+                                (vertex.pos[0] - left) * scale_x,
+                                (vertex.pos[1] - top) * scale_y,
+                            ],
+                            uv: vertex.uv,
+                            color: [
+                                vertex.col[0] as f32 / 255.0,
+                                vertex.col[1] as f32 / 255.0,
+                                vertex.col[2] as f32 / 255.0,
+                                vertex.col[3] as f32 / 255.0,
+                            ],
+                        }
+                    };
+                    let start = cmd_params.idx_offset;
+                    let triangles = &indices[start..start + count];
+                    for triangle in triangles.chunks_exact(3) {
+                        let triangle = [
+                            vertex(triangle[0]),
+                            vertex(triangle[1]),
+                            vertex(triangle[2]),
+                        ];
+                        fill_triangle(pixels, width, clip, &triangle, texture);
+                    }
+                }
+            }
+        }
+
+        // Every pixel is opaque after the clear, so the premultiplied colors are the colors.
+        self.frame.resize(pixels.len(), 0);
+        for (pixel, out) in pixels.chunks_exact(4).zip(self.frame.chunks_exact_mut(4)) {
+            out.copy_from_slice(&[pixel[2], pixel[1], pixel[0], 255]);
+        }
+        crate::platform::present_pixels(&self.window, [width, height], &mut self.frame);
+    }
+
+    fn create_texture(&mut self, width: u32, height: u32, rgba: &[u8]) -> imgui::TextureId {
+        self.next_texture += 1;
+        self.textures.insert(
+            self.next_texture,
+            Texture {
+                width,
+                height,
+                rgba: rgba.to_vec(),
+            },
+        );
+        imgui::TextureId::new(self.next_texture)
+    }
+
+    fn delete_texture(&mut self, texture: imgui::TextureId) {
+        self.textures.remove(&texture.id());
+    }
+
+    fn destroy(&mut self) {
+        self.textures.clear();
+        self.pixmap = None;
+        self.frame = Vec::new();
+    }
+
+    fn name(&self) -> String {
+        String::from("software")
+    }
+}
+
+fn edge(a: [f32; 2], b: [f32; 2], p: [f32; 2]) -> f32 {
+    (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+}
+
+/// Blend a triangle into premultiplied RGBA8 `pixels`, within `clip`.
+fn fill_triangle(
+    pixels: &mut [u8],
+    width: u32,
+    clip: [i64; 4],
+    triangle: &[Vertex; 3],
+    texture: Option<&Texture>,
+) {
+    let [a, b, c] = triangle;
+    let area = edge(a.pos, b.pos, c.pos);
+    if area.abs() < f32::EPSILON {
+        return;
+    }
+
+    let min_x = (a.pos[0].min(b.pos[0]).min(c.pos[0]).floor() as i64).max(clip[0]);
+    let min_y = (a.pos[1].min(b.pos[1]).min(c.pos[1]).floor() as i64).max(clip[1]);
+    let max_x = (a.pos[0].max(b.pos[0]).max(c.pos[0]).ceil() as i64).min(clip[2]);
+    let max_y = (a.pos[1].max(b.pos[1]).max(c.pos[1]).ceil() as i64).min(clip[3]);
+
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            let p = [x as f32 + 0.5, y as f32 + 0.5];
+            // The weights of the vertices at the center of the pixel, negative outside.
+            let wa = edge(b.pos, c.pos, p) / area;
+            let wb = edge(c.pos, a.pos, p) / area;
+            let wc = 1.0 - wa - wb;
+            if wa < 0.0 || wb < 0.0 || wc < 0.0 {
+                continue;
+            }
+
+            let mut color = [0.0; 4];
+            for i in 0..4 {
+                color[i] = a.color[i] * wa + b.color[i] * wb + c.color[i] * wc;
+            }
+            if let Some(texture) = texture {
+                let uv = [
+                    a.uv[0] * wa + b.uv[0] * wb + c.uv[0] * wc,
+                    a.uv[1] * wa + b.uv[1] * wb + c.uv[1] * wc,
+                ];
+                let texel = texture.sample(uv);
+                for i in 0..4 {
+                    color[i] *= texel[i];
+                }
+            }
+
+            let alpha = color[3];
+            if alpha <= 0.0 {
+                continue;
+            }
+            let i = (y as usize * width as usize + x as usize) * 4;
+            let pixel = &mut pixels[i..i + 4];
+            for channel in 0..3 {
+                let source = color[channel] * alpha * 255.0;
+                pixel[channel] = (source + pixel[channel] as f32 * (1.0 - alpha)) as u8;
+            }
+            pixel[3] = (alpha * 255.0 + pixel[3] as f32 * (1.0 - alpha)) as u8;
+        }
+    }
+}
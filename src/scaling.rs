@@ -0,0 +1,144 @@
+//! Converting positions between baseview, imgui and physical pixels.
+//!
+//! baseview works in logical points, which are physical pixels divided by the scale factor
+//! of the system. imgui works in units of its own, which are physical pixels divided by the
+//! hidpi factor. The two are the same with `HiDpiMode::Default` and no UI scale, and differ
+//! otherwise. Code that hit-tests positions from baseview events or native windows against
+//! the UI can convert them with the `PlatformScaling` from `WindowContext::scaling`.
+
+/// A position in the logical points of baseview.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LogicalPos {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl LogicalPos {
+    pub fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+}
+
+impl From<baseview::Point> for LogicalPos {
+    fn from(point: baseview::Point) -> Self {
+        Self::new(point.x, point.y)
+    }
+}
+
+impl From<LogicalPos> for baseview::Point {
+    fn from(pos: LogicalPos) -> Self {
+        baseview::Point::new(pos.x, pos.y)
+    }
+}
+
+/// A position in the pixels of the framebuffer.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PhysicalPos {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl PhysicalPos {
+    pub fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+}
+
+/// The factors of a window in the current frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlatformScaling {
+    scale_factor: f64,
+    hidpi_factor: f64,
+}
+
+impl PlatformScaling {
+    /// Factors that aren't positive are taken as 1, so conversions stay finite.
+    pub fn new(scale_factor: f64, hidpi_factor: f64) -> Self {
+        Self {
+            scale_factor: usable_scale(scale_factor, 1.0),
+            hidpi_factor: usable_scale(hidpi_factor, 1.0),
+        }
+    }
+
+    /// The physical pixels in a point of baseview.
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    /// The physical pixels in a unit of imgui, after `Settings::hidpi_mode` and the UI scale.
+    pub fn hidpi_factor(&self) -> f64 {
+        self.hidpi_factor
+    }
+
+    pub fn logical_to_physical(&self, pos: LogicalPos) -> PhysicalPos {
+        PhysicalPos::new(pos.x * self.scale_factor, pos.y * self.scale_factor)
+    }
+
+    pub fn physical_to_logical(&self, pos: PhysicalPos) -> LogicalPos {
+        LogicalPos::new(pos.x / self.scale_factor, pos.y / self.scale_factor)
+    }
+
+    pub fn imgui_to_physical(&self, pos: [f32; 2]) -> PhysicalPos {
+        PhysicalPos::new(
+            pos[0] as f64 * self.hidpi_factor,
+            pos[1] as f64 * self.hidpi_factor,
+        )
+    }
+
+    pub fn physical_to_imgui(&self, pos: PhysicalPos) -> [f32; 2] {
+        [
+            (pos.x / self.hidpi_factor) as f32,
+            (pos.y / self.hidpi_factor) as f32,
+        ]
+    }
+
+    /// The imgui position of a point from baseview, like the position of the cursor.
+    pub fn logical_to_imgui(&self, pos: LogicalPos) -> [f32; 2] {
+        self.physical_to_imgui(self.logical_to_physical(pos))
+    }
+
+    pub fn imgui_to_logical(&self, pos: [f32; 2]) -> LogicalPos {
+        self.physical_to_logical(self.imgui_to_physical(pos))
+    }
+}
+
+impl Default for PlatformScaling {
+    fn default() -> Self {
+        Self::new(1.0, 1.0)
+    }
+}
+
+/// The scale a window reports, or `last` when it is zero, negative or NaN, like some systems
+/// report for minimized windows.
+pub(crate) fn usable_scale(scale: f64, last: f64) -> f64 {
+    if scale > 0.0 && scale.is_finite() {
+        scale
+    } else {
+        last
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_scale_falls_back() {
+        assert_eq!(usable_scale(1.5, 2.0), 1.5);
+        assert_eq!(usable_scale(0.0, 2.0), 2.0);
+        assert_eq!(usable_scale(-1.0, 2.0), 2.0);
+        assert_eq!(usable_scale(f64::NAN, 2.0), 2.0);
+        assert_eq!(usable_scale(f64::INFINITY, 2.0), 2.0);
+    }
+
+    #[test]
+    fn zero_scale_converts_to_finite_positions() {
+        let scaling = PlatformScaling::new(0.0, f64::NAN);
+        let pos = scaling.physical_to_logical(PhysicalPos::new(10.0, 20.0));
+        assert_eq!(pos, LogicalPos::new(10.0, 20.0));
+        assert_eq!(
+            scaling.physical_to_imgui(PhysicalPos::new(10.0, 20.0)),
+            [10.0, 20.0]
+        );
+    }
+}
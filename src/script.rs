@@ -0,0 +1,325 @@
+//! Building the UI from a rhai script.
+//!
+//! Designers who don't write Rust can iterate on a layout in a script instead. A `UiScript`
+//! runs its script every frame from the update closure, and the script builds the UI with a
+//! small set of functions. They can't reach anything else of the window or the plugin:
+//!
+//! * `window(title)` begins a window and `end()` ends it.
+//! * `text(text)`, `separator()` and `same_line()` lay out the window.
+//! * `button(label)` returns whether the button was clicked.
+//! * `slider(label, min, max)` and `checkbox(label)` return their value. The values are kept
+//!   by label from frame to frame, and can be read with `UiScript::value` and
+//!   `UiScript::checked`.
+//! * `param(id)` and `knob(id)` show a param-bound slider or knob for a parameter that was
+//!   bound with `UiScript::bind_param`.
+//!
+//! ```ignore
+//! window("Synth");
+//! text("Oscillator");
+//! param(0);
+//! if checkbox("Advanced") {
+//!     knob(1);
+//! }
+//! end();
+//! ```
+//!
+//! The script runs before the frame is built, so the buttons, sliders and checkboxes return
+//! what happened to them in the last frame. When the script fails to compile or run, the
+//! error is shown in a panel of its own, and the UI stays as it was built up to the error.
+//!
+//! Scripts can't `import` other files, and what they `print` or `debug` is dropped instead of
+//! landing in the output of the host.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Engine, Scope, AST, INT};
+
+use crate::params::{Param, ParamId};
+use crate::WindowContext;
+
+/// How often a script file is checked for changes.
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+/// The most operations a script runs in a frame, so an endless loop doesn't hang the window.
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// What the script asked to build, in order.
+#[derive(Debug, Clone, PartialEq)]
+enum Command {
+    Window(String),
+    End,
+    Text(String),
+    Separator,
+    SameLine,
+    Button(String),
+    Slider { label: String, min: f32, max: f32 },
+    Checkbox(String),
+    Param(ParamId),
+    Knob(ParamId),
+}
+
+/// What the functions of the script share with the window.
+#[derive(Default)]
+struct Shared {
+    commands: Vec<Command>,
+    values: HashMap<String, f32>,
+    checked: HashMap<String, bool>,
+    /// The buttons clicked in the last frame.
+    clicked: HashSet<String>,
+}
+
+/// A script that builds the UI every frame.
+#[allow(missing_debug_implementations)]
+pub struct UiScript {
+    engine: Engine,
+    ast: Option<AST>,
+    shared: Arc<Mutex<Shared>>,
+    params: HashMap<ParamId, Arc<Param>>,
+    file: Option<PathBuf>,
+    modified: Option<SystemTime>,
+    last_check: Option<Instant>,
+    /// Why the source didn't compile or the file couldn't be read.
+    load_error: Option<String>,
+    run_error: Option<String>,
+}
+
+impl UiScript {
+    /// A script from its source.
+    pub fn new(source: &str) -> Self {
+        let mut script = Self::empty();
+        script.set_source(source);
+        script
+    }
+
+    /// A script from a file, which is read again every time it changes.
+    pub fn from_file(path: impl Into<PathBuf>) -> Self {
+        let mut script = Self::empty();
+        script.file = Some(path.into());
+        script.reload();
+        script
+    }
+
+    fn empty() -> Self {
+        let shared = Arc::new(Mutex::new(Shared::default()));
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_module_resolver(DummyModuleResolver::new());
+        engine.on_print(|_| {});
+        engine.on_debug(|_, _, _| {});
+        register(&mut engine, &shared);
+
+        Self {
+            engine,
+            ast: None,
+            shared,
+            params: HashMap::new(),
+            file: None,
+            modified: None,
+            last_check: None,
+            load_error: None,
+            run_error: None,
+        }
+    }
+
+    /// Compile a new source. If it doesn't compile, the error is shown and the script that
+    /// compiled last keeps running.
+    pub fn set_source(&mut self, source: &str) {
+        match self.engine.compile(source) {
+            Ok(ast) => {
+                self.ast = Some(ast);
+                self.load_error = None;
+            }
+            Err(error) => self.load_error = Some(error.to_string()),
+        }
+    }
+
+    /// Let the script show `param` with `param(id)` and `knob(id)`.
+    pub fn bind_param(&mut self, param: &Arc<Param>) {
+        self.params.insert(param.id(), param.clone());
+    }
+
+    /// The value of the slider with `label`, if the script showed it.
+    pub fn value(&self, label: &str) -> Option<f32> {
+        self.shared.lock().unwrap().values.get(label).copied()
+    }
+
+    /// Whether the checkbox with `label` is checked.
+    pub fn checked(&self, label: &str) -> bool {
+        let shared = self.shared.lock().unwrap();
+        shared.checked.get(label).copied().unwrap_or(false)
+    }
+
+    /// Why the script failed to compile or run in the last frame.
+    pub fn error(&self) -> Option<&str> {
+        self.load_error.as_deref().or(self.run_error.as_deref())
+    }
+
+    /// Read the file again if it changed.
+    fn reload(&mut self) {
+        let path = match &self.file {
+            Some(path) => path.clone(),
+            None => return,
+        };
+        if let Some(last_check) = self.last_check {
+            if last_check.elapsed() < CHECK_INTERVAL {
+                return;
+            }
+        }
+        self.last_check = Some(Instant::now());
+
+        let modified = fs::metadata(&path).and_then(|metadata| metadata.modified());
+        if modified.as_ref().ok() == self.modified.as_ref() {
+            return;
+        }
+        match fs::read_to_string(&path) {
+            Ok(source) => {
+                self.modified = modified.ok();
+                self.set_source(&source);
+            }
+            Err(error) => self.load_error = Some(format!("{}: {}", path.display(), error)),
+        }
+    }
+
+    /// Run the script and build what it asks for, then the error panel if it failed.
+    pub fn draw(&mut self, ui: &imgui::Ui, ctx: &mut WindowContext) {
+        self.reload();
+
+        if let Some(ast) = &self.ast {
+            let result = self.engine.run_ast_with_scope(&mut Scope::new(), ast);
+            self.run_error = result.err().map(|error| error.to_string());
+        }
+
+        let commands = std::mem::take(&mut self.shared.lock().unwrap().commands);
+        self.build(ui, ctx, commands);
+
+        if let Some(error) = self.error() {
+            imgui::Window::new("Script error")
+                .always_auto_resize(true)
+                .build(ui, || {
+                    ui.text_colored([1.0, 0.4, 0.4, 1.0], error);
+                });
+        }
+    }
+
+    fn build(&self, ui: &imgui::Ui, ctx: &mut WindowContext, commands: Vec<Command>) {
+        let mut shared = self.shared.lock().unwrap();
+        shared.clicked.clear();
+
+        let mut windows = Vec::new();
+        // The windows that are collapsed, whose contents are skipped up to their `end()`.
+        let mut skipped = 0;
+        for command in commands {
+            if skipped > 0 {
+                match command {
+                    Command::Window(_) => skipped += 1,
+                    Command::End => skipped -= 1,
+                    _ => {}
+                }
+                continue;
+            }
+
+            match command {
+                Command::Window(title) => match imgui::Window::new(&title).begin(ui) {
+                    Some(token) => windows.push(token),
+                    None => skipped = 1,
+                },
+                Command::End => {
+                    if let Some(token) = windows.pop() {
+                        token.end();
+                    }
+                }
+                Command::Text(text) => ui.text(text),
+                Command::Separator => ui.separator(),
+                Command::SameLine => ui.same_line(),
+                Command::Button(label) => {
+                    if ui.button(&label) {
+                        shared.clicked.insert(label);
+                    }
+                }
+                Command::Slider { label, min, max } => {
+                    let mut value = shared.values.get(&label).copied().unwrap_or(min);
+                    imgui::Slider::new(&label, min, max).build(ui, &mut value);
+                    shared.values.insert(label, value);
+                }
+                Command::Checkbox(label) => {
+                    let mut checked = shared.checked.get(&label).copied().unwrap_or(false);
+                    ui.checkbox(&label, &mut checked);
+                    shared.checked.insert(label, checked);
+                }
+                Command::Param(id) => {
+                    if let Some(param) = self.params.get(&id) {
+                        crate::widgets::param_slider(ui, ctx, param);
+                    }
+                }
+                Command::Knob(id) => {
+                    if let Some(param) = self.params.get(&id) {
+                        crate::widgets::param_knob(ui, ctx, param);
+                    }
+                }
+            }
+        }
+
+        // Windows the script didn't end, in the order they were begun.
+        while let Some(token) = windows.pop() {
+            token.end();
+        }
+    }
+}
+
+/// Register the functions of the script.
+fn register(engine: &mut Engine, shared: &Arc<Mutex<Shared>>) {
+    let push = |command: Command, shared: &Arc<Mutex<Shared>>| {
+        shared.lock().unwrap().commands.push(command);
+    };
+
+    let s = shared.clone();
+    engine.register_fn("window", move |title: &str| {
+        push(Command::Window(title.to_string()), &s)
+    });
+    let s = shared.clone();
+    engine.register_fn("end", move || push(Command::End, &s));
+    let s = shared.clone();
+    engine.register_fn("text", move |text: &str| {
+        push(Command::Text(text.to_string()), &s)
+    });
+    let s = shared.clone();
+    engine.register_fn("separator", move || push(Command::Separator, &s));
+    let s = shared.clone();
+    engine.register_fn("same_line", move || push(Command::SameLine, &s));
+
+    let s = shared.clone();
+    engine.register_fn("button", move |label: &str| {
+        let mut shared = s.lock().unwrap();
+        shared.commands.push(Command::Button(label.to_string()));
+        shared.clicked.contains(label)
+    });
+    let s = shared.clone();
+    engine.register_fn("slider", move |label: &str, min: f64, max: f64| {
+        let mut shared = s.lock().unwrap();
+        shared.commands.push(Command::Slider {
+            label: label.to_string(),
+            min: min as f32,
+            max: max as f32,
+        });
+        shared.values.get(label).copied().unwrap_or(min as f32) as f64
+    });
+    let s = shared.clone();
+    engine.register_fn("checkbox", move |label: &str| {
+        let mut shared = s.lock().unwrap();
+        shared.commands.push(Command::Checkbox(label.to_string()));
+        shared.checked.get(label).copied().unwrap_or(false)
+    });
+
+    let s = shared.clone();
+    engine.register_fn("param", move |id: INT| {
+        push(Command::Param(id as ParamId), &s)
+    });
+    let s = shared.clone();
+    engine.register_fn("knob", move |id: INT| {
+        push(Command::Knob(id as ParamId), &s)
+    });
+}
@@ -0,0 +1,128 @@
+//! Turns wheel deltas into the scrolling of imgui.
+//!
+//! A notched wheel scrolls in steps, which jump when imgui applies them at once. With
+//! `Settings::smooth_scrolling` every step moves a spring on its axis instead, and the
+//! spring scrolls a bit of the step in every frame until it comes to rest. The spring is
+//! critically damped, so it never overshoots, and the whole step is always scrolled. Pixel
+//! deltas from trackpads are already smooth and go to imgui as they are.
+
+use std::time::Duration;
+
+use crate::settings::SmoothScrolling;
+
+/// How far the spring can be from rest, in wheel steps, before it is snapped to it.
+const REST: f32 = 0.001;
+
+/// The lines imgui scrolls for one step of the wheel.
+pub(crate) const LINES_PER_STEP: f32 = 5.0;
+
+/// The logical height of a line in the default font.
+pub(crate) fn line_height(context: &mut imgui::Context) -> f32 {
+    let scale = context.io().font_global_scale;
+    let fonts = context.fonts();
+    let size = fonts
+        .fonts()
+        .first()
+        .and_then(|font| fonts.get_font(*font))
+        .map_or(0.0, |font| font.font_size);
+    size * scale
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Spring {
+    /// The steps that are still to be scrolled.
+    remaining: f32,
+    /// In steps per second.
+    velocity: f32,
+}
+
+impl Spring {
+    /// Move the spring on by `dt`, returning the steps it scrolled.
+    fn step(&mut self, omega: f32, dt: f32) -> f32 {
+        if self.remaining == 0.0 && self.velocity == 0.0 {
+            return 0.0;
+        }
+
+        // The exact solution of a critically damped spring, so big frame times can't make
+        // it unstable. `offset` is how far the scrolled position is from the target.
+        let offset = -self.remaining;
+        let decay = (-omega * dt).exp();
+        let rate = self.velocity + omega * offset;
+        let new_offset = (offset + rate * dt) * decay;
+        self.velocity = (self.velocity - omega * rate * dt) * decay;
+
+        let moved = new_offset - offset;
+        self.remaining = -new_offset;
+        if self.remaining.abs() < REST && self.velocity.abs() < REST * omega {
+            let moved = moved + self.remaining;
+            *self = Spring::default();
+            return moved;
+        }
+        moved
+    }
+}
+
+pub(crate) struct Scroller {
+    smooth_scrolling: Option<SmoothScrolling>,
+    /// The horizontal and vertical axis.
+    springs: [Spring; 2],
+}
+
+impl Scroller {
+    pub fn new(smooth_scrolling: Option<SmoothScrolling>) -> Self {
+        Self {
+            smooth_scrolling,
+            springs: Default::default(),
+        }
+    }
+
+    /// Scroll by steps of the wheel.
+    pub fn lines(&mut self, io: &mut imgui::Io, x: f32, y: f32) {
+        match self.smooth_scrolling {
+            Some(smooth_scrolling) => {
+                if smooth_scrolling.horizontal {
+                    self.springs[0].remaining += x;
+                } else {
+                    io.mouse_wheel_h += x;
+                }
+                if smooth_scrolling.vertical {
+                    self.springs[1].remaining += y;
+                } else {
+                    io.mouse_wheel += y;
+                }
+            }
+            None => {
+                io.mouse_wheel_h += x;
+                io.mouse_wheel += y;
+            }
+        }
+    }
+
+    /// Scroll by a distance in the coordinates of imgui.
+    pub fn pixels(&mut self, io: &mut imgui::Io, line_height: f32, x: f32, y: f32) {
+        let step = LINES_PER_STEP * line_height;
+        if step > 0.0 {
+            io.mouse_wheel_h += x / step;
+            io.mouse_wheel += y / step;
+        }
+    }
+
+    /// Whether a spring has steps left to scroll.
+    pub fn is_moving(&self) -> bool {
+        self.springs
+            .iter()
+            .any(|spring| spring.remaining != 0.0 || spring.velocity != 0.0)
+    }
+
+    /// Scroll what the springs moved since the last frame.
+    pub fn update(&mut self, io: &mut imgui::Io, dt: Duration) {
+        let omega = match self.smooth_scrolling {
+            Some(smooth_scrolling) => smooth_scrolling.omega(),
+            None => return,
+        };
+
+        let dt = dt.as_secs_f32();
+        io.mouse_wheel_h += self.springs[0].step(omega, dt);
+        io.mouse_wheel += self.springs[1].step(omega, dt);
+    }
+}
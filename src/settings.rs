@@ -1,16 +1,57 @@
 //! Configure your application;
 
-use crate::{HiDpiMode, RenderSettings};
-use baseview::WindowOpenOptions;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::activation::{ContextPolicy, TimeoutCallback, TimeoutReport};
+use crate::clock::Clock;
+use crate::crash::{CrashHandler, CrashReport};
+use crate::fonts::{FontAtlasSharing, FontSource};
+use crate::handle::Handle;
+use crate::interaction::Interaction;
+use crate::layout::Breakpoints;
+use crate::locale::{Locale, TextDirection};
+use crate::shader::FragmentShader;
+use crate::{BackendFactory, GlContextMode, HiDpiMode, RenderSettings};
+use baseview::{Size, WindowOpenOptions, WindowScalePolicy};
+use keyboard_types::{Code, KeyState, KeyboardEvent, Modifiers};
 
 /// The settings of an application.
+///
+/// With the `serde` feature the settings can be read from a configuration file, where every
+/// field is optional. The fields that hold code or a connection, like the handle, the clock,
+/// the callbacks and the render settings, are left out and keep their defaults.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Settings {
     /// The `baseview` window settings.
+    #[cfg_attr(feature = "serde", serde(with = "window_options"))]
     pub window: WindowOpenOptions,
 
     /// The color to clear the screen on render (R, G, B).
     pub clear_color: (f32, f32, f32),
 
+    /// The opacity of the background cleared with `clear_color`, from 0 to 1.
+    ///
+    /// Below 1 the framebuffer gets an alpha channel and the window lets it show through, so
+    /// floating windows can have rounded or semi-transparent chrome. Only standalone windows
+    /// on Windows and macOS can be transparent; on X11 and for embedded windows the background
+    /// stays opaque.
+    pub window_alpha: f32,
+
+    /// How the window behaves among the other windows. Ignored for embedded windows.
+    pub window_style: WindowStyle,
+
+    /// Where a standalone window is placed when it opens. The platform decides when not set.
+    pub position: Option<WindowPosition>,
+
+    /// The outline of the window, for windows without decorations.
+    ///
+    /// Rounded windows are made transparent like with `window_alpha`, and the corners are cut
+    /// out of the framebuffer with antialiasing after the UI is rendered. Like transparency,
+    /// this only works for standalone windows on Windows and macOS.
+    pub window_shape: WindowShape,
+
     /// DPI factor handling mode.
     ///
     /// Applications that use imgui-rs might want to customize the used DPI factor and not use
@@ -21,6 +62,1248 @@ pub struct Settings {
     /// position values.**
     pub hidpi_mode: HiDpiMode,
 
+    /// Move the vertices of the UI to the nearest physical pixel at fractional hidpi factors,
+    /// so 1px borders and separators stay crisp at scalings like 125%.
+    pub pixel_snapping: bool,
+
+    /// Open the window at the size the user last resized it to, which is saved in
+    /// `Settings::ui_state`. `window.size` is used the first time. Mind
+    /// `Handle::set_size_limits` when the host constrains the size.
+    pub restore_size: bool,
+
     /// The settings for the rendering backend.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub render_settings: RenderSettings,
+
+    /// Whether the window creates its own OpenGL context, shares one with the host or
+    /// renders into the one the host has.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub gl_context: GlContextMode,
+
+    /// Create a custom renderer instead of the built-in OpenGL one. `render_settings` is
+    /// ignored then.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub renderer_backend: Option<BackendFactory>,
+
+    /// The fonts to load, rasterized at the hidpi factor of the window.
+    ///
+    /// When this is empty the font atlas is left alone, and fonts can be added manually in
+    /// the `build` closure instead.
+    pub fonts: Vec<FontSource>,
+
+    /// How the font atlas is shared with other windows.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub font_atlas: FontAtlasSharing,
+
+    /// Register the window in the process-wide registry of the `instances` module under this
+    /// key, usually the name of the plugin.
+    pub instance_key: Option<String>,
+
+    /// Show the debug overlay with imgui's metrics window, recent baseview events, the DPI
+    /// values and renderer statistics.
+    pub debug_overlay: bool,
+
+    /// Toggle the debug overlay with this key combination.
+    pub debug_overlay_hotkey: Option<Hotkey>,
+
+    /// The tool windows of imgui to show over the UI.
+    pub dev_windows: DevWindows,
+
+    /// Open a panel that shows and hides the dev windows with this key combination.
+    pub dev_windows_hotkey: Option<Hotkey>,
+
+    /// Input that goes to the host even when imgui wants it, like the transport keys.
+    pub host_passthrough: Vec<PassthroughRule>,
+
+    /// The connection to the host. Keep a clone to read the events of the window.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub handle: Handle,
+
+    /// Add items to MIDI learn or unlearn the parameter to the right-click menu of
+    /// param-bound widgets.
+    pub midi_learn: bool,
+
+    /// Copy text selected in text inputs to the primary selection of X11, and paste it into
+    /// a text input clicked with the middle mouse button. Does nothing on other platforms.
+    pub x11_primary_selection: bool,
+
+    /// How the param-bound widgets respond to dragging and modifier keys.
+    pub interaction: Interaction,
+
+    /// How long the pointer has to rest on a widget before its help text is shown.
+    pub tooltip_delay: Duration,
+
+    /// The translations of the text shown by the crate's widgets and overlays.
+    pub locale: Locale,
+
+    /// The direction of the text, used to configure the style and the crate's widgets.
+    pub text_direction: TextDirection,
+
+    /// The UI state from an earlier `UiEvent::SaveUiState`, with the imgui ini settings and
+    /// the `UiStorage` values.
+    pub ui_state: Option<String>,
+
+    /// The fragment shaders to compile for `shader::ui_shader_rect`.
+    pub shaders: Vec<FragmentShader>,
+
+    /// The bytes of GPU memory the textures of `WindowContext::add_texture` may use before
+    /// the ones shown least recently are evicted. Nothing is evicted when not set.
+    pub texture_budget: Option<u64>,
+
+    /// The clock used for the delta time of every frame. Uses real time when not set.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub clock: Option<Arc<dyn Clock>>,
+
+    /// The frame rate of the window while it doesn't have the keyboard focus and the mouse
+    /// is not over it, to leave the CPU and GPU to the window the user works in. Renders at
+    /// the full rate when not set.
+    pub background_fps: Option<f32>,
+
+    /// Whether the window renders every frame baseview asks for, or only when something
+    /// changed.
+    pub frame_mode: FrameMode,
+
+    /// Whether the renderer trades frame rate for less latency between input and the frame
+    /// that shows it.
+    pub latency_mode: LatencyMode,
+
+    /// Lighten the rendering when frames keep taking longer than a budget, so the UI stays
+    /// responsive on weak GPUs. Never lightened when not set.
+    pub frame_budget: Option<FrameBudget>,
+
+    /// Animate the steps of a notched mouse wheel instead of jumping by them.
+    pub smooth_scrolling: Option<SmoothScrolling>,
+
+    /// Whether imgui or a widget under the mouse gets the mouse wheel.
+    pub scroll_priority: ScrollPriority,
+
+    /// When keys that are held down are released, for hosts that don't pass on every key
+    /// release to the window.
+    pub key_recovery: KeyRecovery,
+
+    /// Repeat a held key from the frame clock, for hosts that send a single key press while
+    /// a key is held. The window stops repeating keys once the host repeats one itself.
+    pub key_repeat: Option<KeyRepeat>,
+
+    /// Whether the shortcuts of `WindowContext::shortcuts` and imgui's own, like `Ctrl+Z`,
+    /// follow the physical keys or the letters of the keyboard layout.
+    pub shortcut_matching: ShortcutMatching,
+
+    /// Turn on keyboard navigation and draw a ring in this style around the focused item
+    /// while the focus is moved with the keyboard.
+    pub focus_ring_style: Option<FocusRingStyle>,
+
+    /// Whether to turn off animations that are only for show, like smooth scrolling. `None`
+    /// follows the accessibility settings of the system.
+    pub reduced_motion: Option<bool>,
+
+    /// How long two clicks can be apart to be a double click. `None` follows the mouse
+    /// settings of the system.
+    pub double_click_time: Option<Duration>,
+
+    /// How far the mouse moves while a button is held before it drags, in logical pixels.
+    /// `None` follows the mouse settings of the system.
+    pub drag_threshold: Option<f32>,
+
+    /// Resize the window to fit the content marked with `WindowContext::fit_to_item`.
+    pub auto_resize: Option<AutoResize>,
+
+    /// Lay the UI out at a size of its own, and let the user pan it when the window is
+    /// smaller.
+    pub scroll_container: Option<ScrollContainer>,
+
+    /// The widths where `WindowContext::size_class` changes from compact to regular to
+    /// large.
+    pub breakpoints: Breakpoints,
+
+    /// Show the jobs of `WindowContext::tasks` with their progress in a corner of the window.
+    pub progress_overlay: bool,
+
+    /// Show a splash screen until the work of its loader is done, before the first frame of
+    /// the update closure.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub splash: Option<crate::splash::Splash>,
+
+    /// How the window shares the imgui context with the other windows of the process.
+    pub context_policy: ContextPolicy,
+
+    /// How long the window waits for another window of the process to release the imgui
+    /// context before it skips an event or a frame. Waits forever when not set.
+    pub context_timeout: Option<Duration>,
+
+    /// Called when the window gave up waiting for the imgui context.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub on_context_timeout: Option<TimeoutCallback>,
+
+    /// Catch the panics of the update closure, and call this with a report of them.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub crash_handler: Option<CrashHandler>,
+
+    /// SVG icons to rasterize at the hidpi factor of the window.
+    #[cfg(feature = "svg")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub icons: Option<crate::IconSet>,
+
+    /// Record the input of the window, or replay a recording.
+    #[cfg(feature = "event_recorder")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub event_recorder: Option<crate::recorder::EventRecorder>,
+
+    /// Let OSC messages read and set the parameters of the window. Opening the window fails
+    /// with `OpenError::RemoteControl` if the server can't listen on the address.
+    #[cfg(feature = "remote_control")]
+    pub remote_control: Option<crate::remote_control::RemoteControl>,
+
+    /// Stream the stats and draw lists of every frame over TCP. Opening the window fails with
+    /// `OpenError::RemoteDebug` if the server can't listen on the address.
+    #[cfg(feature = "remote_debug")]
+    pub remote_debug: Option<crate::remote_debug::RemoteDebug>,
+
+    /// Create the platform adapter that publishes the accessibility tree of the window.
+    #[cfg(feature = "accessibility")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub accessibility_adapter: Option<crate::accessibility::AdapterFactory>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            window: WindowOpenOptions {
+                title: String::from("imgui-baseview"),
+                size: Size::new(500.0, 400.0),
+                scale: WindowScalePolicy::SystemScaleFactor,
+            },
+            clear_color: (0.0, 0.0, 0.0),
+            window_alpha: 1.0,
+            window_style: WindowStyle::default(),
+            window_shape: WindowShape::default(),
+            position: None,
+            hidpi_mode: HiDpiMode::default(),
+            pixel_snapping: false,
+            restore_size: false,
+            render_settings: RenderSettings::default(),
+            gl_context: GlContextMode::default(),
+            renderer_backend: None,
+            fonts: Vec::new(),
+            font_atlas: FontAtlasSharing::default(),
+            instance_key: None,
+            debug_overlay: false,
+            debug_overlay_hotkey: None,
+            dev_windows: DevWindows::default(),
+            dev_windows_hotkey: None,
+            host_passthrough: Vec::new(),
+            handle: Handle::new(),
+            midi_learn: false,
+            x11_primary_selection: false,
+            interaction: Interaction::default(),
+            tooltip_delay: Duration::from_millis(500),
+            locale: Locale::default(),
+            text_direction: TextDirection::default(),
+            ui_state: None,
+            shaders: Vec::new(),
+            texture_budget: None,
+            clock: None,
+            background_fps: None,
+            frame_mode: FrameMode::default(),
+            latency_mode: LatencyMode::default(),
+            frame_budget: None,
+            smooth_scrolling: None,
+            scroll_priority: ScrollPriority::default(),
+            key_recovery: KeyRecovery::default(),
+            key_repeat: None,
+            shortcut_matching: ShortcutMatching::default(),
+            focus_ring_style: None,
+            reduced_motion: None,
+            double_click_time: None,
+            drag_threshold: None,
+            auto_resize: None,
+            scroll_container: None,
+            breakpoints: Breakpoints::default(),
+            progress_overlay: false,
+            splash: None,
+            context_policy: ContextPolicy::default(),
+            context_timeout: None,
+            on_context_timeout: None,
+            crash_handler: None,
+            #[cfg(feature = "svg")]
+            icons: None,
+            #[cfg(feature = "event_recorder")]
+            event_recorder: None,
+            #[cfg(feature = "remote_control")]
+            remote_control: None,
+            #[cfg(feature = "remote_debug")]
+            remote_debug: None,
+            #[cfg(feature = "accessibility")]
+            accessibility_adapter: None,
+        }
+    }
+}
+
+impl Settings {
+    /// Start from the default settings, and check them when they are built.
+    pub fn builder() -> SettingsBuilder {
+        SettingsBuilder {
+            settings: Settings::default(),
+        }
+    }
+
+    /// Check that the settings make sense together. `SettingsBuilder::build` and the functions
+    /// that open a window call this.
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        let size = &self.window.size;
+        if !(size.width > 0.0 && size.height > 0.0) {
+            return Err(SettingsError::InvalidSize {
+                width: size.width,
+                height: size.height,
+            });
+        }
+
+        if let WindowScalePolicy::ScaleFactor(scale) = self.window.scale {
+            if !(scale.is_finite() && scale > 0.0) {
+                return Err(SettingsError::InvalidScaleFactor(scale));
+            }
+            // A fixed factor is the one to draw at, a hidpi mode that changes it contradicts it.
+            let hidpi_factor = self.hidpi_mode.apply(scale);
+            if hidpi_factor != scale {
+                return Err(SettingsError::ScaleFactorConflict {
+                    scale,
+                    hidpi_factor,
+                });
+            }
+        }
+
+        if !(0.0..=1.0).contains(&self.window_alpha) {
+            return Err(SettingsError::InvalidWindowAlpha(self.window_alpha));
+        }
+
+        if let WindowShape::Rounded { radius } = self.window_shape {
+            let max = size.width.min(size.height) as f32 / 2.0;
+            if !(0.0..=max).contains(&radius) {
+                return Err(SettingsError::InvalidCornerRadius(radius));
+            }
+        }
+
+        if let Some(fps) = self.background_fps {
+            if !(fps.is_finite() && fps > 0.0) {
+                return Err(SettingsError::InvalidBackgroundFps(fps));
+            }
+        }
+
+        if let Some(budget) = &self.frame_budget {
+            if !(budget.fps.is_finite() && budget.fps > 0.0) {
+                return Err(SettingsError::InvalidFrameBudgetFps(budget.fps));
+            }
+        }
+
+        if self.instance_key.as_deref() == Some("") {
+            return Err(SettingsError::EmptyInstanceKey);
+        }
+
+        if self.context_timeout == Some(Duration::from_secs(0)) {
+            return Err(SettingsError::ZeroContextTimeout);
+        }
+
+        if !self.gl_context.is_supported() {
+            return Err(SettingsError::UnsupportedGlContext);
+        }
+
+        Ok(())
+    }
+}
+
+/// Why `Settings::validate` rejected the settings.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SettingsError {
+    /// The window has no area.
+    InvalidSize { width: f64, height: f64 },
+    /// `WindowScalePolicy::ScaleFactor` is not a positive number.
+    InvalidScaleFactor(f64),
+    /// The `hidpi_mode` turns the fixed factor of `WindowScalePolicy::ScaleFactor` into
+    /// another one.
+    ScaleFactorConflict { scale: f64, hidpi_factor: f64 },
+    /// `window_alpha` is not between 0 and 1.
+    InvalidWindowAlpha(f32),
+    /// The radius of `WindowShape::Rounded` is negative or more than half the window.
+    InvalidCornerRadius(f32),
+    /// `background_fps` is not a positive number.
+    InvalidBackgroundFps(f32),
+    /// The frame rate of `frame_budget` is not a positive number.
+    InvalidFrameBudgetFps(f32),
+    /// `instance_key` is set to an empty key.
+    EmptyInstanceKey,
+    /// A `context_timeout` of zero would skip every event and frame another window overlaps.
+    ZeroContextTimeout,
+    /// The `gl_context` mode doesn't work on this platform.
+    UnsupportedGlContext,
+}
+
+impl std::fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettingsError::InvalidSize { width, height } => {
+                write!(f, "the window size {}x{} has no area", width, height)
+            }
+            SettingsError::InvalidScaleFactor(scale) => {
+                write!(f, "the scale factor {} is not a positive number", scale)
+            }
+            SettingsError::ScaleFactorConflict {
+                scale,
+                hidpi_factor,
+            } => write!(
+                f,
+                "the hidpi mode turns the fixed scale factor {} into {}",
+                scale, hidpi_factor
+            ),
+            SettingsError::InvalidWindowAlpha(alpha) => {
+                write!(f, "the window alpha {} is not between 0 and 1", alpha)
+            }
+            SettingsError::InvalidCornerRadius(radius) => write!(
+                f,
+                "the corner radius {} is negative or larger than half the window",
+                radius
+            ),
+            SettingsError::InvalidBackgroundFps(fps) => {
+                write!(
+                    f,
+                    "the background frame rate {} is not a positive number",
+                    fps
+                )
+            }
+            SettingsError::InvalidFrameBudgetFps(fps) => write!(
+                f,
+                "the frame rate {} of the frame budget is not a positive number",
+                fps
+            ),
+            SettingsError::EmptyInstanceKey => f.write_str("the instance key is empty"),
+            SettingsError::ZeroContextTimeout => f.write_str("the context timeout is zero"),
+            SettingsError::UnsupportedGlContext => {
+                f.write_str("the OpenGL context mode is not supported on this platform")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SettingsError {}
+
+/// Builds `Settings` with a setter for every field, and checks them with
+/// `Settings::validate`.
+#[allow(missing_debug_implementations)]
+pub struct SettingsBuilder {
+    settings: Settings,
+}
+
+impl SettingsBuilder {
+    /// The title of the window, shown by standalone windows.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.settings.window.title = title.into();
+        self
+    }
+
+    /// The logical size of the window.
+    pub fn size(mut self, width: f64, height: f64) -> Self {
+        self.settings.window.size = Size::new(width, height);
+        self
+    }
+
+    /// Whether to follow the scale factor of the system, or use a fixed one.
+    pub fn scale(mut self, scale: WindowScalePolicy) -> Self {
+        self.settings.window.scale = scale;
+        self
+    }
+
+    /// The color to clear the screen with before the UI is rendered.
+    pub fn clear_color(mut self, color: (f32, f32, f32)) -> Self {
+        self.settings.clear_color = color;
+        self
+    }
+
+    /// The opacity of the background, from 0 to 1. See `Settings::window_alpha`.
+    pub fn window_alpha(mut self, alpha: f32) -> Self {
+        self.settings.window_alpha = alpha;
+        self
+    }
+
+    /// How a standalone window behaves among the other windows.
+    pub fn window_style(mut self, style: WindowStyle) -> Self {
+        self.settings.window_style = style;
+        self
+    }
+
+    /// Where a standalone window is placed when it opens.
+    pub fn position(mut self, position: WindowPosition) -> Self {
+        self.settings.position = Some(position);
+        self
+    }
+
+    /// The outline of a window without decorations.
+    pub fn window_shape(mut self, shape: WindowShape) -> Self {
+        self.settings.window_shape = shape;
+        self
+    }
+
+    /// How the scale factor from baseview becomes the hidpi factor of imgui.
+    pub fn hidpi_mode(mut self, mode: HiDpiMode) -> Self {
+        self.settings.hidpi_mode = mode;
+        self
+    }
+
+    /// Move the vertices of the UI to the nearest physical pixel.
+    pub fn pixel_snapping(mut self, pixel_snapping: bool) -> Self {
+        self.settings.pixel_snapping = pixel_snapping;
+        self
+    }
+
+    /// Open the window at the size the user last resized it to.
+    pub fn restore_size(mut self, restore_size: bool) -> Self {
+        self.settings.restore_size = restore_size;
+        self
+    }
+
+    /// The settings of the built-in OpenGL renderer.
+    pub fn render_settings(mut self, render_settings: RenderSettings) -> Self {
+        self.settings.render_settings = render_settings;
+        self
+    }
+
+    /// Whether the window makes its own OpenGL context, or uses the one of the host.
+    pub fn gl_context(mut self, mode: GlContextMode) -> Self {
+        self.settings.gl_context = mode;
+        self
+    }
+
+    /// Render with a backend of your own instead of the built-in OpenGL renderer.
+    pub fn renderer_backend(mut self, factory: BackendFactory) -> Self {
+        self.settings.renderer_backend = Some(factory);
+        self
+    }
+
+    /// Add a font to `Settings::fonts`.
+    pub fn font(mut self, font: FontSource) -> Self {
+        self.settings.fonts.push(font);
+        self
+    }
+
+    /// How the font atlas is shared with the other windows of the process.
+    pub fn font_atlas(mut self, sharing: FontAtlasSharing) -> Self {
+        self.settings.font_atlas = sharing;
+        self
+    }
+
+    /// Register the window in the `instances` registry under this key.
+    pub fn instance_key(mut self, key: impl Into<String>) -> Self {
+        self.settings.instance_key = Some(key.into());
+        self
+    }
+
+    /// Show the debug overlay when the window opens.
+    pub fn debug_overlay(mut self, show: bool) -> Self {
+        self.settings.debug_overlay = show;
+        self
+    }
+
+    /// Toggle the debug overlay with this key combination.
+    pub fn debug_overlay_hotkey(mut self, hotkey: Hotkey) -> Self {
+        self.settings.debug_overlay_hotkey = Some(hotkey);
+        self
+    }
+
+    /// The tool windows of imgui to show over the UI.
+    pub fn dev_windows(mut self, dev_windows: DevWindows) -> Self {
+        self.settings.dev_windows = dev_windows;
+        self
+    }
+
+    /// Toggle the panel of the dev windows with this key combination.
+    pub fn dev_windows_hotkey(mut self, hotkey: Hotkey) -> Self {
+        self.settings.dev_windows_hotkey = Some(hotkey);
+        self
+    }
+
+    /// Add a rule to `Settings::host_passthrough`.
+    pub fn host_passthrough(mut self, rule: PassthroughRule) -> Self {
+        self.settings.host_passthrough.push(rule);
+        self
+    }
+
+    /// The connection to the host. Keep a clone to read the events of the window.
+    pub fn handle(mut self, handle: Handle) -> Self {
+        self.settings.handle = handle;
+        self
+    }
+
+    /// Add MIDI learn to the right-click menu of the param-bound widgets.
+    pub fn midi_learn(mut self, midi_learn: bool) -> Self {
+        self.settings.midi_learn = midi_learn;
+        self
+    }
+
+    /// Copy and paste through the primary selection of X11.
+    pub fn x11_primary_selection(mut self, enabled: bool) -> Self {
+        self.settings.x11_primary_selection = enabled;
+        self
+    }
+
+    /// How the param-bound widgets respond to dragging and modifier keys.
+    pub fn interaction(mut self, interaction: Interaction) -> Self {
+        self.settings.interaction = interaction;
+        self
+    }
+
+    /// How long the pointer rests on a widget before its help text is shown.
+    pub fn tooltip_delay(mut self, delay: Duration) -> Self {
+        self.settings.tooltip_delay = delay;
+        self
+    }
+
+    /// The translations of the text of the crate's widgets and overlays.
+    pub fn locale(mut self, locale: Locale) -> Self {
+        self.settings.locale = locale;
+        self
+    }
+
+    /// The direction of the text of the UI.
+    pub fn text_direction(mut self, direction: TextDirection) -> Self {
+        self.settings.text_direction = direction;
+        self
+    }
+
+    /// Restore the UI state of an earlier `UiEvent::SaveUiState`.
+    pub fn ui_state(mut self, ui_state: impl Into<String>) -> Self {
+        self.settings.ui_state = Some(ui_state.into());
+        self
+    }
+
+    /// Add a shader to `Settings::shaders`.
+    pub fn shader(mut self, shader: FragmentShader) -> Self {
+        self.settings.shaders.push(shader);
+        self
+    }
+
+    /// Evict the textures shown least recently once they use this many bytes.
+    pub fn texture_budget(mut self, bytes: u64) -> Self {
+        self.settings.texture_budget = Some(bytes);
+        self
+    }
+
+    /// The clock for the delta time of the frames, instead of real time.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.settings.clock = Some(clock);
+        self
+    }
+
+    /// The frame rate while the user works in another window.
+    pub fn background_fps(mut self, fps: f32) -> Self {
+        self.settings.background_fps = Some(fps);
+        self
+    }
+
+    /// Render every frame, or only when something changed.
+    pub fn frame_mode(mut self, mode: FrameMode) -> Self {
+        self.settings.frame_mode = mode;
+        self
+    }
+
+    /// Whether to trade frame rate for less input latency.
+    pub fn latency_mode(mut self, mode: LatencyMode) -> Self {
+        self.settings.latency_mode = mode;
+        self
+    }
+
+    /// Lighten the rendering when frames keep taking longer than the budget.
+    pub fn frame_budget(mut self, budget: FrameBudget) -> Self {
+        self.settings.frame_budget = Some(budget);
+        self
+    }
+
+    /// Animate the steps of a notched mouse wheel.
+    pub fn smooth_scrolling(mut self, smooth_scrolling: SmoothScrolling) -> Self {
+        self.settings.smooth_scrolling = Some(smooth_scrolling);
+        self
+    }
+
+    /// Whether imgui or a widget under the mouse gets the mouse wheel.
+    pub fn scroll_priority(mut self, priority: ScrollPriority) -> Self {
+        self.settings.scroll_priority = priority;
+        self
+    }
+
+    /// When held keys are released, for hosts that eat key releases.
+    pub fn key_recovery(mut self, key_recovery: KeyRecovery) -> Self {
+        self.settings.key_recovery = key_recovery;
+        self
+    }
+
+    /// Repeat held keys from the frame clock, for hosts that don't repeat them.
+    pub fn key_repeat(mut self, key_repeat: KeyRepeat) -> Self {
+        self.settings.key_repeat = Some(key_repeat);
+        self
+    }
+
+    /// Whether shortcuts follow the physical keys or the keyboard layout.
+    pub fn shortcut_matching(mut self, matching: ShortcutMatching) -> Self {
+        self.settings.shortcut_matching = matching;
+        self
+    }
+
+    /// Turn on keyboard navigation, with a focus ring in this style.
+    pub fn focus_ring_style(mut self, style: FocusRingStyle) -> Self {
+        self.settings.focus_ring_style = Some(style);
+        self
+    }
+
+    /// Turn animations that are only for show off or on, instead of following the system.
+    pub fn reduced_motion(mut self, reduced_motion: bool) -> Self {
+        self.settings.reduced_motion = Some(reduced_motion);
+        self
+    }
+
+    /// How far apart two clicks can be to be a double click, instead of the system setting.
+    pub fn double_click_time(mut self, time: Duration) -> Self {
+        self.settings.double_click_time = Some(time);
+        self
+    }
+
+    /// How far the mouse moves before it drags, instead of the system setting.
+    pub fn drag_threshold(mut self, threshold: f32) -> Self {
+        self.settings.drag_threshold = Some(threshold);
+        self
+    }
+
+    /// Resize the window to fit the content marked with `WindowContext::fit_to_item`.
+    pub fn auto_resize(mut self, auto_resize: AutoResize) -> Self {
+        self.settings.auto_resize = Some(auto_resize);
+        self
+    }
+
+    /// Lay the UI out at a size of its own, panned in a smaller window.
+    pub fn scroll_container(mut self, scroll_container: ScrollContainer) -> Self {
+        self.settings.scroll_container = Some(scroll_container);
+        self
+    }
+
+    /// The widths where `WindowContext::size_class` changes.
+    pub fn breakpoints(mut self, breakpoints: Breakpoints) -> Self {
+        self.settings.breakpoints = breakpoints;
+        self
+    }
+
+    /// Show the jobs of `WindowContext::tasks` with their progress.
+    pub fn progress_overlay(mut self, progress_overlay: bool) -> Self {
+        self.settings.progress_overlay = progress_overlay;
+        self
+    }
+
+    /// Show a splash screen until the work of its loader is done.
+    pub fn splash(mut self, splash: crate::splash::Splash) -> Self {
+        self.settings.splash = Some(splash);
+        self
+    }
+
+    /// How the window shares the imgui context with the other windows.
+    pub fn context_policy(mut self, policy: ContextPolicy) -> Self {
+        self.settings.context_policy = policy;
+        self
+    }
+
+    /// Wait this long for the imgui context, or forever with `None`.
+    pub fn context_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.settings.context_timeout = timeout;
+        self
+    }
+
+    /// Called when the window gave up waiting for the imgui context.
+    pub fn on_context_timeout<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&TimeoutReport) + Send + Sync + 'static,
+    {
+        self.settings.on_context_timeout = Some(Arc::new(callback));
+        self
+    }
+
+    /// Catch the panics of the update closure, and call `handler` with a report.
+    pub fn crash_handler<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&CrashReport) + Send + Sync + 'static,
+    {
+        self.settings.crash_handler = Some(Arc::new(handler));
+        self
+    }
+
+    /// SVG icons to rasterize at the hidpi factor of the window.
+    #[cfg(feature = "svg")]
+    pub fn icons(mut self, icons: crate::IconSet) -> Self {
+        self.settings.icons = Some(icons);
+        self
+    }
+
+    /// Record the input of the window, or replay a recording.
+    #[cfg(feature = "event_recorder")]
+    pub fn event_recorder(mut self, recorder: crate::recorder::EventRecorder) -> Self {
+        self.settings.event_recorder = Some(recorder);
+        self
+    }
+
+    /// Let OSC messages read and set the parameters of the window.
+    #[cfg(feature = "remote_control")]
+    pub fn remote_control(mut self, remote_control: crate::remote_control::RemoteControl) -> Self {
+        self.settings.remote_control = Some(remote_control);
+        self
+    }
+
+    /// Stream the stats and draw lists of every frame over TCP.
+    #[cfg(feature = "remote_debug")]
+    pub fn remote_debug(mut self, remote_debug: crate::remote_debug::RemoteDebug) -> Self {
+        self.settings.remote_debug = Some(remote_debug);
+        self
+    }
+
+    /// Publish the accessibility tree of the window with this adapter.
+    #[cfg(feature = "accessibility")]
+    pub fn accessibility_adapter(mut self, factory: crate::accessibility::AdapterFactory) -> Self {
+        self.settings.accessibility_adapter = Some(factory);
+        self
+    }
+
+    /// The settings, if they pass `Settings::validate`.
+    pub fn build(self) -> Result<Settings, SettingsError> {
+        self.settings.validate()?;
+        Ok(self.settings)
+    }
+}
+
+/// How a standalone window behaves among the other windows, for detached editor panels that
+/// should act like utility windows.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WindowStyle {
+    /// Keep the window above normal windows.
+    pub always_on_top: bool,
+    /// Leave the window out of the taskbar, or the window list of X11 task bars.
+    pub skip_taskbar: bool,
+    /// Style the window as a tool window: a small title bar on Windows, a utility window type
+    /// on X11, and on macOS a window that is not part of the window cycle.
+    pub tool_window: bool,
+}
+
+/// How the steps of the mouse wheel are animated.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SmoothScrolling {
+    /// About how long a step takes to scroll. Steps in quick succession blend together.
+    pub duration: Duration,
+    /// Animate the horizontal axis, for wheels that tilt.
+    pub horizontal: bool,
+    pub vertical: bool,
+}
+
+impl SmoothScrolling {
+    /// The angular frequency of the spring, which brings it within a few percent of rest
+    /// after `duration`.
+    pub(crate) fn omega(&self) -> f32 {
+        5.0 / self.duration.as_secs_f32().max(0.001)
+    }
+}
+
+impl Default for SmoothScrolling {
+    fn default() -> Self {
+        Self {
+            duration: Duration::from_millis(100),
+            horizontal: true,
+            vertical: true,
+        }
+    }
+}
+
+/// When the window renders a frame.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FrameMode {
+    /// Every time baseview asks for a frame.
+    Continuous,
+    /// Only after input, while the wheel scrolls smoothly, and when `Handle::request_frame`
+    /// was called, for hosts that call the frame timer far more often than the UI changes.
+    /// Any number of requests between two frames give one frame. Animations request the
+    /// next frame from the update closure.
+    OnDemand,
+}
+
+impl Default for FrameMode {
+    fn default() -> Self {
+        FrameMode::Continuous
+    }
+}
+
+/// How far the renderer lets the GPU fall behind.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LatencyMode {
+    /// Let the driver queue frames, for the highest frame rate.
+    Default,
+    /// Wait until the GPU finished every frame after the buffer swap with `glFinish`, so
+    /// knob drags don't lag behind the mouse in hosts whose drivers queue several frames.
+    /// Custom backends wait on their swap chain instead, like with a frame latency waitable
+    /// object. Costs frame rate on slow GPUs.
+    LowLatency,
+}
+
+impl Default for LatencyMode {
+    fn default() -> Self {
+        LatencyMode::Default
+    }
+}
+
+/// When and how a window lightens its rendering.
+///
+/// After `frames` frames in a row took longer than `budget`, the window stops multisampling,
+/// stops blurring the glass backdrop and renders at most `fps` frames a second, until it is
+/// closed. The wait for the buffer swap doesn't count, since with vsync it takes up the rest
+/// of every frame. Multisampling is dropped by creating the OpenGL context again, which is
+/// restored like a lost one with `WindowContext::on_context_restored`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameBudget {
+    pub budget: Duration,
+    pub frames: u32,
+    pub fps: f32,
+}
+
+impl Default for FrameBudget {
+    fn default() -> Self {
+        Self {
+            budget: Duration::from_millis(10),
+            frames: 30,
+            fps: 30.0,
+        }
+    }
+}
+
+/// Who gets the mouse wheel.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScrollPriority {
+    /// imgui scrolls its windows, and widgets can read the same scrolling from
+    /// `WindowContext::input`.
+    Imgui,
+    /// A widget under the mouse that called `WindowContext::claim_scroll` in the last frame
+    /// gets the wheel to itself, so the window around a knob doesn't scroll while it turns.
+    HoveredWidget,
+}
+
+impl Default for ScrollPriority {
+    fn default() -> Self {
+        ScrollPriority::Imgui
+    }
+}
+
+/// When the keys held down in imgui are released without a key release event.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyRecovery {
+    /// Release the keys when the window loses the keyboard focus.
+    pub on_unfocus: bool,
+    /// Release the keys when no key event arrived for this long while one is held down.
+    /// Modifiers don't repeat, so this should be longer than a modifier is usually held.
+    pub timeout: Option<Duration>,
+}
+
+impl Default for KeyRecovery {
+    fn default() -> Self {
+        Self {
+            on_unfocus: true,
+            timeout: None,
+        }
+    }
+}
+
+/// How a held key repeats.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyRepeat {
+    /// How long the key is held before it repeats.
+    pub delay: Duration,
+    /// The time between two repeats.
+    pub interval: Duration,
+}
+
+impl KeyRepeat {
+    /// The key repeat of the keyboard settings of the system, or the default if they can't
+    /// be read.
+    pub fn system() -> Self {
+        match crate::platform::key_repeat() {
+            Some((delay, interval)) => Self { delay, interval },
+            None => Self::default(),
+        }
+    }
+}
+
+impl Default for KeyRepeat {
+    fn default() -> Self {
+        Self {
+            delay: Duration::from_millis(500),
+            interval: Duration::from_millis(33),
+        }
+    }
+}
+
+/// How the key of a shortcut is matched to the key that was pressed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ShortcutMatching {
+    /// By the physical key, so `Code::KeyZ` is the key next to X, which is labeled Y on a
+    /// German keyboard.
+    Physical,
+    /// Letters and digits by the character the keyboard layout types, so `Code::KeyZ` is the
+    /// key labeled Z on any layout. Other keys, like the arrows and the function keys, are
+    /// still matched by the physical key.
+    Layout,
+}
+
+impl Default for ShortcutMatching {
+    fn default() -> Self {
+        ShortcutMatching::Physical
+    }
+}
+
+/// How the ring around the item with keyboard focus looks, in logical pixels.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FocusRingStyle {
+    /// The color, as RGBA from `0.0` to `1.0`.
+    pub color: [f32; 4],
+    pub thickness: f32,
+    /// The space between the item and the ring.
+    pub padding: f32,
+    pub rounding: f32,
+}
+
+impl Default for FocusRingStyle {
+    /// A thick amber ring, which stands out on light and dark themes.
+    fn default() -> Self {
+        Self {
+            color: [1.0, 0.7, 0.0, 1.0],
+            thickness: 3.0,
+            padding: 2.0,
+            rounding: 4.0,
+        }
+    }
+}
+
+/// The sizes `Settings::auto_resize` keeps the window within, in logical pixels, and how
+/// long the content keeps a size before the window is resized to it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AutoResize {
+    pub min: Option<[f32; 2]>,
+    pub max: Option<[f32; 2]>,
+    pub debounce: Duration,
+}
+
+impl Default for AutoResize {
+    fn default() -> Self {
+        Self {
+            min: None,
+            max: None,
+            debounce: Duration::from_millis(150),
+        }
+    }
+}
+
+/// The size `Settings::scroll_container` lays the UI out at, in logical pixels. A window
+/// that is larger stretches the UI to its size.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScrollContainer {
+    pub size: [f32; 2],
+}
+
+/// Where a standalone window opens.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WindowPosition {
+    /// Centered on the primary monitor.
+    Centered,
+    /// With the top left corner at a point, in the coordinates of `monitor::monitors`.
+    AtPoint([i32; 2]),
+    /// Centered on a monitor from `monitor::monitors`, for example the one with the window of
+    /// the host from `monitor::monitor_of`.
+    OnMonitor(usize),
+}
+
+/// The outline of a standalone window.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WindowShape {
+    Rectangle,
+    /// Round the corners with a radius in logical pixels.
+    Rounded {
+        radius: f32,
+    },
+}
+
+impl Default for WindowShape {
+    fn default() -> Self {
+        WindowShape::Rectangle
+    }
+}
+
+/// The tool windows of imgui, which are drawn after the update closure.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DevWindows {
+    /// The demo of every widget, with the code of each.
+    pub demo: bool,
+    /// The internals of the windows, draw lists and tables.
+    pub metrics: bool,
+    pub style_editor: bool,
+    /// The version of imgui and its build flags.
+    pub about: bool,
+}
+
+/// Input that is left to the host. The release of a key or button that matched goes to the
+/// host too, and so does the mouse movement while the button is held.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PassthroughRule {
+    /// The modifiers that need to be held. Caps lock and num lock are ignored.
+    pub modifiers: Modifiers,
+    pub input: PassthroughInput,
+}
+
+impl PassthroughRule {
+    pub fn new(modifiers: Modifiers, input: PassthroughInput) -> Self {
+        Self { modifiers, input }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PassthroughInput {
+    /// A physical key, with its repeats.
+    Key(Code),
+    MouseButton(PassthroughButton),
+    /// The mouse wheel and trackpad scrolling.
+    Wheel,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PassthroughButton {
+    Left,
+    Right,
+    Middle,
+    Back,
+    Forward,
+    Other(u8),
+}
+
+/// A key combination.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hotkey {
+    /// The modifiers that need to be held. Caps lock and num lock are ignored.
+    pub modifiers: Modifiers,
+    /// The physical key.
+    pub code: Code,
+}
+
+impl Hotkey {
+    pub fn new(modifiers: Modifiers, code: Code) -> Self {
+        Self { modifiers, code }
+    }
+
+    pub(crate) fn matches(&self, event: &KeyboardEvent) -> bool {
+        let mask = Modifiers::SHIFT | Modifiers::CONTROL | Modifiers::ALT | Modifiers::META;
+
+        event.state == KeyState::Down
+            && !event.repeat
+            && event.code == self.code
+            && event.modifiers & mask == self.modifiers & mask
+    }
+
+    /// Whether the event is the combination, with `code` as the key that was pressed.
+    pub(crate) fn matches_code(&self, event: &KeyboardEvent, code: Code) -> bool {
+        self.matches(&KeyboardEvent {
+            code,
+            ..event.clone()
+        })
+    }
+}
+
+impl std::fmt::Display for Hotkey {
+    /// Format the combination for menus and help text, like `Ctrl+Shift+S`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names = if cfg!(target_os = "macos") {
+            ["Ctrl", "Option", "Shift", "Cmd"]
+        } else {
+            ["Ctrl", "Alt", "Shift", "Super"]
+        };
+        let modifiers = [
+            Modifiers::CONTROL,
+            Modifiers::ALT,
+            Modifiers::SHIFT,
+            Modifiers::META,
+        ];
+        for (modifier, name) in modifiers.iter().zip(&names) {
+            if self.modifiers.contains(*modifier) {
+                write!(f, "{}+", name)?;
+            }
+        }
+
+        let code = self.code.to_string();
+        if let Some(key) = code.strip_prefix("Numpad") {
+            return write!(f, "Num {}", key);
+        }
+        let key = code
+            .strip_prefix("Key")
+            .or_else(|| code.strip_prefix("Digit"))
+            .or_else(|| code.strip_prefix("Arrow"))
+            .unwrap_or(&code);
+        f.write_str(key)
+    }
+}
+
+/// `WindowOpenOptions` in a configuration file, with a scale of `None` following the system.
+#[cfg(feature = "serde")]
+mod window_options {
+    use baseview::{Size, WindowOpenOptions, WindowScalePolicy};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct WindowOptions {
+        title: String,
+        size: [f64; 2],
+        scale: Option<f64>,
+    }
+
+    pub fn serialize<S>(options: &WindowOpenOptions, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let scale = match options.scale {
+            WindowScalePolicy::ScaleFactor(scale) => Some(scale),
+            WindowScalePolicy::SystemScaleFactor => None,
+        };
+        WindowOptions {
+            title: options.title.clone(),
+            size: [options.size.width, options.size.height],
+            scale,
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<WindowOpenOptions, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let options = WindowOptions::deserialize(deserializer)?;
+        Ok(WindowOpenOptions {
+            title: options.title,
+            size: Size::new(options.size[0], options.size[1]),
+            scale: match options.scale {
+                Some(scale) => WindowScalePolicy::ScaleFactor(scale),
+                None => WindowScalePolicy::SystemScaleFactor,
+            },
+        })
+    }
 }
@@ -0,0 +1,115 @@
+//! Rectangles drawn by your own fragment shaders, for visuals like animated spectrums that
+//! would be too slow to draw with the draw list or to upload as textures every frame.
+//!
+//! Register the shaders in `Settings::shaders`, then call `ui_shader_rect` while building
+//! the UI. Each rectangle is rendered on the GPU into a texture right before the imgui draw
+//! data, and shown as an image, so it is clipped and layered like any other item.
+//!
+//! The source of a shader is the body of a GLSL 1.50 fragment shader, without the `#version`
+//! line. These declarations come before it:
+//!
+//! ```glsl
+//! in vec2 v_uv;              // (0, 0) at the bottom left to (1, 1) at the top right
+//! uniform vec2 u_size;       // the size of the rectangle in physical pixels
+//! uniform float u_values[16]; // the uniforms passed to `ui_shader_rect`
+//! out vec4 out_color;
+//! ```
+
+use std::cell::RefCell;
+
+/// The id of a fragment shader, chosen by the application.
+pub type ShaderId = u32;
+
+/// The number of values that can be passed to a shader every frame.
+pub const MAX_UNIFORMS: usize = 16;
+
+/// A fragment shader to compile when the window opens.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FragmentShader {
+    pub id: ShaderId,
+    pub source: String,
+}
+
+impl FragmentShader {
+    pub fn new(id: ShaderId, source: impl Into<String>) -> Self {
+        Self {
+            id,
+            source: source.into(),
+        }
+    }
+}
+
+/// A rectangle to render with a shader this frame.
+pub(crate) struct Quad {
+    pub shader: ShaderId,
+    /// The size in physical pixels.
+    pub size: [u32; 2],
+    pub uniforms: [f32; MAX_UNIFORMS],
+}
+
+#[derive(Default)]
+struct Frame {
+    quads: Vec<Quad>,
+    /// The textures the renderer keeps for the quads, in the order they are drawn.
+    targets: Vec<imgui::TextureId>,
+    hidpi_factor: f32,
+}
+
+thread_local! {
+    // Windows build their frames one after the other on the thread they were opened on.
+    static FRAME: RefCell<Frame> = RefCell::new(Frame::default());
+}
+
+/// Start collecting the quads of a frame of a window.
+pub(crate) fn begin_frame(targets: Vec<imgui::TextureId>, hidpi_factor: f64) {
+    FRAME.with(|frame| {
+        *frame.borrow_mut() = Frame {
+            quads: Vec::new(),
+            targets,
+            hidpi_factor: hidpi_factor as f32,
+        };
+    });
+}
+
+/// The quads to render for the frame.
+pub(crate) fn end_frame() -> Vec<Quad> {
+    FRAME.with(|frame| std::mem::take(&mut frame.borrow_mut().quads))
+}
+
+/// A rectangle of `size` filled by the fragment shader registered with `shader`.
+///
+/// Up to `MAX_UNIFORMS` values are passed to the shader in `u_values`, the rest is ignored.
+/// The first time a rectangle is drawn there is no texture for it yet, so it stays empty for
+/// that frame.
+pub fn ui_shader_rect(ui: &imgui::Ui, shader: ShaderId, size: [f32; 2], uniforms: &[f32]) {
+    let target = FRAME.with(|frame| {
+        let mut frame = frame.borrow_mut();
+
+        let mut values = [0.0; MAX_UNIFORMS];
+        for (value, uniform) in values.iter_mut().zip(uniforms) {
+            *value = *uniform;
+        }
+        let scale = frame.hidpi_factor;
+        let target = frame.targets.get(frame.quads.len()).copied();
+        frame.quads.push(Quad {
+            shader,
+            size: [
+                (size[0] * scale).round().max(1.0) as u32,
+                (size[1] * scale).round().max(1.0) as u32,
+            ],
+            uniforms: values,
+        });
+
+        target
+    });
+
+    match target {
+        // The texture is rendered bottom up.
+        Some(texture) => imgui::Image::new(texture, size)
+            .uv0([0.0, 1.0])
+            .uv1([1.0, 0.0])
+            .build(ui),
+        None => ui.dummy(size),
+    }
+}
@@ -0,0 +1,279 @@
+//! Keyboard shortcuts that trigger actions of the application.
+//!
+//! Shortcuts are registered with an action of any type, like a `SavePreset` variant of an
+//! enum of the application. When the key combination is pressed, the action can be taken
+//! from the registry in the update closure with `Shortcuts::take_actions`.
+//!
+//! A shortcut is either global, or scoped to a widget and only active while that widget has
+//! keyboard focus. Scoped shortcuts take precedence over global ones. Key presses that
+//! trigger a shortcut are not passed on to imgui, so `Ctrl+S` works while a text field has
+//! focus. Shortcuts without Ctrl, Alt or the command key are left to the text field instead.
+
+use std::any::Any;
+
+use keyboard_types::{Code, KeyboardEvent, Modifiers};
+
+use crate::locale::{Locale, Text};
+use crate::settings::Hotkey;
+
+/// Where a shortcut is active.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Scope {
+    /// Anywhere in the window.
+    Global,
+    /// While the widget marked with `WindowContext::shortcut_scope` under this name has
+    /// keyboard focus.
+    Widget(String),
+}
+
+/// A shortcut could not be registered because its key combination is already used in the
+/// same scope.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShortcutConflict {
+    pub hotkey: Hotkey,
+    pub scope: Scope,
+    /// The description of the shortcut that already uses it.
+    pub existing: String,
+}
+
+/// The key combinations of the registered shortcuts, to save the ones the user changed and
+/// restore them with `Shortcuts::apply_keymap` the next time.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Keymap {
+    pub bindings: Vec<Binding>,
+}
+
+/// The key combination of the shortcut with a description in a scope.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Binding {
+    pub scope: Scope,
+    pub description: String,
+    pub hotkey: Hotkey,
+}
+
+struct Entry {
+    hotkey: Hotkey,
+    scope: Scope,
+    description: String,
+    action: Box<dyn Fn() -> Box<dyn Any>>,
+}
+
+/// The shortcuts of a window.
+#[allow(missing_debug_implementations)]
+#[derive(Default)]
+pub struct Shortcuts {
+    entries: Vec<Entry>,
+    /// The scopes that had focus in the last frame.
+    active_scopes: Vec<String>,
+    next_scopes: Vec<String>,
+    triggered: Vec<Box<dyn Any>>,
+}
+
+impl Shortcuts {
+    /// Trigger `action` when `hotkey` is pressed in `scope`. The description is shown in the
+    /// shortcuts help window.
+    pub fn register<A>(
+        &mut self,
+        hotkey: Hotkey,
+        scope: Scope,
+        description: impl Into<String>,
+        action: A,
+    ) -> Result<(), ShortcutConflict>
+    where
+        A: Clone + 'static,
+    {
+        if let Some(existing) = self.find(hotkey, &scope) {
+            return Err(ShortcutConflict {
+                hotkey,
+                scope,
+                existing: existing.description.clone(),
+            });
+        }
+
+        self.entries.push(Entry {
+            hotkey,
+            scope,
+            description: description.into(),
+            action: Box::new(move || Box::new(action.clone())),
+        });
+        Ok(())
+    }
+
+    /// Remove the shortcut with this key combination from the scope.
+    pub fn unregister(&mut self, hotkey: Hotkey, scope: &Scope) {
+        self.entries
+            .retain(|entry| !(same_keys(entry.hotkey, hotkey) && entry.scope == *scope));
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// The registered shortcuts with their scope and description, in the order they were
+    /// registered.
+    pub fn iter(&self) -> impl Iterator<Item = (Hotkey, &Scope, &str)> {
+        self.entries
+            .iter()
+            .map(|entry| (entry.hotkey, &entry.scope, entry.description.as_str()))
+    }
+
+    /// The current key combination of every shortcut.
+    pub fn keymap(&self) -> Keymap {
+        Keymap {
+            bindings: self
+                .entries
+                .iter()
+                .map(|entry| Binding {
+                    scope: entry.scope.clone(),
+                    description: entry.description.clone(),
+                    hotkey: entry.hotkey,
+                })
+                .collect(),
+        }
+    }
+
+    /// Give the shortcuts with the scope and description of a binding its key combination.
+    /// Bindings of shortcuts that are not registered are ignored, and bindings that would
+    /// use a key combination twice in a scope are returned and not applied.
+    pub fn apply_keymap(&mut self, keymap: &Keymap) -> Vec<ShortcutConflict> {
+        let mut conflicts = Vec::new();
+        for binding in &keymap.bindings {
+            let index = self.entries.iter().position(|entry| {
+                entry.scope == binding.scope && entry.description == binding.description
+            });
+            let index = match index {
+                Some(index) => index,
+                None => continue,
+            };
+
+            let existing = self.entries.iter().enumerate().find(|(i, entry)| {
+                *i != index
+                    && entry.scope == binding.scope
+                    && same_keys(entry.hotkey, binding.hotkey)
+            });
+            match existing {
+                Some((_, existing)) => conflicts.push(ShortcutConflict {
+                    hotkey: binding.hotkey,
+                    scope: binding.scope.clone(),
+                    existing: existing.description.clone(),
+                }),
+                None => self.entries[index].hotkey = binding.hotkey,
+            }
+        }
+        conflicts
+    }
+
+    /// The actions of type `A` that were triggered since the last frame, in the order their
+    /// keys were pressed. Actions that are not taken are dropped after the update closure.
+    pub fn take_actions<A: 'static>(&mut self) -> Vec<A> {
+        let mut actions = Vec::new();
+        let mut i = 0;
+        while i < self.triggered.len() {
+            if self.triggered[i].is::<A>() {
+                actions.push(*self.triggered.remove(i).downcast().unwrap());
+            } else {
+                i += 1;
+            }
+        }
+        actions
+    }
+
+    fn find(&self, hotkey: Hotkey, scope: &Scope) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .find(|entry| same_keys(entry.hotkey, hotkey) && entry.scope == *scope)
+    }
+
+    pub(crate) fn mark_scope(&mut self, ui: &imgui::Ui, scope: &str) {
+        if ui.is_item_focused() || ui.is_item_active() {
+            self.next_scopes.push(scope.to_string());
+        }
+    }
+
+    /// Trigger the shortcut of a key press, with `code` as the key that was pressed under
+    /// `Settings::shortcut_matching`. Returns whether the key should be kept from imgui.
+    pub(crate) fn dispatch(
+        &mut self,
+        event: &KeyboardEvent,
+        code: Code,
+        want_text_input: bool,
+    ) -> bool {
+        let active_scopes = &self.active_scopes;
+        let matches = |entry: &&Entry| entry.hotkey.matches_code(event, code);
+        let scoped = self.entries.iter().find(|entry| match &entry.scope {
+            Scope::Widget(name) => active_scopes.contains(name) && matches(entry),
+            Scope::Global => false,
+        });
+        let entry = scoped.or_else(|| {
+            self.entries
+                .iter()
+                .find(|entry| entry.scope == Scope::Global && matches(entry))
+        });
+
+        match entry {
+            Some(entry) if !want_text_input || is_command(entry.hotkey) => {
+                self.triggered.push((entry.action)());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub(crate) fn end_frame(&mut self) {
+        self.active_scopes = std::mem::take(&mut self.next_scopes);
+        self.triggered.clear();
+    }
+
+    pub(crate) fn help_window(&self, ui: &imgui::Ui, locale: &Locale, opened: &mut bool) {
+        use imgui::{Condition, Window};
+
+        let mut scopes: Vec<&Scope> = Vec::new();
+        for entry in &self.entries {
+            if !scopes.contains(&&entry.scope) {
+                scopes.push(&entry.scope);
+            }
+        }
+        // Global shortcuts are listed first.
+        scopes.sort_by_key(|scope| **scope != Scope::Global);
+
+        Window::new(locale.get(Text::KeyboardShortcuts))
+            .opened(opened)
+            .size([360.0, 300.0], Condition::FirstUseEver)
+            .build(ui, || {
+                for scope in scopes {
+                    let header = match scope {
+                        Scope::Global => locale.get(Text::GlobalShortcuts),
+                        Scope::Widget(name) => name.as_str(),
+                    };
+                    ui.text_disabled(header);
+                    ui.separator();
+
+                    ui.columns(2, header, false);
+                    for entry in self.entries.iter().filter(|entry| entry.scope == *scope) {
+                        ui.text(&entry.description);
+                        ui.next_column();
+                        ui.text(entry.hotkey.to_string());
+                        ui.next_column();
+                    }
+                    ui.columns(1, header, false);
+                    ui.spacing();
+                }
+            });
+    }
+}
+
+/// Whether two key combinations are the same once caps lock and num lock are ignored.
+fn same_keys(a: Hotkey, b: Hotkey) -> bool {
+    let mask = Modifiers::SHIFT | Modifiers::CONTROL | Modifiers::ALT | Modifiers::META;
+    a.code == b.code && a.modifiers & mask == b.modifiers & mask
+}
+
+/// Whether the combination can't be typed text.
+fn is_command(hotkey: Hotkey) -> bool {
+    hotkey
+        .modifiers
+        .intersects(Modifiers::CONTROL | Modifiers::ALT | Modifiers::META)
+}
@@ -0,0 +1,187 @@
+//! A splash screen shown while the UI loads.
+//!
+//! A UI that decodes its textures or reads its presets before it can show anything leaves
+//! the window black for seconds. With `Settings::splash` that work runs on a worker thread
+//! instead, through the loader of the `Splash`, and the window shows a logo and a spinner
+//! until it is done. The update closure isn't called until then. It picks up what the loader
+//! returned with `WindowContext::take_loaded`.
+//!
+//! The loader is a job of `WindowContext::tasks`, so the progress it reports is shown under
+//! the spinner.
+
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use imgui::{Condition, WindowFlags};
+
+use crate::tasks::{Progress, Task, TaskStatus};
+use crate::textures::{TextureKey, TexturePixels};
+use crate::WindowContext;
+
+/// The radius of the spinner, in logical pixels.
+const SPINNER_RADIUS: f32 = 12.0;
+/// How many dots go around the spinner.
+const SPINNER_DOTS: usize = 8;
+/// The width of the progress bar under the spinner, in logical pixels.
+const PROGRESS_WIDTH: f32 = 200.0;
+
+type Loader = Arc<dyn Fn(&Progress) -> Box<dyn Any + Send> + Send + Sync>;
+
+/// What the splash screen shows, and the work it waits for.
+#[derive(Clone)]
+pub struct Splash {
+    loader: Loader,
+    logo: Option<Arc<TexturePixels>>,
+    min_duration: Duration,
+}
+
+impl fmt::Debug for Splash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Splash")
+            .field("logo", &self.logo.is_some())
+            .field("min_duration", &self.min_duration)
+            .finish()
+    }
+}
+
+impl Splash {
+    /// A splash screen that waits for `loader`, which runs on a worker thread.
+    pub fn new<T, F>(loader: F) -> Self
+    where
+        T: Send + 'static,
+        F: Fn(&Progress) -> T + Send + Sync + 'static,
+    {
+        Self {
+            loader: Arc::new(move |progress| Box::new(loader(progress))),
+            logo: None,
+            min_duration: Duration::from_millis(0),
+        }
+    }
+
+    /// An image to show above the spinner, at one logical pixel a pixel.
+    pub fn logo(mut self, logo: TexturePixels) -> Self {
+        self.logo = Some(Arc::new(logo));
+        self
+    }
+
+    /// Keep the splash screen up for at least `duration`, so it doesn't flash when the loader
+    /// is quick.
+    pub fn min_duration(mut self, duration: Duration) -> Self {
+        self.min_duration = duration;
+        self
+    }
+}
+
+/// The splash screen of a window that is still loading.
+pub(crate) struct SplashScreen {
+    task: Task<Box<dyn Any + Send>>,
+    job: u64,
+    logo: Option<(TextureKey, [f32; 2])>,
+    shown_until: Instant,
+}
+
+impl SplashScreen {
+    /// Start the loader of `splash`.
+    pub fn new(splash: Splash, ctx: &mut WindowContext) -> Self {
+        let loader = splash.loader;
+        let (job, task) = ctx
+            .tasks
+            .spawn_job("Loading", move |progress| loader(progress));
+        let logo = splash.logo.map(|pixels| {
+            let size = [pixels.width as f32, pixels.height as f32];
+            let key = ctx.add_texture(move || Some((*pixels).clone()));
+            (key, size)
+        });
+
+        Self {
+            task,
+            job,
+            logo,
+            shown_until: Instant::now() + splash.min_duration,
+        }
+    }
+
+    /// Draw the splash screen, and return whether it is still shown. Once the loader is done,
+    /// what it returned goes to `WindowContext::take_loaded`.
+    pub fn draw(&mut self, ui: &imgui::Ui, ctx: &mut WindowContext) -> bool {
+        let status = self.task.status();
+        if status != TaskStatus::Running && Instant::now() >= self.shown_until {
+            ctx.loaded = self.task.take();
+            if let Some((key, _)) = self.logo.take() {
+                ctx.remove_texture(key);
+            }
+            return false;
+        }
+
+        let display_size = ui.io().display_size;
+        let logo = self
+            .logo
+            .and_then(|(key, size)| ctx.texture(key).map(|texture| (texture, size)));
+        let progress = ctx.tasks.jobs().into_iter().find(|job| job.id == self.job);
+
+        let flags = WindowFlags::NO_DECORATION
+            | WindowFlags::NO_SAVED_SETTINGS
+            | WindowFlags::NO_NAV
+            | WindowFlags::NO_MOVE
+            | WindowFlags::NO_BACKGROUND;
+        imgui::Window::new("##splash")
+            .position([0.0, 0.0], Condition::Always)
+            .size(display_size, Condition::Always)
+            .flags(flags)
+            .build(ui, || {
+                let center = display_size[0] * 0.5;
+                let mut y = display_size[1] * 0.4;
+
+                if let Some((texture, size)) = logo {
+                    // Large logos are scaled down to fit half of the window.
+                    let scale = (display_size[0] * 0.5 / size[0])
+                        .min(display_size[1] * 0.5 / size[1])
+                        .min(1.0);
+                    let size = [size[0] * scale, size[1] * scale];
+                    ui.set_cursor_pos([center - size[0] * 0.5, y - size[1]]);
+                    imgui::Image::new(texture, size).build(ui);
+                    y += 16.0;
+                }
+
+                let spinner = [center, y + SPINNER_RADIUS];
+                let draw_list = ui.get_window_draw_list();
+                let color = ui.style_color(imgui::StyleColor::Text);
+                let step = (ui.time() * SPINNER_DOTS as f64 * 1.25) as usize;
+                for i in 0..SPINNER_DOTS {
+                    let angle = i as f32 / SPINNER_DOTS as f32 * std::f32::consts::PI * 2.0;
+                    // The dots fade out behind the one that leads.
+                    let age = (step + SPINNER_DOTS - i) % SPINNER_DOTS;
+                    let alpha = 1.0 - age as f32 / SPINNER_DOTS as f32;
+                    let dot = [
+                        spinner[0] + angle.cos() * SPINNER_RADIUS,
+                        spinner[1] + angle.sin() * SPINNER_RADIUS,
+                    ];
+                    draw_list
+                        .add_circle(dot, 2.5, [color[0], color[1], color[2], color[3] * alpha])
+                        .filled(true)
+                        .build();
+                }
+                y += SPINNER_RADIUS * 2.0 + 16.0;
+
+                if let Some(progress) = progress {
+                    if let Some(message) = &progress.message {
+                        let width = ui.calc_text_size(message)[0];
+                        ui.set_cursor_pos([center - width * 0.5, y]);
+                        ui.text(message);
+                        y += ui.text_line_height_with_spacing();
+                    }
+                    if let Some(fraction) = progress.fraction {
+                        ui.set_cursor_pos([center - PROGRESS_WIDTH * 0.5, y]);
+                        imgui::ProgressBar::new(fraction)
+                            .size([PROGRESS_WIDTH, 0.0])
+                            .build(ui);
+                    }
+                }
+            });
+
+        ctx.handle.request_frame();
+        true
+    }
+}
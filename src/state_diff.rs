@@ -0,0 +1,156 @@
+//! Telling the host which fields of the state changed.
+//!
+//! Hosts that mirror the state of the UI, like a plugin wrapper that keeps its own copy of
+//! the settings, would otherwise compare all of it after every frame or be sent all of it.
+//! A state that implements `StateDiff` can instead be watched with
+//! `WindowContext::diff_state`: after every frame the window compares the state to a
+//! snapshot of the last one, and sends the fields that changed as a
+//! `UiEvent::StateChanged`. Frames that changed nothing send nothing.
+
+use std::any::Any;
+
+use crate::handle::{Handle, UiEvent};
+
+type Tracker = Box<dyn FnMut(&dyn Any) -> Vec<StateChange>>;
+
+/// The new value of a field.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StateValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl From<bool> for StateValue {
+    fn from(value: bool) -> Self {
+        StateValue::Bool(value)
+    }
+}
+
+impl From<i32> for StateValue {
+    fn from(value: i32) -> Self {
+        StateValue::Int(value as i64)
+    }
+}
+
+impl From<i64> for StateValue {
+    fn from(value: i64) -> Self {
+        StateValue::Int(value)
+    }
+}
+
+impl From<u32> for StateValue {
+    fn from(value: u32) -> Self {
+        StateValue::Int(value as i64)
+    }
+}
+
+impl From<f32> for StateValue {
+    fn from(value: f32) -> Self {
+        StateValue::Float(value as f64)
+    }
+}
+
+impl From<f64> for StateValue {
+    fn from(value: f64) -> Self {
+        StateValue::Float(value)
+    }
+}
+
+impl From<String> for StateValue {
+    fn from(value: String) -> Self {
+        StateValue::Text(value)
+    }
+}
+
+impl From<&str> for StateValue {
+    fn from(value: &str) -> Self {
+        StateValue::Text(value.to_string())
+    }
+}
+
+/// A field of the state that changed in a frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateChange {
+    pub field: &'static str,
+    pub value: StateValue,
+}
+
+/// A state that can list what changed since a snapshot of it.
+///
+/// `Snapshot` is what is kept between frames, which is usually a clone of the fields the
+/// host mirrors:
+///
+/// ```ignore
+/// impl StateDiff for State {
+///     type Snapshot = State;
+///
+///     fn snapshot(&self) -> State {
+///         self.clone()
+///     }
+///
+///     fn diff(&self, before: &State, changes: &mut Vec<StateChange>) {
+///         diff_field(changes, "gain", &before.gain, &self.gain);
+///         diff_field(changes, "preset", &before.preset, &self.preset);
+///     }
+/// }
+/// ```
+pub trait StateDiff {
+    type Snapshot: 'static;
+
+    fn snapshot(&self) -> Self::Snapshot;
+
+    /// Push the fields that differ from `before`.
+    fn diff(&self, before: &Self::Snapshot, changes: &mut Vec<StateChange>);
+}
+
+/// Push the field if its value changed, for implementing `StateDiff::diff`.
+pub fn diff_field<T>(changes: &mut Vec<StateChange>, field: &'static str, before: &T, after: &T)
+where
+    T: PartialEq + Clone + Into<StateValue>,
+{
+    if before != after {
+        changes.push(StateChange {
+            field,
+            value: after.clone().into(),
+        });
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct StateTracker {
+    tracker: Option<Tracker>,
+}
+
+impl StateTracker {
+    pub fn set<S: StateDiff + 'static>(&mut self) {
+        let mut snapshot: Option<S::Snapshot> = None;
+        self.tracker = Some(Box::new(move |state| {
+            let state = match state.downcast_ref::<S>() {
+                Some(state) => state,
+                None => return Vec::new(),
+            };
+            let mut changes = Vec::new();
+            // The first frame only takes the snapshot, the host knows the state it opened with.
+            if let Some(before) = &snapshot {
+                state.diff(before, &mut changes);
+                if changes.is_empty() {
+                    return changes;
+                }
+            }
+            snapshot = Some(state.snapshot());
+            changes
+        }));
+    }
+
+    /// Send what changed in the frame that just ended.
+    pub fn after_frame(&mut self, state: &dyn Any, handle: &Handle) {
+        if let Some(tracker) = &mut self.tracker {
+            let changes = tracker(state);
+            if !changes.is_empty() {
+                handle.emit(UiEvent::StateChanged(changes));
+            }
+        }
+    }
+}
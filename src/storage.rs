@@ -0,0 +1,207 @@
+//! Persistent state for custom widgets.
+//!
+//! Values are keyed by a string hashed with the imgui ID stack, like the ids of widgets, so
+//! a widget that is built several times under different ids gets a value for each. The
+//! storage is saved together with the imgui ini settings: when either changes, the window
+//! emits `UiEvent::SaveUiState` with both in one string, which the host keeps and passes back
+//! in `Settings::ui_state` when the window is opened again.
+
+use std::collections::HashMap;
+
+/// The ini section the values are saved in. imgui skips sections it doesn't know.
+const SECTION: &str = "[imgui-baseview][Storage]";
+
+/// The id the scale of `WindowContext::set_ui_scale` is kept under.
+pub(crate) const UI_SCALE_ID: u32 = 0x5549_5343;
+/// The id the size of `Settings::restore_size` is kept under.
+pub(crate) const WINDOW_SIZE_ID: u32 = 0x5549_535A;
+/// The id the offset of `Settings::scroll_container` is kept under.
+pub(crate) const SCROLL_OFFSET_ID: u32 = 0x5549_534F;
+
+/// A value saved in a UI state string, before the window has a storage.
+pub(crate) fn load_id<T: StorageValue>(ui_state: &str, id: u32) -> Option<T> {
+    let mut storage = UiStorage::default();
+    storage.load(ui_state);
+    storage.get_id(id)
+}
+
+/// A value that can be kept in a `UiStorage`.
+pub trait StorageValue: Sized {
+    /// Write the value as text. Newlines are escaped by the storage.
+    fn save(&self) -> String;
+    /// Read a value written by `save`.
+    fn load(text: &str) -> Option<Self>;
+}
+
+macro_rules! impl_storage_value {
+    ($($t:ty),*) => {
+        $(
+            impl StorageValue for $t {
+                fn save(&self) -> String {
+                    self.to_string()
+                }
+
+                fn load(text: &str) -> Option<Self> {
+                    text.parse().ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_storage_value!(bool, i32, u32, i64, u64, f32, f64, String);
+
+impl StorageValue for [f32; 2] {
+    fn save(&self) -> String {
+        format!("{},{}", self[0], self[1])
+    }
+
+    fn load(text: &str) -> Option<Self> {
+        let mut parts = text.split(',');
+        let x = parts.next()?.parse().ok()?;
+        let y = parts.next()?.parse().ok()?;
+        Some([x, y])
+    }
+}
+
+/// Values that are kept between sessions, keyed by imgui id.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UiStorage {
+    values: HashMap<u32, String>,
+    /// How long the values have been changed without being saved.
+    dirty_time: Option<f32>,
+}
+
+impl UiStorage {
+    /// The id of `key` in the current ID stack.
+    pub fn id(_ui: &imgui::Ui, key: &str) -> u32 {
+        // Taking the `Ui` makes sure the context is current.
+        let range = key.as_bytes().as_ptr_range();
+        unsafe { imgui::sys::igGetID_StrStr(range.start as _, range.end as _) }
+    }
+
+    pub fn get<T: StorageValue>(&self, ui: &imgui::Ui, key: &str) -> Option<T> {
+        self.get_id(Self::id(ui, key))
+    }
+
+    /// The value of `key`, or `default` if it was never set.
+    pub fn get_or<T: StorageValue>(&self, ui: &imgui::Ui, key: &str, default: T) -> T {
+        self.get(ui, key).unwrap_or(default)
+    }
+
+    pub fn set<T: StorageValue>(&mut self, ui: &imgui::Ui, key: &str, value: &T) {
+        self.set_id(Self::id(ui, key), value);
+    }
+
+    pub fn remove(&mut self, ui: &imgui::Ui, key: &str) {
+        self.remove_id(Self::id(ui, key));
+    }
+
+    /// The value stored under an id from `UiStorage::id`, to use a value outside of the ID
+    /// stack it was set in.
+    pub fn get_id<T: StorageValue>(&self, id: u32) -> Option<T> {
+        self.values.get(&id).and_then(|text| T::load(text))
+    }
+
+    pub fn set_id<T: StorageValue>(&mut self, id: u32, value: &T) {
+        let text = value.save();
+        if self.values.get(&id) != Some(&text) {
+            self.values.insert(id, text);
+            self.mark_dirty();
+        }
+    }
+
+    pub fn remove_id(&mut self, id: u32) {
+        if self.values.remove(&id).is_some() {
+            self.mark_dirty();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        if !self.values.is_empty() {
+            self.values.clear();
+            self.mark_dirty();
+        }
+    }
+
+    fn mark_dirty(&mut self) {
+        self.dirty_time.get_or_insert(0.0);
+    }
+
+    /// Whether the values should be saved, once they have been left alone for `saving_rate`
+    /// seconds like the imgui settings.
+    pub(crate) fn should_save(&mut self, delta_time: f32, saving_rate: f32) -> bool {
+        match &mut self.dirty_time {
+            Some(time) => {
+                *time += delta_time;
+                if *time >= saving_rate {
+                    self.dirty_time = None;
+                    true
+                } else {
+                    false
+                }
+            }
+            None => false,
+        }
+    }
+
+    /// Append the values to an ini string.
+    pub(crate) fn save(&self, ini: &mut String) {
+        if self.values.is_empty() {
+            return;
+        }
+
+        ini.push_str(SECTION);
+        ini.push('\n');
+        for (id, text) in &self.values {
+            let text = text
+                .replace('\\', "\\\\")
+                .replace('\n', "\\n")
+                .replace('\r', "\\r");
+            ini.push_str(&format!("0x{:08X}={}\n", id, text));
+        }
+        ini.push('\n');
+    }
+
+    /// Read the values from an ini string written by `save`.
+    pub(crate) fn load(&mut self, ini: &str) {
+        let mut in_section = false;
+        for line in ini.lines() {
+            if line.starts_with('[') {
+                in_section = line == SECTION;
+                continue;
+            }
+            if !in_section {
+                continue;
+            }
+
+            let (id, text) = match line.split_once('=') {
+                Some(entry) => entry,
+                None => continue,
+            };
+            let id = match u32::from_str_radix(id.trim_start_matches("0x"), 16) {
+                Ok(id) => id,
+                Err(_) => continue,
+            };
+            self.values.insert(id, unescape(text));
+        }
+    }
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some(other) => unescaped.push(other),
+            None => {}
+        }
+    }
+    unescaped
+}
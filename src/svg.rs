@@ -0,0 +1,167 @@
+//! Resolution-independent icons rasterized from SVG.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::renderer::Renderer;
+
+struct Icon {
+    data: Vec<u8>,
+    size: [f32; 2],
+    texture_id: Option<imgui::TextureId>,
+}
+
+struct Inner {
+    icons: HashMap<String, Icon>,
+    stale: Vec<imgui::TextureId>,
+    scale: f64,
+    dirty: bool,
+}
+
+/// A set of named SVG icons.
+///
+/// The icons are rasterized at the current hidpi factor of the window and re-rasterized
+/// whenever it changes, so they stay sharp on any display.
+///
+/// An `IconSet` is a cheap handle. Keep a clone in your state to look up textures while
+/// building the UI, and pass another clone to `Settings::icons`.
+#[derive(Clone)]
+pub struct IconSet {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl IconSet {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                icons: HashMap::new(),
+                stale: Vec::new(),
+                scale: 0.0,
+                dirty: false,
+            })),
+        }
+    }
+
+    /// Add an icon to the set.
+    ///
+    /// * `name` - The name used to look up the icon.
+    /// * `data` - The contents of the SVG file.
+    /// * `size` - The logical size the icon will be displayed at.
+    pub fn add(
+        &self,
+        name: impl Into<String>,
+        data: impl Into<Vec<u8>>,
+        size: [f32; 2],
+    ) -> Result<(), usvg::Error> {
+        let data = data.into();
+
+        // Validate the data now so errors are reported to the caller instead of being
+        // silently dropped on the next frame.
+        usvg::Tree::from_data(&data, &usvg::Options::default())?;
+
+        let mut inner = self.inner.lock().unwrap();
+        let old = inner.icons.insert(
+            name.into(),
+            Icon {
+                data,
+                size,
+                texture_id: None,
+            },
+        );
+        if let Some(texture_id) = old.and_then(|icon| icon.texture_id) {
+            inner.stale.push(texture_id);
+        }
+        inner.dirty = true;
+
+        Ok(())
+    }
+
+    /// The texture of the icon with the given name.
+    ///
+    /// Returns `None` if there is no such icon, or if it has not been rasterized yet.
+    pub fn get(&self, name: &str) -> Option<imgui::TextureId> {
+        self.inner
+            .lock()
+            .unwrap()
+            .icons
+            .get(name)
+            .and_then(|icon| icon.texture_id)
+    }
+
+    /// The logical size of the icon with the given name.
+    pub fn size(&self, name: &str) -> Option<[f32; 2]> {
+        self.inner
+            .lock()
+            .unwrap()
+            .icons
+            .get(name)
+            .map(|icon| icon.size)
+    }
+
+    /// Rasterize any new icons, or all of them if the scale has changed.
+    pub(crate) fn update(&self, renderer: &mut Renderer, scale: f64) {
+        let mut inner = self.inner.lock().unwrap();
+
+        let rescale = (inner.scale - scale).abs() > f64::EPSILON;
+        if !rescale && !inner.dirty {
+            return;
+        }
+
+        for texture_id in inner.stale.drain(..) {
+            renderer.delete_texture(texture_id);
+        }
+
+        for icon in inner.icons.values_mut() {
+            if icon.texture_id.is_some() && !rescale {
+                continue;
+            }
+
+            if let Some(texture_id) = icon.texture_id.take() {
+                renderer.delete_texture(texture_id);
+            }
+
+            if let Some((width, height, rgba)) = rasterize(&icon.data, icon.size, scale) {
+                icon.texture_id = Some(renderer.create_texture(width, height, &rgba));
+            }
+        }
+
+        inner.scale = scale;
+        inner.dirty = false;
+    }
+
+    /// Use the new texture ids after the context of the window was recreated.
+    pub(crate) fn restore(&self, ctx: &crate::WindowContext) {
+        let mut inner = self.inner.lock().unwrap();
+        for icon in inner.icons.values_mut() {
+            icon.texture_id = icon.texture_id.map(|texture| ctx.restored_texture(texture));
+        }
+        for texture in &mut inner.stale {
+            *texture = ctx.restored_texture(*texture);
+        }
+    }
+}
+
+impl Default for IconSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn rasterize(data: &[u8], size: [f32; 2], scale: f64) -> Option<(u32, u32, Vec<u8>)> {
+    let tree = usvg::Tree::from_data(data, &usvg::Options::default()).ok()?;
+
+    let width = ((size[0] as f64 * scale).round() as u32).max(1);
+    let height = ((size[1] as f64 * scale).round() as u32).max(1);
+
+    let mut pixmap = tiny_skia::Pixmap::new(width, height)?;
+    resvg::render(&tree, usvg::FitTo::Size(width, height), pixmap.as_mut())?;
+
+    // tiny-skia works with premultiplied alpha, but imgui blends straight alpha.
+    let mut rgba = Vec::with_capacity((width * height * 4) as usize);
+    for pixel in pixmap.pixels() {
+        let color = pixel.demultiply();
+        rgba.extend_from_slice(&[color.red(), color.green(), color.blue(), color.alpha()]);
+    }
+
+    Some((width, height, rgba))
+}
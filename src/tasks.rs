@@ -0,0 +1,340 @@
+//! Run slow work off the UI thread.
+//!
+//! This is the async bridge of the crate: `spawn` runs a closure on a worker thread and
+//! returns a `Task`, which the update closure polls every frame until the result arrives. It
+//! is used for scanning directories, building waveform overviews and other work that would
+//! otherwise stall the UI.
+//!
+//! Work the crate starts many of at once, like loading the textures of a long list, goes
+//! through `spawn_pooled` instead, which queues it for a few threads shared by all windows.
+//!
+//! Jobs the user waits for, like analyzing a sample or downloading a preset pack, run through
+//! the `TaskRegistry` of the window instead, from `WindowContext::tasks`. They report their
+//! progress as they go, which `TaskRegistry::jobs` gives to the UI and
+//! `Settings::progress_overlay` shows in a corner of the window.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use imgui::{Condition, WindowFlags};
+
+/// The distance of the progress overlay from the corner, in logical pixels.
+const OVERLAY_MARGIN: f32 = 12.0;
+/// The width of the progress bars of the overlay, in logical pixels.
+const OVERLAY_WIDTH: f32 = 240.0;
+/// The number of threads that run the closures of `spawn_pooled`.
+const POOL_THREADS: usize = 4;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// The queue of the shared threads, started with the first pooled task.
+static POOL: Mutex<Option<Sender<Job>>> = Mutex::new(None);
+
+/// The state of a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskStatus {
+    Running,
+    Finished,
+    /// The closure panicked.
+    Failed,
+}
+
+/// The result of a closure running on a worker thread.
+#[derive(Debug)]
+pub struct Task<T> {
+    receiver: Receiver<T>,
+    result: Option<T>,
+    status: TaskStatus,
+}
+
+/// Run `f` on a worker thread.
+pub fn spawn<T, F>(f: F) -> Task<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        // The receiver may be gone if the window was closed in the meantime.
+        let _ = sender.send(f());
+    });
+
+    Task {
+        receiver,
+        result: None,
+        status: TaskStatus::Running,
+    }
+}
+
+/// Run `f` on one of a few threads shared by all windows, after the closures queued before it.
+pub(crate) fn spawn_pooled<T, F>(f: F) -> Task<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    let job: Job = Box::new(move || {
+        // A panic drops the sender, so the task fails and the thread goes on with the next job.
+        if let Ok(result) = panic::catch_unwind(AssertUnwindSafe(f)) {
+            let _ = sender.send(result);
+        }
+    });
+
+    let mut pool = POOL.lock().unwrap_or_else(|e| e.into_inner());
+    pool.get_or_insert_with(start_pool)
+        .send(job)
+        .expect("the pool threads never stop");
+
+    Task {
+        receiver,
+        result: None,
+        status: TaskStatus::Running,
+    }
+}
+
+fn start_pool() -> Sender<Job> {
+    let (sender, receiver) = mpsc::channel::<Job>();
+    let receiver = Arc::new(Mutex::new(receiver));
+    for _ in 0..POOL_THREADS {
+        let receiver = receiver.clone();
+        thread::spawn(move || loop {
+            // The lock is released before the job runs, so the other threads can take the next one.
+            let job = receiver.lock().unwrap_or_else(|e| e.into_inner()).recv();
+            match job {
+                Ok(job) => job(),
+                Err(_) => break,
+            }
+        });
+    }
+
+    sender
+}
+
+impl<T> Task<T> {
+    /// A task that has already finished.
+    pub fn ready(value: T) -> Self {
+        let (_, receiver) = mpsc::channel();
+
+        Self {
+            receiver,
+            result: Some(value),
+            status: TaskStatus::Finished,
+        }
+    }
+
+    pub fn status(&mut self) -> TaskStatus {
+        if self.status == TaskStatus::Running {
+            match self.receiver.try_recv() {
+                Ok(result) => {
+                    self.result = Some(result);
+                    self.status = TaskStatus::Finished;
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => self.status = TaskStatus::Failed,
+            }
+        }
+
+        self.status
+    }
+
+    pub fn is_running(&mut self) -> bool {
+        self.status() == TaskStatus::Running
+    }
+
+    /// The result, once the task has finished.
+    pub fn get(&mut self) -> Option<&T> {
+        self.status();
+        self.result.as_ref()
+    }
+
+    /// Take the result, once the task has finished.
+    pub fn take(&mut self) -> Option<T> {
+        self.status();
+        self.result.take()
+    }
+}
+
+/// What a running job of a `TaskRegistry` reported last.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobProgress {
+    pub id: u64,
+    /// What the job does, like "Analyzing sample".
+    pub label: String,
+    /// What the job does right now, like the name of the file it downloads.
+    pub message: Option<String>,
+    /// How far the job is, from 0 to 1. `None` while it can't tell.
+    pub fraction: Option<f32>,
+}
+
+struct Job {
+    progress: JobProgress,
+    cancelled: Arc<AtomicBool>,
+}
+
+/// The jobs of a window, shared with their worker threads.
+#[derive(Clone, Default)]
+pub struct TaskRegistry {
+    jobs: Arc<Mutex<Vec<Job>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl std::fmt::Debug for TaskRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskRegistry")
+            .field("jobs", &self.jobs())
+            .finish()
+    }
+}
+
+impl TaskRegistry {
+    /// Run `f` on a worker thread as a job called `label`, which reports its progress through
+    /// the `Progress` it is given. The job is gone from the registry once `f` returns or
+    /// panics.
+    pub fn spawn<T, F>(&self, label: impl Into<String>, f: F) -> Task<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Progress) -> T + Send + 'static,
+    {
+        self.spawn_job(label, f).1
+    }
+
+    /// `spawn`, with the id of the job.
+    pub(crate) fn spawn_job<T, F>(&self, label: impl Into<String>, f: F) -> (u64, Task<T>)
+    where
+        T: Send + 'static,
+        F: FnOnce(&Progress) -> T + Send + 'static,
+    {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let cancelled = Arc::new(AtomicBool::new(false));
+        self.jobs.lock().unwrap().push(Job {
+            progress: JobProgress {
+                id,
+                label: label.into(),
+                message: None,
+                fraction: None,
+            },
+            cancelled: cancelled.clone(),
+        });
+
+        let progress = Progress {
+            id,
+            jobs: self.jobs.clone(),
+            cancelled,
+        };
+        (id, spawn(move || f(&progress)))
+    }
+
+    /// The jobs that are running, in the order they were spawned.
+    pub fn jobs(&self) -> Vec<JobProgress> {
+        let jobs = self.jobs.lock().unwrap();
+        jobs.iter().map(|job| job.progress.clone()).collect()
+    }
+
+    /// Whether any job is running.
+    pub fn is_busy(&self) -> bool {
+        !self.jobs.lock().unwrap().is_empty()
+    }
+
+    /// Ask the job with `id` to stop. It is up to the job to check `Progress::is_cancelled`.
+    pub fn cancel(&self, id: u64) {
+        let jobs = self.jobs.lock().unwrap();
+        if let Some(job) = jobs.iter().find(|job| job.progress.id == id) {
+            job.cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Draw the running jobs with their progress in the bottom left corner, over the UI.
+    pub(crate) fn draw_overlay(&self, ui: &imgui::Ui) {
+        let jobs = self.jobs();
+        if jobs.is_empty() {
+            return;
+        }
+
+        let display_size = ui.io().display_size;
+        let flags = WindowFlags::NO_DECORATION
+            | WindowFlags::ALWAYS_AUTO_RESIZE
+            | WindowFlags::NO_SAVED_SETTINGS
+            | WindowFlags::NO_FOCUS_ON_APPEARING
+            | WindowFlags::NO_NAV
+            | WindowFlags::NO_MOVE;
+        imgui::Window::new("##progress_overlay")
+            .position(
+                [OVERLAY_MARGIN, display_size[1] - OVERLAY_MARGIN],
+                Condition::Always,
+            )
+            .position_pivot([0.0, 1.0])
+            .bg_alpha(0.85)
+            .flags(flags)
+            .build(ui, || {
+                for job in &jobs {
+                    let _id = ui.push_id(job.id.to_string());
+                    match &job.message {
+                        Some(message) => ui.text(format!("{}: {}", job.label, message)),
+                        None => ui.text(&job.label),
+                    }
+                    // A job that can't tell how far it is gets a bar that sweeps back and
+                    // forth.
+                    let fraction = job
+                        .fraction
+                        .unwrap_or_else(|| (ui.time() as f32 * 0.8).fract());
+                    let overlay = match job.fraction {
+                        Some(fraction) => format!("{:.0}%", fraction * 100.0),
+                        None => String::new(),
+                    };
+                    imgui::ProgressBar::new(fraction)
+                        .size([OVERLAY_WIDTH, 0.0])
+                        .overlay_text(&overlay)
+                        .build(ui);
+                }
+            });
+    }
+}
+
+/// How a job of a `TaskRegistry` reports its progress from its worker thread.
+pub struct Progress {
+    id: u64,
+    jobs: Arc<Mutex<Vec<Job>>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl std::fmt::Debug for Progress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Progress").field("id", &self.id).finish()
+    }
+}
+
+impl Progress {
+    fn update(&self, f: impl FnOnce(&mut JobProgress)) {
+        let mut jobs = self.jobs.lock().unwrap();
+        if let Some(job) = jobs.iter_mut().find(|job| job.progress.id == self.id) {
+            f(&mut job.progress);
+        }
+    }
+
+    /// How far the job is, from 0 to 1.
+    pub fn set_fraction(&self, fraction: f32) {
+        self.update(|progress| progress.fraction = Some(fraction.max(0.0).min(1.0)));
+    }
+
+    /// What the job does right now.
+    pub fn set_message(&self, message: impl Into<String>) {
+        let message = message.into();
+        self.update(|progress| progress.message = Some(message));
+    }
+
+    /// Whether the UI asked the job to stop with `TaskRegistry::cancel`.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for Progress {
+    /// The job returned or panicked, so it is done.
+    fn drop(&mut self) {
+        let mut jobs = self.jobs.lock().unwrap_or_else(|error| error.into_inner());
+        jobs.retain(|job| job.progress.id != self.id);
+    }
+}
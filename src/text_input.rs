@@ -0,0 +1,84 @@
+//! The characters that key presses type into imgui.
+//!
+//! On Windows and macOS the keyboard events already have the characters that dead keys
+//! compose, like é for ´ and then e. baseview on X11 reports a dead key as `Key::Dead` and the
+//! next key as its own character, so the window composes them itself: the accent is looked
+//! up with `NativeWindow::dead_key` and put together with the next letter. A letter that has
+//! no accented form, and the space bar, type the accent on its own.
+
+use keyboard_types::{Key, KeyboardEvent};
+
+use crate::platform::NativeWindow;
+
+/// The letters every accent composes with, and what they become.
+const COMPOSED: &[(char, &str, &str)] = &[
+    ('`', "aeiouAEIOU", "àèìòùÀÈÌÒÙ"),
+    ('´', "aeiouycnszAEIOUYCNSZ", "áéíóúýćńśźÁÉÍÓÚÝĆŃŚŹ"),
+    ('^', "aeiouAEIOU", "âêîôûÂÊÎÔÛ"),
+    ('~', "anoANO", "ãñõÃÑÕ"),
+    ('¨', "aeiouyAEIOUY", "äëïöüÿÄËÏÖÜŸ"),
+    ('°', "auAU", "åůÅŮ"),
+    ('ˇ', "cdeinrstzCDENRSTZ", "čďěǐňřšťžČĎĚŇŘŠŤŽ"),
+    ('¸', "cgkstCGKST", "çģķşţÇĢĶŞŢ"),
+];
+
+fn compose(accent: char, letter: char) -> Option<char> {
+    let (_, letters, composed) = COMPOSED.iter().find(|(a, _, _)| *a == accent)?;
+    let i = letters.chars().position(|c| c == letter)?;
+    composed.chars().nth(i)
+}
+
+#[derive(Default)]
+pub(crate) struct TextInput {
+    /// The accent of the dead key that was pressed last, waiting for the next key.
+    dead_key: Option<char>,
+}
+
+impl TextInput {
+    /// The characters a key press types.
+    pub fn characters(&mut self, event: &KeyboardEvent, window: &NativeWindow) -> Vec<char> {
+        // An input method is still putting the text together.
+        if event.is_composing {
+            return Vec::new();
+        }
+
+        match &event.key {
+            Key::Dead => {
+                let accent = window.dead_key(event.code, event.modifiers);
+                match self.dead_key.take() {
+                    // Pressing a dead key twice types its accent.
+                    Some(pending) if Some(pending) == accent => vec![pending],
+                    pending => {
+                        self.dead_key = accent;
+                        pending.into_iter().collect()
+                    }
+                }
+            }
+            Key::Character(written) => {
+                // Exclude the backspace key ('\u{7f}'). Otherwise we will insert this char and
+                // then delete it.
+                let chars: Vec<char> = written.chars().filter(|c| *c != '\u{7f}').collect();
+                let accent = match self.dead_key.take() {
+                    Some(accent) => accent,
+                    None => return chars,
+                };
+                match chars.as_slice() {
+                    [' '] => vec![accent],
+                    [letter] => match compose(accent, *letter) {
+                        Some(composed) => vec![composed],
+                        None => vec![accent, *letter],
+                    },
+                    _ => std::iter::once(accent)
+                        .chain(chars.iter().copied())
+                        .collect(),
+                }
+            }
+            // The modifiers for capitals and the third level keep the accent.
+            Key::Shift | Key::AltGraph | Key::CapsLock => Vec::new(),
+            _ => {
+                self.dead_key = None;
+                Vec::new()
+            }
+        }
+    }
+}
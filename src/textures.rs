@@ -0,0 +1,339 @@
+//! Textures the window uploads and evicts by itself, within a GPU memory budget.
+//!
+//! A preset browser with hundreds of thumbnails can't keep all of them on the GPU of a
+//! laptop. Textures added with `WindowContext::add_texture` come with a loader instead of
+//! pixels: they are uploaded the first time `WindowContext::texture` shows them, and with
+//! `Settings::texture_budget` the ones shown least recently are deleted at the end of a
+//! frame once the textures use more memory than the budget. A texture that was evicted is
+//! loaded again the next time it is shown.
+//!
+//! Memory is counted as four bytes a pixel, for the textures of the registry only. Textures
+//! shown in the current frame are never evicted, so a single frame can go over the budget.
+//!
+//! Large images, like a background, stall the window if they are decoded and uploaded in
+//! one frame. Textures added with `WindowContext::add_texture_async` are loaded on the shared
+//! threads of `tasks::spawn_pooled` instead, and uploaded a few rows every frame. Until then a placeholder is
+//! shown in their place.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::renderer::Renderer;
+use crate::tasks::{self, Task};
+
+/// The most bytes of pixels uploaded in a frame for the textures loaded in the background.
+const UPLOAD_BYTES: usize = 2 << 20;
+/// The color of the placeholder, a faint gray that shows on light and dark themes.
+const PLACEHOLDER: [u8; 4] = [128, 128, 128, 64];
+
+enum Loader {
+    /// Loaded on the thread of the window when it is shown.
+    Local(Box<dyn FnMut() -> Option<TexturePixels>>),
+    /// Loaded on the pool threads, then uploaded a few rows a frame.
+    Background(Arc<dyn Fn() -> Option<TexturePixels> + Send + Sync>),
+}
+
+/// Where a texture loaded in the background is.
+enum Upload {
+    Loading(Task<Option<TexturePixels>>),
+    Rows {
+        pixels: TexturePixels,
+        texture: imgui::TextureId,
+        next_row: u32,
+    },
+}
+
+/// An RGBA8 image to upload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TexturePixels {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+impl TexturePixels {
+    fn bytes(&self) -> u64 {
+        self.width as u64 * self.height as u64 * 4
+    }
+
+    fn is_valid(&self) -> bool {
+        self.width > 0 && self.height > 0 && self.rgba.len() as u64 == self.bytes()
+    }
+}
+
+/// A texture of the registry, which keeps the same key when it is evicted and loaded again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextureKey(u64);
+
+/// How much of the budget the textures of the registry use.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextureMemory {
+    /// The bytes of the textures that are uploaded.
+    pub used: u64,
+    pub budget: Option<u64>,
+    pub uploaded: usize,
+    /// The textures that are not uploaded, because they were evicted, not shown yet or are
+    /// still loading.
+    pub unloaded: usize,
+}
+
+struct Entry {
+    loader: Loader,
+    uploaded: Option<(imgui::TextureId, u64)>,
+    upload: Option<Upload>,
+    /// Whether the background loader had no pixels, so it isn't run again.
+    failed: bool,
+    /// The frame the texture was last shown in.
+    last_shown: u64,
+}
+
+impl Entry {
+    fn new(loader: Loader) -> Self {
+        Self {
+            loader,
+            uploaded: None,
+            upload: None,
+            failed: false,
+            last_shown: 0,
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct TextureRegistry {
+    budget: Option<u64>,
+    entries: HashMap<TextureKey, Entry>,
+    next_key: u64,
+    frame: u64,
+    used: u64,
+    placeholder: Option<imgui::TextureId>,
+}
+
+impl TextureRegistry {
+    pub fn new(budget: Option<u64>) -> Self {
+        Self {
+            budget,
+            ..Self::default()
+        }
+    }
+
+    pub fn add(&mut self, loader: Box<dyn FnMut() -> Option<TexturePixels>>) -> TextureKey {
+        self.insert(Loader::Local(loader))
+    }
+
+    pub fn add_async(
+        &mut self,
+        loader: Arc<dyn Fn() -> Option<TexturePixels> + Send + Sync>,
+    ) -> TextureKey {
+        self.insert(Loader::Background(loader))
+    }
+
+    fn insert(&mut self, loader: Loader) -> TextureKey {
+        self.next_key += 1;
+        let key = TextureKey(self.next_key);
+        self.entries.insert(key, Entry::new(loader));
+        key
+    }
+
+    /// The texture to show this frame, uploading it if it isn't. `None` if the loader has no
+    /// pixels for it, and the placeholder while it loads in the background.
+    pub fn show(&mut self, renderer: &mut Renderer, key: TextureKey) -> Option<imgui::TextureId> {
+        let entry = self.entries.get_mut(&key)?;
+        entry.last_shown = self.frame;
+        if let Some((texture, _)) = entry.uploaded {
+            return Some(texture);
+        }
+
+        match &mut entry.loader {
+            Loader::Local(loader) => {
+                let pixels = loader().filter(TexturePixels::is_valid)?;
+                let texture = renderer.create_texture(pixels.width, pixels.height, &pixels.rgba);
+                let bytes = pixels.bytes();
+                entry.uploaded = Some((texture, bytes));
+                self.used += bytes;
+                Some(texture)
+            }
+            Loader::Background(loader) => {
+                if entry.failed {
+                    return None;
+                }
+                if entry.upload.is_none() {
+                    let loader = loader.clone();
+                    entry.upload = Some(Upload::Loading(tasks::spawn_pooled(move || loader())));
+                }
+                Some(self.placeholder(renderer))
+            }
+        }
+    }
+
+    /// Whether the texture is still loading in the background, so the placeholder is shown.
+    pub fn is_loading(&self, key: TextureKey) -> bool {
+        self.entries
+            .get(&key)
+            .map_or(false, |entry| entry.upload.is_some())
+    }
+
+    fn placeholder(&mut self, renderer: &mut Renderer) -> imgui::TextureId {
+        *self
+            .placeholder
+            .get_or_insert_with(|| renderer.create_texture(1, 1, &PLACEHOLDER))
+    }
+
+    pub fn remove(&mut self, renderer: &mut Renderer, key: TextureKey) {
+        if let Some(entry) = self.entries.remove(&key) {
+            if let Some((texture, bytes)) = entry.uploaded {
+                renderer.delete_texture(texture);
+                self.used -= bytes;
+            }
+            if let Some(Upload::Rows {
+                pixels, texture, ..
+            }) = entry.upload
+            {
+                renderer.delete_texture(texture);
+                self.used -= pixels.bytes();
+            }
+        }
+    }
+
+    /// Upload the next rows of the textures that were loaded in the background.
+    fn upload(&mut self, renderer: &mut Renderer) {
+        let mut budget = UPLOAD_BYTES;
+        for entry in self.entries.values_mut() {
+            if budget == 0 {
+                break;
+            }
+            let upload = match entry.upload.take() {
+                Some(upload) => upload,
+                None => continue,
+            };
+
+            entry.upload = match upload {
+                Upload::Loading(mut task) => match task.take() {
+                    None if task.is_running() => Some(Upload::Loading(task)),
+                    Some(Some(pixels)) if pixels.is_valid() => {
+                        let bytes = pixels.bytes();
+                        self.used += bytes;
+                        match renderer.create_empty_texture(pixels.width, pixels.height) {
+                            Some(texture) => Some(Upload::Rows {
+                                pixels,
+                                texture,
+                                next_row: 0,
+                            }),
+                            // Custom backends get the whole texture at once.
+                            None => {
+                                let texture = renderer.create_texture(
+                                    pixels.width,
+                                    pixels.height,
+                                    &pixels.rgba,
+                                );
+                                entry.uploaded = Some((texture, bytes));
+                                budget = budget.saturating_sub(bytes as usize);
+                                None
+                            }
+                        }
+                    }
+                    // The loader had no pixels, or panicked.
+                    _ => {
+                        entry.failed = true;
+                        None
+                    }
+                },
+                Upload::Rows {
+                    pixels,
+                    texture,
+                    next_row,
+                } => {
+                    let stride = pixels.width as usize * 4;
+                    let rows = (budget / stride)
+                        .max(1)
+                        .min((pixels.height - next_row) as usize);
+                    let start = next_row as usize * stride;
+                    let end = start + rows * stride;
+                    renderer.upload_texture_rows(
+                        texture,
+                        pixels.width,
+                        next_row,
+                        &pixels.rgba[start..end],
+                    );
+                    budget = budget.saturating_sub(rows * stride);
+
+                    let next_row = next_row + rows as u32;
+                    if next_row < pixels.height {
+                        Some(Upload::Rows {
+                            pixels,
+                            texture,
+                            next_row,
+                        })
+                    } else {
+                        entry.uploaded = Some((texture, pixels.bytes()));
+                        None
+                    }
+                }
+            };
+        }
+    }
+
+    /// Upload the next rows of the textures loaded in the background, evict the textures
+    /// shown least recently until the rest fit the budget, and start the next frame.
+    pub fn end_frame(&mut self, renderer: &mut Renderer) {
+        self.upload(renderer);
+
+        if let Some(budget) = self.budget {
+            if self.used > budget {
+                let mut candidates: Vec<_> = self
+                    .entries
+                    .iter()
+                    .filter(|(_, entry)| entry.uploaded.is_some() && entry.last_shown < self.frame)
+                    .map(|(key, entry)| (entry.last_shown, *key))
+                    .collect();
+                candidates.sort();
+
+                for (_, key) in candidates {
+                    if self.used <= budget {
+                        break;
+                    }
+                    let entry = self.entries.get_mut(&key).unwrap();
+                    if let Some((texture, bytes)) = entry.uploaded.take() {
+                        renderer.delete_texture(texture);
+                        self.used -= bytes;
+                    }
+                }
+            }
+        }
+        self.frame += 1;
+    }
+
+    /// Use the new texture ids after the context was recreated.
+    pub fn restore(&mut self, texture_remap: &HashMap<usize, imgui::TextureId>) {
+        let restore = |texture: &mut imgui::TextureId| {
+            if let Some(restored) = texture_remap.get(&texture.id()) {
+                *texture = *restored;
+            }
+        };
+        for entry in self.entries.values_mut() {
+            if let Some((texture, _)) = &mut entry.uploaded {
+                restore(texture);
+            }
+            if let Some(Upload::Rows { texture, .. }) = &mut entry.upload {
+                restore(texture);
+            }
+        }
+        if let Some(placeholder) = &mut self.placeholder {
+            restore(placeholder);
+        }
+    }
+
+    pub fn memory(&self) -> TextureMemory {
+        let uploaded = self
+            .entries
+            .values()
+            .filter(|entry| entry.uploaded.is_some())
+            .count();
+        TextureMemory {
+            used: self.used,
+            budget: self.budget,
+            uploaded,
+            unloaded: self.entries.len() - uploaded,
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! Snapshots of the imgui style colors that can be blended.
+//!
+//! Take a theme from a configured style with `Theme::from_style`, and blend two themes with
+//! `Theme::lerp` every frame to animate a switch, for example from dark to light:
+//!
+//! ```ignore
+//! let t = (elapsed / fade_time).min(1.0);
+//! Theme::lerp(&dark, &light, t).apply(context.style_mut());
+//! ```
+//!
+//! About one in twelve men can't tell red from green. `Theme::color_blind_safe` turns any
+//! theme into one that only tells colors apart by blue and orange and by lightness, which
+//! they can. How a UI looks to them can be checked in the debug overlay, which simulates
+//! the deficiencies of `ColorVision`.
+
+use crate::color::{self, Oklab};
+
+/// A color vision deficiency, for the simulation in the debug overlay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorVision {
+    /// No red cones. Red looks dark and close to green.
+    Protanopia,
+    /// No green cones, the most common one. Red and green look alike.
+    Deuteranopia,
+    /// No blue cones. Blue looks like green and yellow like pink.
+    Tritanopia,
+}
+
+impl ColorVision {
+    pub const ALL: &'static [ColorVision] = &[
+        ColorVision::Protanopia,
+        ColorVision::Deuteranopia,
+        ColorVision::Tritanopia,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorVision::Protanopia => "Protanopia",
+            ColorVision::Deuteranopia => "Deuteranopia",
+            ColorVision::Tritanopia => "Tritanopia",
+        }
+    }
+}
+
+/// The hues of the Okabe-Ito palette that every theme is mapped onto, in OKLab radians.
+const BLUE_HUE: f32 = -2.02;
+const ORANGE_HUE: f32 = 1.34;
+/// Colors with less chroma are grays, and keep their color.
+const MIN_CHROMA: f32 = 0.03;
+
+/// Move the hue of a color to blue or orange, whichever side of the red-green axis it is on:
+/// red, orange and yellow become orange, and green, cyan, blue and purple become blue.
+fn color_blind_safe(color: [f32; 4]) -> [f32; 4] {
+    let lab = Oklab::from_srgb([color[0], color[1], color[2]]);
+    let chroma = lab.a.hypot(lab.b);
+    if chroma < MIN_CHROMA {
+        return color;
+    }
+
+    let hue = lab.b.atan2(lab.a);
+    let warm = hue > -0.5 && hue < 1.9;
+    let hue = if warm { ORANGE_HUE } else { BLUE_HUE };
+    let [r, g, b] = Oklab {
+        l: lab.l,
+        a: chroma * hue.cos(),
+        b: chroma * hue.sin(),
+    }
+    .to_srgb();
+    [r, g, b, color[3]]
+}
+
+/// The colors of an imgui style.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Theme {
+    /// Written to files as a map from the names of the `imgui::StyleColor` variants.
+    #[cfg_attr(feature = "serde", serde(with = "named_colors"))]
+    pub colors: [[f32; 4]; imgui::StyleColor::COUNT],
+}
+
+impl Theme {
+    pub fn from_style(style: &imgui::Style) -> Self {
+        Self {
+            colors: style.colors,
+        }
+    }
+
+    /// The color used for an element.
+    pub fn color(&self, color: imgui::StyleColor) -> [f32; 4] {
+        self.colors[color as usize]
+    }
+
+    pub fn set_color(&mut self, color: imgui::StyleColor, value: [f32; 4]) {
+        self.colors[color as usize] = value;
+    }
+
+    /// Use the colors of the theme in a style.
+    pub fn apply(&self, style: &mut imgui::Style) {
+        style.colors = self.colors;
+    }
+
+    /// Blend two themes, with `t` from 0 for `from` to 1 for `to`. The colors are blended in
+    /// OKLab, so a switch between a dark and a light theme doesn't go through gray.
+    pub fn lerp(from: &Theme, to: &Theme, t: f32) -> Theme {
+        let mut colors = from.colors;
+        for (color, target) in colors.iter_mut().zip(to.colors.iter()) {
+            *color = color::lerp(*color, *target, t);
+        }
+        Theme { colors }
+    }
+
+    /// The theme with every colored element in blue or orange at the same lightness, so it
+    /// reads the same with the common color vision deficiencies.
+    pub fn color_blind_safe(&self) -> Theme {
+        let mut colors = self.colors;
+        for color in colors.iter_mut() {
+            *color = color_blind_safe(*color);
+        }
+        Theme { colors }
+    }
+}
+
+#[cfg(feature = "serde")]
+mod named_colors {
+    use std::collections::HashMap;
+
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    type Colors = [[f32; 4]; imgui::StyleColor::COUNT];
+
+    fn name(color: imgui::StyleColor) -> String {
+        format!("{:?}", color)
+    }
+
+    pub fn serialize<S: Serializer>(colors: &Colors, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(
+            imgui::StyleColor::VARIANTS
+                .iter()
+                .map(|color| (name(*color), colors[*color as usize])),
+        )
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Colors, D::Error> {
+        let mut named = HashMap::<String, [f32; 4]>::deserialize(deserializer)?;
+        let mut colors = [[0.0; 4]; imgui::StyleColor::COUNT];
+        for color in imgui::StyleColor::VARIANTS.iter() {
+            colors[*color as usize] = named
+                .remove(&name(*color))
+                .ok_or_else(|| D::Error::custom(format!("missing color {}", name(*color))))?;
+        }
+        if let Some(unknown) = named.keys().next() {
+            return Err(D::Error::custom(format!("unknown color {}", unknown)));
+        }
+        Ok(colors)
+    }
+}
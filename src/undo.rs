@@ -0,0 +1,221 @@
+//! Undo and redo.
+//!
+//! Edits made through the param-bound widgets are recorded automatically, with a whole drag
+//! recorded as a single step. Changes to the application state can be recorded with
+//! `UndoStack::push`. Ctrl+Z undoes and Ctrl+Shift+Z or Ctrl+Y redoes, unless a text field
+//! has keyboard focus.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::handle::{Handle, UiEvent};
+use crate::params::{Param, ParamId};
+
+/// The number of steps that are kept.
+const MAX_ENTRIES: usize = 256;
+
+type Action = Box<dyn FnMut(&mut dyn Any)>;
+
+enum Entry {
+    Param {
+        param: Arc<Param>,
+        before: f32,
+        after: f32,
+    },
+    Custom {
+        name: String,
+        undo: Action,
+        redo: Action,
+    },
+}
+
+impl Entry {
+    fn name(&self) -> &str {
+        match self {
+            Entry::Param { param, .. } => param.name(),
+            Entry::Custom { name, .. } => name,
+        }
+    }
+
+    fn apply(&mut self, handle: &Handle, state: &mut dyn Any, undo: bool) {
+        match self {
+            Entry::Param {
+                param,
+                before,
+                after,
+            } => {
+                param.set(if undo { *before } else { *after });
+                handle.emit(UiEvent::BeginEdit(param.id()));
+                handle.emit(UiEvent::SetParam {
+                    id: param.id(),
+                    value: param.get(),
+                });
+                handle.emit(UiEvent::EndEdit(param.id()));
+            }
+            Entry::Custom {
+                undo: undo_action,
+                redo,
+                ..
+            } => {
+                if undo {
+                    undo_action(state)
+                } else {
+                    redo(state)
+                }
+            }
+        }
+    }
+}
+
+/// The undo history of a window.
+#[allow(missing_debug_implementations)]
+pub struct UndoStack {
+    handle: Handle,
+    entries: Vec<Entry>,
+    /// The number of entries that are done, the rest can be redone.
+    position: usize,
+    /// The values of the parameters being dragged when the drag started.
+    open_edits: HashMap<ParamId, (Arc<Param>, f32)>,
+}
+
+impl UndoStack {
+    pub(crate) fn new(handle: Handle) -> Self {
+        Self {
+            handle,
+            entries: Vec::new(),
+            position: 0,
+            open_edits: HashMap::new(),
+        }
+    }
+
+    /// Record a change to the application state.
+    ///
+    /// `undo` and `redo` are called with the state of the window, and are only called if it
+    /// is of type `S`. The change itself should already be applied.
+    pub fn push<S, U, R>(&mut self, name: impl Into<String>, mut undo: U, mut redo: R)
+    where
+        S: 'static,
+        U: FnMut(&mut S) + 'static,
+        R: FnMut(&mut S) + 'static,
+    {
+        self.push_entry(Entry::Custom {
+            name: name.into(),
+            undo: Box::new(move |state| {
+                if let Some(state) = state.downcast_mut() {
+                    undo(state)
+                }
+            }),
+            redo: Box::new(move |state| {
+                if let Some(state) = state.downcast_mut() {
+                    redo(state)
+                }
+            }),
+        });
+    }
+
+    /// Record a change whose undo and redo don't need the state of the window.
+    pub(crate) fn push_action<U, R>(&mut self, name: impl Into<String>, mut undo: U, mut redo: R)
+    where
+        U: FnMut() + 'static,
+        R: FnMut() + 'static,
+    {
+        self.push_entry(Entry::Custom {
+            name: name.into(),
+            undo: Box::new(move |_| undo()),
+            redo: Box::new(move |_| redo()),
+        });
+    }
+
+    /// Record that `param` was changed from `before` to its current value.
+    pub fn push_param(&mut self, param: &Arc<Param>, before: f32) {
+        let after = param.get();
+        if after != before {
+            self.push_entry(Entry::Param {
+                param: param.clone(),
+                before,
+                after,
+            });
+        }
+    }
+
+    fn push_entry(&mut self, entry: Entry) {
+        self.entries.truncate(self.position);
+        if self.entries.len() == MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+        self.entries.push(entry);
+        self.position = self.entries.len();
+    }
+
+    pub(crate) fn begin_param_edit(&mut self, param: &Arc<Param>) {
+        self.open_edits
+            .entry(param.id())
+            .or_insert_with(|| (param.clone(), param.get()));
+    }
+
+    pub(crate) fn end_param_edit(&mut self, id: ParamId) {
+        if let Some((param, before)) = self.open_edits.remove(&id) {
+            self.push_param(&param, before);
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.position > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.position < self.entries.len()
+    }
+
+    /// The name of the step that would be undone.
+    pub fn undo_name(&self) -> Option<&str> {
+        self.position
+            .checked_sub(1)
+            .map(|index| self.entries[index].name())
+    }
+
+    /// The name of the step that would be redone.
+    pub fn redo_name(&self) -> Option<&str> {
+        self.entries.get(self.position).map(Entry::name)
+    }
+
+    pub fn undo(&mut self, state: &mut dyn Any) {
+        if self.can_undo() {
+            self.position -= 1;
+            self.entries[self.position].apply(&self.handle, state, true);
+        }
+    }
+
+    pub fn redo(&mut self, state: &mut dyn Any) {
+        if self.can_redo() {
+            self.entries[self.position].apply(&self.handle, state, false);
+            self.position += 1;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.position = 0;
+    }
+
+    /// Undo or redo when the shortcut is pressed.
+    pub(crate) fn handle_shortcuts(&mut self, ui: &imgui::Ui, state: &mut dyn Any) {
+        use imgui::Key;
+
+        let io = ui.io();
+        if io.want_text_input || !(io.key_ctrl || io.key_super) {
+            return;
+        }
+
+        if ui.is_key_pressed(Key::Z) {
+            if io.key_shift {
+                self.redo(state);
+            } else {
+                self.undo(state);
+            }
+        } else if ui.is_key_pressed(Key::Y) {
+            self.redo(state);
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! Web views docked to a region of the UI, for vendors who keep part of their UI in HTML.
+//!
+//! A web view is created with `WindowContext::create_web_view` under a name and placed like
+//! a native child with `WindowContext::web_view`. Pages talk to the host through the handle
+//! of the window: `window.ipc.postMessage(text)` emits `UiEvent::WebMessage`, and
+//! `Handle::post_web_message` dispatches a `message` event with the text as `data` on the
+//! `window` of the page.
+//!
+//! On Linux wry needs GTK to be initialized, and only works on X11.
+
+use std::collections::HashMap;
+use std::num::{NonZeroIsize, NonZeroU32};
+use std::ptr::NonNull;
+
+use raw_window_handle::RawWindowHandle;
+use raw_window_handle_06 as rwh;
+
+use crate::handle::{Handle, UiEvent};
+use crate::native_child;
+
+/// The window of the UI as a parent for wry.
+struct Parent(rwh::RawWindowHandle);
+
+impl rwh::HasWindowHandle for Parent {
+    fn window_handle(&self) -> Result<rwh::WindowHandle<'_>, rwh::HandleError> {
+        // The window outlives the web views it holds.
+        Ok(unsafe { rwh::WindowHandle::borrow_raw(self.0) })
+    }
+}
+
+fn parent(handle: &RawWindowHandle) -> Option<Parent> {
+    let handle = match handle {
+        RawWindowHandle::Windows(handle) => rwh::RawWindowHandle::Win32(
+            rwh::Win32WindowHandle::new(NonZeroIsize::new(handle.hwnd as isize)?),
+        ),
+        RawWindowHandle::MacOS(handle) => rwh::RawWindowHandle::AppKit(
+            rwh::AppKitWindowHandle::new(NonNull::new(handle.ns_view)?),
+        ),
+        RawWindowHandle::Xlib(handle) => {
+            rwh::RawWindowHandle::Xlib(rwh::XlibWindowHandle::new(handle.window))
+        }
+        RawWindowHandle::Xcb(handle) => {
+            rwh::RawWindowHandle::Xcb(rwh::XcbWindowHandle::new(NonZeroU32::new(handle.window)?))
+        }
+        _ => return None,
+    };
+    Some(Parent(handle))
+}
+
+/// Quote a string for JavaScript.
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            // Line and paragraph separators end a string literal in older engines.
+            '\u{2028}' => quoted.push_str("\\u2028"),
+            '\u{2029}' => quoted.push_str("\\u2029"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+struct Entry {
+    view: wry::WebView,
+    reserved: Option<([f32; 4], [f32; 4])>,
+    /// The bounds last applied, or `None` while hidden.
+    applied: Option<[i32; 4]>,
+}
+
+#[derive(Default)]
+pub(crate) struct WebViews {
+    views: HashMap<String, Entry>,
+}
+
+impl WebViews {
+    pub fn create(
+        &mut self,
+        window: &RawWindowHandle,
+        handle: &Handle,
+        name: &str,
+        url: &str,
+    ) -> Result<(), wry::Error> {
+        let parent = parent(window).ok_or(wry::Error::UnsupportedWindowHandle)?;
+
+        let handle = handle.clone();
+        let view_name = name.to_string();
+        let view = wry::WebViewBuilder::new_as_child(&parent)
+            .with_url(url)
+            .with_visible(false)
+            .with_ipc_handler(move |message: String| {
+                handle.emit(UiEvent::WebMessage {
+                    view: view_name.clone(),
+                    message,
+                })
+            })
+            .build()?;
+
+        self.views.insert(
+            name.to_string(),
+            Entry {
+                view,
+                reserved: None,
+                applied: None,
+            },
+        );
+        Ok(())
+    }
+
+    pub fn close(&mut self, name: &str) {
+        self.views.remove(name);
+    }
+
+    pub fn reserve(&mut self, ui: &imgui::Ui, name: &str, size: [f32; 2]) {
+        let reserved = native_child::reserve(ui, size);
+        if let Some(entry) = self.views.get_mut(name) {
+            entry.reserved = Some(reserved);
+        }
+    }
+
+    /// Deliver the messages the host sent with `Handle::post_web_message`.
+    pub fn deliver_messages(&mut self, handle: &Handle) {
+        for (name, message) in handle.take_web_messages() {
+            if let Some(entry) = self.views.get(&name) {
+                let script = format!(
+                    "window.dispatchEvent(new MessageEvent('message', {{ data: {} }}));",
+                    quote(&message)
+                );
+                let _ = entry.view.evaluate_script(&script);
+            }
+        }
+    }
+
+    /// Deliver the messages of the host, then move the web views over the regions reserved
+    /// this frame and hide the others.
+    ///
+    /// wry can't clip a web view, so one that is partly scrolled out of view covers what is
+    /// around the region.
+    pub fn end_frame(&mut self, handle: &Handle, hidpi_factor: f64, scale_factor: f64) {
+        // wry places web views in physical pixels on Windows, and in points or GTK's logical
+        // pixels elsewhere.
+        let scale = if cfg!(target_os = "windows") {
+            hidpi_factor
+        } else {
+            hidpi_factor / scale_factor
+        };
+
+        self.deliver_messages(handle);
+
+        for entry in self.views.values_mut() {
+            let bounds = entry
+                .reserved
+                .take()
+                .and_then(|(rect, clip)| native_child::placement(rect, clip, scale))
+                .map(|(bounds, _)| bounds);
+
+            if bounds == entry.applied {
+                continue;
+            }
+            match bounds {
+                Some(bounds) => {
+                    entry.view.set_bounds(wry::Rect {
+                        x: bounds[0],
+                        y: bounds[1],
+                        width: bounds[2] as u32,
+                        height: bounds[3] as u32,
+                    });
+                    entry.view.set_visible(true);
+                }
+                None => entry.view.set_visible(false),
+            }
+            entry.applied = bounds;
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use std::sync::{Arc, Mutex};
+
+use crate::handle::{Handle, UiEvent};
+use crate::locale::Text;
+use crate::params::{Param, ParamId};
+use crate::WindowContext;
+
+/// One of the two settings an `AbCompare` switches between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AbSlot {
+    A,
+    B,
+}
+
+impl AbSlot {
+    pub fn other(self) -> Self {
+        match self {
+            AbSlot::A => AbSlot::B,
+            AbSlot::B => AbSlot::A,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            AbSlot::A => "A",
+            AbSlot::B => "B",
+        }
+    }
+}
+
+/// The values of both slots of an `AbCompare`, to keep them with the plugin settings.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AbSlots {
+    pub active: AbSlot,
+    pub a: Vec<(ParamId, f32)>,
+    pub b: Vec<(ParamId, f32)>,
+}
+
+/// The active slot and the values of both, to restore them on undo.
+#[derive(Clone, PartialEq)]
+struct Snapshot {
+    active: AbSlot,
+    stored: Vec<f32>,
+    current: Vec<f32>,
+}
+
+#[derive(Debug)]
+struct Compare {
+    params: Vec<Arc<Param>>,
+    active: AbSlot,
+    /// The values of the slot that isn't active. Those of the active slot are the values of
+    /// the parameters.
+    stored: Vec<f32>,
+}
+
+impl Compare {
+    fn current(&self) -> Vec<f32> {
+        self.params.iter().map(|param| param.get()).collect()
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            active: self.active,
+            stored: self.stored.clone(),
+            current: self.current(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot, handle: &Handle) {
+        self.active = snapshot.active;
+        self.stored = snapshot.stored.clone();
+        self.apply(&snapshot.current, handle);
+    }
+
+    /// Set the parameters to `values`, and tell the host about the ones that changed.
+    fn apply(&self, values: &[f32], handle: &Handle) {
+        for (param, value) in self.params.iter().zip(values) {
+            if param.get() == *value {
+                continue;
+            }
+            param.set(*value);
+            handle.emit(UiEvent::BeginEdit(param.id()));
+            handle.emit(UiEvent::SetParam {
+                id: param.id(),
+                value: param.get(),
+            });
+            handle.emit(UiEvent::EndEdit(param.id()));
+        }
+    }
+
+    fn slots(&self) -> AbSlots {
+        let current = self.params.iter().map(|param| (param.id(), param.get()));
+        let stored = self
+            .params
+            .iter()
+            .zip(&self.stored)
+            .map(|(param, value)| (param.id(), *value));
+        let (a, b) = match self.active {
+            AbSlot::A => (current.collect(), stored.collect()),
+            AbSlot::B => (stored.collect(), current.collect()),
+        };
+        AbSlots {
+            active: self.active,
+            a,
+            b,
+        }
+    }
+}
+
+/// A header bar to compare two settings of a set of parameters, with buttons for the A and B
+/// slots and one to copy the active slot to the other.
+///
+/// Keep it in the application state and call `build` every frame. The active slot is the
+/// values of the parameters, so editing them edits it. Switching slots sets the parameters to
+/// the values of the other slot and reports them through the `Handle` of the window like the
+/// param-bound widgets do. Switching and copying can be undone, and emit `UiEvent::AbCompare`
+/// with both slots so the host can keep them.
+#[derive(Debug)]
+pub struct AbCompare {
+    compare: Arc<Mutex<Compare>>,
+}
+
+impl AbCompare {
+    /// Compare the values of `params`. Both slots start with their current values.
+    pub fn new(params: Vec<Arc<Param>>) -> Self {
+        let stored = params.iter().map(|param| param.get()).collect();
+        Self {
+            compare: Arc::new(Mutex::new(Compare {
+                params,
+                active: AbSlot::A,
+                stored,
+            })),
+        }
+    }
+
+    pub fn active(&self) -> AbSlot {
+        self.compare.lock().unwrap().active
+    }
+
+    pub fn slots(&self) -> AbSlots {
+        self.compare.lock().unwrap().slots()
+    }
+
+    /// Restore the slots the host kept. The parameters aren't changed, the plugin restores
+    /// their values itself, so only the values of the slot that isn't active are used.
+    pub fn restore(&mut self, slots: &AbSlots) {
+        let mut compare = self.compare.lock().unwrap();
+        compare.active = slots.active;
+        let stored = match slots.active {
+            AbSlot::A => &slots.b,
+            AbSlot::B => &slots.a,
+        };
+        let values: Vec<f32> = compare
+            .params
+            .iter()
+            .zip(compare.stored.iter())
+            .map(|(param, value)| {
+                stored
+                    .iter()
+                    .find(|(id, _)| *id == param.id())
+                    .map_or(*value, |(_, value)| *value)
+            })
+            .collect();
+        compare.stored = values;
+    }
+
+    /// Make `slot` the active one.
+    pub fn select(&mut self, ctx: &mut WindowContext, slot: AbSlot) {
+        self.change(ctx, slot.label(), |compare, handle| {
+            if compare.active == slot {
+                return;
+            }
+            let current = compare.current();
+            let stored = std::mem::replace(&mut compare.stored, current);
+            compare.apply(&stored, handle);
+            compare.active = slot;
+        });
+    }
+
+    /// Copy the values of the active slot to the other one.
+    pub fn copy_to_other(&mut self, ctx: &mut WindowContext) {
+        let name = format!("{} {}", ctx.locale.get(Text::CopySlot), self.copy_label());
+        self.change(ctx, &name, |compare, _| {
+            compare.stored = compare.current();
+        });
+    }
+
+    fn copy_label(&self) -> String {
+        let active = self.active();
+        format!("{} > {}", active.label(), active.other().label())
+    }
+
+    /// Apply `f`, and record it in the undo history if it changed anything.
+    fn change(
+        &mut self,
+        ctx: &mut WindowContext,
+        name: &str,
+        f: impl FnOnce(&mut Compare, &Handle),
+    ) {
+        let handle = ctx.handle.clone();
+        let (before, after, slots) = {
+            let mut compare = self.compare.lock().unwrap();
+            let before = compare.snapshot();
+            f(&mut compare, &handle);
+            (before, compare.snapshot(), compare.slots())
+        };
+        if before == after {
+            return;
+        }
+        handle.emit(UiEvent::AbCompare(slots));
+
+        let restore = |snapshot: Snapshot| {
+            let compare = self.compare.clone();
+            let handle = handle.clone();
+            move || {
+                let mut compare = compare.lock().unwrap();
+                compare.restore(&snapshot, &handle);
+                handle.emit(UiEvent::AbCompare(compare.slots()));
+            }
+        };
+        let undo = restore(before);
+        let redo = restore(after);
+        ctx.undo_stack().push_action(name, undo, redo);
+    }
+
+    /// Draw the bar on the current line.
+    pub fn build(&mut self, ui: &imgui::Ui, ctx: &mut WindowContext) {
+        use imgui::StyleColor;
+
+        let _id = ui.push_id("##ab_compare");
+        let active = self.active();
+        let active_color = ui.style_color(StyleColor::ButtonActive);
+        for slot in &[AbSlot::A, AbSlot::B] {
+            let _color = if *slot == active {
+                Some(ui.push_style_color(StyleColor::Button, active_color))
+            } else {
+                None
+            };
+            if ui.button(slot.label()) {
+                self.select(ctx, *slot);
+            }
+            ui.same_line();
+        }
+
+        let label = format!("{} {}", ctx.locale.get(Text::CopySlot), self.copy_label());
+        if ui.button(&label) {
+            self.copy_to_other(ctx);
+        }
+    }
+}
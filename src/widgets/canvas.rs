@@ -0,0 +1,284 @@
+use std::os::raw::c_char;
+
+/// The smallest font size text is drawn at, in screen pixels. Smaller text is drawn as a bar.
+const MIN_TEXT_SIZE: f32 = 6.0;
+/// The smallest distance between grid lines, in screen pixels. Closer lines are left out.
+const MIN_GRID_SPACING: f32 = 8.0;
+/// Every how many lines of the grid a major line is drawn.
+const MAJOR_LINES: i64 = 8;
+/// The smallest zoom `Canvas::zoom_range` allows, as the view divides by it.
+const SMALLEST_ZOOM: f32 = 0.001;
+
+/// What a frame of a `Canvas` shows, to convert between canvas and screen positions.
+///
+/// Canvas positions are in units that are logical pixels at a zoom of one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CanvasView {
+    /// The top left corner of the canvas on the screen.
+    pub origin: [f32; 2],
+    pub size: [f32; 2],
+    /// The canvas position at the top left corner.
+    pub offset: [f32; 2],
+    pub zoom: f32,
+    /// Whether the mouse is over the canvas, and no pan is going on.
+    pub hovered: bool,
+    /// The canvas position of the mouse.
+    pub mouse: [f32; 2],
+}
+
+impl CanvasView {
+    pub fn to_screen(&self, pos: [f32; 2]) -> [f32; 2] {
+        [
+            self.origin[0] + (pos[0] - self.offset[0]) * self.zoom,
+            self.origin[1] + (pos[1] - self.offset[1]) * self.zoom,
+        ]
+    }
+
+    pub fn to_canvas(&self, pos: [f32; 2]) -> [f32; 2] {
+        [
+            self.offset[0] + (pos[0] - self.origin[0]) / self.zoom,
+            self.offset[1] + (pos[1] - self.origin[1]) / self.zoom,
+        ]
+    }
+
+    /// A length on the canvas in screen pixels.
+    pub fn scale(&self, length: f32) -> f32 {
+        length * self.zoom
+    }
+
+    /// The top left and bottom right corner of the part of the canvas that is shown.
+    pub fn visible(&self) -> ([f32; 2], [f32; 2]) {
+        let max = [self.origin[0] + self.size[0], self.origin[1] + self.size[1]];
+        (self.offset, self.to_canvas(max))
+    }
+
+    /// Draw `text` at a canvas position, scaled with the zoom. Once it gets too small to read
+    /// it is drawn as a bar of its size instead, which is cheaper and keeps the layout.
+    pub fn text(&self, ui: &imgui::Ui, pos: [f32; 2], color: [f32; 4], text: &str) {
+        let font_size = ui.current_font_size() * self.zoom;
+        let screen = self.to_screen(pos);
+
+        if font_size < MIN_TEXT_SIZE {
+            let width = ui.calc_text_size(text)[0] * self.zoom;
+            let top = screen[1] + font_size * 0.3;
+            ui.get_window_draw_list()
+                .add_rect(
+                    [screen[0], top],
+                    [screen[0] + width, top + font_size * 0.4],
+                    [color[0], color[1], color[2], color[3] * 0.5],
+                )
+                .filled(true)
+                .build();
+            return;
+        }
+
+        unsafe {
+            let color = imgui::sys::igColorConvertFloat4ToU32(imgui::sys::ImVec4 {
+                x: color[0],
+                y: color[1],
+                z: color[2],
+                w: color[3],
+            });
+            let start = text.as_ptr() as *const c_char;
+            imgui::sys::ImDrawList_AddText_FontPtr(
+                imgui::sys::igGetWindowDrawList(),
+                imgui::sys::igGetFont(),
+                font_size,
+                imgui::sys::ImVec2 {
+                    x: screen[0],
+                    y: screen[1],
+                },
+                color,
+                start,
+                start.add(text.len()),
+                0.0,
+                std::ptr::null(),
+            );
+        }
+    }
+}
+
+/// An endless surface to pan and zoom, with a grid, for node editors and other views that
+/// are larger than the window.
+///
+/// Keep it in the application state and call `build` every frame with what to draw on it.
+/// Scroll to zoom around the pointer, and drag with the middle button or scroll sideways to
+/// pan. What is drawn through the `CanvasView` is clipped to the canvas.
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    offset: [f32; 2],
+    zoom: f32,
+    min_zoom: f32,
+    max_zoom: f32,
+    grid: Option<f32>,
+    panning: bool,
+}
+
+impl Default for Canvas {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Canvas {
+    pub fn new() -> Self {
+        Self {
+            offset: [0.0, 0.0],
+            zoom: 1.0,
+            min_zoom: 0.1,
+            max_zoom: 4.0,
+            grid: Some(32.0),
+            panning: false,
+        }
+    }
+
+    /// Draw grid lines `spacing` canvas units apart, or no grid with `None`.
+    pub fn grid(mut self, spacing: Option<f32>) -> Self {
+        self.grid = spacing;
+        self
+    }
+
+    /// Zoom between `min` and `max`. A `min` that isn't positive is raised to a small zoom
+    /// above 0, and a `max` below `min` to `min`.
+    pub fn zoom_range(mut self, min: f32, max: f32) -> Self {
+        self.min_zoom = min.max(SMALLEST_ZOOM);
+        self.max_zoom = max.max(self.min_zoom);
+        self.zoom = self.zoom.max(self.min_zoom).min(self.max_zoom);
+        self
+    }
+
+    /// The canvas position at the top left corner.
+    pub fn offset(&self) -> [f32; 2] {
+        self.offset
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Show the canvas from `offset` at `zoom`, for example to restore a saved view.
+    pub fn set_view(&mut self, offset: [f32; 2], zoom: f32) {
+        self.offset = offset;
+        self.zoom = zoom.max(self.min_zoom).min(self.max_zoom);
+    }
+
+    /// Draw the canvas at the cursor, and then what `f` draws on it.
+    pub fn build<R>(
+        &mut self,
+        ui: &imgui::Ui,
+        label: &str,
+        size: [f32; 2],
+        f: impl FnOnce(&CanvasView) -> R,
+    ) -> R {
+        use imgui::{MouseButton, StyleColor};
+
+        let origin = ui.cursor_screen_pos();
+        let max = [origin[0] + size[0], origin[1] + size[1]];
+        ui.invisible_button(label, size);
+        let hovered = ui.is_item_hovered();
+
+        let io = ui.io();
+        if hovered {
+            if io.mouse_wheel != 0.0 {
+                let zoom = (self.zoom * 1.2f32.powf(io.mouse_wheel))
+                    .max(self.min_zoom)
+                    .min(self.max_zoom);
+                // The canvas position under the pointer stays where it is.
+                for i in 0..2 {
+                    let screen = io.mouse_pos[i] - origin[i];
+                    self.offset[i] += screen / self.zoom - screen / zoom;
+                }
+                self.zoom = zoom;
+            }
+            self.offset[0] -= io.mouse_wheel_h * 32.0 / self.zoom;
+            if ui.is_mouse_clicked(MouseButton::Middle) {
+                self.panning = true;
+            }
+        }
+        if self.panning {
+            self.offset[0] -= io.mouse_delta[0] / self.zoom;
+            self.offset[1] -= io.mouse_delta[1] / self.zoom;
+            if !ui.is_mouse_down(MouseButton::Middle) {
+                self.panning = false;
+            }
+        }
+
+        let mut view = CanvasView {
+            origin,
+            size,
+            offset: self.offset,
+            zoom: self.zoom,
+            hovered: hovered && !self.panning,
+            mouse: [0.0, 0.0],
+        };
+        view.mouse = view.to_canvas(io.mouse_pos);
+
+        let draw_list = ui.get_window_draw_list();
+        draw_list
+            .add_rect(origin, max, ui.style_color(StyleColor::FrameBg))
+            .filled(true)
+            .build();
+        if let Some(spacing) = self.grid {
+            let color = ui.style_color(StyleColor::Separator);
+            draw_grid(&draw_list, &view, spacing, color);
+        }
+        drop(draw_list);
+
+        unsafe {
+            imgui::sys::igPushClipRect(
+                imgui::sys::ImVec2 {
+                    x: origin[0],
+                    y: origin[1],
+                },
+                imgui::sys::ImVec2 {
+                    x: max[0],
+                    y: max[1],
+                },
+                true,
+            );
+        }
+        let result = f(&view);
+        unsafe {
+            imgui::sys::igPopClipRect();
+        }
+        result
+    }
+}
+
+/// Draw the lines of the grid that are in view. Minor lines are left out when they would be
+/// too close on the screen, and major lines too when even those would be.
+fn draw_grid(draw_list: &imgui::DrawListMut<'_>, view: &CanvasView, spacing: f32, color: [f32; 4]) {
+    let screen_spacing = view.scale(spacing);
+    if screen_spacing * (MAJOR_LINES as f32) < MIN_GRID_SPACING {
+        return;
+    }
+    let minor = screen_spacing >= MIN_GRID_SPACING;
+    let minor_color = [color[0], color[1], color[2], color[3] * 0.4];
+    let (min, max) = view.visible();
+    let origin = view.origin;
+    let end = [origin[0] + view.size[0], origin[1] + view.size[1]];
+
+    for axis in 0..2 {
+        let first = (min[axis] / spacing).floor() as i64;
+        let last = (max[axis] / spacing).ceil() as i64;
+        for line in first..=last {
+            let major = line % MAJOR_LINES == 0;
+            if !major && !minor {
+                continue;
+            }
+            let color = if major { color } else { minor_color };
+            let pos = line as f32 * spacing;
+            if axis == 0 {
+                let x = view.to_screen([pos, 0.0])[0];
+                draw_list
+                    .add_line([x, origin[1]], [x, end[1]], color)
+                    .build();
+            } else {
+                let y = view.to_screen([0.0, pos])[1];
+                draw_list
+                    .add_line([origin[0], y], [end[0], y], color)
+                    .build();
+            }
+        }
+    }
+}
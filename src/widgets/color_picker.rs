@@ -0,0 +1,98 @@
+use crate::WindowContext;
+
+/// imgui's color picker, with an eyedropper that takes a color from anywhere on the screen.
+///
+/// The eyedropper needs the `eyedropper` feature. While it is on, the color follows the pixel
+/// under the mouse, also outside of the window, until Enter, Space or a click in the window
+/// takes it. Escape puts the color back. The window keeps the keyboard focus meanwhile, so
+/// the keys reach it wherever the mouse is. Keep it in the application state and call `build`
+/// every frame.
+#[derive(Debug, Clone, Default)]
+pub struct ColorPicker {
+    /// Edit the alpha channel too. The eyedropper keeps the alpha.
+    pub alpha: bool,
+    picking: bool,
+    /// The color before the eyedropper, to put back when it is cancelled.
+    original: [f32; 4],
+}
+
+impl ColorPicker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the eyedropper is on.
+    pub fn is_picking(&self) -> bool {
+        self.picking
+    }
+
+    /// Returns whether the color changed.
+    #[cfg_attr(not(feature = "eyedropper"), allow(unused_variables))]
+    pub fn build(
+        &mut self,
+        ui: &imgui::Ui,
+        ctx: &mut WindowContext,
+        label: &str,
+        color: &mut [f32; 4],
+    ) -> bool {
+        #[cfg_attr(not(feature = "eyedropper"), allow(unused_mut))]
+        let mut changed = imgui::ColorPicker::new(label, &mut *color)
+            .alpha(self.alpha)
+            .build(ui);
+
+        #[cfg(feature = "eyedropper")]
+        {
+            changed |= self.eyedropper(ui, ctx, label, color);
+        }
+        changed
+    }
+
+    #[cfg(feature = "eyedropper")]
+    fn eyedropper(
+        &mut self,
+        ui: &imgui::Ui,
+        ctx: &mut WindowContext,
+        label: &str,
+        color: &mut [f32; 4],
+    ) -> bool {
+        use crate::locale::Text;
+        use imgui::{Key, MouseButton};
+
+        if !self.picking {
+            let text = ctx.locale.get(Text::PickFromScreen);
+            if ui.button(&format!("{}##{}", text, label)) {
+                self.picking = true;
+                self.original = *color;
+            }
+            return false;
+        }
+
+        // The mouse is mostly outside of the window, where it sends no events.
+        ctx.handle().request_frame();
+        ui.text(ctx.locale.get(Text::PickingColor));
+
+        let mut changed = false;
+        if let Some([r, g, b]) = ctx.native_window.pixel_under_cursor() {
+            let picked = [
+                r as f32 / 255.0,
+                g as f32 / 255.0,
+                b as f32 / 255.0,
+                color[3],
+            ];
+            changed = picked != *color;
+            *color = picked;
+        }
+
+        if ui.is_key_pressed(Key::Escape) {
+            changed = *color != self.original;
+            *color = self.original;
+            self.picking = false;
+        } else if ui.is_key_pressed(Key::Enter)
+            || ui.is_key_pressed(Key::Space)
+            || ui.is_mouse_clicked(MouseButton::Left)
+        {
+            self.picking = false;
+        }
+        changed
+    }
+}
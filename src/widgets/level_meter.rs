@@ -0,0 +1,244 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The peak level written by the audio thread.
+///
+/// The audio thread calls `write` with the absolute peak of every block, and the meter takes
+/// the highest peak since the last frame. Nothing locks, so it is safe to use from the
+/// audio thread.
+#[derive(Debug, Default)]
+pub struct MeterInput {
+    peak: AtomicU32,
+}
+
+impl MeterInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Report a linear peak amplitude.
+    pub fn write(&self, peak: f32) {
+        // The bit patterns of positive floats sort like the floats themselves.
+        let peak = peak.abs();
+        if peak.is_finite() {
+            self.peak.fetch_max(peak.to_bits(), Ordering::Relaxed);
+        }
+    }
+
+    fn take(&self) -> f32 {
+        f32::from_bits(self.peak.swap(0, Ordering::Relaxed))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Vertical,
+    Horizontal,
+}
+
+/// The colors of a `LevelMeter`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeterPalette {
+    /// Up to -18 dB.
+    pub low: [f32; 4],
+    /// Up to -6 dB.
+    pub mid: [f32; 4],
+    /// Above -6 dB.
+    pub high: [f32; 4],
+    pub clip: [f32; 4],
+}
+
+impl MeterPalette {
+    /// Blue, yellow and vermilion from the Okabe-Ito palette, which stay apart with the
+    /// common color vision deficiencies.
+    pub fn color_blind_safe() -> Self {
+        Self {
+            low: [0.0, 0.45, 0.7, 1.0],
+            mid: [0.94, 0.89, 0.26, 1.0],
+            high: [0.84, 0.37, 0.0, 1.0],
+            clip: [0.84, 0.37, 0.0, 1.0],
+        }
+    }
+}
+
+impl Default for MeterPalette {
+    /// Green, yellow and red.
+    fn default() -> Self {
+        Self {
+            low: [0.2, 0.8, 0.3, 1.0],
+            mid: [0.9, 0.8, 0.2, 1.0],
+            high: [0.9, 0.25, 0.2, 1.0],
+            clip: [1.0, 0.1, 0.1, 1.0],
+        }
+    }
+}
+
+/// A meter that shows a level in decibels, with peak hold and a clip indicator.
+///
+/// The level rises with the attack time and falls with the release time, measured from the
+/// frame delta, so the meter moves the same at any frame rate. Clicking the meter resets the
+/// clip indicator. Keep it in the application state and call `build` every frame.
+#[derive(Debug)]
+pub struct LevelMeter {
+    input: Arc<MeterInput>,
+    /// The time the level takes to rise most of the way to a higher peak.
+    pub attack: Duration,
+    /// The time the level takes to fall most of the way to a lower peak.
+    pub release: Duration,
+    /// How long the peak marker stays before falling.
+    pub hold: Duration,
+    /// The range of the meter in dB.
+    pub range: (f32, f32),
+    pub orientation: Orientation,
+    pub palette: MeterPalette,
+    level_db: f32,
+    peak_db: f32,
+    peak_age: Duration,
+    clipped: bool,
+}
+
+impl LevelMeter {
+    pub fn new(orientation: Orientation) -> Self {
+        Self {
+            input: Arc::new(MeterInput::new()),
+            attack: Duration::from_millis(10),
+            release: Duration::from_millis(300),
+            hold: Duration::from_millis(1500),
+            range: (-60.0, 6.0),
+            orientation,
+            palette: MeterPalette::default(),
+            level_db: f32::NEG_INFINITY,
+            peak_db: f32::NEG_INFINITY,
+            peak_age: Duration::default(),
+            clipped: false,
+        }
+    }
+
+    /// Where the audio thread writes its peaks.
+    pub fn input(&self) -> &Arc<MeterInput> {
+        &self.input
+    }
+
+    pub fn level_db(&self) -> f32 {
+        self.level_db
+    }
+
+    pub fn is_clipped(&self) -> bool {
+        self.clipped
+    }
+
+    pub fn reset(&mut self) {
+        self.level_db = f32::NEG_INFINITY;
+        self.peak_db = f32::NEG_INFINITY;
+        self.clipped = false;
+    }
+
+    fn update(&mut self, delta: Duration) {
+        let peak = self.input.take();
+        let target = if peak > 0.0 {
+            20.0 * peak.log10()
+        } else {
+            f32::NEG_INFINITY
+        };
+        let floor = self.range.0;
+
+        if peak >= 1.0 {
+            self.clipped = true;
+        }
+
+        let time = if target > self.level_db {
+            self.attack
+        } else {
+            self.release
+        };
+        let coefficient = if time.as_secs_f32() > 0.0 {
+            1.0 - (-delta.as_secs_f32() / time.as_secs_f32()).exp()
+        } else {
+            1.0
+        };
+        let from = self.level_db.max(floor);
+        let to = target.max(floor);
+        self.level_db = from + (to - from) * coefficient;
+
+        if self.level_db >= self.peak_db {
+            self.peak_db = self.level_db;
+            self.peak_age = Duration::default();
+        } else {
+            self.peak_age += delta;
+            if self.peak_age > self.hold {
+                let from = self.peak_db.max(floor);
+                self.peak_db = from + (self.level_db - from) * coefficient;
+            }
+        }
+    }
+
+    pub fn build(&mut self, ui: &imgui::Ui, size: [f32; 2]) {
+        use imgui::StyleColor;
+
+        self.update(Duration::from_secs_f32(ui.io().delta_time));
+
+        let pos = ui.cursor_screen_pos();
+        if ui.invisible_button("##level_meter", size) {
+            self.clipped = false;
+        }
+
+        let (min_db, max_db) = self.range;
+        let fraction = |db: f32| ((db - min_db) / (max_db - min_db)).max(0.0).min(1.0);
+        // The rectangle covering the range from `from` to `to`, as fractions of the meter.
+        let rect = |from: f32, to: f32| match self.orientation {
+            Orientation::Vertical => (
+                [pos[0], pos[1] + (1.0 - to) * size[1]],
+                [pos[0] + size[0], pos[1] + (1.0 - from) * size[1]],
+            ),
+            Orientation::Horizontal => (
+                [pos[0] + from * size[0], pos[1]],
+                [pos[0] + to * size[0], pos[1] + size[1]],
+            ),
+        };
+
+        let draw_list = ui.get_window_draw_list();
+        let (min, max) = rect(0.0, 1.0);
+        draw_list
+            .add_rect(min, max, ui.style_color(StyleColor::FrameBg))
+            .filled(true)
+            .build();
+
+        let level = fraction(self.level_db);
+        let zones = [
+            (min_db, -18.0, self.palette.low),
+            (-18.0, -6.0, self.palette.mid),
+            (-6.0, max_db, self.palette.high),
+        ];
+        for (from, to, color) in &zones {
+            let from = fraction(*from);
+            let to = fraction(*to).min(level);
+            if to > from {
+                let (min, max) = rect(from, to);
+                draw_list.add_rect(min, max, *color).filled(true).build();
+            }
+        }
+
+        let peak = fraction(self.peak_db);
+        if peak > 0.0 {
+            let (min, max) = rect(peak, peak);
+            let (min, max) = match self.orientation {
+                Orientation::Vertical => (min, [max[0], max[1] + 2.0]),
+                Orientation::Horizontal => (min, [max[0] + 2.0, max[1]]),
+            };
+            draw_list
+                .add_rect(min, max, ui.style_color(StyleColor::Text))
+                .filled(true)
+                .build();
+        }
+
+        if self.clipped {
+            // A strip at the top or right end of the meter.
+            let (min, max) = rect(0.97, 1.0);
+            draw_list
+                .add_rect(min, max, self.palette.clip)
+                .filled(true)
+                .build();
+        }
+    }
+}
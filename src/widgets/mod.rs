@@ -0,0 +1,204 @@
+//! Widgets commonly needed by audio plugins.
+//!
+//! Param-bound widgets edit a `Param` directly and report the edit through the `Handle` of the
+//! window as `BeginEdit`, `SetParam` and `EndEdit` events, so the host can record automation.
+//! Every edit is recorded in the undo history of the window as well. They all follow the
+//! interaction policy in `Settings::interaction`.
+
+mod ab_compare;
+mod canvas;
+mod color_picker;
+mod level_meter;
+mod mod_matrix;
+mod param_menu;
+mod piano_keyboard;
+mod preset_browser;
+mod waveform;
+mod xy_pad;
+
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+use crate::handle::UiEvent;
+use crate::interaction::{self, Axis};
+use crate::params::Param;
+use crate::WindowContext;
+
+pub use ab_compare::{AbCompare, AbSlot, AbSlots};
+pub use canvas::{Canvas, CanvasView};
+pub use color_picker::ColorPicker;
+pub use level_meter::{LevelMeter, MeterInput, MeterPalette, Orientation};
+pub use mod_matrix::{ModMatrix, ModMatrixEdit};
+pub use param_menu::param_context_menu;
+pub(crate) use param_menu::{open_type_in, ParamMenu};
+pub use piano_keyboard::{PianoKeyboard, PlayedNotes};
+pub use preset_browser::{PresetBrowser, PresetFolder};
+pub use waveform::{PeakPyramid, Waveform, WaveformEvent};
+pub(crate) use xy_pad::XyDrag;
+pub use xy_pad::{xy_pad, XyNode};
+
+/// A horizontal slider bound to a parameter, with the name on the right like imgui's own
+/// sliders. Returns whether the value changed.
+pub fn param_slider(ui: &imgui::Ui, ctx: &mut WindowContext, param: &Arc<Param>) -> bool {
+    use imgui::StyleColor;
+
+    let style = ui.clone_style();
+    let size = [ui.calc_item_width(), ui.frame_height()];
+    let pos = ui.cursor_screen_pos();
+
+    ui.invisible_button(&format!("##{}{}", param.name(), param.id()), size);
+    ctx.focus_item(ui);
+    let changed = interaction::drag(ui, ctx, param, Axis::Horizontal);
+
+    let frame_color = if ui.is_item_active() {
+        StyleColor::FrameBgActive
+    } else if ui.is_item_hovered() {
+        StyleColor::FrameBgHovered
+    } else {
+        StyleColor::FrameBg
+    };
+    let grab_color = if ui.is_item_active() {
+        StyleColor::SliderGrabActive
+    } else {
+        StyleColor::SliderGrab
+    };
+
+    let max = [pos[0] + size[0], pos[1] + size[1]];
+    let grab_width = style.grab_min_size;
+    let grab_x = pos[0] + param.normalized() * (size[0] - grab_width);
+
+    let draw_list = ui.get_window_draw_list();
+    draw_list
+        .add_rect(pos, max, ui.style_color(frame_color))
+        .filled(true)
+        .rounding(style.frame_rounding)
+        .build();
+    draw_list
+        .add_rect(
+            [grab_x, pos[1] + 2.0],
+            [grab_x + grab_width, max[1] - 2.0],
+            ui.style_color(grab_color),
+        )
+        .filled(true)
+        .rounding(style.grab_rounding)
+        .build();
+
+    let text = param.format();
+    let text_size = ui.calc_text_size(&text);
+    draw_list.add_text(
+        [
+            pos[0] + (size[0] - text_size[0]) * 0.5,
+            pos[1] + (size[1] - text_size[1]) * 0.5,
+        ],
+        ui.style_color(StyleColor::Text),
+        &text,
+    );
+
+    param_context_menu(ui, ctx, param);
+
+    ui.same_line_with_spacing(0.0, style.item_inner_spacing[0]);
+    ui.text(param.name());
+
+    changed
+}
+
+/// A rotary knob bound to a parameter, with the name and value below it. Returns whether
+/// the value changed.
+pub fn param_knob(ui: &imgui::Ui, ctx: &mut WindowContext, param: &Arc<Param>) -> bool {
+    use imgui::StyleColor;
+
+    let diameter = ui.frame_height() * 2.5;
+    let radius = diameter * 0.5;
+    let pos = ui.cursor_screen_pos();
+    let center = [pos[0] + radius, pos[1] + radius];
+
+    let mut changed = false;
+    ui.group(|| {
+        ui.invisible_button(
+            &format!("##{}{}", param.name(), param.id()),
+            [diameter, diameter],
+        );
+        ctx.focus_item(ui);
+        changed = interaction::drag(ui, ctx, param, Axis::Vertical);
+
+        let active = ui.is_item_active();
+        let hovered = ui.is_item_hovered();
+        param_context_menu(ui, ctx, param);
+
+        let body_color = if active {
+            StyleColor::FrameBgActive
+        } else if hovered {
+            StyleColor::FrameBgHovered
+        } else {
+            StyleColor::FrameBg
+        };
+
+        // The knob goes from the bottom left to the bottom right, clockwise.
+        let start = PI * 0.75;
+        let end = PI * 2.25;
+        let angle = start + param.normalized() * (end - start);
+        let arc = |from: f32, to: f32| -> Vec<[f32; 2]> {
+            let segments = 32;
+            (0..=segments)
+                .map(|i| {
+                    let a = from + (to - from) * i as f32 / segments as f32;
+                    [
+                        center[0] + a.cos() * (radius - 2.0),
+                        center[1] + a.sin() * (radius - 2.0),
+                    ]
+                })
+                .collect()
+        };
+
+        let draw_list = ui.get_window_draw_list();
+        draw_list
+            .add_circle(center, radius * 0.75, ui.style_color(body_color))
+            .filled(true)
+            .num_segments(32)
+            .build();
+        draw_list
+            .add_polyline(arc(start, end), ui.style_color(StyleColor::FrameBg))
+            .thickness(3.0)
+            .build();
+        draw_list
+            .add_polyline(arc(start, angle), ui.style_color(StyleColor::SliderGrab))
+            .thickness(3.0)
+            .build();
+        draw_list
+            .add_line(
+                center,
+                [
+                    center[0] + angle.cos() * radius * 0.75,
+                    center[1] + angle.sin() * radius * 0.75,
+                ],
+                ui.style_color(StyleColor::Text),
+            )
+            .thickness(2.0)
+            .build();
+
+        for text in &[param.name().to_string(), param.format()] {
+            let width = ui.calc_text_size(text)[0];
+            ui.set_cursor_screen_pos([
+                pos[0] + (diameter - width) * 0.5,
+                ui.cursor_screen_pos()[1],
+            ]);
+            ui.text(text);
+        }
+    });
+
+    changed
+}
+
+/// Set a parameter as a single edit, for changes that are not a drag.
+pub(crate) fn set_param(ctx: &mut WindowContext, param: &Arc<Param>, value: f32) {
+    let before = param.get();
+    param.set(value);
+    ctx.undo.push_param(param, before);
+
+    ctx.handle.emit(UiEvent::BeginEdit(param.id()));
+    ctx.handle.emit(UiEvent::SetParam {
+        id: param.id(),
+        value: param.get(),
+    });
+    ctx.handle.emit(UiEvent::EndEdit(param.id()));
+}
@@ -0,0 +1,553 @@
+use crate::WindowContext;
+
+/// A change to a depth of the modulation matrix, with the same gestures as parameter edits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModMatrixEdit {
+    BeginEdit {
+        source: usize,
+        destination: usize,
+    },
+    SetDepth {
+        source: usize,
+        destination: usize,
+        depth: f32,
+    },
+    EndEdit {
+        source: usize,
+        destination: usize,
+    },
+}
+
+/// A table of modulation depths, with a row per source and a column per destination.
+///
+/// Only the visible cells are laid out, so matrices with hundreds of rows and columns stay
+/// cheap. Drag a cell horizontally to set its depth between -1 and 1, following the
+/// interaction policy of the window. Right click a source or destination name to pin it, which
+/// keeps it in view at the top or left while scrolling. With the table focused, the arrow keys
+/// move between cells, page up and page down change the depth and delete resets it. Keep it in
+/// the application state and call `build` every frame.
+#[derive(Debug, Clone)]
+pub struct ModMatrix {
+    sources: Vec<String>,
+    destinations: Vec<String>,
+    /// The depths, a row of destinations for each source.
+    depths: Vec<f32>,
+    pinned_sources: Vec<bool>,
+    pinned_destinations: Vec<bool>,
+    /// The cell with keyboard focus, as `(source, destination)`.
+    cursor: (usize, usize),
+    dragging: Option<(usize, usize)>,
+}
+
+impl ModMatrix {
+    pub fn new(sources: Vec<String>, destinations: Vec<String>) -> Self {
+        Self {
+            depths: vec![0.0; sources.len() * destinations.len()],
+            pinned_sources: vec![false; sources.len()],
+            pinned_destinations: vec![false; destinations.len()],
+            sources,
+            destinations,
+            cursor: (0, 0),
+            dragging: None,
+        }
+    }
+
+    pub fn sources(&self) -> &[String] {
+        &self.sources
+    }
+
+    pub fn destinations(&self) -> &[String] {
+        &self.destinations
+    }
+
+    pub fn depth(&self, source: usize, destination: usize) -> f32 {
+        self.depths[source * self.destinations.len() + destination]
+    }
+
+    /// Set a depth without reporting an edit, for changes coming from the plugin.
+    pub fn set_depth(&mut self, source: usize, destination: usize, depth: f32) {
+        let index = source * self.destinations.len() + destination;
+        self.depths[index] = depth.max(-1.0).min(1.0);
+    }
+
+    pub fn is_source_pinned(&self, source: usize) -> bool {
+        self.pinned_sources[source]
+    }
+
+    pub fn set_source_pinned(&mut self, source: usize, pinned: bool) {
+        self.pinned_sources[source] = pinned;
+    }
+
+    pub fn is_destination_pinned(&self, destination: usize) -> bool {
+        self.pinned_destinations[destination]
+    }
+
+    pub fn set_destination_pinned(&mut self, destination: usize, pinned: bool) {
+        self.pinned_destinations[destination] = pinned;
+    }
+
+    /// The cell with keyboard focus, as `(source, destination)`.
+    pub fn cursor(&self) -> (usize, usize) {
+        self.cursor
+    }
+
+    /// Set a depth as a single edit.
+    fn edit(
+        &mut self,
+        edits: &mut Vec<ModMatrixEdit>,
+        source: usize,
+        destination: usize,
+        depth: f32,
+    ) {
+        self.set_depth(source, destination, depth);
+        edits.push(ModMatrixEdit::BeginEdit {
+            source,
+            destination,
+        });
+        edits.push(ModMatrixEdit::SetDepth {
+            source,
+            destination,
+            depth: self.depth(source, destination),
+        });
+        edits.push(ModMatrixEdit::EndEdit {
+            source,
+            destination,
+        });
+    }
+
+    /// Returns the edits the user made this frame.
+    pub fn build(
+        &mut self,
+        ui: &imgui::Ui,
+        ctx: &mut WindowContext,
+        label: &str,
+        size: [f32; 2],
+    ) -> Vec<ModMatrixEdit> {
+        let mut edits = Vec::new();
+        if self.sources.is_empty() || self.destinations.is_empty() {
+            return edits;
+        }
+
+        let padding = ui.push_style_var(imgui::StyleVar::WindowPadding([0.0, 0.0]));
+        imgui::ChildWindow::new(label)
+            .size(size)
+            .horizontal_scrollbar(true)
+            .build(ui, || {
+                padding.pop();
+                self.build_table(ui, ctx, &mut edits);
+            });
+
+        edits
+    }
+
+    fn build_table(
+        &mut self,
+        ui: &imgui::Ui,
+        ctx: &mut WindowContext,
+        edits: &mut Vec<ModMatrixEdit>,
+    ) {
+        use imgui::{Key, MouseButton, StyleColor};
+
+        let style = ui.clone_style();
+        let cell = [ui.frame_height() * 3.0, ui.frame_height()];
+        let header = ui.frame_height();
+        let name_width = self
+            .sources
+            .iter()
+            .map(|name| ui.calc_text_size(name)[0])
+            .fold(0.0, f32::max)
+            + style.frame_padding[0] * 2.0;
+
+        // Pinned rows and columns come first and do not scroll.
+        let (rows, pinned_rows) = pinned_first(&self.pinned_sources);
+        let (columns, pinned_columns) = pinned_first(&self.pinned_destinations);
+
+        let visible = ui.content_region_avail();
+        let origin = ui.window_pos();
+        let scroll = [ui.scroll_x(), ui.scroll_y()];
+        let content = [
+            name_width + columns.len() as f32 * cell[0],
+            header + rows.len() as f32 * cell[1],
+        ];
+        ui.dummy(content);
+
+        // One button over the visible area takes the mouse and the keyboard focus.
+        ui.set_cursor_pos(scroll);
+        ui.invisible_button("##cells", [visible[0].max(1.0), visible[1].max(1.0)]);
+        ctx.focus_item(ui);
+
+        // The screen position of a row or column from its place in the display order.
+        let position = |index: usize, pinned: usize, start: f32, cell: f32, scroll: f32| {
+            let offset = start + index as f32 * cell;
+            if index < pinned {
+                offset
+            } else {
+                offset - scroll
+            }
+        };
+        let column_x =
+            |i: usize| origin[0] + position(i, pinned_columns, name_width, cell[0], scroll[0]);
+        let row_y = |i: usize| origin[1] + position(i, pinned_rows, header, cell[1], scroll[1]);
+
+        // The place in the display order under a local position.
+        let index = |local: f32, pinned: usize, start: f32, cell: f32, scroll: f32, len: usize| {
+            let local = local - start;
+            let index = if local < pinned as f32 * cell {
+                (local / cell) as usize
+            } else {
+                ((local + scroll) / cell) as usize
+            };
+            if local >= 0.0 && index < len {
+                Some(index)
+            } else {
+                None
+            }
+        };
+
+        let io = ui.io();
+        let table_hovered = ui.is_item_hovered();
+        let mouse = [io.mouse_pos[0] - origin[0], io.mouse_pos[1] - origin[1]];
+        let hovered_column = index(
+            mouse[0],
+            pinned_columns,
+            name_width,
+            cell[0],
+            scroll[0],
+            columns.len(),
+        );
+        let hovered_row = index(
+            mouse[1],
+            pinned_rows,
+            header,
+            cell[1],
+            scroll[1],
+            rows.len(),
+        );
+
+        if table_hovered && ui.is_mouse_clicked(MouseButton::Right) {
+            match (hovered_row, hovered_column) {
+                (None, Some(column)) if mouse[1] < header => {
+                    let destination = columns[column];
+                    self.pinned_destinations[destination] = !self.pinned_destinations[destination];
+                }
+                (Some(row), None) if mouse[0] < name_width => {
+                    let source = rows[row];
+                    self.pinned_sources[source] = !self.pinned_sources[source];
+                }
+                _ => {}
+            }
+        }
+
+        if ui.is_item_activated() {
+            if let (Some(row), Some(column)) = (hovered_row, hovered_column) {
+                let (source, destination) = (rows[row], columns[column]);
+                self.cursor = (source, destination);
+                if ctx.interaction.wants_reset(ui) {
+                    self.edit(edits, source, destination, 0.0);
+                } else {
+                    edits.push(ModMatrixEdit::BeginEdit {
+                        source,
+                        destination,
+                    });
+                    self.dragging = Some((source, destination));
+                }
+            }
+        }
+
+        if let Some((source, destination)) = self.dragging {
+            if ui.is_item_active() {
+                let delta = io.mouse_delta[0] * ctx.interaction.factor(io) * 2.0
+                    / ctx.interaction.drag_distance.max(1.0);
+                if delta != 0.0 {
+                    let depth = self.depth(source, destination) + delta;
+                    self.set_depth(source, destination, depth);
+                    edits.push(ModMatrixEdit::SetDepth {
+                        source,
+                        destination,
+                        depth: self.depth(source, destination),
+                    });
+                }
+            } else {
+                edits.push(ModMatrixEdit::EndEdit {
+                    source,
+                    destination,
+                });
+                self.dragging = None;
+            }
+        }
+
+        let focused = ui.is_window_focused() && !io.want_text_input;
+        if focused && self.dragging.is_none() {
+            let mut row = rows.iter().position(|&s| s == self.cursor.0).unwrap_or(0);
+            let mut column = columns
+                .iter()
+                .position(|&d| d == self.cursor.1)
+                .unwrap_or(0);
+            let mut moved = false;
+            if ui.is_key_pressed(Key::UpArrow) && row > 0 {
+                row -= 1;
+                moved = true;
+            }
+            if ui.is_key_pressed(Key::DownArrow) && row + 1 < rows.len() {
+                row += 1;
+                moved = true;
+            }
+            if ui.is_key_pressed(Key::LeftArrow) && column > 0 {
+                column -= 1;
+                moved = true;
+            }
+            if ui.is_key_pressed(Key::RightArrow) && column + 1 < columns.len() {
+                column += 1;
+                moved = true;
+            }
+            let (source, destination) = (rows[row], columns[column]);
+            self.cursor = (source, destination);
+
+            if moved {
+                // Scroll the cursor into view, unless it is pinned.
+                if column >= pinned_columns {
+                    let left = (column - pinned_columns) as f32 * cell[0];
+                    let right = name_width + (column + 1) as f32 * cell[0] - visible[0];
+                    ui.set_scroll_x(scroll[0].max(right).min(left));
+                }
+                if row >= pinned_rows {
+                    let top = (row - pinned_rows) as f32 * cell[1];
+                    let bottom = header + (row + 1) as f32 * cell[1] - visible[1];
+                    ui.set_scroll_y(scroll[1].max(bottom).min(top));
+                }
+            }
+
+            let step = 0.1 * ctx.interaction.factor(io);
+            let depth = self.depth(source, destination);
+            if ui.is_key_pressed(Key::PageUp) {
+                self.edit(edits, source, destination, depth + step);
+            } else if ui.is_key_pressed(Key::PageDown) {
+                self.edit(edits, source, destination, depth - step);
+            } else if ui.is_key_pressed(Key::Delete) || ui.is_key_pressed(Key::Backspace) {
+                self.edit(edits, source, destination, 0.0);
+            }
+        }
+
+        // Only the rows and columns in view are drawn.
+        let in_view =
+            |pinned: usize, start: f32, cell: f32, scroll: f32, extent: f32, len: usize| {
+                let first = (pinned + (scroll / cell) as usize).min(len);
+                let last =
+                    (pinned + ((scroll + extent - start) / cell).ceil().max(0.0) as usize + 1)
+                        .min(len);
+                (0..pinned).chain(first..last).collect::<Vec<_>>()
+            };
+        let visible_columns = in_view(
+            pinned_columns,
+            name_width,
+            cell[0],
+            scroll[0],
+            visible[0],
+            columns.len(),
+        );
+        let visible_rows = in_view(
+            pinned_rows,
+            header,
+            cell[1],
+            scroll[1],
+            visible[1],
+            rows.len(),
+        );
+
+        let draw_list = ui.get_window_draw_list();
+        let view_max = [origin[0] + visible[0], origin[1] + visible[1]];
+        let body_min = [
+            origin[0] + name_width + pinned_columns as f32 * cell[0],
+            origin[1] + header + pinned_rows as f32 * cell[1],
+        ];
+        let text_color = ui.style_color(StyleColor::Text);
+        let border = ui.style_color(StyleColor::Border);
+
+        // Scrolling cells are clipped so they pass under the pinned ones.
+        for &(pinned_row, pinned_column) in
+            &[(false, false), (true, false), (false, true), (true, true)]
+        {
+            let clip_min = [
+                if pinned_column {
+                    origin[0] + name_width
+                } else {
+                    body_min[0]
+                },
+                if pinned_row {
+                    origin[1] + header
+                } else {
+                    body_min[1]
+                },
+            ];
+            draw_list.with_clip_rect_intersect(clip_min, view_max, || {
+                for &row in visible_rows
+                    .iter()
+                    .filter(|&&row| (row < pinned_rows) == pinned_row)
+                {
+                    for &column in visible_columns
+                        .iter()
+                        .filter(|&&column| (column < pinned_columns) == pinned_column)
+                    {
+                        let (source, destination) = (rows[row], columns[column]);
+                        let min = [column_x(column), row_y(row)];
+                        let max = [min[0] + cell[0], min[1] + cell[1]];
+                        let active = self.dragging == Some((source, destination));
+                        let hovered = table_hovered
+                            && hovered_row == Some(row)
+                            && hovered_column == Some(column);
+
+                        let frame_color = if active {
+                            StyleColor::FrameBgActive
+                        } else if hovered {
+                            StyleColor::FrameBgHovered
+                        } else {
+                            StyleColor::FrameBg
+                        };
+                        draw_list
+                            .add_rect(min, max, ui.style_color(frame_color))
+                            .filled(true)
+                            .build();
+
+                        let depth = self.depth(source, destination);
+                        if depth != 0.0 {
+                            let center = (min[0] + max[0]) * 0.5;
+                            let end = center + depth * (cell[0] * 0.5 - 2.0);
+                            let grab_color = if active {
+                                StyleColor::SliderGrabActive
+                            } else {
+                                StyleColor::SliderGrab
+                            };
+                            draw_list
+                                .add_rect(
+                                    [center.min(end), min[1] + 2.0],
+                                    [center.max(end), max[1] - 2.0],
+                                    ui.style_color(grab_color),
+                                )
+                                .filled(true)
+                                .build();
+
+                            let text = format!("{:+.2}", depth);
+                            let text_size = ui.calc_text_size(&text);
+                            draw_list.add_text(
+                                [
+                                    min[0] + (cell[0] - text_size[0]) * 0.5,
+                                    min[1] + (cell[1] - text_size[1]) * 0.5,
+                                ],
+                                text_color,
+                                &text,
+                            );
+                        }
+
+                        draw_list.add_rect(min, max, border).build();
+                        if focused && self.cursor == (source, destination) {
+                            draw_list
+                                .add_rect(min, max, ui.style_color(StyleColor::NavHighlight))
+                                .thickness(2.0)
+                                .build();
+                        }
+                    }
+                }
+            });
+        }
+
+        // The headers, with pinned names marked in the plot color.
+        let header_color = ui.style_color(StyleColor::Header);
+        let pinned_color = ui.style_color(StyleColor::PlotHistogram);
+        for &pinned in &[false, true] {
+            let clip_min = [
+                if pinned {
+                    origin[0] + name_width
+                } else {
+                    body_min[0]
+                },
+                origin[1],
+            ];
+            draw_list.with_clip_rect_intersect(clip_min, view_max, || {
+                for &column in visible_columns
+                    .iter()
+                    .filter(|&&column| (column < pinned_columns) == pinned)
+                {
+                    let min = [column_x(column), origin[1]];
+                    let max = [min[0] + cell[0], min[1] + header];
+                    let name = &self.destinations[columns[column]];
+                    draw_list
+                        .add_rect(min, max, header_color)
+                        .filled(true)
+                        .build();
+                    if pinned {
+                        draw_list
+                            .add_line([min[0], max[1] - 1.0], [max[0], max[1] - 1.0], pinned_color)
+                            .thickness(2.0)
+                            .build();
+                    }
+                    draw_list.add_rect(min, max, border).build();
+                    draw_list.with_clip_rect_intersect(min, max, || {
+                        draw_list.add_text(
+                            [
+                                min[0] + style.frame_padding[0],
+                                min[1] + style.frame_padding[1],
+                            ],
+                            text_color,
+                            name,
+                        );
+                    });
+                }
+            });
+
+            let clip_min = [
+                origin[0],
+                if pinned {
+                    origin[1] + header
+                } else {
+                    body_min[1]
+                },
+            ];
+            draw_list.with_clip_rect_intersect(clip_min, view_max, || {
+                for &row in visible_rows
+                    .iter()
+                    .filter(|&&row| (row < pinned_rows) == pinned)
+                {
+                    let min = [origin[0], row_y(row)];
+                    let max = [min[0] + name_width, min[1] + cell[1]];
+                    let name = &self.sources[rows[row]];
+                    draw_list
+                        .add_rect(min, max, header_color)
+                        .filled(true)
+                        .build();
+                    if pinned {
+                        draw_list
+                            .add_line([max[0] - 1.0, min[1]], [max[0] - 1.0, max[1]], pinned_color)
+                            .thickness(2.0)
+                            .build();
+                    }
+                    draw_list.add_rect(min, max, border).build();
+                    draw_list.add_text(
+                        [
+                            min[0] + style.frame_padding[0],
+                            min[1] + style.frame_padding[1],
+                        ],
+                        text_color,
+                        name,
+                    );
+                }
+            });
+        }
+
+        draw_list
+            .add_rect(
+                origin,
+                [origin[0] + name_width, origin[1] + header],
+                ui.style_color(StyleColor::WindowBg),
+            )
+            .filled(true)
+            .build();
+    }
+}
+
+/// The indices with the pinned ones first, and how many are pinned.
+fn pinned_first(pinned: &[bool]) -> (Vec<usize>, usize) {
+    let mut order: Vec<usize> = (0..pinned.len()).filter(|&i| pinned[i]).collect();
+    let count = order.len();
+    order.extend((0..pinned.len()).filter(|&i| !pinned[i]));
+    (order, count)
+}
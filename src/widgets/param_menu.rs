@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use crate::handle::UiEvent;
+use crate::locale::Text;
+use crate::params::Param;
+use crate::WindowContext;
+
+#[derive(Default)]
+pub(crate) struct ParamMenu {
+    typed_value: String,
+}
+
+/// The standard right-click menu of a parameter, for the widget that was just built.
+///
+/// It offers resetting the parameter to its default, typing in a value, copying and pasting
+/// the value, and the host's automation options, plus MIDI learn when `Settings::midi_learn`
+/// is enabled. The param-bound widgets of this module already show it, call this for custom
+/// widgets.
+pub fn param_context_menu(ui: &imgui::Ui, ctx: &mut WindowContext, param: &Arc<Param>) {
+    #[cfg(feature = "remote_control")]
+    ctx.expose_param(param);
+
+    let popup_id = format!("##param_menu_{}", param.id());
+    if ui.is_item_clicked_with_button(imgui::MouseButton::Right) {
+        ui.open_popup(&popup_id);
+    }
+
+    ui.popup(&popup_id, || {
+        crate::midi_learn::menu_items(ui, ctx, param);
+
+        if imgui::MenuItem::new(ctx.locale.get(Text::ResetToDefault)).build(ui) {
+            super::set_param(ctx, param, param.default_value());
+        }
+
+        if imgui::MenuItem::new(ctx.locale.get(Text::CopyValue)).build(ui) {
+            ui.set_clipboard_text(param.format());
+        }
+        let pasted = ui.clipboard_text().and_then(|text| param.parse(&text));
+        if imgui::MenuItem::new(ctx.locale.get(Text::PasteValue))
+            .enabled(pasted.is_some())
+            .build(ui)
+        {
+            super::set_param(ctx, param, pasted.unwrap());
+        }
+
+        ui.separator();
+        if imgui::MenuItem::new(ctx.locale.get(Text::ShowAutomation)).build(ui) {
+            ctx.handle.emit(UiEvent::ShowAutomation(param.id()));
+        }
+        if imgui::MenuItem::new(ctx.locale.get(Text::ClearAutomation)).build(ui) {
+            ctx.handle.emit(UiEvent::ClearAutomation(param.id()));
+        }
+
+        ui.separator();
+        ui.text(ctx.locale.get(Text::TypeValue));
+        type_in_field(ui, ctx, param);
+    });
+
+    ui.popup(&type_in_id(param), || type_in_field(ui, ctx, param));
+}
+
+fn type_in_id(param: &Param) -> String {
+    format!("##type_in_{}", param.id())
+}
+
+/// Open a popup with just the text field to type in a value, below the widget that was just
+/// built.
+pub(crate) fn open_type_in(ui: &imgui::Ui, param: &Param) {
+    ui.open_popup(&type_in_id(param));
+}
+
+fn type_in_field(ui: &imgui::Ui, ctx: &mut WindowContext, param: &Arc<Param>) {
+    let menu = &mut ctx.param_menu;
+    if ui.is_window_appearing() {
+        menu.typed_value = param.format();
+        ui.set_keyboard_focus_here();
+    }
+
+    let entered = ui
+        .input_text("##value", &mut menu.typed_value)
+        .enter_returns_true(true)
+        .build();
+    if entered {
+        if let Some(value) = param.parse(&menu.typed_value) {
+            super::set_param(ctx, param, value);
+        }
+        ui.close_current_popup();
+    }
+}
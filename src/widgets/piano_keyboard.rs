@@ -0,0 +1,156 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::handle::UiEvent;
+use crate::WindowContext;
+
+/// The notes that are currently played outside of the UI, for example by the host.
+///
+/// The audio thread marks notes with `note_on` and `note_off` without locking, and the
+/// keyboard highlights them every frame.
+#[derive(Debug, Default)]
+pub struct PlayedNotes {
+    notes: [AtomicU64; 2],
+}
+
+impl PlayedNotes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn note_on(&self, note: u8) {
+        let note = note & 127;
+        self.notes[note as usize / 64].fetch_or(1 << (note % 64), Ordering::Relaxed);
+    }
+
+    pub fn note_off(&self, note: u8) {
+        let note = note & 127;
+        self.notes[note as usize / 64].fetch_and(!(1 << (note % 64)), Ordering::Relaxed);
+    }
+
+    pub fn all_notes_off(&self) {
+        for notes in &self.notes {
+            notes.store(0, Ordering::Relaxed);
+        }
+    }
+
+    pub fn is_on(&self, note: u8) -> bool {
+        let note = note & 127;
+        self.notes[note as usize / 64].load(Ordering::Relaxed) & (1 << (note % 64)) != 0
+    }
+}
+
+fn is_black(note: u8) -> bool {
+    matches!(note % 12, 1 | 3 | 6 | 8 | 10)
+}
+
+/// A piano keyboard that plays notes with the mouse.
+///
+/// Clicking a key emits `UiEvent::NoteOn` through the `Handle` of the window, with a velocity
+/// that increases towards the front of the key, and releasing it emits `UiEvent::NoteOff`.
+/// Dragging across the keys plays them in turn. Keep it in the application state and call
+/// `build` every frame.
+#[derive(Debug)]
+pub struct PianoKeyboard {
+    first_note: u8,
+    last_note: u8,
+    played: Arc<PlayedNotes>,
+    pressed: Option<u8>,
+}
+
+impl PianoKeyboard {
+    /// A keyboard from `first_note` to `last_note`, as MIDI note numbers.
+    pub fn new(first_note: u8, last_note: u8) -> Self {
+        let first_note = first_note.min(127);
+
+        Self {
+            first_note,
+            last_note: last_note.min(127).max(first_note),
+            played: Arc::new(PlayedNotes::new()),
+            pressed: None,
+        }
+    }
+
+    /// The notes to highlight, to hand to the audio thread.
+    pub fn played_notes(&self) -> &Arc<PlayedNotes> {
+        &self.played
+    }
+
+    /// The note held down with the mouse.
+    pub fn pressed(&self) -> Option<u8> {
+        self.pressed
+    }
+
+    /// The screen rectangles of the keys, white keys first.
+    fn keys(&self, pos: [f32; 2], size: [f32; 2]) -> Vec<(u8, [f32; 2], [f32; 2])> {
+        let notes = self.first_note..=self.last_note;
+        let white_count = notes.clone().filter(|note| !is_black(*note)).count().max(1);
+        let white_width = size[0] / white_count as f32;
+        let black_width = white_width * 0.6;
+        let black_height = size[1] * 0.6;
+
+        let mut white = Vec::new();
+        let mut black = Vec::new();
+        let mut x = pos[0];
+        for note in notes {
+            if is_black(note) {
+                let min = [x - black_width * 0.5, pos[1]];
+                black.push((note, min, [min[0] + black_width, pos[1] + black_height]));
+            } else {
+                white.push((note, [x, pos[1]], [x + white_width, pos[1] + size[1]]));
+                x += white_width;
+            }
+        }
+
+        white.extend(black);
+        white
+    }
+
+    pub fn build(&mut self, ui: &imgui::Ui, ctx: &mut WindowContext, size: [f32; 2]) {
+        let pos = ui.cursor_screen_pos();
+        ui.invisible_button("##piano_keyboard", size);
+        ctx.focus_item(ui);
+        let keys = self.keys(pos, size);
+
+        let mouse = ui.io().mouse_pos;
+        // Black keys are on top of the white keys, so they are hit first.
+        let hovered = keys.iter().rev().find(|(_, min, max)| {
+            mouse[0] >= min[0] && mouse[0] < max[0] && mouse[1] >= min[1] && mouse[1] < max[1]
+        });
+
+        let target = if ui.is_item_active() {
+            hovered.map(|(note, min, max)| {
+                let velocity = ((mouse[1] - min[1]) / (max[1] - min[1])).max(0.1).min(1.0);
+                (*note, velocity)
+            })
+        } else {
+            None
+        };
+
+        if target.map(|(note, _)| note) != self.pressed {
+            if let Some(note) = self.pressed.take() {
+                ctx.handle.emit(UiEvent::NoteOff { note });
+            }
+            if let Some((note, velocity)) = target {
+                ctx.handle.emit(UiEvent::NoteOn { note, velocity });
+                self.pressed = Some(note);
+            }
+        }
+
+        let draw_list = ui.get_window_draw_list();
+        let highlight = ui.style_color(imgui::StyleColor::PlotHistogram);
+        let border = ui.style_color(imgui::StyleColor::Border);
+        for (note, min, max) in &keys {
+            let color = if self.pressed == Some(*note) || self.played.is_on(*note) {
+                highlight
+            } else if is_black(*note) {
+                [0.1, 0.1, 0.1, 1.0]
+            } else {
+                [0.95, 0.95, 0.95, 1.0]
+            };
+
+            draw_list.add_rect(*min, *max, color).filled(true).build();
+            draw_list.add_rect(*min, *max, border).build();
+        }
+    }
+}
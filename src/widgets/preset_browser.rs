@@ -0,0 +1,288 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::handle::UiEvent;
+use crate::locale::Text;
+use crate::tasks::{self, Task};
+use crate::WindowContext;
+
+/// The scanned folders, shared by every browser in the process so reopening an editor
+/// doesn't scan the disk again.
+static SCAN_CACHE: Mutex<Option<HashMap<ScanKey, Arc<Vec<PresetFolder>>>>> = Mutex::new(None);
+
+type ScanKey = (Vec<PathBuf>, Vec<String>);
+
+/// A folder of presets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PresetFolder {
+    pub name: String,
+    pub path: PathBuf,
+    pub presets: Vec<PathBuf>,
+    pub folders: Vec<PresetFolder>,
+}
+
+impl PresetFolder {
+    fn scan(path: &Path, extensions: &[String]) -> Self {
+        let mut presets = Vec::new();
+        let mut folders = Vec::new();
+
+        if let Ok(entries) = fs::read_dir(path) {
+            for entry in entries.filter_map(Result::ok) {
+                let path = entry.path();
+                if path.is_dir() {
+                    folders.push(PresetFolder::scan(&path, extensions));
+                } else if has_extension(&path, extensions) {
+                    presets.push(path);
+                }
+            }
+        }
+
+        presets.sort();
+        folders.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Self {
+            name: file_name(path),
+            path: path.to_path_buf(),
+            presets,
+            folders,
+        }
+    }
+
+    fn all_presets<'a>(&'a self, presets: &mut Vec<&'a Path>) {
+        presets.extend(self.presets.iter().map(PathBuf::as_path));
+        for folder in &self.folders {
+            folder.all_presets(presets);
+        }
+    }
+}
+
+fn has_extension(path: &Path, extensions: &[String]) -> bool {
+    extensions.is_empty()
+        || path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map_or(false, |extension| {
+                extensions.iter().any(|e| e.eq_ignore_ascii_case(extension))
+            })
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string_lossy().into_owned())
+}
+
+/// A browser for preset files, with a folder tree, a search box and favorites.
+///
+/// Keep it in the application state and call `build` every frame. The directories are
+/// scanned on a worker thread. Loading a preset emits `UiEvent::LoadPreset` through the
+/// `Handle` of the window, and saving emits `UiEvent::SavePreset`, so the plugin can
+/// read or write the file.
+#[derive(Debug)]
+pub struct PresetBrowser {
+    roots: Vec<PathBuf>,
+    extensions: Vec<String>,
+    folders: Arc<Vec<PresetFolder>>,
+    scan: Option<Task<Arc<Vec<PresetFolder>>>>,
+    search: String,
+    favorites: HashSet<PathBuf>,
+    selected: Option<PathBuf>,
+    save_name: String,
+}
+
+impl PresetBrowser {
+    /// Browse the presets in `roots` with one of the given extensions, without the dot.
+    pub fn new(roots: Vec<PathBuf>, extensions: &[&str]) -> Self {
+        let mut browser = Self {
+            roots,
+            extensions: extensions.iter().map(|e| e.to_string()).collect(),
+            folders: Arc::new(Vec::new()),
+            scan: None,
+            search: String::new(),
+            favorites: HashSet::new(),
+            selected: None,
+            save_name: String::new(),
+        };
+        browser.start_scan(false);
+        browser
+    }
+
+    /// Scan the directories again, for example after saving a preset.
+    pub fn rescan(&mut self) {
+        self.start_scan(true);
+    }
+
+    fn start_scan(&mut self, force: bool) {
+        let key = (self.roots.clone(), self.extensions.clone());
+
+        if !force {
+            let cache = SCAN_CACHE.lock().unwrap();
+            if let Some(folders) = cache.as_ref().and_then(|cache| cache.get(&key)) {
+                self.scan = Some(Task::ready(folders.clone()));
+                return;
+            }
+        }
+
+        self.scan = Some(tasks::spawn(move || {
+            let folders = Arc::new(
+                key.0
+                    .iter()
+                    .map(|root| PresetFolder::scan(root, &key.1))
+                    .collect::<Vec<_>>(),
+            );
+            SCAN_CACHE
+                .lock()
+                .unwrap()
+                .get_or_insert_with(HashMap::new)
+                .insert(key, folders.clone());
+            folders
+        }));
+    }
+
+    pub fn folders(&self) -> &[PresetFolder] {
+        &self.folders
+    }
+
+    /// The favorite presets, to store them with the plugin settings.
+    pub fn favorites(&self) -> impl Iterator<Item = &Path> {
+        self.favorites.iter().map(PathBuf::as_path)
+    }
+
+    pub fn set_favorites<I: IntoIterator<Item = PathBuf>>(&mut self, favorites: I) {
+        self.favorites = favorites.into_iter().collect();
+    }
+
+    pub fn selected(&self) -> Option<&Path> {
+        self.selected.as_deref()
+    }
+
+    pub fn build(&mut self, ui: &imgui::Ui, ctx: &mut WindowContext) {
+        if let Some(scan) = &mut self.scan {
+            if let Some(folders) = scan.take() {
+                self.folders = folders;
+                self.scan = None;
+            }
+        }
+
+        let locale = &ctx.locale;
+        ui.input_text("##search", &mut self.search)
+            .hint(locale.get(Text::Search))
+            .build();
+        ui.same_line();
+        if ui.button(locale.get(Text::Rescan)) {
+            self.rescan();
+        }
+        if self.scan.is_some() {
+            ui.same_line();
+            ui.text_disabled(locale.get(Text::Scanning));
+        }
+
+        let mut load = None;
+        let folders = self.folders.clone();
+
+        imgui::ChildWindow::new("presets")
+            .size([0.0, -ui.frame_height_with_spacing()])
+            .border(true)
+            .build(ui, || {
+                if !self.search.is_empty() {
+                    let search = self.search.to_lowercase();
+                    let mut presets = Vec::new();
+                    for folder in folders.iter() {
+                        folder.all_presets(&mut presets);
+                    }
+                    for preset in presets {
+                        if file_name(preset).to_lowercase().contains(&search) {
+                            self.preset_item(ui, preset, &mut load);
+                        }
+                    }
+                    return;
+                }
+
+                if !self.favorites.is_empty() {
+                    let label = format!("{}##favorites", ctx.locale.get(Text::Favorites));
+                    imgui::TreeNode::new(&label).build(ui, || {
+                        let mut favorites: Vec<_> = self.favorites.iter().cloned().collect();
+                        favorites.sort();
+                        for preset in &favorites {
+                            self.preset_item(ui, preset, &mut load);
+                        }
+                    });
+                }
+
+                for folder in folders.iter() {
+                    self.folder_tree(ui, folder, &mut load);
+                }
+            });
+
+        if let Some(preset) = load {
+            ctx.handle.emit(UiEvent::LoadPreset(preset));
+        }
+
+        let locale = &ctx.locale;
+        ui.input_text("##save_name", &mut self.save_name)
+            .hint(locale.get(Text::PresetName))
+            .build();
+        ui.same_line();
+        if ui.button(locale.get(Text::Save)) && !self.save_name.trim().is_empty() {
+            if let Some(path) = self.save_path() {
+                ctx.handle.emit(UiEvent::SavePreset(path));
+                self.save_name.clear();
+            }
+        }
+    }
+
+    /// Where a preset with the typed name is saved: the folder of the selected preset, or
+    /// the first root.
+    fn save_path(&self) -> Option<PathBuf> {
+        let folder = match &self.selected {
+            Some(selected) => selected.parent()?.to_path_buf(),
+            None => self.roots.first()?.clone(),
+        };
+
+        let mut path = folder.join(self.save_name.trim());
+        if let Some(extension) = self.extensions.first() {
+            path.set_extension(extension);
+        }
+        Some(path)
+    }
+
+    fn folder_tree(&mut self, ui: &imgui::Ui, folder: &PresetFolder, load: &mut Option<PathBuf>) {
+        let label = format!("{}##{}", folder.name, folder.path.to_string_lossy());
+        imgui::TreeNode::new(&label).build(ui, || {
+            for child in &folder.folders {
+                self.folder_tree(ui, child, load);
+            }
+            for preset in &folder.presets {
+                self.preset_item(ui, preset, load);
+            }
+        });
+    }
+
+    fn preset_item(&mut self, ui: &imgui::Ui, preset: &Path, load: &mut Option<PathBuf>) {
+        let _id = ui.push_id(&*preset.to_string_lossy());
+
+        let favorite = self.favorites.contains(preset);
+        if ui.small_button(if favorite { "*" } else { "-" }) {
+            if favorite {
+                self.favorites.remove(preset);
+            } else {
+                self.favorites.insert(preset.to_path_buf());
+            }
+        }
+        ui.same_line();
+
+        let selected = self.selected.as_deref() == Some(preset);
+        let clicked = imgui::Selectable::new(&file_name(preset))
+            .selected(selected)
+            .flags(imgui::SelectableFlags::ALLOW_DOUBLE_CLICK)
+            .build(ui);
+        if clicked {
+            self.selected = Some(preset.to_path_buf());
+            if ui.is_mouse_double_clicked(imgui::MouseButton::Left) {
+                *load = Some(preset.to_path_buf());
+            }
+        }
+    }
+}
@@ -0,0 +1,361 @@
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::tasks::{self, Task};
+
+/// The number of samples summarized by a peak of the first level of the pyramid.
+const BASE_BLOCK: usize = 16;
+
+/// The smallest number of samples shown across the widget.
+const MIN_VIEW: f64 = 32.0;
+
+/// The distance in logical pixels within which a loop marker can be grabbed.
+const MARKER_GRAB: f32 = 4.0;
+
+/// Min/max peaks of a sample buffer at decreasing resolutions, so a waveform of any length
+/// can be drawn at any zoom level without touching every sample.
+#[derive(Debug, Clone, Default)]
+pub struct PeakPyramid {
+    /// Level `i` has one peak per `BASE_BLOCK << i` samples.
+    levels: Vec<Vec<(f32, f32)>>,
+}
+
+impl PeakPyramid {
+    pub fn build(samples: &[f32]) -> Self {
+        let mut levels = Vec::new();
+
+        let mut level: Vec<(f32, f32)> = samples
+            .chunks(BASE_BLOCK)
+            .map(|chunk| {
+                chunk.iter().fold((f32::MAX, f32::MIN), |(min, max), &s| {
+                    (min.min(s), max.max(s))
+                })
+            })
+            .collect();
+
+        while level.len() > 1 {
+            let next = level
+                .chunks(2)
+                .map(|pair| {
+                    pair.iter()
+                        .fold((f32::MAX, f32::MIN), |(min, max), &(lo, hi)| {
+                            (min.min(lo), max.max(hi))
+                        })
+                })
+                .collect();
+            levels.push(level);
+            level = next;
+        }
+        levels.push(level);
+
+        Self { levels }
+    }
+
+    /// The min and max of `samples[range]`, using the coarsest level that is still precise
+    /// enough for the length of the range.
+    fn peak(&self, samples: &[f32], range: Range<usize>) -> (f32, f32) {
+        let len = range.end.saturating_sub(range.start);
+        let fold = |iter: &mut dyn Iterator<Item = (f32, f32)>| {
+            iter.fold((f32::MAX, f32::MIN), |(min, max), (lo, hi)| {
+                (min.min(lo), max.max(hi))
+            })
+        };
+
+        let level = (0..self.levels.len())
+            .rev()
+            .find(|level| BASE_BLOCK << level <= len);
+
+        match level {
+            Some(level) => {
+                let block = BASE_BLOCK << level;
+                let peaks = &self.levels[level];
+                let start = (range.start / block).min(peaks.len());
+                let end = ((range.end + block - 1) / block).min(peaks.len());
+                fold(&mut peaks[start..end].iter().copied())
+            }
+            None => {
+                let end = range.end.min(samples.len());
+                let start = range.start.min(end);
+                fold(&mut samples[start..end].iter().map(|&s| (s, s)))
+            }
+        }
+    }
+}
+
+/// Something the user changed in a waveform.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WaveformEvent {
+    /// The selected range of samples changed, `None` when the selection was cleared.
+    Selection(Option<Range<usize>>),
+    /// A loop marker was moved.
+    LoopPoints(Range<usize>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum WaveformDrag {
+    Select { anchor: usize },
+    LoopStart,
+    LoopEnd,
+    Pan,
+}
+
+/// A waveform display with zoom, selection and loop markers, for sampler editors.
+///
+/// The peaks are computed on a worker thread when the samples are set. Scroll to zoom
+/// around the pointer, scroll with shift or drag with the middle button to pan, drag to
+/// select and double click to clear the selection. Keep it in the application state and
+/// call `build` every frame.
+#[derive(Debug)]
+pub struct Waveform {
+    samples: Arc<Vec<f32>>,
+    pyramid: Arc<PeakPyramid>,
+    task: Option<Task<PeakPyramid>>,
+    /// The visible range in samples.
+    view: (f64, f64),
+    selection: Option<Range<usize>>,
+    loop_points: Option<Range<usize>>,
+    drag: Option<WaveformDrag>,
+}
+
+impl Waveform {
+    pub fn new(samples: Arc<Vec<f32>>) -> Self {
+        let mut waveform = Self {
+            samples: Arc::new(Vec::new()),
+            pyramid: Arc::new(PeakPyramid::default()),
+            task: None,
+            view: (0.0, 0.0),
+            selection: None,
+            loop_points: None,
+            drag: None,
+        };
+        waveform.set_samples(samples);
+        waveform
+    }
+
+    /// Show other samples, resetting the zoom and the selection.
+    pub fn set_samples(&mut self, samples: Arc<Vec<f32>>) {
+        let for_task = samples.clone();
+        self.task = Some(tasks::spawn(move || PeakPyramid::build(&for_task)));
+        self.samples = samples;
+        self.selection = None;
+        self.zoom_to_fit();
+    }
+
+    pub fn zoom_to_fit(&mut self) {
+        self.view = (0.0, self.samples.len() as f64);
+    }
+
+    /// The visible range of samples.
+    pub fn view(&self) -> Range<usize> {
+        self.view.0 as usize..self.view.1 as usize
+    }
+
+    pub fn selection(&self) -> Option<Range<usize>> {
+        self.selection.clone()
+    }
+
+    pub fn set_selection(&mut self, selection: Option<Range<usize>>) {
+        self.selection = selection;
+    }
+
+    pub fn loop_points(&self) -> Option<Range<usize>> {
+        self.loop_points.clone()
+    }
+
+    /// Show loop markers, which the user can drag.
+    pub fn set_loop_points(&mut self, loop_points: Option<Range<usize>>) {
+        self.loop_points = loop_points;
+    }
+
+    fn zoom(&mut self, factor: f64, around: f64) {
+        let len = self.samples.len() as f64;
+        let new_len = ((self.view.1 - self.view.0) * factor)
+            .max(MIN_VIEW)
+            .min(len);
+        let t = (around - self.view.0) / (self.view.1 - self.view.0).max(1.0);
+        let start = around - new_len * t;
+        self.view = (start, start + new_len);
+        self.pan(0.0);
+    }
+
+    fn pan(&mut self, samples: f64) {
+        let len = self.samples.len() as f64;
+        let view_len = self.view.1 - self.view.0;
+        let start = (self.view.0 + samples)
+            .max(0.0)
+            .min((len - view_len).max(0.0));
+        self.view = (start, start + view_len);
+    }
+
+    fn mapping(&self, x: f32, width: f32) -> Mapping {
+        Mapping {
+            x,
+            view_start: self.view.0,
+            samples_per_pixel: ((self.view.1 - self.view.0) / width.max(1.0) as f64)
+                .max(f64::EPSILON),
+            len: self.samples.len(),
+        }
+    }
+
+    /// Returns what the user changed this frame.
+    pub fn build(&mut self, ui: &imgui::Ui, size: [f32; 2]) -> Option<WaveformEvent> {
+        use imgui::{MouseButton, StyleColor};
+
+        if let Some(task) = &mut self.task {
+            if let Some(pyramid) = task.take() {
+                self.pyramid = Arc::new(pyramid);
+                self.task = None;
+            }
+        }
+
+        let pos = ui.cursor_screen_pos();
+        let max = [pos[0] + size[0], pos[1] + size[1]];
+        ui.invisible_button("##waveform", size);
+
+        let io = ui.io();
+        let mut event = None;
+
+        let map = self.mapping(pos[0], size[0]);
+        let mouse_sample = map.to_sample(io.mouse_pos[0]);
+        if ui.is_item_hovered() {
+            if io.mouse_wheel != 0.0 && !io.key_shift {
+                self.zoom(0.8f64.powf(io.mouse_wheel as f64), mouse_sample);
+            }
+            let pan = if io.key_shift {
+                io.mouse_wheel
+            } else {
+                io.mouse_wheel_h
+            };
+            if pan != 0.0 {
+                self.pan(-pan as f64 * size[0] as f64 * 0.1 * map.samples_per_pixel);
+            }
+
+            if ui.is_mouse_double_clicked(MouseButton::Left) {
+                self.selection = None;
+                event = Some(WaveformEvent::Selection(None));
+            } else if ui.is_mouse_clicked(MouseButton::Left) {
+                let near =
+                    |sample: usize| (map.to_x(sample) - io.mouse_pos[0]).abs() <= MARKER_GRAB;
+                self.drag = Some(match &self.loop_points {
+                    Some(points) if near(points.start) => WaveformDrag::LoopStart,
+                    Some(points) if near(points.end) => WaveformDrag::LoopEnd,
+                    _ => WaveformDrag::Select {
+                        anchor: map.clamp(mouse_sample),
+                    },
+                });
+            } else if ui.is_mouse_clicked(MouseButton::Middle) {
+                self.drag = Some(WaveformDrag::Pan);
+            }
+        }
+
+        match self.drag {
+            Some(WaveformDrag::Pan) => self.pan(-io.mouse_delta[0] as f64 * map.samples_per_pixel),
+            Some(drag) if io.mouse_delta[0] != 0.0 => {
+                let sample = map.clamp(mouse_sample);
+                if let WaveformDrag::Select { anchor } = drag {
+                    let selection = anchor.min(sample)..anchor.max(sample);
+                    self.selection = Some(selection.clone());
+                    event = Some(WaveformEvent::Selection(Some(selection)));
+                } else if let Some(points) = &mut self.loop_points {
+                    if drag == WaveformDrag::LoopStart {
+                        points.start = sample.min(points.end);
+                    } else {
+                        points.end = sample.max(points.start);
+                    }
+                    event = Some(WaveformEvent::LoopPoints(points.clone()));
+                }
+            }
+            _ => {}
+        }
+        if !ui.is_mouse_down(MouseButton::Left) && !ui.is_mouse_down(MouseButton::Middle) {
+            self.drag = None;
+        }
+
+        // Zooming and panning changed the view.
+        let map = self.mapping(pos[0], size[0]);
+
+        let draw_list = ui.get_window_draw_list();
+        draw_list
+            .add_rect(pos, max, ui.style_color(StyleColor::FrameBg))
+            .filled(true)
+            .build();
+
+        if let Some(selection) = &self.selection {
+            let x0 = map.to_x(selection.start).max(pos[0]);
+            let x1 = map.to_x(selection.end).min(max[0]);
+            if x1 > x0 {
+                let color = ui.style_color(StyleColor::TextSelectedBg);
+                draw_list
+                    .add_rect([x0, pos[1]], [x1, max[1]], color)
+                    .filled(true)
+                    .build();
+            }
+        }
+
+        // Without the pyramid every sample in view would have to be read every frame.
+        let ready = self.task.is_none() || map.samples_per_pixel < BASE_BLOCK as f64;
+        if ready {
+            let color = ui.style_color(StyleColor::PlotLines);
+            let center = pos[1] + size[1] * 0.5;
+            let half_height = size[1] * 0.5;
+            for column in 0..size[0] as usize {
+                let x = pos[0] + column as f32;
+                let start = map.clamp(map.to_sample(x));
+                let end = map.clamp(map.to_sample(x + 1.0)).max(start + 1);
+                if start >= self.samples.len() {
+                    break;
+                }
+
+                let (min, max) = self.pyramid.peak(&self.samples, start..end);
+                if min > max {
+                    continue;
+                }
+                draw_list
+                    .add_line(
+                        [x, center - max.min(1.0).max(-1.0) * half_height],
+                        [x, center - min.min(1.0).max(-1.0) * half_height + 1.0],
+                        color,
+                    )
+                    .build();
+            }
+        }
+
+        if let Some(points) = &self.loop_points {
+            let color = ui.style_color(StyleColor::PlotHistogram);
+            for sample in &[points.start, points.end] {
+                let x = map.to_x(*sample);
+                if x >= pos[0] && x <= max[0] {
+                    draw_list
+                        .add_line([x, pos[1]], [x, max[1]], color)
+                        .thickness(2.0)
+                        .build();
+                }
+            }
+        }
+
+        event
+    }
+}
+
+/// Converts between screen positions and samples for the current view.
+#[derive(Clone, Copy)]
+struct Mapping {
+    x: f32,
+    view_start: f64,
+    samples_per_pixel: f64,
+    len: usize,
+}
+
+impl Mapping {
+    fn to_sample(self, x: f32) -> f64 {
+        self.view_start + (x - self.x) as f64 * self.samples_per_pixel
+    }
+
+    fn to_x(self, sample: usize) -> f32 {
+        self.x + ((sample as f64 - self.view_start) / self.samples_per_pixel) as f32
+    }
+
+    fn clamp(self, sample: f64) -> usize {
+        sample.max(0.0).min(self.len as f64) as usize
+    }
+}
@@ -0,0 +1,215 @@
+use std::sync::Arc;
+
+use crate::handle::UiEvent;
+use crate::params::Param;
+use crate::WindowContext;
+
+/// A node of an XY pad, controlling one parameter on each axis.
+#[derive(Debug, Clone)]
+pub struct XyNode {
+    pub x: Arc<Param>,
+    pub y: Arc<Param>,
+    /// The color of the node, or the slider grab color when `None`.
+    pub color: Option<[f32; 4]>,
+    pub label: Option<String>,
+}
+
+impl XyNode {
+    pub fn new(x: Arc<Param>, y: Arc<Param>) -> Self {
+        Self {
+            x,
+            y,
+            color: None,
+            label: None,
+        }
+    }
+
+    pub fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+}
+
+/// The node of an XY pad that is being dragged.
+pub(crate) struct XyDrag {
+    label: String,
+    node: usize,
+    /// The normalized position, without snapping.
+    position: [f32; 2],
+    velocity: [f32; 2],
+}
+
+/// A pad with draggable nodes that each control two parameters.
+///
+/// Dragging a node is reported as a gesture on both of its parameters, with a `BeginEdit`
+/// and `EndEdit` around the `SetParam` events. With `grid` set the nodes snap to that many
+/// divisions, unless the fine modifier of the interaction policy is held. Returns whether a
+/// value changed.
+pub fn xy_pad(
+    ui: &imgui::Ui,
+    ctx: &mut WindowContext,
+    label: &str,
+    size: [f32; 2],
+    nodes: &[XyNode],
+    grid: Option<u32>,
+) -> bool {
+    use imgui::StyleColor;
+
+    let pos = ui.cursor_screen_pos();
+    let max = [pos[0] + size[0], pos[1] + size[1]];
+    let node_radius = ui.frame_height() * 0.35;
+
+    ui.invisible_button(label, size);
+    ctx.focus_item(ui);
+    let to_screen = |node: &XyNode| {
+        [
+            pos[0] + node.x.normalized() * size[0],
+            pos[1] + (1.0 - node.y.normalized()) * size[1],
+        ]
+    };
+
+    let mut changed = false;
+    let io = ui.io();
+
+    if ui.is_item_activated() {
+        let mouse = io.mouse_pos;
+        let nearest = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| {
+                let p = to_screen(node);
+                (i, (p[0] - mouse[0]).powi(2) + (p[1] - mouse[1]).powi(2))
+            })
+            .filter(|(_, distance)| *distance <= (node_radius * 2.0).powi(2))
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+        if let Some((i, _)) = nearest {
+            let node = &nodes[i];
+            if ctx.interaction.wants_reset(ui) {
+                super::set_param(ctx, &node.x, node.x.default_value());
+                super::set_param(ctx, &node.y, node.y.default_value());
+                changed = true;
+            } else {
+                for param in &[&node.x, &node.y] {
+                    ctx.undo.begin_param_edit(param);
+                    ctx.handle.emit(UiEvent::BeginEdit(param.id()));
+                }
+                ctx.xy_drag = Some(XyDrag {
+                    label: label.to_string(),
+                    node: i,
+                    position: [node.x.normalized(), node.y.normalized()],
+                    velocity: [0.0, 0.0],
+                });
+            }
+        }
+    }
+
+    let dragging = match &mut ctx.xy_drag {
+        Some(drag) if drag.label == label && drag.node < nodes.len() => Some(drag),
+        _ => None,
+    };
+    let active_node = dragging.as_ref().map(|drag| drag.node);
+
+    if let Some(drag) = dragging {
+        let node = &nodes[drag.node];
+        let policy = &ctx.interaction;
+
+        if ui.is_item_active() {
+            let factor = policy.factor(io);
+            let target = [
+                io.mouse_delta[0] * factor / size[0].max(1.0),
+                -io.mouse_delta[1] * factor / size[1].max(1.0),
+            ];
+            for axis in 0..2 {
+                drag.velocity[axis] = policy.smooth(drag.velocity[axis], target[axis]);
+                drag.position[axis] = (drag.position[axis] + drag.velocity[axis])
+                    .max(0.0)
+                    .min(1.0);
+            }
+
+            let snap = |value: f32| match grid {
+                Some(divisions) if divisions > 0 && factor == 1.0 => {
+                    (value * divisions as f32).round() / divisions as f32
+                }
+                _ => value,
+            };
+
+            for (axis, param) in [&node.x, &node.y].iter().enumerate() {
+                let before = param.get();
+                param.set_normalized(snap(drag.position[axis]));
+                if param.get() != before {
+                    ctx.handle.emit(UiEvent::SetParam {
+                        id: param.id(),
+                        value: param.get(),
+                    });
+                    changed = true;
+                }
+            }
+        }
+
+        if ui.is_item_deactivated() {
+            ctx.xy_drag = None;
+            for param in &[&node.x, &node.y] {
+                ctx.undo.end_param_edit(param.id());
+                ctx.handle.emit(UiEvent::EndEdit(param.id()));
+            }
+        }
+    }
+
+    let draw_list = ui.get_window_draw_list();
+    let style = ui.clone_style();
+    draw_list
+        .add_rect(pos, max, ui.style_color(StyleColor::FrameBg))
+        .filled(true)
+        .rounding(style.frame_rounding)
+        .build();
+
+    if let Some(divisions) = grid {
+        let color = ui.style_color(StyleColor::Border);
+        for i in 1..divisions {
+            let t = i as f32 / divisions as f32;
+            let x = pos[0] + t * size[0];
+            let y = pos[1] + t * size[1];
+            draw_list.add_line([x, pos[1]], [x, max[1]], color).build();
+            draw_list.add_line([pos[0], y], [max[0], y], color).build();
+        }
+    }
+
+    let text_color = ui.style_color(StyleColor::Text);
+    for (i, node) in nodes.iter().enumerate() {
+        #[cfg(feature = "remote_control")]
+        {
+            ctx.expose_param(&node.x);
+            ctx.expose_param(&node.y);
+        }
+        let center = to_screen(node);
+        let color = node
+            .color
+            .unwrap_or_else(|| ui.style_color(StyleColor::SliderGrab));
+
+        draw_list
+            .add_circle(center, node_radius, color)
+            .filled(true)
+            .build();
+        if active_node == Some(i) {
+            draw_list
+                .add_circle(center, node_radius + 2.0, text_color)
+                .thickness(2.0)
+                .build();
+        }
+        if let Some(label) = &node.label {
+            draw_list.add_text(
+                [center[0] + node_radius + 2.0, center[1] - node_radius],
+                text_color,
+                label,
+            );
+        }
+    }
+
+    changed
+}
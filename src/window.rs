@@ -20,71 +20,379 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 
+use crate::activation::Activation;
+use crate::auto_resize::AutoResizer;
+use crate::clipboard::PrimaryClipboard;
+use crate::clock::{Clock, SystemClock};
+use crate::crash::{self, CrashHandler, CrashReport};
+use crate::debug_overlay::{DebugInfo, DebugOverlay};
+use crate::dev_windows::DevWindowsState;
+use crate::focus_ring::FocusRing;
+use crate::fonts::{FontAtlas, FontAtlasSharing, FontSource};
+use crate::frame::{DrawStats, FrameWatchdog};
+use crate::handle::{Handle, UiEvent};
+use crate::help::Help;
+use crate::interaction::Interaction;
+use crate::key_codes::KeyCodes;
+use crate::key_repeat::KeyRepeater;
+use crate::layout::Breakpoints;
+use crate::locale::{Locale, TextDirection};
+use crate::midi_learn::MidiLearn;
+use crate::pan::PanContainer;
+use crate::passthrough::Passthrough;
+use crate::platform::NativeWindow;
 use crate::renderer::Renderer;
-use crate::{mouse, renderer};
-use crate::{HiDpiMode, Settings};
-use baseview::{Event, EventStatus, Window, WindowHandler, WindowScalePolicy};
+use crate::scaling::{self, LogicalPos, PlatformScaling};
+use crate::scroll::{self, Scroller};
+use crate::settings::{
+    AutoResize, DevWindows, FocusRingStyle, FrameBudget, FrameMode, Hotkey, KeyRecovery, KeyRepeat,
+    LatencyMode, PassthroughRule, ScrollContainer, ScrollPriority, SettingsError, ShortcutMatching,
+    SmoothScrolling, WindowPosition, WindowShape, WindowStyle,
+};
+use crate::shader::{self, FragmentShader};
+use crate::splash::{Splash, SplashScreen};
+use crate::text_input::TextInput;
+use crate::textures::TextureRegistry;
+use crate::{instances, mouse, pixel_snap, renderer, storage};
+use crate::{HiDpiMode, Settings, WindowContext};
+use baseview::{Event, EventStatus, Size, Window, WindowHandler, WindowScalePolicy};
 use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
 
-use std::time::Instant;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-static CONTEXT_TRY_UNLOCK_WAIT_DURATION: std::time::Duration = std::time::Duration::from_micros(10);
+/// The frames rendered after input with `FrameMode::OnDemand`.
+const INPUT_FRAMES: u32 = 2;
 
 struct OpenSettings {
     pub scale_policy: WindowScalePolicy,
+    /// Whether the window is opened with `open_parented`.
+    pub embedded: bool,
     pub logical_width: f64,
     pub logical_height: f64,
+    /// The UI scale saved in the UI state, which the window size is multiplied with.
+    pub ui_scale: f32,
     pub hidpi_mode: HiDpiMode,
+    pub pixel_snapping: bool,
+    pub restore_size: bool,
     pub clear_color: (f32, f32, f32),
+    pub window_alpha: f32,
+    pub window_style: WindowStyle,
+    pub window_shape: WindowShape,
+    pub position: Option<WindowPosition>,
+    pub fonts: Vec<FontSource>,
+    pub font_atlas: FontAtlasSharing,
+    pub instance_key: Option<String>,
+    pub debug_overlay: bool,
+    pub debug_overlay_hotkey: Option<Hotkey>,
+    pub dev_windows: DevWindows,
+    pub dev_windows_hotkey: Option<Hotkey>,
+    pub host_passthrough: Vec<PassthroughRule>,
+    pub handle: Handle,
+    pub midi_learn: bool,
+    pub x11_primary_selection: bool,
+    pub interaction: Interaction,
+    pub tooltip_delay: Duration,
+    pub background_fps: Option<f32>,
+    pub frame_mode: FrameMode,
+    pub latency_mode: LatencyMode,
+    pub frame_budget: Option<FrameBudget>,
+    pub smooth_scrolling: Option<SmoothScrolling>,
+    pub scroll_priority: ScrollPriority,
+    pub key_recovery: KeyRecovery,
+    pub key_repeat: Option<KeyRepeat>,
+    pub shortcut_matching: ShortcutMatching,
+    pub focus_ring_style: Option<FocusRingStyle>,
+    pub reduced_motion: Option<bool>,
+    pub double_click_time: Option<Duration>,
+    pub drag_threshold: Option<f32>,
+    pub auto_resize: Option<AutoResize>,
+    pub scroll_container: Option<ScrollContainer>,
+    pub breakpoints: Breakpoints,
+    pub progress_overlay: bool,
+    pub splash: Option<Splash>,
+    pub title: String,
+    pub crash_handler: Option<CrashHandler>,
+    pub locale: Locale,
+    pub text_direction: TextDirection,
+    pub ui_state: Option<String>,
+    pub shaders: Vec<FragmentShader>,
+    pub texture_budget: Option<u64>,
+    pub clock: Arc<dyn Clock>,
+    pub activation: Activation,
+    #[cfg(feature = "svg")]
+    pub icons: Option<crate::IconSet>,
+    #[cfg(feature = "event_recorder")]
+    pub event_recorder: Option<crate::recorder::EventRecorder>,
+    #[cfg(feature = "remote_control")]
+    pub remote_control: Option<crate::remote_control::RemoteControlServer>,
+    #[cfg(feature = "remote_debug")]
+    pub remote_debug: Option<crate::remote_debug::RemoteDebugServer>,
+    #[cfg(feature = "accessibility")]
+    pub accessibility_adapter: Option<crate::accessibility::AdapterFactory>,
+    pub renderer_backend: Option<renderer::BackendFactory>,
+    pub gl_context: renderer::GlContextMode,
 }
 
 impl OpenSettings {
-    fn new(settings: &Settings) -> Self {
+    fn new(settings: &Settings) -> Result<Self, OpenError> {
         // WindowScalePolicy does not implement copy/clone.
         let scale_policy = match &settings.window.scale {
             WindowScalePolicy::SystemScaleFactor => WindowScalePolicy::SystemScaleFactor,
             WindowScalePolicy::ScaleFactor(scale) => WindowScalePolicy::ScaleFactor(*scale),
         };
 
-        Self {
+        let ui_state = settings.ui_state.as_deref();
+        let ui_scale = ui_state
+            .and_then(|ui_state| storage::load_id(ui_state, storage::UI_SCALE_ID))
+            .unwrap_or(1.0);
+
+        // The saved size is without the UI scale, like `window.size`.
+        let mut size = [settings.window.size.width, settings.window.size.height];
+        if settings.restore_size {
+            if let Some(saved) = ui_state.and_then(|ui_state| {
+                storage::load_id::<[f32; 2]>(ui_state, storage::WINDOW_SIZE_ID)
+            }) {
+                size = [saved[0] as f64, saved[1] as f64];
+            }
+        }
+        let size = settings
+            .handle
+            .size_limits()
+            .clamp([size[0] * ui_scale as f64, size[1] * ui_scale as f64]);
+
+        Ok(Self {
             scale_policy,
-            logical_width: settings.window.size.width as f64,
-            logical_height: settings.window.size.height as f64,
+            embedded: false,
+            logical_width: size[0],
+            logical_height: size[1],
+            ui_scale,
             hidpi_mode: settings.hidpi_mode,
+            pixel_snapping: settings.pixel_snapping,
+            restore_size: settings.restore_size,
             clear_color: settings.clear_color,
+            window_alpha: settings.window_alpha,
+            window_style: settings.window_style,
+            window_shape: settings.window_shape,
+            position: settings.position,
+            fonts: settings.fonts.clone(),
+            font_atlas: settings.font_atlas,
+            instance_key: settings.instance_key.clone(),
+            debug_overlay: settings.debug_overlay,
+            debug_overlay_hotkey: settings.debug_overlay_hotkey,
+            dev_windows: settings.dev_windows,
+            dev_windows_hotkey: settings.dev_windows_hotkey,
+            host_passthrough: settings.host_passthrough.clone(),
+            handle: settings.handle.clone(),
+            midi_learn: settings.midi_learn,
+            x11_primary_selection: settings.x11_primary_selection,
+            interaction: settings.interaction.clone(),
+            tooltip_delay: settings.tooltip_delay,
+            background_fps: settings.background_fps,
+            frame_mode: settings.frame_mode,
+            latency_mode: settings.latency_mode,
+            frame_budget: settings.frame_budget,
+            smooth_scrolling: settings.smooth_scrolling,
+            scroll_priority: settings.scroll_priority,
+            key_recovery: settings.key_recovery,
+            key_repeat: settings.key_repeat,
+            shortcut_matching: settings.shortcut_matching,
+            focus_ring_style: settings.focus_ring_style,
+            reduced_motion: settings.reduced_motion,
+            double_click_time: settings.double_click_time,
+            drag_threshold: settings.drag_threshold,
+            auto_resize: settings.auto_resize,
+            scroll_container: settings.scroll_container,
+            breakpoints: settings.breakpoints,
+            progress_overlay: settings.progress_overlay,
+            splash: settings.splash.clone(),
+            title: settings.window.title.clone(),
+            crash_handler: settings.crash_handler.clone(),
+            locale: settings.locale.clone(),
+            text_direction: settings.text_direction,
+            ui_state: settings.ui_state.clone(),
+            shaders: settings.shaders.clone(),
+            texture_budget: settings.texture_budget,
+            clock: settings
+                .clock
+                .clone()
+                .unwrap_or_else(|| Arc::new(SystemClock::new())),
+            activation: Activation::new(
+                settings.window.title.clone(),
+                settings.context_policy,
+                settings.context_timeout,
+                settings.on_context_timeout.clone(),
+            ),
+            #[cfg(feature = "svg")]
+            icons: settings.icons.clone(),
+            #[cfg(feature = "event_recorder")]
+            event_recorder: settings.event_recorder.clone(),
+            #[cfg(feature = "remote_control")]
+            remote_control: settings
+                .remote_control
+                .as_ref()
+                .map(|remote_control| {
+                    crate::remote_control::RemoteControlServer::start(
+                        remote_control,
+                        settings.handle.clone(),
+                    )
+                })
+                .transpose()
+                .map_err(OpenError::RemoteControl)?,
+            #[cfg(feature = "remote_debug")]
+            remote_debug: settings
+                .remote_debug
+                .as_ref()
+                .map(|remote_debug| {
+                    crate::remote_debug::RemoteDebugServer::start(
+                        remote_debug,
+                        settings.window.title.clone(),
+                    )
+                })
+                .transpose()
+                .map_err(OpenError::RemoteDebug)?,
+            #[cfg(feature = "accessibility")]
+            accessibility_adapter: settings.accessibility_adapter,
+            renderer_backend: settings.renderer_backend,
+            gl_context: settings.gl_context,
+        })
+    }
+}
+
+/// Why a window could not be opened.
+#[derive(Debug)]
+pub enum OpenError {
+    /// `Settings::validate` rejected the settings.
+    Settings(SettingsError),
+    /// The server of `Settings::remote_control` couldn't listen on its address.
+    #[cfg(feature = "remote_control")]
+    RemoteControl(std::io::Error),
+    /// The server of `Settings::remote_debug` couldn't listen on its address.
+    #[cfg(feature = "remote_debug")]
+    RemoteDebug(std::io::Error),
+}
+
+impl std::fmt::Display for OpenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OpenError::Settings(error) => write!(f, "invalid settings: {}", error),
+            #[cfg(feature = "remote_control")]
+            OpenError::RemoteControl(error) => {
+                write!(f, "the remote control server could not start: {}", error)
+            }
+            #[cfg(feature = "remote_debug")]
+            OpenError::RemoteDebug(error) => {
+                write!(f, "the remote debug server could not start: {}", error)
+            }
         }
     }
 }
 
+impl std::error::Error for OpenError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            OpenError::Settings(error) => Some(error),
+            #[cfg(feature = "remote_control")]
+            OpenError::RemoteControl(error) => Some(error),
+            #[cfg(feature = "remote_debug")]
+            OpenError::RemoteDebug(error) => Some(error),
+        }
+    }
+}
+
+impl From<SettingsError> for OpenError {
+    fn from(error: SettingsError) -> Self {
+        OpenError::Settings(error)
+    }
+}
+
 /// Handles an imgui-baseview application
 #[allow(missing_debug_implementations)]
 pub struct ImguiWindow<State, U>
 where
     State: 'static + Send,
-    U: FnMut(&mut bool, &imgui::Ui, &mut State),
+    U: FnMut(&mut bool, &imgui::Ui, &mut WindowContext, &mut State),
     U: 'static + Send,
 {
     user_state: State,
     user_update: U,
 
     sus_context: Option<imgui::SuspendedContext>,
-    renderer: Renderer,
-    last_frame: Instant,
+    activation: Arc<Activation>,
+    clock: Arc<dyn Clock>,
+    last_frame: Duration,
     clear_color: (f32, f32, f32),
     scale_policy: WindowScalePolicy,
     scale_factor: f64,
 
     hidpi_mode: HiDpiMode,
+    pixel_snapping: bool,
+    restore_size: bool,
+    /// Whether the host asks for the frames, with `GlContextMode::External`.
+    external_frames: bool,
     hidpi_factor: f64,
     cursor_cache: Option<mouse::CursorSettings>,
     mouse_buttons: [mouse::Button; 5],
     run: bool,
+    /// Whether the window has no area, because it is minimized or the host resized it to
+    /// nothing. Frames are skipped until it has one again.
+    minimized: bool,
+    /// The display size of the last resize imgui wasn't told about yet, with the hidpi
+    /// factor its mouse position is at.
+    resized: Option<([f32; 2], f64)>,
+    /// Key events that arrived while another window held the context for too long.
+    skipped_keys: Vec<keyboard_types::KeyboardEvent>,
+    background_fps: Option<f32>,
+    frame_mode: FrameMode,
+    /// Whether the context is created again before the next frame, because the window
+    /// degraded with multisampling.
+    recreate_context: bool,
+    /// The frames still to render after input with `FrameMode::OnDemand`, so hover and
+    /// active states settle.
+    input_frames: u32,
+    /// Whether the window has the keyboard focus, and whether the mouse is over it. Windows
+    /// that are neither render at `background_fps`.
+    focused: bool,
+    hovered: bool,
+    scroller: Scroller,
+    scroll_priority: ScrollPriority,
+    key_recovery: KeyRecovery,
+    key_repeater: Option<KeyRepeater>,
+    key_codes: KeyCodes,
+    /// When the last key event arrived, by the clock of the window.
+    last_key_event: Duration,
+    /// Whether the keys are released before the next frame.
+    release_keys: bool,
+    /// Whether the window closed and the renderer was destroyed, so nothing is rendered.
+    destroyed: bool,
+    auto_resizer: Option<AutoResizer>,
+    pan: Option<PanContainer>,
+    progress_overlay: bool,
+    splash: Option<SplashScreen>,
+    title: String,
+    crash_handler: Option<CrashHandler>,
+    /// Whether the update closure panicked, so it isn't called again.
+    crashed: bool,
+    last_stats: Option<DrawStats>,
+    window_context: WindowContext,
+    debug_overlay: DebugOverlay,
+    dev_windows: DevWindowsState,
+    text_input: TextInput,
+    passthrough: Passthrough,
+    primary_clipboard: Option<PrimaryClipboard>,
+
+    #[cfg(feature = "svg")]
+    icons: Option<crate::IconSet>,
+    #[cfg(feature = "event_recorder")]
+    recorder: Option<crate::recorder::RecorderState>,
+    #[cfg(feature = "remote_debug")]
+    remote_debug: Option<crate::remote_debug::RemoteDebugServer>,
 }
 
 impl<State, U> ImguiWindow<State, U>
 where
     State: 'static + Send,
-    U: FnMut(&mut bool, &imgui::Ui, &mut State),
+    U: FnMut(&mut bool, &imgui::Ui, &mut WindowContext, &mut State),
     U: 'static + Send,
 {
     fn new<B>(
@@ -93,32 +401,89 @@ where
         mut render_settings: Option<renderer::RenderSettings>,
         build: B,
         update: U,
+        state: State,
+    ) -> ImguiWindow<State, U>
+    where
+        B: Fn(&mut imgui::Context, &mut State),
+        B: 'static + Send,
+    {
+        let handle = window.raw_window_handle();
+        Self::with_renderer(
+            handle,
+            open_settings,
+            build,
+            update,
+            state,
+            |context, open_settings, window_alpha, window_shape| match open_settings
+                .renderer_backend
+            {
+                Some(factory) => Renderer::custom(window, context, factory),
+                None => Renderer::open_gl(
+                    window,
+                    context,
+                    render_settings.take().unwrap(),
+                    open_settings.gl_context,
+                    &open_settings.shaders,
+                    window_alpha,
+                    window_shape,
+                ),
+            },
+        )
+    }
+
+    /// Set up the window at `handle`, drawn with the renderer `create_renderer` makes with the
+    /// imgui context, the settings and the alpha and shape of the window.
+    fn with_renderer<B, R>(
+        handle: RawWindowHandle,
+        mut open_settings: OpenSettings,
+        build: B,
+        update: U,
         mut state: State,
+        create_renderer: R,
     ) -> ImguiWindow<State, U>
     where
         B: Fn(&mut imgui::Context, &mut State),
         B: 'static + Send,
+        R: FnOnce(&mut imgui::Context, &OpenSettings, f32, WindowShape) -> Renderer,
     {
         use imgui::{BackendFlags, Key};
         use keyboard_types::Code;
 
-        let mut sus_context = imgui::SuspendedContext::create();
+        // Assume scale for now until there is an event with a new one.
+        let scale = match open_settings.scale_policy {
+            WindowScalePolicy::ScaleFactor(scale) => scale,
+            WindowScalePolicy::SystemScaleFactor => 1.0,
+        };
+        let hidpi_factor = open_settings.hidpi_mode.apply(scale) * open_settings.ui_scale as f64;
+
+        let mut font_atlas = FontAtlas::new(
+            std::mem::take(&mut open_settings.fonts),
+            open_settings.font_atlas,
+            hidpi_factor,
+        );
+        for translation in open_settings.locale.translations() {
+            font_atlas.ensure_glyphs(translation);
+        }
+        let mut sus_context = font_atlas.create_context();
 
-        let mut scale: f64 = 0.0;
-        let mut hidpi_factor: f64 = 0.0;
         let mut renderer: Option<Renderer> = None;
+        let mut primary_clipboard = None;
+        // Embedded windows are drawn over by the host, so they stay opaque and rectangular.
+        let (window_alpha, window_shape) = if open_settings.embedded {
+            (1.0, WindowShape::Rectangle)
+        } else {
+            (open_settings.window_alpha, open_settings.window_shape)
+        };
 
-        sus_context = use_context(sus_context, |mut context| {
+        // The window can't open without the context, so it keeps waiting.
+        sus_context = open_settings.activation.wait(sus_context, |mut context| {
             context.set_ini_filename(None);
+            if let Some(ui_state) = &open_settings.ui_state {
+                context.load_ini_settings(ui_state);
+            }
 
             let io = context.io_mut();
 
-            // Assume scale for now until there is an event with a new one.
-            scale = match open_settings.scale_policy {
-                WindowScalePolicy::ScaleFactor(scale) => scale,
-                WindowScalePolicy::SystemScaleFactor => 1.0,
-            };
-            hidpi_factor = open_settings.hidpi_mode.apply(scale);
             let logical_size = [
                 (open_settings.logical_width * scale / hidpi_factor) as f32,
                 (open_settings.logical_height * scale / hidpi_factor) as f32,
@@ -130,7 +495,7 @@ where
             io.backend_flags.insert(BackendFlags::HAS_SET_MOUSE_POS);
             io[Key::Tab] = Code::Tab as _;
             io[Key::LeftArrow] = Code::ArrowLeft as _;
-            io[Key::RightArrow] = Code::ArrowLeft as _;
+            io[Key::RightArrow] = Code::ArrowRight as _;
             io[Key::UpArrow] = Code::ArrowUp as _;
             io[Key::DownArrow] = Code::ArrowDown as _;
             io[Key::PageUp] = Code::PageUp as _;
@@ -150,6 +515,33 @@ where
             io[Key::X] = Code::KeyX as _;
             io[Key::Y] = Code::KeyY as _;
             io[Key::Z] = Code::KeyZ as _;
+            if let Some(key_repeat) = open_settings.key_repeat {
+                io.key_repeat_delay = key_repeat.delay.as_secs_f32();
+                io.key_repeat_rate = key_repeat.interval.as_secs_f32();
+            }
+            let double_click_time = open_settings
+                .double_click_time
+                .or_else(crate::platform::double_click_time);
+            if let Some(time) = double_click_time {
+                io.mouse_double_click_time = time.as_secs_f32();
+            }
+            let drag_threshold = open_settings.drag_threshold.or_else(|| {
+                crate::platform::drag_threshold().map(|pixels| pixels / hidpi_factor as f32)
+            });
+            if let Some(threshold) = drag_threshold {
+                io.mouse_drag_threshold = threshold;
+            }
+            if open_settings.focus_ring_style.is_some() {
+                io.config_flags
+                    .insert(imgui::ConfigFlags::NAV_ENABLE_KEYBOARD);
+            }
+
+            if open_settings.x11_primary_selection {
+                primary_clipboard = PrimaryClipboard::install(&mut context);
+            }
+            open_settings.text_direction.apply(context.style_mut());
+
+            font_atlas.build(&mut context, hidpi_factor);
 
             (build)(&mut context, &mut state);
 
@@ -157,33 +549,158 @@ where
                 "imgui-baseview {}",
                 env!("CARGO_PKG_VERSION")
             )));
-            context.set_renderer_name(Some(Renderer::name()));
-
-            renderer = Some(Renderer::new(
-                window,
-                &mut context,
-                render_settings.take().unwrap(),
-            ));
+            let new_renderer =
+                create_renderer(&mut context, &open_settings, window_alpha, window_shape);
+            context.set_renderer_name(Some(new_renderer.name()));
+            renderer = Some(new_renderer);
 
             context.suspend()
         });
 
+        // Creating the renderer uploaded the font atlas.
+        font_atlas.mark_uploaded();
+
+        let mut renderer = renderer.unwrap();
+        renderer.set_latency_mode(open_settings.latency_mode);
+
+        #[cfg(feature = "svg")]
+        if let Some(icons) = &open_settings.icons {
+            icons.update(&mut renderer, hidpi_factor);
+        }
+
+        let mut window_context = WindowContext::new(
+            renderer,
+            NativeWindow::new(handle, open_settings.embedded),
+            font_atlas,
+            open_settings.handle,
+        );
+        window_context.instance = open_settings
+            .instance_key
+            .map(|key| instances::register(key, open_settings.background_fps));
+        if open_settings.midi_learn {
+            window_context.midi_learn = Some(MidiLearn::default());
+        }
+        window_context.interaction = open_settings.interaction;
+        window_context.help = Help::new(open_settings.tooltip_delay);
+        window_context.locale = open_settings.locale;
+        window_context.text_direction = open_settings.text_direction;
+        window_context.window_style = open_settings.window_style;
+
+        if let Some(ui_state) = &open_settings.ui_state {
+            window_context.storage.load(ui_state);
+        }
+        window_context.ui_scale = open_settings.ui_scale;
+        window_context.frame_watchdog = FrameWatchdog::new(open_settings.frame_budget);
+        window_context.textures = TextureRegistry::new(open_settings.texture_budget);
+        window_context.focus_ring = open_settings.focus_ring_style.map(FocusRing::new);
+        window_context.breakpoints = open_settings.breakpoints;
+        window_context.reduced_motion = open_settings
+            .reduced_motion
+            .or_else(crate::platform::prefers_reduced_motion)
+            .unwrap_or(false);
+        #[cfg(feature = "remote_control")]
+        {
+            window_context.remote_control = open_settings.remote_control;
+        }
+
+        let splash = open_settings
+            .splash
+            .map(|splash| SplashScreen::new(splash, &mut window_context));
+
+        if window_alpha < 1.0 || window_shape != WindowShape::Rectangle {
+            window_context.native_window.set_transparent();
+        }
+        if open_settings.window_style != WindowStyle::default() {
+            window_context
+                .native_window
+                .set_style(open_settings.window_style);
+        }
+        if let Some(position) = open_settings.position {
+            window_context.native_window.set_position(position);
+        }
+
+        #[cfg(feature = "accessibility")]
+        if let Some(factory) = open_settings.accessibility_adapter {
+            window_context.accessibility =
+                Some(crate::accessibility::Accessibility::new(handle, factory));
+        }
+
         Self {
             user_state: state,
             user_update: update,
 
             sus_context: Some(sus_context),
-            renderer: renderer.unwrap(),
-            last_frame: Instant::now(),
+            activation: Arc::new(open_settings.activation),
+            last_frame: open_settings.clock.now(),
+            clock: open_settings.clock,
             clear_color: open_settings.clear_color,
             scale_policy: open_settings.scale_policy,
             scale_factor: scale,
 
             hidpi_mode: open_settings.hidpi_mode,
+            pixel_snapping: open_settings.pixel_snapping,
+            restore_size: open_settings.restore_size,
+            external_frames: open_settings.renderer_backend.is_none()
+                && matches!(
+                    open_settings.gl_context,
+                    renderer::GlContextMode::External { .. }
+                ),
             hidpi_factor,
             cursor_cache: None,
             mouse_buttons: [mouse::Button::INIT; 5],
             run: true,
+            minimized: open_settings.logical_width <= 0.0 || open_settings.logical_height <= 0.0,
+            resized: None,
+            skipped_keys: Vec::new(),
+            background_fps: open_settings.background_fps,
+            frame_mode: open_settings.frame_mode,
+            recreate_context: false,
+            input_frames: INPUT_FRAMES,
+            focused: true,
+            hovered: false,
+            scroller: Scroller::new(
+                open_settings
+                    .smooth_scrolling
+                    .filter(|_| !window_context.reduced_motion),
+            ),
+            scroll_priority: open_settings.scroll_priority,
+            key_recovery: open_settings.key_recovery,
+            key_repeater: open_settings.key_repeat.map(KeyRepeater::new),
+            key_codes: KeyCodes::new(open_settings.shortcut_matching),
+            last_key_event: Duration::default(),
+            release_keys: false,
+            destroyed: false,
+            auto_resizer: open_settings.auto_resize.map(AutoResizer::new),
+            pan: open_settings
+                .scroll_container
+                .map(|settings| PanContainer::new(settings, &window_context.storage)),
+            progress_overlay: open_settings.progress_overlay,
+            splash,
+            title: open_settings.title,
+            crash_handler: open_settings.crash_handler,
+            crashed: false,
+            last_stats: None,
+            window_context,
+            debug_overlay: DebugOverlay::new(
+                open_settings.debug_overlay,
+                open_settings.debug_overlay_hotkey,
+            ),
+            dev_windows: DevWindowsState::new(
+                open_settings.dev_windows,
+                open_settings.dev_windows_hotkey,
+            ),
+            text_input: TextInput::default(),
+            passthrough: Passthrough::new(open_settings.host_passthrough),
+            primary_clipboard,
+
+            #[cfg(feature = "svg")]
+            icons: open_settings.icons,
+            #[cfg(feature = "event_recorder")]
+            recorder: open_settings
+                .event_recorder
+                .map(crate::recorder::RecorderState::new),
+            #[cfg(feature = "remote_debug")]
+            remote_debug: open_settings.remote_debug,
         }
     }
 
@@ -195,14 +712,27 @@ where
     /// * `build` - Called once in the constructor. This can be used to make any additional
     /// configurations to the `imgui::Context` struct.
     /// * `update` - Called before each frame. Here you should update the state of your
-    /// application and build the UI.
-    pub fn open_parented<P, B>(parent: &P, settings: Settings, state: State, build: B, update: U)
+    /// application and build the UI. The `WindowContext` gives access to the window.
+    ///
+    /// Fails without opening the window if the settings don't pass `Settings::validate`, or a
+    /// server they ask for can't start.
+    pub fn open_parented<P, B>(
+        parent: &P,
+        settings: Settings,
+        state: State,
+        build: B,
+        update: U,
+    ) -> Result<(), OpenError>
     where
         P: HasRawWindowHandle,
         B: Fn(&mut imgui::Context, &mut State),
         B: 'static + Send,
     {
-        let open_settings = OpenSettings::new(&settings);
+        settings.validate()?;
+        let mut settings = settings;
+        let mut open_settings = OpenSettings::new(&settings)?;
+        open_settings.embedded = true;
+        settings.window.size = Size::new(open_settings.logical_width, open_settings.logical_height);
         let render_settings = Some(settings.render_settings);
 
         Window::open_parented(
@@ -211,7 +741,8 @@ where
             move |window: &mut baseview::Window<'_>| -> ImguiWindow<State, U> {
                 ImguiWindow::new(window, open_settings, render_settings, build, update, state)
             },
-        )
+        );
+        Ok(())
     }
 
     /// Open a new window as if it had a parent window.
@@ -221,50 +752,108 @@ where
     /// * `build` - Called once in the constructor. This can be used to make any additional
     /// configurations to the `imgui::Context` struct.
     /// * `update` - Called before each frame. Here you should update the state of your
-    /// application and build the UI.
+    /// application and build the UI. The `WindowContext` gives access to the window.
+    ///
+    /// Fails without opening the window if the settings don't pass `Settings::validate`, or a
+    /// server they ask for can't start.
     pub fn open_as_if_parented<B>(
         settings: Settings,
         state: State,
         build: B,
         update: U,
-    ) -> RawWindowHandle
+    ) -> Result<RawWindowHandle, OpenError>
     where
         B: Fn(&mut imgui::Context, &mut State),
         B: 'static + Send,
     {
-        let open_settings = OpenSettings::new(&settings);
+        settings.validate()?;
+        let mut settings = settings;
+        let open_settings = OpenSettings::new(&settings)?;
+        settings.window.size = Size::new(open_settings.logical_width, open_settings.logical_height);
         let render_settings = Some(settings.render_settings);
 
-        Window::open_as_if_parented(
+        Ok(Window::open_as_if_parented(
             settings.window,
             move |window: &mut baseview::Window<'_>| -> ImguiWindow<State, U> {
                 ImguiWindow::new(window, open_settings, render_settings, build, update, state)
             },
-        )
+        ))
     }
 
     /// Open a new window that blocks the current thread until the window is destroyed.
     ///
+    /// Returns the state as the window left it, so a standalone tool can save the edits that
+    /// were made in it. `None` if the window never opened. Fails without opening the window if
+    /// the settings don't pass `Settings::validate`, or a server they ask for can't start.
+    ///
     /// * `settings` - The settings of the window.
     /// * `state` - The initial state of your application.
     /// * `build` - Called once in the constructor. This can be used to make any additional
     /// configurations to the `imgui::Context` struct.
     /// * `update` - Called before each frame. Here you should update the state of your
-    /// application and build the UI.
-    pub fn open_blocking<B>(settings: Settings, state: State, build: B, update: U)
+    /// application and build the UI. The `WindowContext` gives access to the window.
+    pub fn open_blocking<B>(
+        settings: Settings,
+        state: State,
+        build: B,
+        update: U,
+    ) -> Result<Option<State>, OpenError>
     where
         B: Fn(&mut imgui::Context, &mut State),
         B: 'static + Send,
     {
-        let open_settings = OpenSettings::new(&settings);
+        settings.validate()?;
+        let mut settings = settings;
+        let open_settings = OpenSettings::new(&settings)?;
+        settings.window.size = Size::new(open_settings.logical_width, open_settings.logical_height);
         let render_settings = Some(settings.render_settings);
 
+        let exit_state = Arc::new(Mutex::new(None));
+        let window_exit_state = exit_state.clone();
         Window::open_blocking(
             settings.window,
-            move |window: &mut baseview::Window<'_>| -> ImguiWindow<State, U> {
-                ImguiWindow::new(window, open_settings, render_settings, build, update, state)
+            move |window: &mut baseview::Window<'_>| -> BlockingWindow<State, U> {
+                BlockingWindow {
+                    window: Some(ImguiWindow::new(
+                        window,
+                        open_settings,
+                        render_settings,
+                        build,
+                        update,
+                        state,
+                    )),
+                    exit_state: window_exit_state,
+                }
             },
-        )
+        );
+
+        Ok(exit_state.lock().unwrap().take())
+    }
+
+    /// The report of a panic of the update closure, for `Settings::crash_handler`.
+    fn crash_report(&self, panic: crash::Panic, frames: u64) -> CrashReport {
+        CrashReport {
+            message: panic.message,
+            location: panic.location,
+            backtrace: panic.backtrace,
+            window: self.title.clone(),
+            last_frame: self.last_stats,
+            frames,
+            scale_factor: self.scale_factor,
+            hidpi_mode: self.hidpi_mode,
+            hidpi_factor: self.hidpi_factor,
+            ui_scale: self.window_context.ui_scale,
+            renderer: self.window_context.renderer.name(),
+            adapter: self.window_context.renderer.adapter_info(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+
+    /// The factors between baseview, imgui and physical pixels with the current DPI mode.
+    pub fn scaling(&self) -> PlatformScaling {
+        PlatformScaling::new(self.scale_factor, self.hidpi_factor)
     }
 
     /// Scales a logical position from baseview using the current DPI mode.
@@ -272,12 +861,8 @@ where
     /// This utility function is useful if you are using a DPI mode other than default, and want
     /// your application to use the same logical coordinates as imgui-rs.
     pub fn scale_pos_from_baseview(&self, logical_pos: baseview::Point) -> baseview::Point {
-        scale_pos_from_baseview(
-            logical_pos,
-            self.scale_factor,
-            self.hidpi_mode,
-            self.hidpi_factor,
-        )
+        let [x, y] = self.scaling().logical_to_imgui(logical_pos.into());
+        baseview::Point::new(x as f64, y as f64)
     }
 
     /// Scales a logical position for baseview using the current DPI mode.
@@ -285,51 +870,730 @@ where
     /// This utility function is useful if you are using a DPI mode other than default, and want
     /// your application to use the same logical coordinates as imgui-rs.
     pub fn scale_pos_for_baseview(&self, logical_pos: baseview::Point) -> baseview::Point {
-        scale_pos_for_baseview(
-            logical_pos,
-            self.scale_factor,
-            self.hidpi_mode,
-            self.hidpi_factor,
-        )
+        let pos = [logical_pos.x as f32, logical_pos.y as f32];
+        self.scaling().imgui_to_logical(pos).into()
+    }
+
+    /// Zoom the UI to the scale the update closure set in the last frame. The display size
+    /// keeps the layout while the window is resized to fit it.
+    fn apply_ui_scale(&mut self, io: &mut imgui::Io) {
+        let scale = match self.window_context.ui_scale_request.take() {
+            Some(scale) if scale != self.window_context.ui_scale => scale,
+            _ => return,
+        };
+        let ratio = scale / self.window_context.ui_scale;
+        self.window_context.ui_scale = scale;
+
+        let old_hidpi_factor = self.hidpi_factor;
+        self.hidpi_factor = self.hidpi_mode.apply(self.scale_factor) * scale as f64;
+        self.window_context.fonts.set_dirty();
+
+        if io.mouse_pos[0].is_finite() && io.mouse_pos[1].is_finite() {
+            let factor = (old_hidpi_factor / self.hidpi_factor) as f32;
+            io.mouse_pos = [io.mouse_pos[0] * factor, io.mouse_pos[1] * factor];
+        }
+
+        // The layout keeps its logical size, in a window that grows with the scale.
+        let physical_size = [
+            (io.display_size[0] as f64 * self.hidpi_factor).round(),
+            (io.display_size[1] as f64 * self.hidpi_factor).round(),
+        ];
+        // Until the window is resized, the old physical size is shown at the new scale.
+        io.display_size = [io.display_size[0] / ratio, io.display_size[1] / ratio];
+        io.display_framebuffer_scale = [self.hidpi_factor as f32, self.hidpi_factor as f32];
+
+        self.request_size(physical_size);
+    }
+
+    /// Resize a standalone window to `physical_size`, or ask the host to resize an embedded
+    /// one.
+    fn request_size(&mut self, physical_size: [f64; 2]) {
+        if self.window_context.native_window.is_embedded() {
+            self.window_context.handle.emit(UiEvent::RequestResize {
+                width: physical_size[0] / self.scale_factor,
+                height: physical_size[1] / self.scale_factor,
+            });
+        } else {
+            self.window_context
+                .native_window
+                .set_size([physical_size[0] as u32, physical_size[1] as u32]);
+        }
+    }
+
+    /// Resize the window to the content of the frame that just ended for
+    /// `Settings::auto_resize`, within the limits of the host.
+    fn apply_auto_resize(&mut self, display_size: [f32; 2]) {
+        let content = self.window_context.content_size.take();
+        let resizer = match &mut self.auto_resizer {
+            Some(resizer) => resizer,
+            None => return,
+        };
+        let size = resizer.update(content, display_size, self.clock.now());
+        if resizer.is_settling() {
+            self.window_context.handle.request_frame();
+        }
+
+        if let Some(size) = size {
+            let limits = self.window_context.handle.size_limits();
+            let logical = limits.clamp([
+                size[0] as f64 * self.hidpi_factor / self.scale_factor,
+                size[1] as f64 * self.hidpi_factor / self.scale_factor,
+            ]);
+            self.request_size([
+                (logical[0] * self.scale_factor).round(),
+                (logical[1] * self.scale_factor).round(),
+            ]);
+        }
+    }
+
+    /// Keep the size the window was resized to for `Settings::restore_size`, within the
+    /// limits of the host and without the UI scale.
+    fn save_size(&mut self, physical_size: baseview::PhySize) {
+        let size = self.window_context.handle.size_limits().clamp([
+            physical_size.width as f64 / self.scale_factor,
+            physical_size.height as f64 / self.scale_factor,
+        ]);
+        let ui_scale = self.window_context.ui_scale as f64;
+        let size = [(size[0] / ui_scale) as f32, (size[1] / ui_scale) as f32];
+        self.window_context
+            .storage
+            .set_id(storage::WINDOW_SIZE_ID, &size);
+    }
+
+    /// Take what the host and the other instances sent, without building a frame.
+    fn handle_messages(&mut self) {
+        let instance = &self.window_context.instance;
+        self.sus_context = Some(use_context(
+            &self.activation,
+            self.sus_context.take().unwrap(),
+            |mut context| {
+                if let Some(instance) = instance {
+                    instance.run_commands(&mut context);
+                }
+                context.suspend()
+            },
+        ));
+
+        #[cfg(feature = "webview")]
+        self.window_context
+            .web_views
+            .deliver_messages(&self.window_context.handle);
+    }
+
+    /// Replace a lost OpenGL context, then let the user restore what was in it.
+    fn restore_context(&mut self, window: &Window) {
+        let renderer = &mut self.window_context.renderer;
+        let mut texture_remap = None;
+        self.sus_context = Some(use_context(
+            &self.activation,
+            self.sus_context.take().unwrap(),
+            |mut context| {
+                texture_remap = Some(renderer.recreate(window, &mut context));
+                context.suspend()
+            },
+        ));
+
+        // Without the imgui context it is tried again next frame.
+        match texture_remap {
+            Some(texture_remap) => self.window_context.restore(texture_remap),
+            None => return,
+        }
+
+        #[cfg(feature = "svg")]
+        if let Some(icons) = &self.icons {
+            icons.restore(&self.window_context);
+        }
+
+        if let Some(mut callback) = self.window_context.context_restored.take() {
+            callback(&mut self.user_state, &mut self.window_context);
+            if self.window_context.context_restored.is_none() {
+                self.window_context.context_restored = Some(callback);
+            }
+        }
+    }
+
+    /// Run `Settings::on_destroy` and free the GPU resources of the renderer while the window
+    /// still has its context, then stop handling events and frames.
+    fn close(&mut self) {
+        let user_state = &mut self.user_state;
+        let window_context = &mut self.window_context;
+        self.sus_context = tear_down(&self.activation, self.sus_context.take(), || {
+            if let Some(callback) = window_context.on_destroy.take() {
+                callback(user_state, window_context);
+            }
+            window_context.renderer.destroy();
+        });
+        self.destroyed = true;
+    }
+
+    /// Take the new size and scale of the window. What imgui has to know waits in `resized`
+    /// for the next time the window has the context.
+    fn resize(&mut self, window_info: &baseview::WindowInfo) {
+        // The last size is kept while there is nothing to draw.
+        let physical_size = window_info.physical_size();
+        self.minimized = has_no_area(physical_size);
+        if self.minimized {
+            return;
+        }
+
+        self.window_context
+            .renderer
+            .resize([physical_size.width, physical_size.height]);
+
+        self.scale_factor = match self.scale_policy {
+            WindowScalePolicy::ScaleFactor(scale) => scale,
+            WindowScalePolicy::SystemScaleFactor => {
+                scaling::usable_scale(window_info.scale(), self.scale_factor)
+            }
+        };
+
+        let new_hidpi_factor =
+            self.hidpi_mode.apply(self.scale_factor) * self.window_context.ui_scale as f64;
+        if (new_hidpi_factor - self.hidpi_factor).abs() > f64::EPSILON {
+            self.window_context.fonts.set_dirty();
+        }
+
+        // The mouse position of imgui is still at the factor of the last resize it saw.
+        let mouse_hidpi_factor = self
+            .resized
+            .map_or(self.hidpi_factor, |(_, hidpi_factor)| hidpi_factor);
+        self.hidpi_factor = new_hidpi_factor;
+
+        let logical_size = [
+            (physical_size.width as f64 / self.hidpi_factor) as f32,
+            (physical_size.height as f64 / self.hidpi_factor) as f32,
+        ];
+        self.resized = Some((logical_size, mouse_hidpi_factor));
+
+        if self.restore_size {
+            self.save_size(physical_size);
+        }
+    }
+
+    /// Tell imgui what happened while the window didn't have the context: the last resize,
+    /// and the key events that were skipped.
+    fn catch_up(&mut self, io: &mut imgui::Io) {
+        if let Some((display_size, mouse_hidpi_factor)) = self.resized.take() {
+            if io.mouse_pos[0].is_finite() && io.mouse_pos[1].is_finite() {
+                let factor = (self.hidpi_factor / mouse_hidpi_factor) as f32;
+                io.mouse_pos = [io.mouse_pos[0] * factor, io.mouse_pos[1] * factor];
+            }
+            io.display_framebuffer_scale = [self.hidpi_factor as f32, self.hidpi_factor as f32];
+            io.display_size = display_size;
+        }
+
+        for event in std::mem::take(&mut self.skipped_keys) {
+            self.key_event(io, &event);
+        }
+    }
+
+    fn key_event(&mut self, io: &mut imgui::Io, event: &keyboard_types::KeyboardEvent) {
+        use keyboard_types::Code;
+
+        // Keys that trigger a shortcut don't reach imgui.
+        let code = self.key_codes.shortcut_code(event);
+        if self
+            .window_context
+            .shortcuts
+            .dispatch(event, code, io.want_text_input)
+        {
+            return;
+        }
+
+        let pressed = event.state == keyboard_types::KeyState::Down;
+
+        io.keys_down[self.key_codes.imgui_code(event) as usize] = pressed;
+
+        // This is a bit redundant here, but we'll leave it in. The OS occasionally
+        // fails to send modifiers keys, but it doesn't seem to send false-positives,
+        // so double checking isn't terrible in case some system *doesn't* send
+        // device events sometimes.
+        match event.code {
+            Code::ShiftLeft | Code::ShiftRight => io.key_shift = pressed,
+            Code::ControlLeft | Code::ControlRight => io.key_ctrl = pressed,
+            Code::AltLeft | Code::AltRight => io.key_alt = pressed,
+            Code::MetaLeft | Code::MetaRight => io.key_super = pressed,
+            _ => (),
+        }
+
+        if pressed {
+            let window = &self.window_context.native_window;
+            for chr in self.text_input.characters(event, window) {
+                io.add_input_character(chr)
+            }
+        }
+    }
+
+    fn handle_event(&mut self, event: Event) -> EventStatus {
+        if let Event::Window(baseview::WindowEvent::WillClose) = &event {
+            self.close();
+            return EventStatus::Captured;
+        }
+
+        self.input_frames = INPUT_FRAMES;
+        match &event {
+            Event::Window(baseview::WindowEvent::Focused) => self.focused = true,
+            Event::Window(baseview::WindowEvent::Unfocused) => {
+                self.focused = false;
+                self.release_keys |= self.key_recovery.on_unfocus;
+            }
+            Event::Keyboard(_) => self.last_key_event = self.clock.now(),
+            Event::Mouse(baseview::MouseEvent::CursorEntered) => self.hovered = true,
+            Event::Mouse(baseview::MouseEvent::CursorLeft) => self.hovered = false,
+            Event::Mouse(baseview::MouseEvent::ButtonPressed(button)) => {
+                match mouse::imgui_button(*button) {
+                    Ok(slot) => self.mouse_buttons[slot].set(true),
+                    Err(button) => {
+                        let raw_buttons = &mut self.window_context.input.raw_buttons;
+                        raw_buttons.push(mouse::RawButtonEvent {
+                            button,
+                            pressed: true,
+                        });
+                    }
+                }
+            }
+            Event::Mouse(baseview::MouseEvent::ButtonReleased(button)) => {
+                match mouse::imgui_button(*button) {
+                    Ok(slot) => self.mouse_buttons[slot].set(false),
+                    Err(button) => {
+                        let raw_buttons = &mut self.window_context.input.raw_buttons;
+                        raw_buttons.push(mouse::RawButtonEvent {
+                            button,
+                            pressed: false,
+                        });
+                    }
+                }
+            }
+            Event::Window(baseview::WindowEvent::Resized(window_info)) => self.resize(window_info),
+            Event::Mouse(baseview::MouseEvent::WheelScrolled(scroll_delta)) => {
+                let input = &mut self.window_context.input;
+                match scroll_delta {
+                    baseview::ScrollDelta::Lines { x, y } => {
+                        input.scroll_lines[0] += x;
+                        input.scroll_lines[1] += y;
+                    }
+                    baseview::ScrollDelta::Pixels { x, y } => {
+                        let delta = self
+                            .scaling()
+                            .logical_to_imgui(LogicalPos::new(*x as f64, *y as f64));
+                        input.scroll_pixels[0] += delta[0];
+                        input.scroll_pixels[1] += delta[1];
+                    }
+                }
+
+                // A widget under the mouse took the wheel from imgui.
+                if self.scroll_priority == ScrollPriority::HoveredWidget && input.scroll_claimed {
+                    return EventStatus::Captured;
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(ctx) = self.sus_context.take() {
+            let activation = self.activation.clone();
+            let result = activation.run(ctx, |mut context| {
+                let line_height = scroll::line_height(&mut context);
+                let io = context.io_mut();
+                self.catch_up(io);
+
+                match &event {
+                    baseview::Event::Mouse(event) => match event {
+                        baseview::MouseEvent::CursorMoved { position } => {
+                            io.mouse_pos = self.scaling().logical_to_imgui((*position).into());
+                        }
+                        baseview::MouseEvent::WheelScrolled(scroll_delta) => match scroll_delta {
+                            baseview::ScrollDelta::Lines { x, y } => {
+                                self.scroller.lines(io, *x, *y);
+                            }
+                            baseview::ScrollDelta::Pixels { x, y } => {
+                                let delta = self
+                                    .scaling()
+                                    .logical_to_imgui(LogicalPos::new(*x as f64, *y as f64));
+                                self.scroller.pixels(io, line_height, delta[0], delta[1]);
+                            }
+                        },
+                        _ => {}
+                    },
+                    baseview::Event::Keyboard(event) => self.key_event(io, event),
+                    baseview::Event::Window(_) => {}
+                }
+
+                if self.window_context.fonts.take_dirty() {
+                    self.window_context
+                        .fonts
+                        .build(&mut context, self.hidpi_factor);
+                }
+
+                context.suspend()
+            });
+
+            // Events that arrive while another window holds the context for too long are
+            // dropped, except for what the window keeps itself. Key events are kept for the
+            // next time it has the context, so no key stays down.
+            match result {
+                Ok(sus_context) => {
+                    self.sus_context = Some(sus_context);
+                    EventStatus::Captured
+                }
+                Err(sus_context) => {
+                    self.sus_context = Some(sus_context);
+                    if let Event::Keyboard(event) = event {
+                        self.skipped_keys.push(event);
+                    }
+                    EventStatus::Ignored
+                }
+            }
+        } else {
+            EventStatus::Ignored
+        }
     }
 }
 
-impl<State, U> WindowHandler for ImguiWindow<State, U>
+/// A window opened with `ImguiWindow::open_blocking`, which hands the state back when baseview
+/// drops it.
+struct BlockingWindow<State, U>
+where
+    State: 'static + Send,
+    U: FnMut(&mut bool, &imgui::Ui, &mut WindowContext, &mut State),
+    U: 'static + Send,
+{
+    window: Option<ImguiWindow<State, U>>,
+    exit_state: Arc<Mutex<Option<State>>>,
+}
+
+impl<State, U> WindowHandler for BlockingWindow<State, U>
+where
+    State: 'static + Send,
+    U: FnMut(&mut bool, &imgui::Ui, &mut WindowContext, &mut State),
+    U: 'static + Send,
+{
+    fn on_frame(&mut self, window: &mut Window) {
+        if let Some(imgui_window) = &mut self.window {
+            imgui_window.on_frame(window);
+        }
+    }
+
+    fn on_event(&mut self, window: &mut Window, event: Event) -> EventStatus {
+        match &mut self.window {
+            Some(imgui_window) => imgui_window.on_event(window, event),
+            None => EventStatus::Ignored,
+        }
+    }
+}
+
+impl<State, U> Drop for BlockingWindow<State, U>
 where
     State: 'static + Send,
-    U: FnMut(&mut bool, &imgui::Ui, &mut State),
+    U: FnMut(&mut bool, &imgui::Ui, &mut WindowContext, &mut State),
     U: 'static + Send,
 {
-    fn on_frame(&mut self, _window: &mut Window) {
+    fn drop(&mut self) {
+        if let Some(window) = self.window.take() {
+            let ImguiWindow { user_state, .. } = window;
+            if let Ok(mut exit_state) = self.exit_state.lock() {
+                *exit_state = Some(user_state);
+            }
+        }
+    }
+}
+
+impl<State, U> ImguiWindow<State, U>
+where
+    State: 'static + Send,
+    U: FnMut(&mut bool, &imgui::Ui, &mut WindowContext, &mut State),
+    U: 'static + Send,
+{
+    /// Handle a frame baseview asks for. Only windows without a baseview window, which the
+    /// tests draw headless, have no `window`, and they never lose the context.
+    fn frame(&mut self, window: Option<&Window>) {
+        if self.destroyed {
+            return;
+        }
+        let frame_start = Instant::now();
+
+        // Windows the user doesn't work in skip frames to render at the background rate. The
+        // delta time of the next frame covers the skipped ones.
+        let background_fps = match &self.window_context.instance {
+            Some(instance) => instance.background_fps(),
+            None => self.background_fps,
+        };
+        if let Some(background_fps) = background_fps {
+            let since_last_frame = self.clock.now().saturating_sub(self.last_frame);
+            if !self.focused
+                && !self.hovered
+                && since_last_frame.as_secs_f32() * background_fps < 1.0
+            {
+                self.handle_messages();
+                return;
+            }
+        }
+        if let Some(fps) = self.window_context.frame_watchdog.fps() {
+            let since_last_frame = self.clock.now().saturating_sub(self.last_frame);
+            if since_last_frame.as_secs_f32() * fps < 1.0 {
+                self.handle_messages();
+                return;
+            }
+        }
+
+        // When replaying, the recorded events are handled before the frame they arrived in.
+        #[cfg(feature = "event_recorder")]
+        let fixed_delta_time = match self.recorder.as_mut().and_then(|r| r.on_frame()) {
+            Some((events, delta_time)) => {
+                for event in events {
+                    self.handle_event(event);
+                }
+                Some(delta_time)
+            }
+            None => None,
+        };
+        #[cfg(not(feature = "event_recorder"))]
+        let fixed_delta_time: Option<Duration> = None;
+
+        // Nothing is drawn while the window has no area or can't be seen, but what was sent to
+        // it is still handled. The clock is paused, so the first frame after the window is
+        // shown again doesn't jump ahead.
+        if self.minimized
+            || !self.window_context.handle.is_visible()
+            || self.window_context.native_window.is_hidden()
+        {
+            self.last_frame = self.clock.now();
+            self.handle_messages();
+            return;
+        }
+        // The clock keeps running between the frames the host asks for.
+        if self.external_frames && !self.window_context.handle.take_external_frame() {
+            self.handle_messages();
+            return;
+        }
+        if self.frame_mode == FrameMode::OnDemand {
+            // Taken first so requests are cleared by the frame they cause.
+            let requested = self.window_context.handle.take_frame_request();
+            let repeating = self
+                .key_repeater
+                .as_ref()
+                .map_or(false, KeyRepeater::is_held);
+            let pasting = self
+                .primary_clipboard
+                .as_ref()
+                .map_or(false, PrimaryClipboard::is_waiting);
+            if !requested
+                && self.input_frames == 0
+                && !self.scroller.is_moving()
+                && !repeating
+                && !pasting
+            {
+                self.handle_messages();
+                return;
+            }
+            self.input_frames = self.input_frames.saturating_sub(1);
+        }
+
+        let repeats = match &mut self.key_repeater {
+            Some(repeater) => repeater.poll(self.clock.now()),
+            None => Vec::new(),
+        };
+        for event in repeats {
+            self.handle_event(Event::Keyboard(event));
+        }
+
+        let recreate_context = std::mem::take(&mut self.recreate_context);
+        if recreate_context || self.window_context.renderer.is_context_lost() {
+            if let Some(window) = window {
+                self.restore_context(window);
+            }
+        }
+
+        #[cfg(feature = "svg")]
+        if let Some(icons) = &self.icons {
+            icons.update(&mut self.window_context.renderer, self.hidpi_factor);
+        }
+
+        let activation = self.activation.clone();
         self.sus_context = Some(use_context(
+            &activation,
             self.sus_context.take().unwrap(),
             |mut context| {
                 {
                     let io = context.io_mut();
+                    self.catch_up(io);
+
+                    // Keys are released when the host may have eaten their release events.
+                    let now = self.clock.now();
+                    let keys_timed_out = match self.key_recovery.timeout {
+                        Some(timeout) => {
+                            io.keys_down.iter().any(|down| *down)
+                                && now.saturating_sub(self.last_key_event) >= timeout
+                        }
+                        None => false,
+                    };
+                    if self.window_context.handle.take_input_reset() {
+                        release_keys(io);
+                        self.key_codes.release_all();
+                        for button in &self.mouse_buttons {
+                            button.reset();
+                        }
+                    } else if self.release_keys || keys_timed_out {
+                        release_keys(io);
+                        self.key_codes.release_all();
+                    }
+                    self.release_keys = false;
 
                     // Sync mouse info.
                     for (io_down, button) in io.mouse_down.iter_mut().zip(&self.mouse_buttons) {
                         *io_down = button.get();
                     }
+                    if let Some(clipboard) = &mut self.primary_clipboard {
+                        clipboard.begin_frame(io);
+                    }
                     if io.want_set_mouse_pos {
-                        let _baseview_position = scale_pos_for_baseview(
-                            baseview::Point::new(io.mouse_pos[0] as f64, io.mouse_pos[1] as f64),
-                            self.scale_factor,
-                            self.hidpi_mode,
-                            self.hidpi_factor,
-                        );
+                        let _baseview_position = self.scaling().imgui_to_logical(io.mouse_pos);
 
                         // TODO: Set baseview cursor position.
                     }
 
-                    let now = Instant::now();
-                    io.update_delta_time(now.duration_since(self.last_frame));
-                    self.last_frame = now;
+                    let delta_time = next_frame(&mut self.last_frame, now);
+                    let delta_time = fixed_delta_time.unwrap_or(delta_time);
+                    self.scroller.update(io, delta_time);
+                    io.update_delta_time(delta_time);
+                }
+
+                self.apply_ui_scale(context.io_mut());
+                if self.window_context.fonts.take_dirty() {
+                    self.window_context
+                        .fonts
+                        .build(&mut context, self.hidpi_factor);
+                }
+
+                if self.window_context.fonts.needs_upload() {
+                    self.window_context
+                        .renderer
+                        .reload_font_texture(&mut context);
+                    self.window_context.fonts.mark_uploaded();
+                    // Another window may have rasterized it for a higher hidpi factor.
+                    context.io_mut().font_global_scale = self.window_context.fonts.global_scale();
+                }
+                // When the atlas is shared, other windows point it to their own texture.
+                context.fonts().tex_id = self.window_context.renderer.font_texture();
+
+                if let Some(instance) = &self.window_context.instance {
+                    instance.run_commands(&mut context);
+                }
+                let display_size = context.io().display_size;
+                if let Some(pan) = &mut self.pan {
+                    let line_height = scroll::line_height(&mut context);
+                    pan.begin_frame(
+                        context.io_mut(),
+                        line_height,
+                        &mut self.window_context.storage,
+                    );
                 }
 
+                self.window_context
+                    .frame_hooks
+                    .before_frame(&mut self.user_state);
+
+                shader::begin_frame(
+                    self.window_context.renderer.shader_targets(),
+                    self.hidpi_factor,
+                );
                 let ui = context.frame();
 
-                (self.user_update)(&mut self.run, &ui, &mut self.user_state);
+                self.window_context.help.begin_frame();
+                self.window_context.urls.poll();
+                #[cfg(feature = "remote_control")]
+                crate::remote_control::poll(&mut self.window_context);
+                #[cfg(feature = "file_dialog")]
+                self.window_context.file_dialogs.poll(&mut self.user_state);
+
+                #[cfg(feature = "accessibility")]
+                if let Some(accessibility) = &mut self.window_context.accessibility {
+                    accessibility.begin_frame(self.hidpi_factor);
+                }
+                self.window_context.scaling = self.scaling();
+
+                // The update closure waits for the loader of `Settings::splash`.
+                if let Some(splash) = &mut self.splash {
+                    if !splash.draw(&ui, &mut self.window_context) {
+                        self.splash = None;
+                    }
+                }
+                if self.splash.is_none() && !self.crashed {
+                    let run = &mut self.run;
+                    let update = &mut self.user_update;
+                    let window_context = &mut self.window_context;
+                    let state = &mut self.user_state;
+                    match &self.crash_handler {
+                        Some(handler) => {
+                            let result = crash::catch(|| update(run, &ui, window_context, state));
+                            if let Err(panic) = result {
+                                handler(&self.crash_report(panic, ui.frame_count() as u64));
+                                self.crashed = true;
+                            }
+                        }
+                        None => update(run, &ui, window_context, state),
+                    }
+                }
+                if self.crashed {
+                    crash::draw_notice(&ui);
+                }
+                self.window_context
+                    .undo
+                    .handle_shortcuts(&ui, &mut self.user_state);
+                self.window_context.shortcuts.end_frame();
+                self.window_context
+                    .native_children
+                    .end_frame(self.hidpi_factor);
+                #[cfg(feature = "webview")]
+                self.window_context.web_views.end_frame(
+                    &self.window_context.handle,
+                    self.hidpi_factor,
+                    self.scale_factor,
+                );
+
+                #[cfg(feature = "accessibility")]
+                if let Some(accessibility) = &mut self.window_context.accessibility {
+                    accessibility.end_frame();
+                }
+
+                let reduced_motion = self.window_context.reduced_motion;
+                self.window_context.notifications.draw(&ui, reduced_motion);
+                if self.window_context.notifications.is_active() {
+                    self.window_context.handle.request_frame();
+                }
+                // Jobs report their progress from their own threads, so the UI that shows it
+                // is drawn again as long as they run.
+                if self.progress_overlay {
+                    self.window_context.tasks.draw_overlay(&ui);
+                }
+                if self.window_context.tasks.is_busy() {
+                    self.window_context.handle.request_frame();
+                }
+
+                if let Some(focus_ring) = &mut self.window_context.focus_ring {
+                    focus_ring.draw(&ui);
+                }
+
+                self.dev_windows.draw(&ui);
+
+                if self.debug_overlay.is_visible() {
+                    let info = DebugInfo {
+                        scale_factor: self.scale_factor,
+                        hidpi_mode: self.hidpi_mode,
+                        hidpi_factor: self.hidpi_factor,
+                        renderer_name: self.window_context.renderer.name(),
+                        adapter: self.window_context.renderer.adapter_info(),
+                        texture_count: self.window_context.renderer.texture_count(),
+                    };
+                    self.debug_overlay
+                        .draw(&ui, &info, &self.window_context.locale);
+                }
+                self.window_context
+                    .renderer
+                    .set_color_vision(self.debug_overlay.color_vision());
+
+                if let Some(clipboard) = &mut self.primary_clipboard {
+                    clipboard.end_frame(&ui);
+                }
 
                 let io = ui.io();
                 if !io
@@ -348,203 +1612,496 @@ where
                     }
                 }
 
-                self.renderer.render(ui, self.clear_color);
+                if let Some(pan) = &self.pan {
+                    pan.draw_indicators(&ui);
+                }
+
+                let quads = shader::end_frame();
+                if let Some(pan) = &self.pan {
+                    pan.translate();
+                }
+                if self.pixel_snapping {
+                    pixel_snap::snap(self.hidpi_factor as f32);
+                }
+                let renderer = &mut self.window_context.renderer;
+                let clear_color = self.clear_color;
+                self.window_context
+                    .frame_hooks
+                    .render_with(&mut self.user_state, |state| {
+                        renderer.render(ui, clear_color, &quads, state)
+                    });
+
+                let mut stats = DrawStats::current(frame_start.elapsed());
+                let busy = stats
+                    .frame_time
+                    .saturating_sub(self.window_context.renderer.swap_time());
+                if self.window_context.frame_watchdog.record(busy) {
+                    self.recreate_context = self.window_context.renderer.degrade();
+                }
+                stats.degraded = self.window_context.frame_watchdog.is_degraded();
+                stats.input_latency = self.debug_overlay.frame_presented();
+                self.window_context
+                    .textures
+                    .end_frame(&mut self.window_context.renderer);
+                self.window_context
+                    .frame_hooks
+                    .after_frame(&mut self.user_state, &stats);
+                self.last_stats = Some(stats);
+                self.window_context
+                    .state_tracker
+                    .after_frame(&self.user_state, &self.window_context.handle);
+                #[cfg(feature = "remote_debug")]
+                if let Some(server) = &mut self.remote_debug {
+                    server.send_frame(&stats, self.hidpi_factor, display_size);
+                }
+                self.apply_auto_resize(display_size);
+                if let Some(pan) = &self.pan {
+                    pan.end_frame(context.io_mut());
+                }
+
+                if self.window_context.fonts.take_dirty() {
+                    self.window_context
+                        .fonts
+                        .build(&mut context, self.hidpi_factor);
+                }
+
+                let io = context.io();
+                let save_storage = self
+                    .window_context
+                    .storage
+                    .should_save(io.delta_time, io.ini_saving_rate);
+                if io.want_save_ini_settings || save_storage {
+                    let mut ui_state = String::new();
+                    context.save_ini_settings(&mut ui_state);
+                    self.window_context.storage.save(&mut ui_state);
+                    self.window_context
+                        .handle
+                        .emit(UiEvent::SaveUiState(ui_state));
+                }
+
+                self.window_context.texture_remap.clear();
+                self.window_context.input.end_frame();
+
+                if let Some(instance) = &self.window_context.instance {
+                    instance.record_frame(
+                        frame_start.elapsed(),
+                        display_size,
+                        self.hidpi_factor,
+                        self.focused || self.hovered,
+                    );
+                }
 
                 context.suspend()
             },
         ));
     }
+}
 
-    fn on_event(&mut self, _window: &mut Window, event: Event) -> EventStatus {
-        if let Some(ctx) = self.sus_context.take() {
-            self.sus_context = Some(use_context(
-                ctx,
-                |mut context| {
-                    let io = context.io_mut();
+impl<State, U> WindowHandler for ImguiWindow<State, U>
+where
+    State: 'static + Send,
+    U: FnMut(&mut bool, &imgui::Ui, &mut WindowContext, &mut State),
+    U: 'static + Send,
+{
+    fn on_frame(&mut self, window: &mut Window) {
+        self.frame(Some(window));
+    }
 
-                    match &event {
-                        baseview::Event::Mouse(event) => match event {
-                            baseview::MouseEvent::CursorMoved { position } => {
-                                let position = scale_pos_from_baseview(
-                                    *position,
-                                    self.scale_factor,
-                                    self.hidpi_mode,
-                                    self.hidpi_factor,
-                                );
-                                io.mouse_pos = [position.x as f32, position.y as f32];
-                            }
-                            baseview::MouseEvent::ButtonPressed(button) => match button {
-                                baseview::MouseButton::Left => self.mouse_buttons[0].set(true),
-                                baseview::MouseButton::Middle => self.mouse_buttons[2].set(true),
-                                baseview::MouseButton::Right => self.mouse_buttons[1].set(true),
-                                baseview::MouseButton::Other(3) => self.mouse_buttons[3].set(true),
-                                baseview::MouseButton::Other(4) => self.mouse_buttons[4].set(true),
-                                _ => {}
-                            },
-                            baseview::MouseEvent::ButtonReleased(button) => match button {
-                                baseview::MouseButton::Left => self.mouse_buttons[0].set(false),
-                                baseview::MouseButton::Middle => self.mouse_buttons[2].set(false),
-                                baseview::MouseButton::Right => self.mouse_buttons[1].set(false),
-                                baseview::MouseButton::Other(3) => self.mouse_buttons[3].set(false),
-                                baseview::MouseButton::Other(4) => self.mouse_buttons[4].set(false),
-                                _ => {}
-                            },
-                            baseview::MouseEvent::WheelScrolled(scroll_delta) => match scroll_delta {
-                                baseview::ScrollDelta::Lines { x, y } => {
-                                    io.mouse_wheel_h = *x;
-                                    io.mouse_wheel = *y;
-                                }
-                                baseview::ScrollDelta::Pixels { x, y } => {
-                                    if *x < 0.0 {
-                                        io.mouse_wheel_h -= 1.0;
-                                    } else if *x > 1.0 {
-                                        io.mouse_wheel_h += 1.0;
-                                    }
-
-                                    if *y < 0.0 {
-                                        io.mouse_wheel -= 1.0;
-                                    } else if *y > 1.0 {
-                                        io.mouse_wheel_h += 1.0;
-                                    }
-                                }
-                            },
-                            _ => {}
-                        },
-                        baseview::Event::Keyboard(event) => {
-                            use keyboard_types::Code;
-
-                            let pressed = event.state == keyboard_types::KeyState::Down;
-
-                            io.keys_down[event.code as usize] = pressed;
-
-                            // This is a bit redundant here, but we'll leave it in. The OS occasionally
-                            // fails to send modifiers keys, but it doesn't seem to send false-positives,
-                            // so double checking isn't terrible in case some system *doesn't* send
-                            // device events sometimes.
-                            match event.code {
-                                Code::ShiftLeft | Code::ShiftRight => io.key_shift = pressed,
-                                Code::ControlLeft | Code::ControlRight => io.key_ctrl = pressed,
-                                Code::AltLeft | Code::AltRight => io.key_alt = pressed,
-                                Code::MetaLeft | Code::MetaRight => io.key_super = pressed,
-                                _ => (),
-                            }
+    fn on_event(&mut self, _window: &mut Window, event: Event) -> EventStatus {
+        if self.destroyed {
+            return EventStatus::Ignored;
+        }
+        self.debug_overlay.on_event(&event);
+        self.dev_windows.on_event(&event);
 
-                            if pressed {
-                                if let keyboard_types::Key::Character(written) = &event.key {
-                                    for chr in written.chars() {
-                                        // Exclude the backspace key ('\u{7f}'). Otherwise we will insert this char and then
-                                        // delete it.
-                                        if chr != '\u{7f}' {
-                                            io.add_input_character(chr)
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        baseview::Event::Window(event) => {
-                            match event {
-                                baseview::WindowEvent::Resized(window_info) => {
-                                    self.scale_factor = match self.scale_policy {
-                                        WindowScalePolicy::ScaleFactor(scale) => scale,
-                                        WindowScalePolicy::SystemScaleFactor => window_info.scale(),
-                                    };
-
-                                    let new_hidpi_factor = self.hidpi_mode.apply(self.scale_factor);
-
-                                    // Mouse position needs to be changed while we still have both the old and the new
-                                    // values
-                                    if io.mouse_pos[0].is_finite() && io.mouse_pos[1].is_finite() {
-                                        io.mouse_pos = [
-                                            io.mouse_pos[0]
-                                                * (new_hidpi_factor / self.hidpi_factor) as f32,
-                                            io.mouse_pos[1]
-                                                * (new_hidpi_factor / self.hidpi_factor) as f32,
-                                        ];
-                                    }
-
-                                    self.hidpi_factor = new_hidpi_factor;
-
-                                    let logical_size = [
-                                        (window_info.physical_size().width as f64 / self.hidpi_factor)
-                                            as f32,
-                                        (window_info.physical_size().height as f64 / self.hidpi_factor)
-                                            as f32,
-                                    ];
-
-                                    io.display_framebuffer_scale =
-                                        [self.hidpi_factor as f32, self.hidpi_factor as f32];
-                                    io.display_size = logical_size;
-                                }
-                                baseview::WindowEvent::WillClose => {}
-                                _ => {}
-                            }
-                        }
-                    }
+        // Closing can't be passed through or held back by the recorder, or nothing is freed.
+        if let Event::Window(baseview::WindowEvent::WillClose) = &event {
+            return self.handle_event(event);
+        }
+        if self.passthrough.passes(&event) {
+            return EventStatus::Ignored;
+        }
+        let event = match &mut self.primary_clipboard {
+            Some(clipboard) => clipboard.map_event(event),
+            None => event,
+        };
 
-                    context.suspend()
-                },
-            ));
+        #[cfg(feature = "event_recorder")]
+        if let Some(recorder) = &mut self.recorder {
+            if !recorder.on_event(&event) {
+                return EventStatus::Captured;
+            }
+        }
 
-            EventStatus::Captured
-        } else {
-            EventStatus::Ignored
+        if let Some(repeater) = &mut self.key_repeater {
+            repeater.on_event(&event, self.clock.now());
         }
+        self.handle_event(event)
     }
 }
 
-/// Scales a logical position from baseview using the current DPI mode.
-///
-/// This utility function is useful if you are using a DPI mode other than default, and want
-/// your application to use the same logical coordinates as imgui-rs.
-#[inline]
-fn scale_pos_from_baseview(
-    logical_pos: baseview::Point,
-    scale_factor: f64,
-    hidpi_mode: HiDpiMode,
-    hidpi_factor: f64,
-) -> baseview::Point {
-    match hidpi_mode {
-        HiDpiMode::Default => logical_pos,
-        _ => baseview::Point::new(
-            logical_pos.x * scale_factor / hidpi_factor,
-            logical_pos.y * scale_factor / hidpi_factor,
-        ),
-    }
+fn release_keys(io: &mut imgui::Io) {
+    io.keys_down = [false; 512];
+    io.key_ctrl = false;
+    io.key_shift = false;
+    io.key_alt = false;
+    io.key_super = false;
 }
 
-/// Scales a logical position for baseview using the current DPI mode.
-///
-/// This utility function is useful if you are using a DPI mode other than default, and want
-/// your application to use the same logical coordinates as imgui-rs.
-#[inline]
-fn scale_pos_for_baseview(
-    logical_pos: baseview::Point,
-    scale_factor: f64,
-    hidpi_mode: HiDpiMode,
-    hidpi_factor: f64,
-) -> baseview::Point {
-    match hidpi_mode {
-        HiDpiMode::Default => logical_pos,
-        _ => baseview::Point::new(
-            logical_pos.x * hidpi_factor / scale_factor,
-            logical_pos.y * hidpi_factor / scale_factor,
-        ),
+/// Run `f` with the imgui context, or skip it if another window holds the context for longer
+/// than the timeout.
+fn use_context<F>(
+    activation: &Activation,
+    sus_context: imgui::SuspendedContext,
+    f: F,
+) -> imgui::SuspendedContext
+where
+    F: FnOnce(imgui::Context) -> imgui::SuspendedContext,
+{
+    activation
+        .run(sus_context, f)
+        .unwrap_or_else(|sus_context| sus_context)
+}
+
+/// Whether a window of `size` has nothing to draw on, because it is minimized or the host
+/// resized it to nothing.
+fn has_no_area(size: baseview::PhySize) -> bool {
+    size.width == 0 || size.height == 0
+}
+
+/// The delta time of a frame that starts at `now`, which becomes the start of the next one.
+fn next_frame(last_frame: &mut Duration, now: Duration) -> Duration {
+    let delta_time = now.saturating_sub(*last_frame);
+    *last_frame = now;
+    delta_time
+}
+
+/// Run the teardown `f` of a closing window with the imgui context. Unlike `use_context` this
+/// waits for as long as it takes, since what isn't freed now is dropped without its context.
+/// Without a context `f` runs anyway.
+fn tear_down<F>(
+    activation: &Activation,
+    sus_context: Option<imgui::SuspendedContext>,
+    f: F,
+) -> Option<imgui::SuspendedContext>
+where
+    F: FnOnce(),
+{
+    match sus_context {
+        Some(sus_context) => Some(activation.wait(sus_context, |context| {
+            f();
+            context.suspend()
+        })),
+        None => {
+            f();
+            None
+        }
     }
 }
 
-fn use_context<F: FnMut(imgui::Context) -> imgui::SuspendedContext>(
-    mut sus_context: imgui::SuspendedContext,
-    mut f: F,
-) -> imgui::SuspendedContext {
-    loop {
-        match sus_context.activate() {
-            Ok(context) => {
-                return (f)(context);
-            }
-            Err(new_sus_context) => {
-                std::thread::sleep(CONTEXT_TRY_UNLOCK_WAIT_DURATION);
-                sus_context = new_sus_context
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activation::{ContextPolicy, TEST_CONTEXT};
+    use crate::clock::ManualClock;
+    use crate::renderer::RendererBackend;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc;
+    use std::thread;
+
+    /// A backend that draws nothing and logs what it is asked to do, and whether an imgui
+    /// context was current.
+    struct Headless {
+        log: Arc<Mutex<Vec<(&'static str, bool)>>>,
+    }
+
+    impl Headless {
+        fn record(&self, what: &'static str) {
+            let current = unsafe { !imgui::sys::igGetCurrentContext().is_null() };
+            self.log.lock().unwrap().push((what, current));
+        }
+    }
+
+    impl RendererBackend for Headless {
+        fn upload_font_atlas(&mut self, _context: &mut imgui::Context) -> imgui::TextureId {
+            imgui::TextureId::new(1)
+        }
+
+        fn render(&mut self, _draw_data: &imgui::DrawData, _clear_color: (f32, f32, f32)) {}
+
+        fn create_texture(&mut self, _width: u32, _height: u32, _rgba: &[u8]) -> imgui::TextureId {
+            imgui::TextureId::new(2)
+        }
+
+        fn delete_texture(&mut self, _texture: imgui::TextureId) {
+            self.record("delete_texture");
+        }
+
+        fn destroy(&mut self) {
+            self.record("destroy");
+        }
+    }
+
+    fn headless_renderer(
+        log: &Arc<Mutex<Vec<(&'static str, bool)>>>,
+    ) -> (Renderer, imgui::SuspendedContext) {
+        let mut context = imgui::Context::create();
+        let backend = Headless { log: log.clone() };
+        let renderer = Renderer::headless(&mut context, Box::new(backend));
+        (renderer, context.suspend())
+    }
+
+    #[test]
+    fn tear_down_frees_the_renderer_after_the_hook_with_the_context() {
+        let _imgui = TEST_CONTEXT.lock().unwrap_or_else(|e| e.into_inner());
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let (mut renderer, sus_context) = headless_renderer(&log);
+        let activation = Activation::new(String::new(), ContextPolicy::Queue, None, None);
+
+        let sus_context = tear_down(&activation, Some(sus_context), || {
+            let current = unsafe { !imgui::sys::igGetCurrentContext().is_null() };
+            log.lock().unwrap().push(("on_destroy", current));
+            renderer.destroy();
+        });
+
+        assert!(sus_context.is_some());
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                ("on_destroy", true),
+                ("delete_texture", true),
+                ("destroy", true)
+            ]
+        );
+    }
+
+    #[test]
+    fn tear_down_waits_out_a_holder_past_the_timeout() {
+        let _imgui = TEST_CONTEXT.lock().unwrap_or_else(|e| e.into_inner());
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let (mut renderer, sus_context) = headless_renderer(&log);
+        let timeouts = Arc::new(AtomicUsize::new(0));
+        let on_timeout = {
+            let timeouts = timeouts.clone();
+            Arc::new(move |_: &crate::activation::TimeoutReport| {
+                timeouts.fetch_add(1, Ordering::SeqCst);
+            })
+        };
+        let activation = Activation::new(
+            String::new(),
+            ContextPolicy::Queue,
+            Some(Duration::from_millis(5)),
+            Some(on_timeout),
+        );
+
+        // Another window holds the context for much longer than the timeout.
+        let (held, is_held) = mpsc::channel();
+        let holder = thread::spawn(move || {
+            let context = imgui::Context::create();
+            held.send(()).unwrap();
+            thread::sleep(Duration::from_millis(200));
+            drop(context);
+        });
+        is_held.recv().unwrap();
+
+        // Events and frames give up on it.
+        let mut skipped = true;
+        let sus_context = use_context(&activation, sus_context, |context| {
+            skipped = false;
+            context.suspend()
+        });
+        assert!(skipped);
+
+        tear_down(&activation, Some(sus_context), || renderer.destroy());
+        holder.join().unwrap();
+
+        assert!(timeouts.load(Ordering::SeqCst) >= 2);
+        assert_eq!(
+            *log.lock().unwrap(),
+            [("delete_texture", true), ("destroy", true)]
+        );
+    }
+
+    /// What imgui saw in a frame the update closure built.
+    struct FrameRecord {
+        display_size: [f32; 2],
+        delta_time: f32,
+        left_button: bool,
+        keys_down: usize,
+    }
+
+    type Frames = Vec<FrameRecord>;
+    type RecordFrame = fn(&mut bool, &imgui::Ui, &mut WindowContext, &mut Frames);
+
+    /// A handle to no window, which the platform calls ignore.
+    #[cfg(target_os = "macos")]
+    fn no_window() -> RawWindowHandle {
+        RawWindowHandle::MacOS(raw_window_handle::macos::MacOSHandle::empty())
+    }
+
+    #[cfg(target_os = "windows")]
+    fn no_window() -> RawWindowHandle {
+        RawWindowHandle::Windows(raw_window_handle::windows::WindowsHandle::empty())
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn no_window() -> RawWindowHandle {
+        RawWindowHandle::Xlib(raw_window_handle::unix::XlibHandle::empty())
+    }
+
+    /// A window drawn by `Headless` that records its frames, without a baseview window.
+    fn headless_window(settings: Settings) -> ImguiWindow<Frames, RecordFrame> {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let record: RecordFrame = |_, ui, _, frames| {
+            let io = ui.io();
+            frames.push(FrameRecord {
+                display_size: io.display_size,
+                delta_time: io.delta_time,
+                left_button: io.mouse_down[0],
+                keys_down: io.keys_down.iter().filter(|down| **down).count(),
+            });
         };
+        ImguiWindow::with_renderer(
+            no_window(),
+            OpenSettings::new(&settings).unwrap(),
+            |_: &mut imgui::Context, _: &mut Frames| {},
+            record,
+            Vec::new(),
+            move |context, _, _, _| Renderer::headless(context, Box::new(Headless { log })),
+        )
+    }
+
+    fn resized(width: u32, height: u32) -> Event {
+        let size = baseview::PhySize { width, height };
+        let window_info = baseview::WindowInfo::from_physical_size(size, 1.0);
+        Event::Window(baseview::WindowEvent::Resized(window_info))
+    }
+
+    fn with_clock(clock: &ManualClock) -> Settings {
+        let mut settings = Settings::default();
+        settings.window.scale = WindowScalePolicy::ScaleFactor(1.0);
+        settings.clock = Some(Arc::new(clock.clone()));
+        settings
+    }
+
+    #[test]
+    fn minimized_while_resized_to_no_area() {
+        let _imgui = TEST_CONTEXT.lock().unwrap_or_else(|e| e.into_inner());
+        let clock = ManualClock::new();
+        let mut window = headless_window(with_clock(&clock));
+        let frame = Duration::from_millis(16);
+
+        window.handle_event(resized(400, 300));
+        clock.advance(frame);
+        window.frame(None);
+        assert_eq!(window.user_state.len(), 1);
+
+        for size in &[(0, 0), (0, 300), (400, 0)] {
+            window.handle_event(resized(size.0, size.1));
+            assert!(window.minimized);
+            clock.advance(frame);
+            window.frame(None);
+        }
+        assert_eq!(window.user_state.len(), 1);
+
+        // The last size with an area is kept.
+        window.handle_event(resized(200, 100));
+        assert!(!window.minimized);
+        clock.advance(frame);
+        window.frame(None);
+        let sizes: Vec<_> = window
+            .user_state
+            .iter()
+            .map(|frame| frame.display_size)
+            .collect();
+        assert_eq!(sizes, [[400.0, 300.0], [200.0, 100.0]]);
+    }
+
+    #[test]
+    fn clock_stays_paused_while_minimized() {
+        let _imgui = TEST_CONTEXT.lock().unwrap_or_else(|e| e.into_inner());
+        let clock = ManualClock::new();
+        let mut window = headless_window(with_clock(&clock));
+        let frame = Duration::from_millis(16);
+
+        clock.advance(frame);
+        window.frame(None);
+
+        // Minimized for a while, no frame is built.
+        window.handle_event(resized(0, 0));
+        for _ in 0..10 {
+            clock.advance(Duration::from_secs(1));
+            window.frame(None);
+        }
+        assert_eq!(window.user_state.len(), 1);
+
+        // The first frame after it doesn't jump ahead.
+        window.handle_event(resized(400, 300));
+        clock.advance(frame);
+        window.frame(None);
+        assert_eq!(window.user_state.len(), 2);
+        assert!((window.user_state[1].delta_time - frame.as_secs_f32()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn input_that_skipped_the_context_reaches_imgui_later() {
+        let _imgui = TEST_CONTEXT.lock().unwrap_or_else(|e| e.into_inner());
+        let clock = ManualClock::new();
+        let mut settings = with_clock(&clock);
+        settings.context_timeout = Some(Duration::from_millis(5));
+        let mut window = headless_window(settings);
+        let frame = Duration::from_millis(16);
+        let key = |state| {
+            Event::Keyboard(keyboard_types::KeyboardEvent {
+                state,
+                code: keyboard_types::Code::KeyA,
+                ..keyboard_types::KeyboardEvent::default()
+            })
+        };
+
+        window.handle_event(resized(400, 300));
+        window.handle_event(Event::Mouse(baseview::MouseEvent::ButtonPressed(
+            baseview::MouseButton::Left,
+        )));
+        window.handle_event(key(keyboard_types::KeyState::Down));
+        clock.advance(frame);
+        window.frame(None);
+
+        // Another window holds the context while the button and key are released and the
+        // window is resized.
+        let (held, is_held) = mpsc::channel();
+        let (release, released) = mpsc::channel::<()>();
+        let holder = thread::spawn(move || {
+            let context = imgui::Context::create();
+            held.send(()).unwrap();
+            let _ = released.recv();
+            drop(context);
+        });
+        is_held.recv().unwrap();
+        window.handle_event(Event::Mouse(baseview::MouseEvent::ButtonReleased(
+            baseview::MouseButton::Left,
+        )));
+        window.handle_event(key(keyboard_types::KeyState::Up));
+        window.handle_event(resized(200, 100));
+        drop(release);
+        holder.join().unwrap();
+
+        clock.advance(frame);
+        window.frame(None);
+        let held = &window.user_state[0];
+        assert!(held.left_button);
+        assert_eq!(held.keys_down, 1);
+        let released = &window.user_state[1];
+        assert!(!released.left_button);
+        assert_eq!(released.keys_down, 0);
+        assert_eq!(released.display_size, [200.0, 100.0]);
+    }
+
+    #[test]
+    fn tear_down_without_a_context() {
+        let mut ran = false;
+        let activation = Activation::new(String::new(), ContextPolicy::Queue, None, None);
+        assert!(tear_down(&activation, None, || ran = true).is_none());
+        assert!(ran);
     }
 }